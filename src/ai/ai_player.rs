@@ -1,12 +1,17 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
-use crate::{board::moves::Move, game::{manager::{BoardMakeMove, GameManager, CanMakeMove}, player::Player}, ui::ingame_menu::CalcStatistics, state::AppMode};
-use super::stats::SearchStatistics;
+use crate::{board::moves::Move, engine::position::Position};
+#[cfg(feature = "gui")]
+use crate::{game::{manager::{BoardMakeMove, GameManager, CanMakeMove}, player::Player, clock::ChessClock}, ui::ingame_menu::CalcStatistics, state::AppMode, board::board::Board};
+use super::{stats::SearchStatistics, book::{OpeningBook, DEFAULT_BOOK_PATH}, time_manager::ClockTime};
+#[cfg(feature = "gui")]
+use super::time_manager::TimeManager;
 
 
 pub const DEFAULT_AI_THINK_TIME_MS: u32 = 1000;
 
 
-#[derive(PartialEq, Default, Clone, Copy)]
+#[derive(PartialEq, Default, Clone, Copy, Debug)]
 pub enum AIVersion {
     #[default]
     V0,
@@ -22,18 +27,24 @@ pub enum AIVersion {
     V10,
     V11,
     V12,
+    V13,
 }
 
 impl AIVersion {
+    pub const ALL: [AIVersion; 14] = [
+        Self::V0, Self::V1, Self::V2, Self::V3, Self::V4, Self::V5, Self::V6,
+        Self::V7, Self::V8, Self::V9, Self::V10, Self::V11, Self::V12, Self::V13,
+    ];
+
     // Newest version (version to test)
     pub fn primary_version() -> Self {
-        AIVersion::V12
+        AIVersion::V13
     }
     // Version for primary version to fight
     pub fn secondary_version() -> Self {
-        AIVersion::V11
+        AIVersion::V12
     }
-    pub fn label(&self) -> &str {
+    pub fn label(&self) -> &'static str {
         match self {
             Self::V0 => "V0 - Random Moves",
             Self::V1 => "V1 - Minimax",
@@ -48,9 +59,20 @@ impl AIVersion {
             Self::V10 => "V10 - Imbalance Evaluation",
             Self::V11 => "V11 - Mobility Evaluation",
             Self::V12 => "V12 - Faster Evaluation",
+            Self::V13 => "V13 - Null Move Pruning",
         }
     }
 
+    /// Finds a version by its short name like `V12`, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| version.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Short name like `V12`, the start of `label`
+    pub fn name(&self) -> &'static str {
+        self.label().split(' ').next().unwrap()
+    }
+
     pub fn opponent(self) -> Option<Self> {
         if self == Self::primary_version() {
             return Some(Self::secondary_version());
@@ -61,28 +83,59 @@ impl AIVersion {
     }
 }
 
-#[derive(Component)]
+#[cfg_attr(feature = "gui", derive(Component))]
 pub struct AIPlayer {
     pub searching: bool,
     pub think_time_ms: u32,
-    pub version: AIVersion
+    pub version: AIVersion,
+    /// Play moves from the opening book at `book_path` before searching
+    pub use_book: bool,
+    pub book_path: String,
+    book: Option<OpeningBook>,
+    loaded_book_path: Option<String>,
+    /// Why the book couldn't be opened, taken once by whoever reports it
+    pub book_error: Option<String>,
 }
 
 impl AIPlayer {
     pub fn versus_p1() -> Self {
         AIPlayer {
             version: AIVersion::primary_version(),
-            think_time_ms: DEFAULT_AI_THINK_TIME_MS,
-            searching: false,
+            ..Default::default()
         }
     }
     pub fn versus_p2() -> Self {
         AIPlayer {
             version: AIVersion::secondary_version(),
-            think_time_ms: DEFAULT_AI_THINK_TIME_MS,
-            searching: false,
+            ..Default::default()
         }
     }
+
+    pub fn with_book(mut self, use_book: bool, book_path: &str) -> Self {
+        self.use_book = use_book;
+        self.book_path = book_path.to_string();
+        self
+    }
+
+    /// Picks a move from the opening book, the book is (re)loaded when `book_path` changes.
+    /// A book that can't be opened turns `use_book` off and leaves the reason in `book_error`
+    pub fn book_move(&mut self, position: &Position) -> Option<Move> {
+        if !self.use_book {
+            return None;
+        }
+        if self.loaded_book_path.as_ref() != Some(&self.book_path) {
+            self.book = match OpeningBook::open(&self.book_path) {
+                Ok(book) => Some(book),
+                Err(err) => {
+                    self.use_book = false;
+                    self.book_error = Some(format!("Failed to open opening book {}: {}", self.book_path, err));
+                    None
+                }
+            };
+            self.loaded_book_path = Some(self.book_path.clone());
+        }
+        self.book.as_ref()?.choose_move(position, &mut rand::thread_rng())
+    }
 }
 
 impl Default for AIPlayer {
@@ -91,29 +144,41 @@ impl Default for AIPlayer {
             version: AIVersion::primary_version(),
             think_time_ms: DEFAULT_AI_THINK_TIME_MS,
             searching: false,
+            // No book ships with the engine, it is turned on in the main menu once one has been built
+            use_book: false,
+            book_path: DEFAULT_BOOK_PATH.to_string(),
+            book: None,
+            loaded_book_path: None,
+            book_error: None,
         }
     }
 }
 
-#[derive(Event)]
+#[cfg_attr(feature = "gui", derive(Event))]
 pub struct BeginSearch {
     pub version: AIVersion,
     pub think_time: u32,
+    /// Clock of the side to move, searchers with a time manager split it up themselves instead of using `think_time`
+    pub clock: Option<ClockTime>,
 }
 
-#[derive(Event)]
+#[cfg_attr(feature = "gui", derive(Event))]
 pub struct SearchComplete {
     pub depth: i32,
     pub chosen_move: Move,
+    /// Line the AI expects to be played, starting with `chosen_move`
+    pub pv: Vec<Move>,
     pub eval: i32,
     pub stats: SearchStatistics
 }
 
+#[cfg(feature = "gui")]
 pub fn ai_make_move(
     mut make_move_evw: EventWriter<BoardMakeMove>,
     mut player_query: Query<&mut AIPlayer>,
     mut search_complete_evr: EventReader<SearchComplete>,
     mut calc_stats: ResMut<CalcStatistics>,
+    board: Res<Board>,
 ) {
     
     for search_complete in search_complete_evr.iter() {
@@ -121,28 +186,56 @@ pub fn ai_make_move(
             ai.searching = false;
         }
         calc_stats.ai_depth = search_complete.depth;
+        calc_stats.ai_pv = search_complete.pv.clone();
+        calc_stats.ai_pv_san = Position { board: board.clone() }.line_to_san(&search_complete.pv).join(" ");
         calc_stats.ai_eval = search_complete.eval;
         calc_stats.ai_positions_evaled = search_complete.stats.num_position_evals;
         calc_stats.ai_mates_found = search_complete.stats.num_mates;
         calc_stats.ai_num_cutoffs = search_complete.stats.num_cutoffs;
         calc_stats.ai_think_time = search_complete.stats.think_time_ms;
         calc_stats.ai_transpositions = search_complete.stats.num_transpositions;
+        calc_stats.ai_is_book = search_complete.stats.is_book;
+        calc_stats.ai_tb_hits = search_complete.stats.tb_hits;
         make_move_evw.send(BoardMakeMove {
             mov: search_complete.chosen_move
         });
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn ai_begin_search(
     mut begin_search_evw: EventWriter<BeginSearch>,
+    mut search_complete_evw: EventWriter<SearchComplete>,
     mut player_query: Query<(&mut AIPlayer, &Player)>,
     manager: Res<GameManager>,
     app_mode: Res<State<AppMode>>,
+    board: Res<Board>,
+    clock: Option<Res<ChessClock>>,
     mut can_make_move_evr: EventReader<CanMakeMove>,
 ) {
+    // On the clock the AI thinks for its share of the remaining time instead
+    let clock_time = clock.map(|clock| clock.clock_time(board.move_color_idx));
+    let clock_think_time = clock_time.map(|clock_time| TimeManager::new(clock_time).soft_limit_ms());
     for _can_make_move_ev in can_make_move_evr.iter() {
         for (mut ai, player_data) in player_query.iter_mut() {
             if player_data.team == manager.move_color && !ai.searching {
+                if app_mode.clone() != AppMode::GameHumanHuman {
+                    let book_move = ai.book_move(&Position { board: board.clone() });
+                    if let Some(err) = ai.book_error.take() {
+                        warn!("{}", err);
+                    }
+                    if let Some(book_move) = book_move {
+                        ai.searching = true;
+                        search_complete_evw.send(SearchComplete {
+                            depth: 0,
+                            chosen_move: book_move,
+                            pv: vec![book_move],
+                            eval: 0,
+                            stats: SearchStatistics { is_book: true, ..Default::default() },
+                        });
+                        continue;
+                    }
+                }
                 match app_mode.clone() {
                     AppMode::None | AppMode::GameHumanHuman => (),
                     AppMode::GameHumanAI => {
@@ -150,7 +243,8 @@ pub fn ai_begin_search(
                             ai.searching = true;
                             begin_search_evw.send(BeginSearch {
                                 version: ai.version,
-                                think_time: DEFAULT_AI_THINK_TIME_MS,
+                                think_time: clock_think_time.unwrap_or(DEFAULT_AI_THINK_TIME_MS),
+                                clock: clock_time,
                             });
                         }
                     },
@@ -158,7 +252,8 @@ pub fn ai_begin_search(
                         ai.searching = true;
                         begin_search_evw.send(BeginSearch {
                             version: ai.version,
-                            think_time: ai.think_time_ms,
+                            think_time: clock_think_time.unwrap_or(ai.think_time_ms),
+                            clock: clock_time,
                         });
                     }
 
@@ -0,0 +1,265 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use rand::Rng;
+
+use crate::{board::{board::Board, coord::Coord, moves::Move, polyglot::polyglot_key}, engine::position::Position, utils::pgn::{parse_pgn_games, PgnError, PgnGame}};
+
+
+pub const DEFAULT_BOOK_PATH: &str = "assets/books/book.bin";
+
+/// A single entry of a Polyglot opening book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    /// Move in the Polyglot encoding, castling is written as the king capturing its own rook
+    pub mov: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl BookEntry {
+    /// Entries are stored as 16 big-endian bytes
+    pub const SIZE: usize = 16;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            key: u64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+            mov: u16::from_be_bytes(bytes[8..10].try_into().unwrap()),
+            weight: u16::from_be_bytes(bytes[10..12].try_into().unwrap()),
+            learn: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.mov.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+
+    /// Polyglot encoding of a move, castling is written as the king capturing its own rook
+    pub fn encode_move(board: &Board, mov: Move) -> u16 {
+        let target = if mov.move_flag() == Move::CASTLING { board.castling_squares(mov).1 } else { mov.target() };
+        let promotion = if mov.is_promotion() { mov.promotion_ptype() as u16 - 1 } else { 0 };
+        target.index() as u16 | ((mov.start().index() as u16) << 6) | (promotion << 12)
+    }
+
+    /// Finds the legal move matching the entry's move
+    pub fn decode_move(&self, board: &Board, legal_moves: &[Move]) -> Option<Move> {
+        let target = Coord::from_idx((self.mov & 0b111111) as i8);
+        let start = Coord::from_idx(((self.mov >> 6) & 0b111111) as i8);
+        // 1 to 4 for knight to queen promotions, the piece types are one higher
+        let promotion = ((self.mov >> 12) & 0b111) as u8;
+
+        legal_moves.iter().find(|mov| {
+            if mov.start() != start {
+                return false;
+            }
+            if mov.move_flag() == Move::CASTLING {
+                let (_, rook_from, _) = board.castling_squares(**mov);
+                return mov.target() == target || rook_from == target;
+            }
+            let promotion_matches = if mov.is_promotion() { mov.promotion_ptype() == promotion + 1 } else { promotion == 0 };
+            mov.target() == target && promotion_matches
+        }).copied()
+    }
+}
+
+/// Opening book in the Polyglot `.bin` format
+pub struct OpeningBook {
+    /// Sorted by key, so all moves of a position are next to each other
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::from_bytes(&fs::read(path)?))
+    }
+
+    /// Reads the entries of a book file, a trailing partial entry is ignored
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_entries(bytes.chunks_exact(BookEntry::SIZE)
+            .map(|chunk| BookEntry::from_bytes(chunk.try_into().unwrap()))
+            .collect())
+    }
+
+    pub fn from_entries(mut entries: Vec<BookEntry>) -> Self {
+        // Books written by other tools are sorted already, but lookups rely on it.
+        // Within a position the most played moves come first, like in other Polyglot books.
+        entries.sort_by_key(|entry| (entry.key, u16::MAX - entry.weight));
+        Self { entries }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.entries.iter().flat_map(|entry| entry.to_bytes()).collect()
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries stored for a Polyglot key
+    pub fn entries(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// Legal book moves of the position with their weights
+    pub fn book_moves(&self, position: &Position) -> Vec<(Move, u16)> {
+        let entries = self.entries(polyglot_key(&position.board));
+        if entries.is_empty() {
+            return Vec::new();
+        }
+        let legal_moves = position.legal_moves();
+        entries.iter()
+            .filter_map(|entry| entry.decode_move(&position.board, &legal_moves).map(|mov| (mov, entry.weight)))
+            .collect()
+    }
+
+    /// Picks a book move with a probability proportional to its weight.
+    /// Moves with a weight of zero are never played.
+    pub fn choose_move(&self, position: &Position, rng: &mut impl Rng) -> Option<Move> {
+        let moves = self.book_moves(position);
+        let total_weight: u32 = moves.iter().map(|(_, weight)| *weight as u32).sum();
+        if total_weight == 0 {
+            return None;
+        }
+        let mut choice = rng.gen_range(0..total_weight);
+        for (mov, weight) in moves.iter() {
+            if choice < *weight as u32 {
+                return Some(*mov);
+            }
+            choice -= *weight as u32;
+        }
+        None
+    }
+}
+
+
+/// Games, wins and draws of a move, from the point of view of the side playing it
+#[derive(Clone, Copy, Default, Debug)]
+pub struct BookMoveStats {
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+}
+
+impl BookMoveStats {
+    /// Score between 0 and 1 for the side playing the move
+    pub fn score(&self) -> f32 {
+        (self.wins as f32 + self.draws as f32 / 2.0) / self.games.max(1) as f32
+    }
+}
+
+/// Builds an opening book from the moves and results of played games
+pub struct BookBuilder {
+    /// Moves after this many plies from the start of a game are ignored
+    pub max_ply: usize,
+    /// Moves played in fewer games are left out of the book
+    pub min_games: u32,
+    /// Moves scoring less than this for the side playing them are left out of the book
+    pub min_score: f32,
+    stats: HashMap<(u64, u16), BookMoveStats>,
+    num_games: usize,
+    num_skipped: usize,
+}
+
+impl BookBuilder {
+    pub fn new(max_ply: usize) -> Self {
+        Self {
+            max_ply,
+            min_games: 1,
+            min_score: 0.0,
+            stats: HashMap::new(),
+            num_games: 0,
+            num_skipped: 0,
+        }
+    }
+
+    pub fn num_games(&self) -> usize {
+        self.num_games
+    }
+
+    /// Number of games left out because they couldn't be parsed or replayed
+    pub fn num_skipped(&self) -> usize {
+        self.num_skipped
+    }
+
+    /// Number of different position and move pairs seen so far
+    pub fn num_moves(&self) -> usize {
+        self.stats.len()
+    }
+
+    /// Replays the mainline of a game and counts its moves. Games without a result are skipped,
+    /// returns whether the game was used.
+    pub fn add_game(&mut self, game: &PgnGame) -> Result<bool, PgnError> {
+        let white_score = match game.result() {
+            "1-0" => 2,
+            "0-1" => 0,
+            "1/2-1/2" => 1,
+            _ => return Ok(false),
+        };
+        let mut position = game.start_position()?;
+        for mov in game.mainline().into_iter().take(self.max_ply) {
+            let key = polyglot_key(&position.board);
+            let book_move = BookEntry::encode_move(&position.board, mov);
+            // Score of the side playing the move, 2 for a win and 1 for a draw
+            let score = if position.white_to_move() { white_score } else { 2 - white_score };
+
+            let stats = self.stats.entry((key, book_move)).or_default();
+            stats.games += 1;
+            stats.wins += (score == 2) as u32;
+            stats.draws += (score == 1) as u32;
+            position.make_move(mov);
+        }
+        self.num_games += 1;
+        Ok(true)
+    }
+
+    /// Adds every game of a PGN file, returns the number of games used.
+    /// Games that can't be parsed are skipped and counted in `num_skipped`.
+    pub fn add_pgn(&mut self, pgn: &str) -> usize {
+        let mut num_added = 0;
+        for game in parse_pgn_games(pgn) {
+            match game.and_then(|game| self.add_game(&game)) {
+                Ok(added) => num_added += added as usize,
+                Err(_) => self.num_skipped += 1,
+            }
+        }
+        num_added
+    }
+
+    pub fn move_stats(&self, key: u64, book_move: u16) -> Option<BookMoveStats> {
+        self.stats.get(&(key, book_move)).copied()
+    }
+
+    /// Weights are the points scored with a move (2 per win and 1 per draw),
+    /// scaled down when needed to fit the 16 bit weights of the book format
+    pub fn build(&self) -> OpeningBook {
+        let kept: Vec<(&(u64, u16), u32)> = self.stats.iter()
+            .filter(|(_, stats)| stats.games >= self.min_games && stats.score() >= self.min_score)
+            .map(|(key_move, stats)| (key_move, 2 * stats.wins + stats.draws))
+            .filter(|(_, points)| *points > 0)
+            .collect();
+        let max_points = kept.iter().map(|(_, points)| *points).max().unwrap_or(0);
+        let scale = (max_points as f64 / u16::MAX as f64).max(1.0);
+
+        OpeningBook::from_entries(kept.iter().map(|((key, mov), points)| BookEntry {
+            key: *key,
+            mov: *mov,
+            weight: ((*points as f64 / scale) as u16).max(1),
+            learn: 0,
+        }).collect())
+    }
+}
@@ -1,61 +1,71 @@
 pub mod ai_player;
 pub mod stats;
+pub mod book;
+pub mod time_manager;
+#[cfg(feature = "gui")]
+pub mod search_thread;
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
-use crate::state::{AppState, AppMode};
-use self::ai_player::*;
+#[cfg(feature = "gui")]
+use crate::{state::{AppState, AppMode}, ui::ingame_menu::DebugPositionLoaded};
+#[cfg(feature = "gui")]
+use self::{ai_player::*, search_thread::*};
+
+pub mod shared;
 
 pub mod v0;
 pub mod v1;
 pub mod v2;
 pub mod v3;
-pub mod v4;
 pub mod v5;
 pub mod v6;
 pub mod v7;
 pub mod v8;
 pub mod v9;
-pub mod v10;
 pub mod v11;
 pub mod v12;
+pub mod v13;
+
 
+#[cfg(feature = "gui")]
+fn load(
+    mut commands: Commands,
+) {
+    commands.insert_resource(SearchThread::default());
+}
 
+#[cfg(feature = "gui")]
 fn finalize(
     mut commands: Commands,
 ) {
     commands.insert_resource(NextState(Some(AppState::InGame)));
 }
 
+#[cfg(feature = "gui")]
 pub struct AIPlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for AIPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<SearchComplete>()
             .add_event::<BeginSearch>()
-            // .add_plugins(v5::AIPluginV5)
-            // .add_plugins(v6::AIPluginV6)
-            // .add_plugins(v7::AIPluginV7)
-            // .add_plugins(v8::AIPluginV8)
-            // .add_plugins(v9::AIPluginV9)
-            .add_plugins(v10::AIPluginV10)
-            .add_plugins(v11::AIPluginV11)
-            .add_plugins(v12::AIPluginV12)
             .add_systems(OnEnter(AppState::LoadAI), (
+                load,
                 finalize,
             ))
             .add_systems(Update, (
-                // v5::search::searcher::start_search,
-                // v6::search::searcher::start_search,
-                // v7::search::searcher::start_search,
-                // v8::search::searcher::start_search,
-                // v9::search::searcher::start_search,
-                v10::search::searcher::start_search,
-                v11::search::searcher::start_search,
-                v12::search::searcher::start_search,
+                start_search,
+                poll_search,
 
                 ai_begin_search,
                 ai_make_move,
             ).chain().run_if(in_state(AppState::InGame)).run_if(in_state(AppMode::GameHumanAI).or_else(in_state(AppMode::GameAIAI))))
+            // A search started for the loaded position must not be aborted along with the old one
+            .add_systems(Update, (
+                abort_search,
+            ).before(start_search).run_if(on_event::<DebugPositionLoaded>()))
+            .add_systems(OnExit(AppState::InGame), abort_search)
         ;
     }
 }
\ No newline at end of file
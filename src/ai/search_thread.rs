@@ -0,0 +1,131 @@
+use std::{sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}, thread::{self, JoinHandle}};
+
+use bevy::prelude::*;
+
+use crate::{board::board::Board, game::player::Player, engine::{Engine, registry::create_engine, position::Position, search::{SearchLimits, SearchResult}}, ai::stats::SearchInfo, ui::ingame_menu::CalcStatistics, tablebase::{syzygy::DEFAULT_SYZYGY_PATH, retrograde::DEFAULT_TABLEBASE_PATH}};
+use super::ai_player::{AIPlayer, AIVersion, BeginSearch, SearchComplete};
+
+
+/// Runs the engines on a worker thread so the app keeps rendering while the AI thinks.
+/// Every version gets its own engine the first time it searches, so both sides of a versus game keep their own state.
+#[derive(Resource, Default)]
+pub struct SearchThread {
+    /// Idle engines, the searching engine is moved into the worker thread
+    engines: Vec<Box<dyn Engine>>,
+    handle: Option<JoinHandle<(Box<dyn Engine>, SearchResult)>>,
+    stop_flag: Option<Arc<AtomicBool>>,
+    /// Latest completed iteration of the running search
+    progress: Arc<Mutex<Option<SearchInfo>>>,
+}
+
+impl SearchThread {
+    pub fn is_searching(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Stops the running search and throws away its result
+    pub fn abort(&mut self) {
+        if let (Some(handle), Some(stop_flag)) = (self.handle.take(), self.stop_flag.take()) {
+            stop_flag.store(true, Ordering::Relaxed);
+            let (engine, _) = handle.join().expect("Search thread panicked");
+            stop_flag.store(false, Ordering::Relaxed);
+            self.engines.push(engine);
+        }
+        *self.progress.lock().unwrap() = None;
+    }
+
+    fn take_engine(&mut self, version: AIVersion) -> Box<dyn Engine> {
+        match self.engines.iter().position(|engine| engine.version() == version) {
+            Some(idx) => self.engines.swap_remove(idx),
+            None => self.create_engine(version),
+        }
+    }
+
+    fn create_engine(&self, version: AIVersion) -> Box<dyn Engine> {
+        let mut engine = create_engine(version);
+        // Tablebases are optional, versions without them reject the options
+        let _ = engine.set_option("SyzygyPath", DEFAULT_SYZYGY_PATH);
+        let _ = engine.set_option("TablebasePath", DEFAULT_TABLEBASE_PATH);
+        let progress = self.progress.clone();
+        engine.set_info_callback(Box::new(move |info: &SearchInfo| {
+            *progress.lock().unwrap() = Some(info.clone());
+        }));
+        engine
+    }
+}
+
+/// Starts searching the board on the worker thread. A search that is still running was started from
+/// an earlier position, it is aborted and replaced by the new one.
+pub fn start_search(
+    mut search_thread: ResMut<SearchThread>,
+    mut begin_search_evr: EventReader<BeginSearch>,
+    mut player_query: Query<(&mut AIPlayer, &Player)>,
+    board: Res<Board>,
+) {
+    for begin_search_event in begin_search_evr.iter() {
+        if search_thread.is_searching() {
+            search_thread.abort();
+            // The AI of the other side would wait forever for its aborted search
+            for (mut ai, player_data) in player_query.iter_mut() {
+                if player_data.team != board.move_color {
+                    ai.searching = false;
+                }
+            }
+        }
+        let mut engine = search_thread.take_engine(begin_search_event.version);
+        engine.set_position(&Position { board: board.clone() });
+        let limits = SearchLimits {
+            think_time_ms: Some(begin_search_event.think_time),
+            clock: begin_search_event.clock,
+            ..Default::default()
+        };
+        search_thread.stop_flag = Some(engine.stop_flag());
+        search_thread.handle = Some(thread::spawn(move || {
+            let result = engine.go(limits);
+            (engine, result)
+        }));
+    }
+}
+
+/// Reports progress of the running search and sends `SearchComplete` once it finishes
+pub fn poll_search(
+    mut search_thread: ResMut<SearchThread>,
+    mut search_complete_evw: EventWriter<SearchComplete>,
+    mut calc_stats: ResMut<CalcStatistics>,
+    board: Res<Board>,
+) {
+    if let Some(info) = search_thread.progress.lock().unwrap().take() {
+        calc_stats.ai_depth = info.depth;
+        calc_stats.ai_pv_san = Position { board: board.clone() }.line_to_san(&info.pv).join(" ");
+        calc_stats.ai_pv = info.pv;
+        calc_stats.ai_eval = info.eval;
+        calc_stats.ai_think_time = info.time_ms;
+    }
+    if !search_thread.handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+        return;
+    }
+    let (engine, result) = search_thread.handle.take().unwrap().join().expect("Search thread panicked");
+    search_thread.stop_flag = None;
+    search_thread.engines.push(engine);
+    search_complete_evw.send(SearchComplete {
+        depth: result.depth,
+        chosen_move: result.best_move,
+        pv: result.pv,
+        eval: result.eval,
+        stats: result.stats,
+    });
+}
+
+/// Cancels the running search when the position it was started from is no longer on the board
+pub fn abort_search(
+    mut search_thread: ResMut<SearchThread>,
+    mut player_query: Query<&mut AIPlayer>,
+) {
+    if !search_thread.is_searching() {
+        return;
+    }
+    search_thread.abort();
+    for mut ai in player_query.iter_mut() {
+        ai.searching = false;
+    }
+}
@@ -0,0 +1,3 @@
+pub mod searcher;
+pub mod transpositions;
+pub mod move_ordering;
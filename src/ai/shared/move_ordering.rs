@@ -1,5 +1,4 @@
-use crate::{board::{moves::Move, board::Board, piece::Piece}, move_gen::bitboard::{utils::BitBoardUtils, bb::BitBoard}};
-use super::super::evaluation::eval::Evaluation;
+use crate::{board::{moves::Move, board::Board, piece::Piece}, move_gen::move_generator::MoveGenerator};
 
 pub struct KillerMove {
     pub move_a: Move,
@@ -24,6 +23,8 @@ pub struct MoveOrdering {
     pub killers: [KillerMove; Self::MAX_KILLER_MOVE_PLY],
     pub history: [[[i32; 64]; 64]; 2],
     move_scores: [i32; Self::MAX_MOVE_COUNT],
+    /// Values of pawn, knight, bishop, rook and queen that captures are ordered by, taken from the evaluation
+    piece_values: [i32; 5],
 }
 
 impl MoveOrdering {
@@ -40,9 +41,10 @@ impl MoveOrdering {
     const LOSING_CAPTURE_BIAS: i32 = 2 * Self::MILLION;
     const REGULAR_BIAS: i32 = 0;
 
-    pub fn new() -> Self {
+    pub fn new(piece_values: [i32; 5]) -> Self {
         Self {
             move_scores: [0; Self::MAX_MOVE_COUNT],
+            piece_values,
             killers: [KillerMove::NULL; Self::MAX_KILLER_MOVE_PLY],
             history: [[[0; 64]; 64]; 2],
         }
@@ -58,12 +60,12 @@ impl MoveOrdering {
         self.clear_killers();
     }
 
-    pub fn order_moves(&mut self, hash_move: Move, moves: &mut Vec<Move>, board: &Board, _bbutils: &BitBoardUtils, opp_attacks: BitBoard, opp_pawn_attacks: BitBoard, in_q_search: bool, ply: usize) {
-        // let opp_pieces = board.enemy_diagonal_sliders | board.enemy_orthogonal_sliders | board.piece_bitboards[Piece::new(Piece::KNIGHT | board.opponent_color).index()];
-        // let pawn_attacks = if board.white_to_move { bbutils.white_pawn_attacks } else { bbutils.black_pawn_attacks };
+    /// Sorts `moves` best first, `move_gen` must hold the attack maps of the position they were generated in
+    pub fn order_moves(&mut self, hash_move: Move, moves: &mut [Move], board: &Board, move_gen: &MoveGenerator, in_q_search: bool, ply: usize) {
+        let opp_attacks = move_gen.enemy_attack_map;
+        let opp_pawn_attacks = move_gen.enemy_pawn_attack_map;
 
-        for i in 0..moves.len() {
-            let mov = moves[i];
+        for (i, &mov) in moves.iter().enumerate() {
 
             // Highly favor best moves in previous iterations and shallower searches of iterative deepening
             if mov == hash_move {
@@ -78,13 +80,13 @@ impl MoveOrdering {
             let move_piece = board.square[start_sqr.index()];
             let move_ptype = move_piece.piece_type();
             let capture_ptype = board.square[target_sqr.index()].piece_type();
-            let is_capture = capture_ptype != Piece::NONE;
-            let flag = moves[i].move_flag();
-            let piece_value = Self::get_piece_value(move_ptype);
+            let is_capture = mov.is_capture(board);
+            let flag = mov.move_flag();
+            let piece_value = self.get_piece_value(move_ptype);
 
             if is_capture {
                 // Favor capturing higher value pieces with lower value pieces
-                let capture_material_data = Self::get_piece_value(capture_ptype) - piece_value;
+                let capture_material_data = self.get_piece_value(capture_ptype) - piece_value;
                 let opp_can_recapture = (opp_pawn_attacks | opp_attacks).contains_square(target_sqr.square());
 
                 // Punish moves where the opponent can recapture
@@ -122,28 +124,28 @@ impl MoveOrdering {
         // ! TODO: USE BETTER SORTING ALGORITHM (E.G. RADIX SORT, INTROSORT)
         Self::quicksort(moves, &mut self.move_scores, 0, moves.len() as isize - 1);
     }
-    const fn get_piece_value(ptype: u8) -> i32 {
+    const fn get_piece_value(&self, ptype: u8) -> i32 {
         match ptype {
-            Piece::QUEEN => Evaluation::QUEEN_VALUE,
-            Piece::ROOK => Evaluation::ROOK_VALUE,
-            Piece::BISHOP => Evaluation::BISHOP_VALUE,
-            Piece::KNIGHT => Evaluation::KNIGHT_VALUE,
-            Piece::PAWN => Evaluation::PAWN_VALUE,
+            Piece::QUEEN => self.piece_values[4],
+            Piece::ROOK => self.piece_values[3],
+            Piece::BISHOP => self.piece_values[2],
+            Piece::KNIGHT => self.piece_values[1],
+            Piece::PAWN => self.piece_values[0],
             _ => 0,
         }
     }
-    fn quicksort(moves: &mut Vec<Move>, scores: &mut [i32; Self::MAX_MOVE_COUNT], low: isize, high: isize) {
+    fn quicksort(moves: &mut [Move], scores: &mut [i32; Self::MAX_MOVE_COUNT], low: isize, high: isize) {
         if low <= high {
             let pivot = Self::partition(moves, scores, low, high);
             Self::quicksort(moves, scores, low, pivot - 1);
             Self::quicksort(moves, scores, pivot + 1, high);
         }
     }
-    fn partition(moves: &mut Vec<Move>, scores: &mut [i32; Self::MAX_MOVE_COUNT], low: isize, high: isize) -> isize {
+    fn partition(moves: &mut [Move], scores: &mut [i32; Self::MAX_MOVE_COUNT], low: isize, high: isize) -> isize {
         let pivot_score = scores[high as usize];
         let mut i = low - 1;
 
-        for j in low..=(high - 1) {
+        for j in low..high {
             if scores[j as usize] > pivot_score {
                 i += 1;
                 moves.swap(i as usize, j as usize);
@@ -153,6 +155,6 @@ impl MoveOrdering {
         moves.swap((i + 1) as usize, high as usize);
         scores.swap((i + 1) as usize, high as usize);
 
-        return i + 1;
+        i + 1
     }
 }
\ No newline at end of file
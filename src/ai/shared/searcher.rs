@@ -1,16 +1,38 @@
-use std::time::Instant;
+use std::{time::Instant, sync::{Arc, atomic::{AtomicBool, Ordering}}};
 
-use bevy::prelude::*;
-use crate::{board::{moves::Move, board::Board, zobrist::Zobrist, piece::Piece}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}, ai::{ai_player::{BeginSearch, SearchComplete, AIVersion}, stats::SearchStatistics}};
+use crate::{board::{moves::Move, board::Board, piece::Piece}, move_gen::move_generator::MoveGenerator};
+use crate::{ai::stats::SearchStatistics, engine::{legacy::LegacySearcher, search::SearchResult, tables::LookupTables}};
 
-use super::{super::evaluation::eval::Evaluation, transpositions::{TranspositionTable, EvaluationType}, move_ordering::MoveOrdering};
+use super::{transpositions::{TranspositionTable, EvaluationType}, move_ordering::MoveOrdering};
 
-#[derive(Resource)]
+/// Static evaluation of a version, from the perspective of the side to move
+pub type EvaluateFn = fn(&Board, &MoveGenerator, &LookupTables) -> i32;
+
+/// Place of a node in the search tree
+#[derive(Clone, Copy)]
+struct SearchNode {
+    depth_remaining: u8,
+    current_depth: u8,
+    prev_move: Move,
+    prev_was_capture: bool,
+    num_extensions: i32,
+}
+
+/// Alpha-beta searcher shared by V4 to V12. The versions differ in their evaluation and in
+/// the search features they had, which are switched off for the older ones
 pub struct Searcher {
     pub current_depth: i32,
     pub best_move_so_far: Move,
     pub best_eval_so_far: i32,
     pub max_think_time_ms: u32,
+    /// Turned off to play as V4, which searches moves in generation order
+    pub order_moves: bool,
+    /// Turned off to play as V7 and older, which evaluate the leaves without resolving captures
+    pub quiescence: bool,
+    /// Late move reductions, check and pawn push extensions and stopping once a mate is certain.
+    /// Turned off to play as V8 and older
+    pub reductions: bool,
+    evaluate: EvaluateFn,
     best_move_this_iter: Move,
     best_eval_this_iter: i32,
 
@@ -28,6 +50,7 @@ pub struct Searcher {
     search_total_time: Instant,
     current_iter_depth: i32,
     move_is_from_partial_search: bool,
+    stop_flag: Arc<AtomicBool>,
 }
 
 impl Searcher {
@@ -39,16 +62,55 @@ impl Searcher {
     const MAX_MATE_DEPTH: i32 = 1000;
     const MAX_EXTENSIONS: i32 = 16;
 
-    pub fn start_search(&mut self,
-        board: &mut Board, 
-        move_gen: &mut MoveGenerator,
-        precomp: &PrecomputedMoveData,
-        bbutils: &BitBoardUtils,
-        magic: &MagicBitBoards,
-        zobrist: &Zobrist,
-    ) {
+    /// Searcher with every feature, `piece_values` order captures and come from the version's evaluation
+    pub fn new(evaluate: EvaluateFn, piece_values: [i32; 5]) -> Self {
+        Self {
+            repetition_table: Vec::new(),
+            transposition_table: TranspositionTable::new(Self::TRANSPOSITION_TABLE_SIZE_MB),
+            move_ordering: MoveOrdering::new(piece_values),
+
+            current_depth: 0,
+            positions_evaled: 0,
+            best_eval_so_far: 0,
+            best_move_so_far: Move::NULL,
+            num_mates: 0,
+            num_cutoffs: 0,
+            num_transpositions: 0,
+            has_searched_one_move: false,
+            search_cancelled: false,
+            max_think_time_ms: 1000,
+            order_moves: true,
+            quiescence: true,
+            reductions: true,
+            evaluate,
+            best_eval_this_iter: 0,
+            best_move_this_iter: Move::NULL,
+            current_iter_depth: 0,
+            move_is_from_partial_search: false,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+
+            search_total_time: Instant::now(),
+        }
+    }
+
+    pub fn with_move_ordering(mut self, order_moves: bool) -> Self {
+        self.order_moves = order_moves;
+        self
+    }
+
+    pub fn with_quiescence(mut self, quiescence: bool) -> Self {
+        self.quiescence = quiescence;
+        self
+    }
+
+    pub fn with_reductions(mut self, reductions: bool) -> Self {
+        self.reductions = reductions;
+        self
+    }
+
+    pub fn start_search(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables) {
         let init_moves = move_gen.moves.clone();
-        if init_moves.len() == 0 {
+        if init_moves.is_empty() {
             self.best_move_so_far = Move::NULL;
             return;
         }
@@ -70,43 +132,26 @@ impl Searcher {
 
         self.search_total_time = Instant::now();
 
-        self.start_iterative_deepening(
-            board,
-            move_gen,
-            precomp,
-            bbutils,
-            magic,
-            zobrist,
-        );
+        self.start_iterative_deepening(board, move_gen, tables);
 
         if self.best_move_so_far == Move::NULL {
             self.best_move_so_far = init_moves[0];
         }
     }
 
-    fn start_iterative_deepening(
-        &mut self, 
-        board: &mut Board,
-        move_gen: &mut MoveGenerator,
-        precomp: &PrecomputedMoveData,
-        bbutils: &BitBoardUtils,
-        magic: &MagicBitBoards,
-        zobrist: &Zobrist, 
-    ) {
+    fn start_iterative_deepening(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables) {
         for search_depth in 1u8..=255u8 {
             self.has_searched_one_move = false;
             self.current_iter_depth = search_depth as i32;
-            self.search(
-                search_depth, 0, Self::NEG_INF, Self::POS_INF,
-                Move::NULL, false, 0,
-                board,
-                move_gen,
-                precomp,
-                bbutils,
-                magic,
-                zobrist,
-            );
-            
+            let root = SearchNode {
+                depth_remaining: search_depth,
+                current_depth: 0,
+                prev_move: Move::NULL,
+                prev_was_capture: false,
+                num_extensions: 0,
+            };
+            self.search(root, Self::NEG_INF, Self::POS_INF, board, move_gen, tables);
+
             if self.search_cancelled {
                 if self.has_searched_one_move {
                     self.best_move_so_far = self.best_move_this_iter;
@@ -122,25 +167,18 @@ impl Searcher {
                 self.best_eval_this_iter = Self::NEG_INF;
                 self.best_move_this_iter = Move::NULL;
 
-                if Self::is_mate_score(self.best_eval_so_far) && Self::num_ply_in_mate(self.best_eval_so_far) <= search_depth as i32 {
+                if self.reductions && Self::is_mate_score(self.best_eval_so_far) && Self::num_ply_in_mate(self.best_eval_so_far) <= search_depth as i32 {
                     break;
                 }
             }
         }
     }
-    
-    fn search(
-        &mut self, depth_remaining: u8, current_depth: u8, mut alpha: i32, mut beta: i32,
-        prev_move: Move, prev_was_capture: bool, num_extensions: i32,
-        board: &mut Board,
-        move_gen: &mut MoveGenerator,
-        precomp: &PrecomputedMoveData,
-        bbutils: &BitBoardUtils,
-        magic: &MagicBitBoards,
-        zobrist: &Zobrist,
-    ) -> i32 {
-        // Cancel search if over max think time
-        if Instant::now().duration_since(self.search_total_time).as_millis() as u32 > self.max_think_time_ms {
+
+    fn search(&mut self, node: SearchNode, mut alpha: i32, mut beta: i32, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables) -> i32 {
+        let SearchNode { depth_remaining, current_depth, prev_move, prev_was_capture, num_extensions } = node;
+        // Cancel search if over max think time or stopped from another thread
+        if Instant::now().duration_since(self.search_total_time).as_millis() as u32 > self.max_think_time_ms
+            || self.stop_flag.load(Ordering::Relaxed) {
             self.search_cancelled = true;
             return 0;
         }
@@ -171,17 +209,23 @@ impl Searcher {
 
         // If leaf node is reached, evaluate the board
         if depth_remaining == 0 {
-            return self.quiescence_search(alpha, beta, board, move_gen, precomp, bbutils, magic, zobrist);
+            if !self.quiescence {
+                self.positions_evaled += 1;
+                return (self.evaluate)(board, move_gen, tables);
+            }
+            return self.quiescence_search(alpha, beta, board, move_gen, tables);
         };
 
-        move_gen.generate_moves(board, precomp, bbutils, magic, false);
+        move_gen.generate_moves(board, &tables.precomp, &tables.bbutils, &tables.magic, false);
         let mut moves = move_gen.moves.clone();
-        let tt_stored_move = self.transposition_table.get_stored_move(board);
-        let prev_best_move = if current_depth == 0 { self.best_move_so_far } else { match tt_stored_move { None => Move::NULL, Some(mov) => mov } };
-        self.move_ordering.order_moves(prev_best_move, &mut moves, board, bbutils, move_gen.enemy_attack_map, move_gen.enemy_pawn_attack_map, false, current_depth as usize);
+        if self.order_moves {
+            let tt_stored_move = self.transposition_table.get_stored_move(board);
+            let prev_best_move = if current_depth == 0 { self.best_move_so_far } else { tt_stored_move.unwrap_or(Move::NULL) };
+            self.move_ordering.order_moves(prev_best_move, &mut moves, board, move_gen, false, current_depth as usize);
+        }
 
         // Check if position is terminal
-        if moves.len() == 0 {
+        if moves.is_empty() {
             if move_gen.in_check() {
                 self.num_mates += 1;
                 // Favor faster mates
@@ -202,63 +246,41 @@ impl Searcher {
         let mut best_move_this_position = Move::NULL;
 
         // Loop through legal moves
-        for (i, mov) in moves.iter().enumerate() {
-            let captured_ptype = board.square[mov.target().index()].piece_type();
-            let is_capture = captured_ptype != Piece::NONE;
-            board.make_move(*mov, true, zobrist);
+        for (i, &mov) in moves.iter().enumerate() {
+            let is_capture = mov.is_capture(board);
+            board.make_move(mov, true, &tables.zobrist);
 
             let mut extensions = 0;
-            if num_extensions < Self::MAX_EXTENSIONS {
+            if self.reductions && num_extensions < Self::MAX_EXTENSIONS {
                 let moved_ptype = board.square[mov.target().index()].piece_type();
                 let target_rank = mov.target().rank();
-                if board.in_check(magic, bbutils) {
-                    extensions = 1;
-                } else if moved_ptype == Piece::PAWN && (target_rank == 1) || (target_rank == 6) {
+                if board.in_check(&tables.magic, &tables.bbutils) || (moved_ptype == Piece::PAWN && target_rank == 1) || target_rank == 6 {
                     extensions = 1;
                 }
             }
+            let child = SearchNode {
+                depth_remaining: depth_remaining - 1,
+                current_depth: current_depth + 1,
+                prev_move: mov,
+                prev_was_capture: is_capture,
+                num_extensions,
+            };
             let mut needs_full_search = true;
             let mut eval = 0;
 
-            if i >= 3 && extensions == 0 && depth_remaining >= 3 && !is_capture {
-                eval = -self.search(
-                    depth_remaining - 1 - 1,
-                    current_depth + 1,
-                    -alpha - 1,
-                    -alpha,
-                    mov.clone(),
-                    is_capture,
-                    num_extensions, 
-                    board, 
-                    move_gen,
-                    precomp,
-                    bbutils,
-                    magic,
-                    zobrist,
-                );
+            if self.reductions && i >= 3 && extensions == 0 && depth_remaining >= 3 && !is_capture {
+                let reduced = SearchNode { depth_remaining: depth_remaining - 2, ..child };
+                eval = -self.search(reduced, -alpha - 1, -alpha, board, move_gen, tables);
                 needs_full_search = eval > alpha;
             }
 
             if needs_full_search {
                 // Negate evaluation -- A bad position for the opponent is good for us and vice versa
-                eval = -self.search(
-                    depth_remaining - 1 + extensions,
-                    current_depth + 1,
-                    -beta,
-                    -alpha,
-                    mov.clone(), 
-                    is_capture,
-                    num_extensions + extensions as i32,
-                    board,
-                    move_gen, 
-                    precomp,
-                    bbutils,
-                    magic,
-                    zobrist,
-                );
+                let extended = SearchNode { depth_remaining: depth_remaining - 1 + extensions, num_extensions: num_extensions + extensions as i32, ..child };
+                eval = -self.search(extended, -beta, -alpha, board, move_gen, tables);
             }
 
-            board.unmake_move(*mov, true);
+            board.unmake_move(mov, true);
             // Exit early if search is cancelled
             if self.search_cancelled {
                 return 0;
@@ -267,12 +289,12 @@ impl Searcher {
             // Beta cutoff / Fail high
             if eval >= beta {
                 // Very good move but not the best, store as lower bound
-                self.transposition_table.store_evaluation(depth_remaining, current_depth, beta, EvaluationType::LowerBound, mov.clone(), board);
-                
+                self.transposition_table.store_evaluation(depth_remaining, current_depth, beta, EvaluationType::LowerBound, mov, board);
+
                 if !is_capture {
                     // Favor killer moves (moves that cause branches to be pruned) when ordering moves
                     if current_depth < MoveOrdering::MAX_KILLER_MOVE_PLY as u8 {
-                        self.move_ordering.killers[current_depth as usize].add(mov.clone());
+                        self.move_ordering.killers[current_depth as usize].add(mov);
                     }
 
                     let history_score = depth_remaining as i32 * depth_remaining as i32;
@@ -290,11 +312,11 @@ impl Searcher {
             // New best move for this position
             if eval > alpha {
                 evaluation_bound = EvaluationType::Exact;
-                best_move_this_position = mov.clone();
+                best_move_this_position = mov;
                 alpha = eval;
                 if current_depth == 0 {
                     self.best_eval_this_iter = eval;
-                    self.best_move_this_iter = *mov;
+                    self.best_move_this_iter = mov;
                     self.has_searched_one_move = true;
                 }
             }
@@ -305,21 +327,13 @@ impl Searcher {
         }
         self.transposition_table.store_evaluation(depth_remaining, current_depth, alpha, evaluation_bound, best_move_this_position, board);
 
-        return alpha;
+        alpha
     }
 
-    fn quiescence_search(
-        &mut self, mut alpha: i32, beta: i32,
-        board: &mut Board,
-        move_gen: &mut MoveGenerator,
-        precomp: &PrecomputedMoveData,
-        bbutils: &BitBoardUtils,
-        magic: &MagicBitBoards,
-        zobrist: &Zobrist,
-    ) -> i32 {
+    fn quiescence_search(&mut self, mut alpha: i32, beta: i32, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables) -> i32 {
         if self.search_cancelled { return 0; }
 
-        let mut eval = Evaluation::evaluate(board);
+        let mut eval = (self.evaluate)(board, move_gen, tables);
         self.positions_evaled += 1;
 
         if eval > beta {
@@ -330,14 +344,13 @@ impl Searcher {
             alpha = eval;
         }
 
-        move_gen.generate_moves(board, precomp, bbutils, magic, true);
+        move_gen.generate_moves(board, &tables.precomp, &tables.bbutils, &tables.magic, true);
         let mut moves = move_gen.moves.clone();
-        self.move_ordering.order_moves(Move::NULL, &mut moves, board, bbutils, move_gen.enemy_attack_map, move_gen.enemy_pawn_attack_map, true, 0);
-        for mov in moves.iter() {
-            if board.square[mov.start().index()].piece_type() == Piece::NONE { println!("null move"); }
-            board.make_move(mov.clone(), true, zobrist);
-            eval = -self.quiescence_search(-beta, -alpha, board, move_gen, precomp, bbutils, magic, zobrist);
-            board.unmake_move(mov.clone(), true);
+        self.move_ordering.order_moves(Move::NULL, &mut moves, board, move_gen, true, 0);
+        for &mov in moves.iter() {
+            board.make_move(mov, true, &tables.zobrist);
+            eval = -self.quiescence_search(-beta, -alpha, board, move_gen, tables);
+            board.unmake_move(mov, true);
 
             if eval >= beta {
                 self.num_cutoffs += 1;
@@ -347,85 +360,42 @@ impl Searcher {
                 alpha = eval;
             }
         }
-        return alpha;
+        alpha
     }
 
     pub fn is_mate_score(score: i32) -> bool {
         if score == i32::MIN { return false; };
-        return score.abs() > Self::MATE_SCORE - Self::MAX_MATE_DEPTH;
+        score.abs() > Self::MATE_SCORE - Self::MAX_MATE_DEPTH
     }
     pub fn num_ply_in_mate(score: i32) -> i32 {
-        return Self::MATE_SCORE - score.abs();
+        Self::MATE_SCORE - score.abs()
     }
 }
 
-impl Default for Searcher {
-    fn default() -> Self {
-        Self {
-            repetition_table: Vec::new(),
-            transposition_table: TranspositionTable::new(Self::TRANSPOSITION_TABLE_SIZE_MB),
-            move_ordering: MoveOrdering::new(),
-
-            current_depth: 0,
-            positions_evaled: 0,
-            best_eval_so_far: 0,
-            best_move_so_far: Move::NULL,
-            num_mates: 0,
-            num_cutoffs: 0,
-            num_transpositions: 0,
-            has_searched_one_move: false,
-            search_cancelled: false,
-            max_think_time_ms: 1000,
-            best_eval_this_iter: 0,
-            best_move_this_iter: Move::NULL,
-            current_iter_depth: 0,
-            move_is_from_partial_search: false,
-
-            search_total_time: Instant::now(),
-        }
+impl LegacySearcher for Searcher {
+    fn search_board(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables, think_time_ms: u32, stop_flag: &Arc<AtomicBool>) {
+        self.max_think_time_ms = think_time_ms;
+        self.stop_flag = stop_flag.clone();
+        self.start_search(board, move_gen, tables);
     }
-}
 
-pub fn start_search(
-    mut searcher: ResMut<Searcher>,
-    mut begin_search_evr: EventReader<BeginSearch>,
-    mut search_complete_evw: EventWriter<SearchComplete>,
-
-    mut board: ResMut<Board>,
-    mut move_gen: ResMut<MoveGenerator>,
-    precomp: Res<PrecomputedMoveData>,
-    bbutils: Res<BitBoardUtils>,
-    magic: Res<MagicBitBoards>,
-    zobrist: Res<Zobrist>,
-) {
-    for begin_search_event in begin_search_evr.iter() {
-        if begin_search_event.version != AIVersion::V10 {
-            continue;
-        }
-        let time_start = std::time::Instant::now();
-        searcher.max_think_time_ms = begin_search_event.think_time;
-        searcher.start_search(
-            board.as_mut(),
-            move_gen.as_mut(),
-            precomp.as_ref(),
-            bbutils.as_ref(),
-            magic.as_ref(),
-            zobrist.as_ref(),
-        );
-        let think_time = std::time::Instant::now().duration_since(time_start).as_millis();
-        search_complete_evw.send(SearchComplete {
-            depth: searcher.current_depth,
-            chosen_move: searcher.best_move_so_far,
-            eval: searcher.best_eval_so_far,
+    fn result(&self) -> SearchResult {
+        SearchResult {
+            best_move: self.best_move_so_far,
+            pv: vec![self.best_move_so_far],
+            eval: self.best_eval_so_far,
+            depth: self.current_depth,
             stats: SearchStatistics {
-                num_position_evals: searcher.positions_evaled,
-                num_cutoffs: searcher.num_cutoffs,
-                num_transpositions: searcher.num_transpositions,
-                think_time_ms: think_time as u32,
+                num_position_evals: self.positions_evaled,
+                num_cutoffs: self.num_cutoffs,
+                num_transpositions: self.num_transpositions,
                 num_checks: 0,
-                num_mates: searcher.num_mates,
+                num_mates: self.num_mates,
                 is_book: false,
-            }
-        });
+                hashfull: 0,
+                tb_hits: 0,
+                ..Default::default()
+            },
+        }
     }
 }
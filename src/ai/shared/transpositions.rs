@@ -37,35 +37,29 @@ impl TranspositionTable {
         }
     }
     pub fn clear(&mut self) {
-        self.entries.clear();
+        self.entries.fill(None);
     }
     pub fn index(&self, board: &Board) -> usize {
         (board.current_state.zobrist_key % self.count) as usize
     }
     pub fn get_stored_move(&self, board: &Board) -> Option<Move> {
-        if let Some(entry) = &self.entries[self.index(board)] {
-            Some(entry.mov)
-        } else {
-            None
-        }
+        self.entries[self.index(board)].as_ref().map(|entry| entry.mov)
     }
     pub fn get_evaluation(&self, depth_remaining: u8, current_depth: u8, alpha: i32, beta: i32, board: &Board) -> Option<i32> {
         if !self.enabled {
             return None;
         }
         if let Some(entry) = &self.entries[self.index(board)] {
-            if entry.key == board.current_state.zobrist_key {
-                if entry.depth >= depth_remaining {
-                    let corrected_score = Self::correct_retrieved_mate_score(entry.value, current_depth);
-                    if entry.node_type == EvaluationType::Exact {
-                        return Some(corrected_score);
-                    }
-                    if entry.node_type == EvaluationType::UpperBound && corrected_score <= alpha {
-                        return Some(corrected_score);
-                    }
-                    if entry.node_type == EvaluationType::LowerBound && corrected_score >= beta {
-                        return Some(corrected_score);
-                    }
+            if entry.key == board.current_state.zobrist_key && entry.depth >= depth_remaining {
+                let corrected_score = Self::correct_retrieved_mate_score(entry.value, current_depth);
+                if entry.node_type == EvaluationType::Exact {
+                    return Some(corrected_score);
+                }
+                if entry.node_type == EvaluationType::UpperBound && corrected_score <= alpha {
+                    return Some(corrected_score);
+                }
+                if entry.node_type == EvaluationType::LowerBound && corrected_score >= beta {
+                    return Some(corrected_score);
                 }
             }
         }
@@ -90,17 +84,17 @@ impl TranspositionTable {
             let sign = score.signum();
             return (score * sign + current_depth as i32) * sign;
         }
-        return score;
+        score
     }
     fn correct_retrieved_mate_score(score: i32, current_depth: u8) -> i32 {
         if Searcher::is_mate_score(score) {
             let sign = score.signum();
             return (score * sign - current_depth as i32) * sign;
         }
-        return score;
+        score
     }
 
     pub fn get_entry(&self, key: u64) -> Option<TranspositionEntry> {
-        return self.entries[(key % self.entries.len() as u64) as usize].clone();
+        self.entries[(key % self.entries.len() as u64) as usize].clone()
     }
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+use crate::board::moves::Move;
+
 pub struct SearchStatistics {
     pub num_position_evals: u32,
     pub num_cutoffs: i32,
@@ -6,6 +8,10 @@ pub struct SearchStatistics {
     pub num_mates: i32,
     pub num_transpositions: i32,
     pub is_book: bool,
+    /// Permille of the transposition table filled by the last search
+    pub hashfull: u32,
+    /// Positions found in the endgame tablebases
+    pub tb_hits: u32,
 }
 
 impl Default for SearchStatistics {
@@ -18,6 +24,24 @@ impl Default for SearchStatistics {
             num_mates: 0,
             num_transpositions: 0,
             is_book: false,
+            hashfull: 0,
+            tb_hits: 0,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Progress report for a single completed iteration of iterative deepening
+#[derive(Clone)]
+pub struct SearchInfo {
+    pub depth: i32,
+    pub eval: i32,
+    pub best_move: Move,
+    /// Principal variation, starting with `best_move`
+    pub pv: Vec<Move>,
+    pub num_nodes: u32,
+    pub time_ms: u32,
+    pub hashfull: u32,
+    pub tb_hits: u32,
+}
+
+pub type IterationCallback = Box<dyn Fn(&SearchInfo) + Send + Sync>;
@@ -0,0 +1,117 @@
+use std::time::Instant;
+
+use crate::board::moves::Move;
+
+
+/// Time left on the clock of the side to move
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct ClockTime {
+    pub time_left_ms: u32,
+    pub increment_ms: u32,
+    /// Moves to play before the clock is topped up again, `None` for sudden death
+    pub moves_to_go: Option<u32>,
+}
+
+/// Splits the time on the clock into a soft and a hard limit for a single move.
+/// The soft limit is stretched or shortened after every iteration depending on how settled the search is,
+/// the hard limit is never exceeded.
+#[derive(Clone, Debug)]
+pub struct TimeManager {
+    soft_limit_ms: u32,
+    hard_limit_ms: u32,
+    start_time: Instant,
+    last_iteration_start_ms: u32,
+    best_move: Move,
+    /// Number of iterations in a row that ended on the same best move
+    best_move_stability: usize,
+    last_eval: Option<i32>,
+}
+
+impl TimeManager {
+    /// Kept back for the time it takes to send the move and for the clock to switch
+    pub const MOVE_OVERHEAD_MS: u32 = 50;
+    const DEFAULT_MOVES_TO_GO: u32 = 30;
+    /// The hard limit is at most this many times the target time for a move
+    const HARD_LIMIT_FACTOR: u32 = 4;
+    /// Each iteration is expected to take about this many times longer than the previous one
+    const BRANCHING_FACTOR: u32 = 2;
+
+    /// Soft limit scale in percent, by the number of iterations the best move stayed the same
+    const STABILITY_SCALE: [u32; 5] = [150, 120, 100, 85, 70];
+    /// Score drops from the previous iteration, in centipawns, and how much they extend the soft limit in percent
+    const SCORE_DROP_SCALE: [(i32, u32); 2] = [(80, 160), (30, 125)];
+
+    pub fn new(clock: ClockTime) -> Self {
+        let available = clock.time_left_ms.saturating_sub(Self::MOVE_OVERHEAD_MS).max(1);
+        let moves_to_go = clock.moves_to_go.unwrap_or(Self::DEFAULT_MOVES_TO_GO).max(1);
+        let target = available / moves_to_go + clock.increment_ms * 3 / 4;
+
+        let soft_limit_ms = target.min(available / 2).max(1);
+        let hard_limit_ms = (target * Self::HARD_LIMIT_FACTOR).min(available * 4 / 5).max(soft_limit_ms);
+        Self {
+            soft_limit_ms,
+            hard_limit_ms,
+            start_time: Instant::now(),
+            last_iteration_start_ms: 0,
+            best_move: Move::NULL,
+            best_move_stability: 0,
+            last_eval: None,
+        }
+    }
+
+    pub fn soft_limit_ms(&self) -> u32 {
+        self.soft_limit_ms
+    }
+
+    pub fn hard_limit_ms(&self) -> u32 {
+        self.hard_limit_ms
+    }
+
+    /// Restarts the timer at the beginning of a search
+    pub fn start(&mut self) {
+        self.start_time = Instant::now();
+        self.last_iteration_start_ms = 0;
+        self.best_move = Move::NULL;
+        self.best_move_stability = 0;
+        self.last_eval = None;
+    }
+
+    pub fn elapsed_ms(&self) -> u32 {
+        self.start_time.elapsed().as_millis() as u32
+    }
+
+    /// Soft limit after scaling it by the stability of the best move and the change of the score
+    fn adjusted_soft_limit_ms(&self, eval_drop: i32) -> u32 {
+        let stability_scale = Self::STABILITY_SCALE[self.best_move_stability.min(Self::STABILITY_SCALE.len() - 1)];
+        let drop_scale = Self::SCORE_DROP_SCALE.iter()
+            .find(|(drop, _)| eval_drop >= *drop)
+            .map_or(100, |(_, scale)| *scale);
+        let soft_limit_ms = self.soft_limit_ms as u64 * stability_scale as u64 * drop_scale as u64 / 10000;
+        (soft_limit_ms as u32).min(self.hard_limit_ms)
+    }
+
+    /// Called after every completed iteration, returns whether there is time to search one more
+    pub fn iteration_complete(&mut self, best_move: Move, eval: i32) -> bool {
+        self.iteration_complete_at(best_move, eval, self.elapsed_ms())
+    }
+
+    /// Same as `iteration_complete` with the time elapsed since the start of the search given explicitly
+    pub fn iteration_complete_at(&mut self, best_move: Move, eval: i32, elapsed_ms: u32) -> bool {
+        if best_move == self.best_move {
+            self.best_move_stability += 1;
+        } else {
+            self.best_move = best_move;
+            self.best_move_stability = 0;
+        }
+        let eval_drop = self.last_eval.map_or(0, |last_eval| last_eval - eval);
+        self.last_eval = Some(eval);
+
+        let iteration_ms = elapsed_ms - self.last_iteration_start_ms.min(elapsed_ms);
+        self.last_iteration_start_ms = elapsed_ms;
+        if elapsed_ms >= self.adjusted_soft_limit_ms(eval_drop) {
+            return false;
+        }
+        // An iteration cut off by the hard limit is mostly wasted, so don't start one that can't finish
+        elapsed_ms + iteration_ms * Self::BRANCHING_FACTOR <= self.hard_limit_ms
+    }
+}
@@ -1,10 +1,11 @@
-use bevy::prelude::*;
+use std::sync::{Arc, atomic::AtomicBool};
+
 use rand::Rng;
 
-use crate::{board::moves::Move, move_gen::move_generator::MoveGenerator, ai::{ai_player::{BeginSearch, SearchComplete, AIVersion}, stats::SearchStatistics}};
+use crate::{board::{moves::Move, board::Board}, move_gen::move_generator::MoveGenerator};
+use crate::{ai::stats::SearchStatistics, engine::{legacy::LegacySearcher, search::SearchResult, tables::LookupTables}};
 
 
-#[derive(Resource)]
 pub struct Searcher {
     pub current_depth: i32,
     pub best_move_so_far: Move,
@@ -35,33 +36,18 @@ impl Default for Searcher {
     }
 }
 
-pub fn start_search(
-    mut searcher: ResMut<Searcher>,
-    mut begin_search_evr: EventReader<BeginSearch>,
-    mut search_complete_evw: EventWriter<SearchComplete>,
+impl LegacySearcher for Searcher {
+    fn search_board(&mut self, _board: &mut Board, move_gen: &mut MoveGenerator, _tables: &LookupTables, _think_time_ms: u32, _stop_flag: &Arc<AtomicBool>) {
+        self.start_search(move_gen);
+    }
 
-    mut move_gen: ResMut<MoveGenerator>,
-) {
-    for begin_search_event in begin_search_evr.iter() {
-        if begin_search_event.version != AIVersion::V0 {
-            continue;
-        }
-        searcher.start_search(
-            move_gen.as_mut(),
-        );
-        search_complete_evw.send(SearchComplete {
-            depth: 0,
-            chosen_move: searcher.best_move_so_far,
+    fn result(&self) -> SearchResult {
+        SearchResult {
+            best_move: self.best_move_so_far,
+            pv: vec![self.best_move_so_far],
             eval: 0,
-            stats: SearchStatistics {
-                num_position_evals: 0,
-                num_cutoffs: 0,
-                think_time_ms: 0,
-                num_checks: 0,
-                num_mates: 0,
-                is_book: false,
-                ..default()
-            }
-        });
+            depth: 0,
+            stats: SearchStatistics::default(),
+        }
     }
 }
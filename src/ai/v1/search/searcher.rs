@@ -1,9 +1,10 @@
-use bevy::prelude::*;
-use crate::{board::{moves::Move, board::Board, zobrist::Zobrist}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}, ai::{ai_player::{BeginSearch, SearchComplete, AIVersion}, stats::SearchStatistics}};
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+
+use crate::{board::{moves::Move, board::Board, zobrist::Zobrist}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}};
+use crate::{ai::stats::SearchStatistics, engine::{legacy::LegacySearcher, search::SearchResult, tables::LookupTables}};
 
 use super::super::evaluation::eval::Evaluation;
 
-#[derive(Resource)]
 pub struct Searcher {
     pub current_depth: i32,
     pub best_move_so_far: Move,
@@ -12,6 +13,7 @@ pub struct Searcher {
     positions_evaled: u32,
     num_mates: i32,
     has_searched_one_move: bool,
+    stop_flag: Arc<AtomicBool>,
 }
 
 impl Searcher {
@@ -34,6 +36,7 @@ impl Searcher {
             self.best_move_so_far = Move::NULL;
             return;
         }
+        let first_move = init_moves[0];
 
         self.positions_evaled = 0;
         self.best_eval_so_far = 0.0;
@@ -52,6 +55,11 @@ impl Searcher {
             zobrist,
         );
 
+        // Stopped before the first move was searched
+        if self.best_move_so_far == Move::NULL {
+            self.best_move_so_far = first_move;
+        }
+
         // board.white_to_move = white_to_move;
         // board.move_color = if white_to_move { Piece::WHITE } else { Piece::BLACK };
         // board.opponent_color = if white_to_move { Piece::BLACK } else { Piece::WHITE };
@@ -67,6 +75,9 @@ impl Searcher {
         magic: &MagicBitBoards,
         zobrist: &Zobrist,
     ) -> f32 {
+        if self.stop_flag.load(Ordering::Relaxed) {
+            return 0.0;
+        }
         if depth_remaining == 0 {
             self.positions_evaled += 1;
             return Evaluation::evaluate(board);
@@ -103,6 +114,10 @@ impl Searcher {
                 zobrist,
             );
             board.unmake_move(*mov, true);
+            // The evaluation of a stopped search is meaningless
+            if self.stop_flag.load(Ordering::Relaxed) {
+                return 0.0;
+            }
             if eval > best_eval {
                 best_eval = eval;
                 // best_move = *mov;
@@ -126,49 +141,31 @@ impl Default for Searcher {
             positions_evaled: 0,
             num_mates: 0,
             has_searched_one_move: false,
+            stop_flag: Arc::new(AtomicBool::new(false)),
         }
     }
 }
 
-pub fn start_search(
-    mut searcher: ResMut<Searcher>,
-    mut begin_search_evr: EventReader<BeginSearch>,
-    mut search_complete_evw: EventWriter<SearchComplete>,
-
-    mut board: ResMut<Board>,
-    mut move_gen: ResMut<MoveGenerator>,
-    precomp: Res<PrecomputedMoveData>,
-    bbutils: Res<BitBoardUtils>,
-    magic: Res<MagicBitBoards>,
-    zobrist: Res<Zobrist>,
-) {
-    for begin_search_event in begin_search_evr.iter() {
-        if begin_search_event.version != AIVersion::V1 {
-            continue;
-        }
-        let time_start = std::time::Instant::now();
-        searcher.start_search(
-            board.as_mut(),
-            move_gen.as_mut(),
-            precomp.as_ref(),
-            bbutils.as_ref(),
-            magic.as_ref(),
-            zobrist.as_ref(),
-        );
-        let think_time = std::time::Instant::now().duration_since(time_start).as_millis();
-        search_complete_evw.send(SearchComplete {
+impl LegacySearcher for Searcher {
+    fn search_board(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables, _think_time_ms: u32, stop_flag: &Arc<AtomicBool>) {
+        self.stop_flag = stop_flag.clone();
+        self.start_search(board, move_gen, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+    }
+
+    fn result(&self) -> SearchResult {
+        SearchResult {
+            best_move: self.best_move_so_far,
+            pv: vec![self.best_move_so_far],
+            eval: self.best_eval_so_far as i32,
             depth: Searcher::SEARCH_DEPTH,
-            chosen_move: searcher.best_move_so_far,
-            eval: searcher.best_eval_so_far as i32,
             stats: SearchStatistics {
-                num_position_evals: searcher.positions_evaled,
+                num_position_evals: self.positions_evaled,
                 num_cutoffs: 0,
-                think_time_ms: think_time as u32,
                 num_checks: 0,
-                num_mates: searcher.num_mates,
+                num_mates: self.num_mates,
                 is_book: false,
-                ..default()
-            }
-        });
+                ..Default::default()
+            },
+        }
     }
 }
@@ -1,9 +0,0 @@
-pub mod eval;
-pub mod utils;
-pub mod perspective;
-
-pub mod helpers;
-pub mod material;
-pub mod passed_pawns;
-pub mod pawns;
-pub mod imbalance;
\ No newline at end of file
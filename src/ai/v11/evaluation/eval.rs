@@ -11,14 +11,30 @@ use super::{
 
 
 
+/// Evaluation terms that can be turned off, V10 is this evaluation without imbalance and mobility
+#[derive(Clone, Copy)]
+pub struct EvalFeatures {
+    pub imbalance: bool,
+    pub mobility: bool,
+}
+
+impl Default for EvalFeatures {
+    fn default() -> Self {
+        Self {
+            imbalance: true,
+            mobility: true,
+        }
+    }
+}
+
 pub struct Evaluation {}
 
 impl Evaluation {
     // Performs evaluation of the board
     // A positive value means a better position for the player to move
-    pub fn evaluate(board: &Board) -> i32 {
-        let mg = Self::midgame_eval(board, false);
-        let eg = Self::endgame_eval(board, false);
+    pub fn evaluate(board: &Board, features: &EvalFeatures) -> i32 {
+        let mg = Self::midgame_eval(board, features, false);
+        let eg = Self::endgame_eval(board, features, false);
         let p = Self::phase(board);
         let rule50 = Self::rule50(board);
 
@@ -33,24 +49,32 @@ impl Evaluation {
     }
 
     // Evaluates position during opening and middle game stages
-    fn midgame_eval(board: &Board, _nowinnable: bool) -> i32 {
+    fn midgame_eval(board: &Board, features: &EvalFeatures, _nowinnable: bool) -> i32 {
         let mut eval = 0;
 
         eval += piece_value_bonus(board, Perspective::White, true) - piece_value_bonus(board, Perspective::Black, true);
         eval += psqt_bonus(board, Perspective::White, true) - psqt_bonus(board, Perspective::Black, true);
-        eval += imbalance_total(board);
-        eval += mobility_bonus(board, Perspective::White, true) - mobility_bonus(board, Perspective::Black, true);
+        if features.imbalance {
+            eval += imbalance_total(board);
+        }
+        if features.mobility {
+            eval += mobility_bonus(board, Perspective::White, true) - mobility_bonus(board, Perspective::Black, true);
+        }
 
         return eval;
     }
 
-    fn endgame_eval(board: &Board, _nowinnable: bool) -> i32 {
+    fn endgame_eval(board: &Board, features: &EvalFeatures, _nowinnable: bool) -> i32 {
         let mut eval = 0;
 
         eval += piece_value_bonus(board, Perspective::White, false) - piece_value_bonus(board, Perspective::Black, false);
         eval += psqt_bonus(board, Perspective::White, false) - psqt_bonus(board, Perspective::Black, false);
-        eval += imbalance_total(board);
-        eval += mobility_bonus(board, Perspective::White, false) - mobility_bonus(board, Perspective::Black, false);
+        if features.imbalance {
+            eval += imbalance_total(board);
+        }
+        if features.mobility {
+            eval += mobility_bonus(board, Perspective::White, false) - mobility_bonus(board, Perspective::Black, false);
+        }
 
         return eval;
     }
@@ -72,7 +96,7 @@ impl Evaluation {
         let mut sf = 64;
         let eg_eval = match eg {
             Some(eval) => eval,
-            None => Self::endgame_eval(board, false)
+            None => Self::endgame_eval(board, &EvalFeatures::default(), false)
         };
         let perspective = if eg_eval > 0 { Perspective::White } else { Perspective::Black };
         let (pc_w, pc_b) = (pawn_count(board, perspective), pawn_count(board, perspective.other()));
@@ -1,3 +1,14 @@
-pub mod searcher;
-pub mod transpositions;
-pub mod move_ordering;
\ No newline at end of file
+pub use crate::ai::shared::{searcher, transpositions, move_ordering};
+
+use self::searcher::Searcher;
+use super::evaluation::{eval::{Evaluation, EvalFeatures}, material::PIECE_VALUE_BONUSES_MG};
+
+pub fn create_searcher() -> Searcher {
+    Searcher::new(|board, _, _| Evaluation::evaluate(board, &EvalFeatures::default()), PIECE_VALUE_BONUSES_MG)
+}
+
+/// V10 is V11 before the imbalance and mobility terms
+pub fn create_v10_searcher() -> Searcher {
+    const FEATURES: EvalFeatures = EvalFeatures { imbalance: false, mobility: false };
+    Searcher::new(|board, _, _| Evaluation::evaluate(board, &FEATURES), PIECE_VALUE_BONUSES_MG)
+}
@@ -1,3 +1,2 @@
 pub mod searcher;
 pub mod transpositions;
-pub mod move_ordering;
\ No newline at end of file
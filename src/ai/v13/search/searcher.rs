@@ -0,0 +1,689 @@
+use std::{time::Instant, sync::{Arc, atomic::{AtomicBool, Ordering}}, thread};
+
+use crate::{board::{moves::Move, board::Board, zobrist::Zobrist, piece::Piece}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}, ai::{stats::{SearchStatistics, SearchInfo, IterationCallback}, time_manager::TimeManager}, tablebase::{Wdl, syzygy::{SyzygyTablebase, MAX_PIECES}, retrograde::{Dtm, EndgameTables}}};
+
+use crate::ai::{shared::move_ordering::MoveOrdering, v12::evaluation::{eval::Evaluation, material::PIECE_VALUE_BONUSES_MG}};
+use super::transpositions::{TranspositionTable, EvaluationType};
+
+/// Lookup tables used at every node, passed down the search as one argument
+#[derive(Clone, Copy)]
+struct SearchTables<'a> {
+    precomp: &'a PrecomputedMoveData,
+    bbutils: &'a BitBoardUtils,
+    magic: &'a MagicBitBoards,
+    zobrist: &'a Zobrist,
+}
+
+/// Where a node is in the search tree
+#[derive(Clone, Copy)]
+struct SearchNode {
+    depth_remaining: u8,
+    /// Number of plies from the root
+    current_depth: u8,
+    num_extensions: i32,
+    prev_was_null: bool,
+}
+
+impl SearchNode {
+    fn root(depth: u8) -> Self {
+        Self { depth_remaining: depth, current_depth: 0, num_extensions: 0, prev_was_null: false }
+    }
+    fn child(&self, depth_remaining: u8, num_extensions: i32) -> Self {
+        Self { depth_remaining, current_depth: self.current_depth + 1, num_extensions, prev_was_null: false }
+    }
+}
+
+pub struct Searcher {
+    pub current_depth: i32,
+    pub best_move_so_far: Move,
+    pub best_eval_so_far: i32,
+    /// Line the engine expects from the root, starting with `best_move_so_far`
+    pub principal_variation: Vec<Move>,
+    pub max_think_time_ms: u32,
+    pub max_depth: u8,
+    pub max_nodes: u32,
+    /// Set from another thread to stop the search at the next node
+    pub stop_flag: Arc<AtomicBool>,
+    /// Called after every completed iteration of iterative deepening
+    pub on_iteration_complete: Option<IterationCallback>,
+    /// Number of threads searching the position, helper threads share the transposition table with the main thread
+    pub num_threads: usize,
+    /// Endgame tablebases, probed after captures and pawn moves and at the root
+    pub tablebase: Option<Arc<SyzygyTablebase>>,
+    /// Tablebases are only probed in positions with at most this many pieces
+    pub tb_probe_limit: usize,
+    /// Tablebases generated by retrograde analysis, with the exact distance to mate
+    pub endgame_tables: Option<Arc<EndgameTables>>,
+    /// Decides when to stop iterative deepening when playing on a clock, `max_think_time_ms` is used as is otherwise
+    pub time_manager: Option<TimeManager>,
+    /// Turning it off gives the same search without null move pruning, to measure what it gains
+    pub null_move_pruning: bool,
+    thread_idx: usize,
+    best_move_this_iter: Move,
+    best_eval_this_iter: i32,
+
+    repetition_table: Vec<u64>,
+    /// Triangular PV table, the entry for each ply holds the best line found from that ply
+    pv_table: Vec<Vec<Move>>,
+    transposition_table: Arc<TranspositionTable>,
+    move_ordering: MoveOrdering,
+
+    positions_evaled: u32,
+    num_nodes: u32,
+    num_mates: i32,
+    num_cutoffs: i32,
+    num_transpositions: i32,
+    num_tb_hits: u32,
+    has_searched_one_move: bool,
+    search_cancelled: bool,
+
+    search_total_time: Instant,
+    current_iter_depth: i32,
+    move_is_from_partial_search: bool,
+}
+
+impl Searcher {
+    pub const MATE_SCORE: i32 = 100000;
+    const POS_INF: i32 = 9999999;
+    const NEG_INF: i32 = -Self::POS_INF;
+
+    pub const DEFAULT_TRANSPOSITION_TABLE_SIZE_MB: usize = 64;
+    pub const MAX_MATE_DEPTH: i32 = 1000;
+    /// Tablebase wins score below every mate, so mates found by the search are still preferred
+    pub const TB_WIN_SCORE: i32 = Self::MATE_SCORE - Self::MAX_MATE_DEPTH - 1;
+    const MAX_EXTENSIONS: i32 = 16;
+
+    const NULL_MOVE_MIN_DEPTH: u8 = 3;
+    const NULL_MOVE_BASE_REDUCTION: u8 = 2;
+    const NULL_MOVE_EVAL_MARGIN: i32 = 200;
+    const NULL_MOVE_VERIFICATION_DEPTH: u8 = 8;
+
+    // Helper threads skip different depths of iterative deepening so they spread out over the tree
+    const HELPER_SKIP_SIZE: [usize; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+    const HELPER_SKIP_PHASE: [usize; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+    pub fn start_search(&mut self,
+        board: &mut Board, 
+        move_gen: &mut MoveGenerator,
+        precomp: &PrecomputedMoveData,
+        bbutils: &BitBoardUtils,
+        magic: &MagicBitBoards,
+        zobrist: &Zobrist,
+    ) {
+        let init_moves = move_gen.moves.clone();
+        if init_moves.is_empty() {
+            self.best_move_so_far = Move::NULL;
+            self.principal_variation.clear();
+            return;
+        }
+
+        self.move_ordering.clear_history();
+        self.repetition_table = board.repeat_position_history.clone();
+        if self.thread_idx == 0 {
+            self.transposition_table.new_search();
+        }
+
+        self.best_move_so_far = Move::NULL;
+        self.best_eval_so_far = 0;
+        self.principal_variation.clear();
+
+        self.positions_evaled = 0;
+        self.num_nodes = 0;
+        self.num_mates = 0;
+        self.num_cutoffs = 0;
+        self.num_transpositions = 0;
+        self.num_tb_hits = 0;
+
+        self.has_searched_one_move = false;
+        self.move_is_from_partial_search = false;
+        self.search_cancelled = false;
+
+        self.search_total_time = Instant::now();
+        if let Some(time_manager) = &mut self.time_manager {
+            time_manager.start();
+            self.max_think_time_ms = time_manager.hard_limit_ms();
+        }
+
+        if self.thread_idx == 0 && self.probe_root_tablebase(board) {
+            return;
+        }
+
+        let tables = SearchTables { precomp, bbutils, magic, zobrist };
+        if self.num_threads <= 1 {
+            self.start_iterative_deepening(board, move_gen, tables);
+        } else {
+            self.start_lazy_smp(board, move_gen, tables);
+        }
+
+        if self.best_move_so_far == Move::NULL {
+            self.best_move_so_far = init_moves[0];
+            self.principal_variation = vec![init_moves[0]];
+        }
+    }
+
+    /// Runs helper threads on copies of the board until the main thread finishes its own iterative deepening
+    fn start_lazy_smp(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: SearchTables) {
+        let helpers_stop_flag = Arc::new(AtomicBool::new(false));
+        let mut helpers: Vec<Searcher> = (1..self.num_threads).map(|thread_idx| Searcher {
+            transposition_table: self.transposition_table.clone(),
+            stop_flag: helpers_stop_flag.clone(),
+            max_think_time_ms: u32::MAX,
+            max_depth: self.max_depth,
+            tablebase: self.tablebase.clone(),
+            tb_probe_limit: self.tb_probe_limit,
+            endgame_tables: self.endgame_tables.clone(),
+            null_move_pruning: self.null_move_pruning,
+            thread_idx,
+            ..Searcher::empty()
+        }).collect();
+
+        thread::scope(|scope| {
+            for helper in helpers.iter_mut() {
+                let mut helper_board = board.clone();
+                scope.spawn(move || {
+                    let mut helper_move_gen = MoveGenerator::default();
+                    helper_move_gen.generate_moves(&helper_board, tables.precomp, tables.bbutils, tables.magic, false);
+                    helper.start_search(&mut helper_board, &mut helper_move_gen, tables.precomp, tables.bbutils, tables.magic, tables.zobrist);
+                });
+            }
+            self.start_iterative_deepening(board, move_gen, tables);
+            helpers_stop_flag.store(true, Ordering::Relaxed);
+        });
+
+        for helper in helpers.iter() {
+            self.num_nodes += helper.num_nodes;
+            self.positions_evaled += helper.positions_evaled;
+            self.num_cutoffs += helper.num_cutoffs;
+            self.num_transpositions += helper.num_transpositions;
+            self.num_tb_hits += helper.num_tb_hits;
+        }
+    }
+
+    fn start_iterative_deepening(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: SearchTables) {
+        for search_depth in 1u8..=self.max_depth {
+            if self.thread_idx > 0 && search_depth < self.max_depth {
+                let i = (self.thread_idx - 1) % Self::HELPER_SKIP_SIZE.len();
+                if !((search_depth as usize + Self::HELPER_SKIP_PHASE[i]) / Self::HELPER_SKIP_SIZE[i]).is_multiple_of(2) {
+                    continue;
+                }
+            }
+            self.has_searched_one_move = false;
+            self.current_iter_depth = search_depth as i32;
+            self.search(SearchNode::root(search_depth), Self::NEG_INF, Self::POS_INF, board, move_gen, tables);
+            
+            if self.search_cancelled {
+                if self.has_searched_one_move {
+                    self.best_move_so_far = self.best_move_this_iter;
+                    self.best_eval_so_far = self.best_eval_this_iter;
+                    self.principal_variation = self.collect_principal_variation(board, move_gen, tables);
+                    self.move_is_from_partial_search = true;
+                }
+                break;
+            } else {
+                self.current_depth = search_depth as i32;
+                self.best_move_so_far = self.best_move_this_iter;
+                self.best_eval_so_far = self.best_eval_this_iter;
+                self.principal_variation = self.collect_principal_variation(board, move_gen, tables);
+
+                self.best_eval_this_iter = Self::NEG_INF;
+                self.best_move_this_iter = Move::NULL;
+
+                if let Some(on_iteration_complete) = &self.on_iteration_complete {
+                    on_iteration_complete(&SearchInfo {
+                        depth: self.current_depth,
+                        eval: self.best_eval_so_far,
+                        best_move: self.best_move_so_far,
+                        pv: self.principal_variation.clone(),
+                        num_nodes: self.num_nodes,
+                        time_ms: self.search_total_time.elapsed().as_millis() as u32,
+                        hashfull: self.transposition_table.hashfull(),
+                        tb_hits: self.num_tb_hits,
+                    });
+                }
+
+                if Self::is_mate_score(self.best_eval_so_far) && Self::num_ply_in_mate(self.best_eval_so_far) <= search_depth as i32 {
+                    break;
+                }
+                if let Some(time_manager) = &mut self.time_manager {
+                    if !time_manager.iteration_complete(self.best_move_so_far, self.best_eval_so_far) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    
+    fn search(&mut self, node: SearchNode, mut alpha: i32, mut beta: i32, board: &mut Board, move_gen: &mut MoveGenerator, tables: SearchTables) -> i32 {
+        let SearchNode { depth_remaining, current_depth, num_extensions, prev_was_null } = node;
+        let SearchTables { precomp, bbutils, magic, zobrist } = tables;
+        // Cancel search if over max think time, node limit or stopped externally
+        if Instant::now().duration_since(self.search_total_time).as_millis() as u32 > self.max_think_time_ms
+        || self.num_nodes >= self.max_nodes
+        || self.stop_flag.load(Ordering::Relaxed) {
+            self.search_cancelled = true;
+            return 0;
+        }
+        self.num_nodes += 1;
+
+        // Lines from this ply are rebuilt by the search below, nodes returning early have no line
+        let ply = current_depth as usize;
+        if self.pv_table.len() <= ply + 1 {
+            self.pv_table.resize(ply + 2, Vec::new());
+        }
+        self.pv_table[ply].clear();
+
+        if current_depth > 0 {
+            // Punish repeated positions
+            if board.current_state.fifty_move_counter >= 100 || self.repetition_table.contains(&board.current_state.zobrist_key) {
+                return 0;
+            }
+
+            // Prune if shorter mating sequence has been found
+            alpha = alpha.max(-Self::MATE_SCORE + current_depth as i32);
+            beta = beta.min(Self::MATE_SCORE - current_depth as i32);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
+        // Try getting the position from the transposition table
+        if let Some((tt_val, tt_move)) = self.transposition_table.get_evaluation(depth_remaining, current_depth, alpha, beta, board) {
+            self.num_transpositions += 1;
+            if current_depth == 0 {
+                self.best_move_this_iter = tt_move;
+                self.best_eval_this_iter = tt_val;
+                self.pv_table[0].push(tt_move);
+            }
+            return tt_val;
+        }
+
+        // Generated tables give the distance to mate, which is scored like a mate found by the search
+        if current_depth > 0 {
+            if let Some(dtm) = self.endgame_tables.as_ref().and_then(|tables| tables.probe(board)) {
+                self.num_tb_hits += 1;
+                return Self::dtm_score(dtm, current_depth);
+            }
+        }
+
+        // The tables ignore the fifty-move counter, so they are only trusted right after it was reset
+        if current_depth > 0 && board.current_state.fifty_move_counter == 0 && self.can_probe_tablebase(board) {
+            if let Some(wdl) = self.tablebase.as_ref().and_then(|tablebase| tablebase.probe_wdl(board)) {
+                self.num_tb_hits += 1;
+                return Self::tablebase_score(wdl, current_depth);
+            }
+        }
+
+        // If leaf node is reached, evaluate the board
+        if depth_remaining == 0 {
+            return self.quiescence_search(alpha, beta, board, move_gen, tables);
+        };
+
+        // Null move pruning -- If passing the turn still fails high, a real move almost certainly would too.
+        // Not done in check, after another null move, or with only pawns left where zugzwang is common.
+        if self.null_move_pruning && current_depth > 0 && !prev_was_null && depth_remaining >= Self::NULL_MOVE_MIN_DEPTH
+        && !Self::is_mate_score(beta) && !board.in_check(magic, bbutils) && Self::has_non_pawn_material(board) {
+            let static_eval = self.static_eval(board, move_gen, precomp, magic);
+            if static_eval >= beta {
+                // Reduce more at higher depths and when the position is far above beta
+                let eval_margin_reduction = ((static_eval - beta) / Self::NULL_MOVE_EVAL_MARGIN).min(2) as u8;
+                let reduction = Self::NULL_MOVE_BASE_REDUCTION + depth_remaining / 4 + eval_margin_reduction;
+                let null_depth = depth_remaining.saturating_sub(1 + reduction);
+
+                board.make_null_move(zobrist);
+                let null_node = SearchNode { depth_remaining: null_depth, current_depth: current_depth + 1, num_extensions, prev_was_null: true };
+                let null_eval = -self.search(null_node, -beta, -beta + 1, board, move_gen, tables);
+                board.unmake_null_move();
+                if self.search_cancelled {
+                    return 0;
+                }
+
+                if null_eval >= beta {
+                    // Guard against zugzwang at high depths by confirming the cutoff with a reduced search that doesn't pass
+                    let verification_node = SearchNode { depth_remaining: null_depth, prev_was_null: true, ..node };
+                    let is_verified = depth_remaining < Self::NULL_MOVE_VERIFICATION_DEPTH
+                        || self.search(verification_node, beta - 1, beta, board, move_gen, tables) >= beta;
+                    if self.search_cancelled {
+                        return 0;
+                    }
+                    if is_verified {
+                        self.num_cutoffs += 1;
+                        return beta;
+                    }
+                }
+            }
+        }
+
+        move_gen.generate_moves(board, precomp, bbutils, magic, false);
+        let mut moves = move_gen.moves.clone();
+        let tt_stored_move = self.transposition_table.get_stored_move(board);
+        let prev_best_move = if current_depth == 0 { self.best_move_so_far } else { match tt_stored_move { None => Move::NULL, Some(mov) => mov } };
+        self.move_ordering.order_moves(prev_best_move, &mut moves, board, move_gen, false, current_depth as usize);
+
+        // Check if position is terminal
+        if moves.is_empty() {
+            if move_gen.in_check() {
+                self.num_mates += 1;
+                // Favor faster mates
+                let mate_score = Self::MATE_SCORE - current_depth as i32;
+                return -mate_score;
+            } else { // Stalemate
+                return 0;
+            };
+        };
+
+        if current_depth > 0 {
+            // Positions before a capture or pawn move can't come back
+            if board.current_state.fifty_move_counter == 0 { self.repetition_table.clear() };
+            self.repetition_table.push(board.current_state.zobrist_key);
+        }
+
+        let mut evaluation_bound = EvaluationType::UpperBound;
+        let mut best_move_this_position = Move::NULL;
+
+        // Loop through legal moves
+        for (i, mov) in moves.iter().enumerate() {
+            let is_capture = mov.is_capture(board);
+            board.make_move(*mov, true, zobrist);
+            self.transposition_table.prefetch(board.current_state.zobrist_key);
+
+            let mut extensions = 0;
+            if num_extensions < Self::MAX_EXTENSIONS {
+                let moved_ptype = board.square[mov.target().index()].piece_type();
+                let target_rank = mov.target().rank();
+                // Checks and pawns about to promote
+                if board.in_check(magic, bbutils) || (moved_ptype == Piece::PAWN && (target_rank == 1 || target_rank == 6)) {
+                    extensions = 1;
+                }
+            }
+            let mut needs_full_search = true;
+            let mut eval = 0;
+
+            if i >= 3 && extensions == 0 && depth_remaining >= 3 && !is_capture {
+                eval = -self.search(node.child(depth_remaining - 1 - 1, num_extensions), -alpha - 1, -alpha, board, move_gen, tables);
+                needs_full_search = eval > alpha;
+            }
+
+            if needs_full_search {
+                // Negate evaluation -- A bad position for the opponent is good for us and vice versa
+                eval = -self.search(node.child(depth_remaining - 1 + extensions, num_extensions + extensions as i32), -beta, -alpha, board, move_gen, tables);
+            }
+
+            board.unmake_move(*mov, true);
+            // Exit early if search is cancelled
+            if self.search_cancelled {
+                return 0;
+            }
+
+            // Beta cutoff / Fail high
+            if eval >= beta {
+                // Very good move but not the best, store as lower bound
+                self.transposition_table.store_evaluation(depth_remaining, current_depth, beta, EvaluationType::LowerBound, *mov, board);
+                
+                if !is_capture {
+                    // Favor killer moves (moves that cause branches to be pruned) when ordering moves
+                    if current_depth < MoveOrdering::MAX_KILLER_MOVE_PLY as u8 {
+                        self.move_ordering.killers[current_depth as usize].add(*mov);
+                    }
+
+                    let history_score = depth_remaining as i32 * depth_remaining as i32;
+                    self.move_ordering.history[board.move_color_idx][mov.start().index()][mov.target().index()] += history_score;
+                }
+
+                if current_depth > 0 {
+                    self.repetition_table.pop();
+                }
+
+                self.num_cutoffs += 1;
+                return beta;
+            }
+
+            // New best move for this position
+            if eval > alpha {
+                evaluation_bound = EvaluationType::Exact;
+                best_move_this_position = *mov;
+                alpha = eval;
+                self.update_pv(ply, *mov);
+                if current_depth == 0 {
+                    self.best_eval_this_iter = eval;
+                    self.best_move_this_iter = *mov;
+                    self.has_searched_one_move = true;
+                }
+            }
+        };
+
+        if current_depth > 0 {
+            self.repetition_table.pop();
+        }
+        self.transposition_table.store_evaluation(depth_remaining, current_depth, alpha, evaluation_bound, best_move_this_position, board);
+
+        alpha
+    }
+
+    fn quiescence_search(&mut self, mut alpha: i32, beta: i32, board: &mut Board, move_gen: &mut MoveGenerator, tables: SearchTables) -> i32 {
+        let SearchTables { precomp, bbutils, magic, zobrist } = tables;
+        if self.search_cancelled { return 0; }
+        self.num_nodes += 1;
+
+        let mut eval = self.static_eval(board, move_gen, precomp, magic);
+
+        if eval > beta {
+            self.num_cutoffs += 1;
+            return beta;
+        }
+        if eval > alpha {
+            alpha = eval;
+        }
+
+        move_gen.generate_moves(board, precomp, bbutils, magic, true);
+        let mut moves = move_gen.moves.clone();
+        self.move_ordering.order_moves(Move::NULL, &mut moves, board, move_gen, true, 0);
+        for mov in moves.iter() {
+            board.make_move(*mov, true, zobrist);
+            self.transposition_table.prefetch(board.current_state.zobrist_key);
+            eval = -self.quiescence_search(-beta, -alpha, board, move_gen, tables);
+            board.unmake_move(*mov, true);
+
+            if eval >= beta {
+                self.num_cutoffs += 1;
+                return beta;
+            }
+            if eval > alpha {
+                alpha = eval;
+            }
+        }
+        alpha
+    }
+
+    /// Sets the line from `ply` to the move followed by the line found below it
+    fn update_pv(&mut self, ply: usize, mov: Move) {
+        let (lines, child_lines) = self.pv_table.split_at_mut(ply + 1);
+        lines[ply].clear();
+        lines[ply].push(mov);
+        lines[ply].extend_from_slice(&child_lines[0]);
+    }
+
+    /// Principal variation of the last iteration. Lines cut short by transposition table hits
+    /// are extended with the moves stored in the table, as long as they are legal and don't repeat a position.
+    fn collect_principal_variation(&self, board: &Board, move_gen: &mut MoveGenerator, tables: SearchTables) -> Vec<Move> {
+        let SearchTables { precomp, bbutils, magic, zobrist } = tables;
+        if self.best_move_this_iter == Move::NULL {
+            return Vec::new();
+        }
+        let mut pv = self.pv_table[0].clone();
+        if pv.first() != Some(&self.best_move_this_iter) {
+            pv = vec![self.best_move_this_iter];
+        }
+
+        let mut board = board.clone();
+        let mut visited = vec![board.current_state.zobrist_key];
+        for mov in pv.iter() {
+            board.make_move(*mov, true, zobrist);
+            visited.push(board.current_state.zobrist_key);
+        }
+        while pv.len() < self.current_iter_depth as usize {
+            let Some(mov) = self.transposition_table.get_stored_move(&board) else { break; };
+            move_gen.generate_moves(&board, precomp, bbutils, magic, false);
+            if !move_gen.moves.contains(&mov) {
+                break;
+            }
+            board.make_move(mov, true, zobrist);
+            if visited.contains(&board.current_state.zobrist_key) {
+                break;
+            }
+            visited.push(board.current_state.zobrist_key);
+            pv.push(mov);
+        }
+        pv
+    }
+
+    /// Static evaluation of the position, cached in the transposition table
+    fn static_eval(&mut self, board: &Board, move_gen: &MoveGenerator, precomp: &PrecomputedMoveData, magic: &MagicBitBoards) -> i32 {
+        if let Some(static_eval) = self.transposition_table.get_static_eval(board) {
+            return static_eval;
+        }
+        let static_eval = Evaluation::evaluate(board, move_gen, precomp, magic);
+        self.positions_evaled += 1;
+        self.transposition_table.store_static_eval(static_eval, board);
+        static_eval
+    }
+
+    fn has_non_pawn_material(board: &Board) -> bool {
+        [Piece::KNIGHT, Piece::BISHOP, Piece::ROOK, Piece::QUEEN].iter()
+            .any(|ptype| board.get_piece_list(Piece::new(ptype | board.move_color)).count() > 0)
+    }
+
+    pub fn search_statistics(&self) -> SearchStatistics {
+        SearchStatistics {
+            num_position_evals: self.positions_evaled,
+            num_cutoffs: self.num_cutoffs,
+            num_transpositions: self.num_transpositions,
+            think_time_ms: self.search_total_time.elapsed().as_millis() as u32,
+            num_checks: 0,
+            num_mates: self.num_mates,
+            is_book: false,
+            hashfull: self.transposition_table.hashfull(),
+            tb_hits: self.num_tb_hits,
+        }
+    }
+    /// Replaces the transposition table with an empty one of the given size
+    pub fn set_hash_size_mb(&mut self, size_mb: usize) {
+        self.transposition_table = Arc::new(TranspositionTable::new(size_mb));
+    }
+    /// Clears the transposition table and move ordering data between games
+    pub fn new_game(&mut self) {
+        self.transposition_table.clear();
+        self.move_ordering.clear();
+    }
+
+    fn can_probe_tablebase(&self, board: &Board) -> bool {
+        self.tablebase.as_ref().is_some_and(|tablebase| {
+            tablebase.can_probe(board) && board.all_pieces_bitboard.count() as usize <= self.tb_probe_limit
+        })
+    }
+
+    /// Plays the tablebase move that keeps the result of the position under the fifty-move rule, without searching
+    fn probe_root_tablebase(&mut self, board: &mut Board) -> bool {
+        if let Some((mov, dtm)) = self.endgame_tables.as_ref().and_then(|tables| tables.probe_root(board)) {
+            self.num_tb_hits += 1;
+            self.best_move_so_far = mov;
+            self.best_eval_so_far = Self::dtm_score(dtm, 0);
+            self.principal_variation = vec![mov];
+            self.current_depth = 0;
+            return true;
+        }
+        if !self.can_probe_tablebase(board) {
+            return false;
+        }
+        let Some(root) = self.tablebase.as_ref().and_then(|tablebase| tablebase.probe_root(board)) else {
+            return false;
+        };
+        self.num_tb_hits += 1;
+        self.best_move_so_far = root.mov;
+        self.best_eval_so_far = Self::tablebase_score(root.wdl(), 0);
+        self.principal_variation = vec![root.mov];
+        self.current_depth = 0;
+        true
+    }
+
+    /// Cursed wins and blessed losses are draws under the fifty-move rule, they are only nudged away from zero
+    fn tablebase_score(wdl: Wdl, ply: u8) -> i32 {
+        match wdl {
+            Wdl::Win => Self::TB_WIN_SCORE - ply as i32,
+            Wdl::Loss => -Self::TB_WIN_SCORE + ply as i32,
+            _ => 2 * wdl as i32,
+        }
+    }
+
+    fn dtm_score(dtm: Dtm, ply: u8) -> i32 {
+        match dtm {
+            Dtm::Win(plies) => Self::MATE_SCORE - ply as i32 - plies as i32,
+            Dtm::Draw => 0,
+            Dtm::Loss(plies) => -Self::MATE_SCORE + ply as i32 + plies as i32,
+        }
+    }
+
+    pub fn is_mate_score(score: i32) -> bool {
+        if score == i32::MIN { return false; };
+        score.abs() > Self::MATE_SCORE - Self::MAX_MATE_DEPTH
+    }
+    /// Tablebase wins and losses, which score just below the mates found by the search
+    pub fn is_tablebase_score(score: i32) -> bool {
+        if score == i32::MIN { return false; };
+        score.abs() <= Self::TB_WIN_SCORE && score.abs() > Self::TB_WIN_SCORE - Self::MAX_MATE_DEPTH
+    }
+    pub fn num_ply_in_mate(score: i32) -> i32 {
+        Self::MATE_SCORE - score.abs()
+    }
+}
+
+impl Searcher {
+    /// Searcher without a transposition table of its own
+    fn empty() -> Self {
+        Self {
+            repetition_table: Vec::new(),
+            pv_table: Vec::new(),
+            transposition_table: Arc::new(TranspositionTable::new(0)),
+            move_ordering: MoveOrdering::new(PIECE_VALUE_BONUSES_MG),
+
+            current_depth: 0,
+            positions_evaled: 0,
+            num_nodes: 0,
+            best_eval_so_far: 0,
+            best_move_so_far: Move::NULL,
+            principal_variation: Vec::new(),
+            num_mates: 0,
+            num_cutoffs: 0,
+            num_transpositions: 0,
+            num_tb_hits: 0,
+            has_searched_one_move: false,
+            search_cancelled: false,
+            max_think_time_ms: 1000,
+            max_depth: 255,
+            max_nodes: u32::MAX,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            on_iteration_complete: None,
+            num_threads: 1,
+            tablebase: None,
+            tb_probe_limit: MAX_PIECES,
+            endgame_tables: None,
+            time_manager: None,
+            null_move_pruning: true,
+            thread_idx: 0,
+            best_eval_this_iter: 0,
+            best_move_this_iter: Move::NULL,
+            current_iter_depth: 0,
+            move_is_from_partial_search: false,
+
+            search_total_time: Instant::now(),
+        }
+    }
+}
+
+impl Default for Searcher {
+    fn default() -> Self {
+        Self {
+            transposition_table: Arc::new(TranspositionTable::new(Self::DEFAULT_TRANSPOSITION_TABLE_SIZE_MB)),
+            ..Self::empty()
+        }
+    }
+}
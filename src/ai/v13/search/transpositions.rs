@@ -0,0 +1,335 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::board::{moves::Move, board::Board};
+use super::searcher::Searcher;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum EvaluationType {
+    Exact,
+    LowerBound,
+    UpperBound,
+}
+
+impl EvaluationType {
+    fn from_bits(bits: u64) -> Option<Self> {
+        match bits {
+            1 => Some(Self::Exact),
+            2 => Some(Self::LowerBound),
+            3 => Some(Self::UpperBound),
+            _ => None,
+        }
+    }
+    fn to_bits(self) -> u64 {
+        match self {
+            Self::Exact => 1,
+            Self::LowerBound => 2,
+            Self::UpperBound => 3,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TranspositionEntry {
+    pub key: u64,
+    pub value: i32,
+    pub static_eval: Option<i32>,
+    pub mov: Move,
+    pub depth: u8,
+    /// `None` for entries that only cache the static evaluation of the position
+    pub node_type: Option<EvaluationType>,
+    pub generation: u8,
+}
+
+impl TranspositionEntry {
+    // Packed layout: move (16 bits), value (16), static eval (16), depth (8), bound (3), generation (5)
+    const BOUND_EMPTY: u64 = 0;
+    const BOUND_EVAL_ONLY: u64 = 4;
+    const NO_STATIC_EVAL: i16 = i16::MIN;
+    const PACKED_MATE_SCORE: i32 = 32000;
+    const PACKED_TB_WIN_SCORE: i32 = Self::PACKED_MATE_SCORE - Searcher::MAX_MATE_DEPTH;
+    const MAX_PACKED_EVAL: i32 = Self::PACKED_TB_WIN_SCORE - Searcher::MAX_MATE_DEPTH;
+
+    fn pack(&self) -> u64 {
+        let bound = self.node_type.map_or(Self::BOUND_EVAL_ONLY, |node_type| node_type.to_bits());
+        let static_eval = self.static_eval.map_or(Self::NO_STATIC_EVAL, Self::pack_eval);
+        self.mov.value() as u64
+        | (Self::pack_score(self.value) as u16 as u64) << 16
+        | (static_eval as u16 as u64) << 32
+        | (self.depth as u64) << 48
+        | bound << 56
+        | ((self.generation & TranspositionTable::GENERATION_MASK) as u64) << 59
+    }
+    fn unpack(key: u64, data: u64) -> Option<Self> {
+        let bound = (data >> 56) & 0b111;
+        if bound == Self::BOUND_EMPTY {
+            return None;
+        }
+        let static_eval = (data >> 32) as u16 as i16;
+        Some(Self {
+            key,
+            mov: Move::from_value(data as u16),
+            value: Self::unpack_score((data >> 16) as u16 as i16),
+            static_eval: if static_eval == Self::NO_STATIC_EVAL { None } else { Some(static_eval as i32) },
+            depth: (data >> 48) as u8,
+            node_type: EvaluationType::from_bits(bound),
+            generation: (data >> 59) as u8,
+        })
+    }
+
+    fn pack_eval(eval: i32) -> i16 {
+        eval.clamp(-Self::MAX_PACKED_EVAL, Self::MAX_PACKED_EVAL) as i16
+    }
+    // Mate and tablebase scores are squeezed into two bands above `MAX_PACKED_EVAL`, keeping the number of plies to the mate or the tablebase position
+    fn pack_score(score: i32) -> i16 {
+        if Searcher::is_mate_score(score) {
+            let ply_in_mate = Searcher::num_ply_in_mate(score).clamp(0, Searcher::MAX_MATE_DEPTH - 1);
+            return (score.signum() * (Self::PACKED_MATE_SCORE - ply_in_mate)) as i16;
+        }
+        if Searcher::is_tablebase_score(score) {
+            let ply_to_tablebase = Searcher::TB_WIN_SCORE - score.abs();
+            return (score.signum() * (Self::PACKED_TB_WIN_SCORE - ply_to_tablebase)) as i16;
+        }
+        Self::pack_eval(score)
+    }
+    fn unpack_score(packed: i16) -> i32 {
+        let packed = packed as i32;
+        if packed.abs() > Self::PACKED_TB_WIN_SCORE {
+            return packed.signum() * (Searcher::MATE_SCORE - (Self::PACKED_MATE_SCORE - packed.abs()));
+        }
+        if packed.abs() > Self::MAX_PACKED_EVAL {
+            return packed.signum() * (Searcher::TB_WIN_SCORE - (Self::PACKED_TB_WIN_SCORE - packed.abs()));
+        }
+        packed
+    }
+}
+
+/// Slot that can be read and written by several search threads without locking.
+/// The key is stored xor'ed with the data, so a torn write from two threads fails verification instead of returning a mismatched entry.
+#[derive(Default)]
+struct TranspositionSlot {
+    key_xor_data: AtomicU64,
+    data: AtomicU64,
+}
+
+impl TranspositionSlot {
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+        (self.key_xor_data.load(Ordering::Relaxed) ^ data, data)
+    }
+    fn store(&self, key: u64, data: u64) {
+        self.key_xor_data.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+/// Slots sharing a hash index, sized to fill exactly one cache line
+#[derive(Default)]
+#[repr(align(64))]
+struct TranspositionBucket {
+    slots: [TranspositionSlot; TranspositionTable::BUCKET_SIZE],
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<TranspositionBucket>,
+    /// Incremented every search so entries from earlier searches are replaced first
+    generation: AtomicU8,
+    pub count: u64,
+    pub enabled: bool,
+}
+
+impl TranspositionTable {
+    pub const BUCKET_SIZE: usize = 4;
+    const GENERATION_MASK: u8 = 0b11111;
+    const HASHFULL_SAMPLE_BUCKETS: usize = 250;
+    const TT_BUCKET_SIZE_BYTES: usize = std::mem::size_of::<TranspositionBucket>();
+
+    pub fn new(size_mb: usize) -> Self {
+        let desired_table_size_bytes = size_mb * 1024 * 1024;
+        let n_buckets = (desired_table_size_bytes / Self::TT_BUCKET_SIZE_BYTES).max(1);
+
+        Self {
+            buckets: (0..n_buckets).map(|_| TranspositionBucket::default()).collect(),
+            generation: AtomicU8::new(0),
+            count: (n_buckets * Self::BUCKET_SIZE) as u64,
+            enabled: true,
+        }
+    }
+    pub fn clear(&self) {
+        for bucket in self.buckets.iter() {
+            for slot in bucket.slots.iter() {
+                slot.store(0, 0);
+            }
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+    /// Ages all stored entries, called once at the start of every search
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed) & Self::GENERATION_MASK
+    }
+    fn bucket(&self, key: u64) -> &TranspositionBucket {
+        &self.buckets[(key % self.buckets.len() as u64) as usize]
+    }
+
+    /// Starts loading the bucket of the given key into the cache ahead of the lookup
+    pub fn prefetch(&self, key: u64) {
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: the pointer points into the table and prefetching never faults or modifies memory
+        unsafe {
+            use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            _mm_prefetch(self.bucket(key) as *const TranspositionBucket as *const i8, _MM_HINT_T0);
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = key;
+    }
+
+    pub fn get_stored_move(&self, board: &Board) -> Option<Move> {
+        self.get_entry(board.current_state.zobrist_key)
+            .map(|entry| entry.mov)
+            .filter(|mov| *mov != Move::NULL)
+    }
+    pub fn get_static_eval(&self, board: &Board) -> Option<i32> {
+        self.get_entry(board.current_state.zobrist_key).and_then(|entry| entry.static_eval)
+    }
+    /// Returns the stored evaluation and move if the entry is deep enough and its bound is usable within the window
+    pub fn get_evaluation(&self, depth_remaining: u8, current_depth: u8, alpha: i32, beta: i32, board: &Board) -> Option<(i32, Move)> {
+        if !self.enabled {
+            return None;
+        }
+        if let Some(entry) = self.get_entry(board.current_state.zobrist_key) {
+            if entry.depth >= depth_remaining {
+                let corrected_score = Self::correct_retrieved_mate_score(entry.value, current_depth);
+                match entry.node_type {
+                    Some(EvaluationType::Exact) => return Some((corrected_score, entry.mov)),
+                    Some(EvaluationType::UpperBound) if corrected_score <= alpha => return Some((corrected_score, entry.mov)),
+                    Some(EvaluationType::LowerBound) if corrected_score >= beta => return Some((corrected_score, entry.mov)),
+                    _ => (),
+                }
+            }
+        }
+        None
+    }
+    pub fn store_evaluation(&self, depth_remaining: u8, current_depth: u8, eval: i32, eval_type: EvaluationType, mov: Move, board: &Board) {
+        if !self.enabled {
+            return;
+        }
+        self.store(TranspositionEntry {
+            key: board.current_state.zobrist_key,
+            value: Self::correct_stored_mate_score(eval, current_depth),
+            static_eval: None,
+            mov,
+            depth: depth_remaining,
+            node_type: Some(eval_type),
+            generation: self.generation(),
+        });
+    }
+    /// Caches the static evaluation of a position without a search result
+    pub fn store_static_eval(&self, eval: i32, board: &Board) {
+        if !self.enabled {
+            return;
+        }
+        self.store(TranspositionEntry {
+            key: board.current_state.zobrist_key,
+            value: 0,
+            static_eval: Some(eval),
+            mov: Move::NULL,
+            depth: 0,
+            node_type: None,
+            generation: self.generation(),
+        });
+    }
+
+    /// Stores the entry over an existing entry of the same position, an empty slot,
+    /// or the slot holding the shallowest and oldest entry of the bucket
+    fn store(&self, mut entry: TranspositionEntry) {
+        let generation = self.generation();
+        let bucket = self.bucket(entry.key);
+
+        let mut replace_idx = 0;
+        let mut replace_score = i32::MAX;
+        for (i, slot) in bucket.slots.iter().enumerate() {
+            let (key, data) = slot.load();
+            let Some(existing) = TranspositionEntry::unpack(key, data) else {
+                if replace_score > i32::MIN {
+                    replace_idx = i;
+                    replace_score = i32::MIN;
+                }
+                continue;
+            };
+            if key == entry.key {
+                if entry.static_eval.is_none() {
+                    entry.static_eval = existing.static_eval;
+                }
+                if entry.node_type.is_none() {
+                    // Only adds the static evaluation to an existing search result
+                    if existing.static_eval.is_some() {
+                        return;
+                    }
+                    entry = TranspositionEntry { static_eval: entry.static_eval, generation, ..existing };
+                } else {
+                    if entry.mov == Move::NULL {
+                        entry.mov = existing.mov;
+                    }
+                    // Keep deeper results of the current search unless the new one is exact
+                    let is_stale = existing.generation != generation || existing.node_type.is_none();
+                    if !is_stale && entry.node_type != Some(EvaluationType::Exact) && (entry.depth as i32) + 2 < existing.depth as i32 {
+                        return;
+                    }
+                }
+                slot.store(entry.key, entry.pack());
+                return;
+            }
+            let age = generation.wrapping_sub(existing.generation) & Self::GENERATION_MASK;
+            let score = existing.depth as i32 - 8 * age as i32;
+            if score < replace_score {
+                replace_idx = i;
+                replace_score = score;
+            }
+        }
+        bucket.slots[replace_idx].store(entry.key, entry.pack());
+    }
+
+    /// Permille of sampled slots holding an entry from the current search
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation();
+        let sampled_buckets = &self.buckets[..self.buckets.len().min(Self::HASHFULL_SAMPLE_BUCKETS)];
+        let mut used = 0;
+        for bucket in sampled_buckets.iter() {
+            for slot in bucket.slots.iter() {
+                let (key, data) = slot.load();
+                if TranspositionEntry::unpack(key, data).is_some_and(|entry| entry.generation == generation) {
+                    used += 1;
+                }
+            }
+        }
+        (used * 1000 / (sampled_buckets.len() * Self::BUCKET_SIZE)) as u32
+    }
+
+    // Mate and tablebase scores count plies from the root, they are stored relative to the position instead
+    fn correct_stored_mate_score(score: i32, current_depth: u8) -> i32 {
+        if Searcher::is_mate_score(score) || Searcher::is_tablebase_score(score) {
+            let sign = score.signum();
+            return (score * sign + current_depth as i32) * sign;
+        }
+        score
+    }
+    fn correct_retrieved_mate_score(score: i32, current_depth: u8) -> i32 {
+        if Searcher::is_mate_score(score) || Searcher::is_tablebase_score(score) {
+            let sign = score.signum();
+            return (score * sign - current_depth as i32) * sign;
+        }
+        score
+    }
+
+    pub fn get_entry(&self, key: u64) -> Option<TranspositionEntry> {
+        for slot in self.bucket(key).slots.iter() {
+            let (slot_key, data) = slot.load();
+            if slot_key == key {
+                return TranspositionEntry::unpack(key, data);
+            }
+        }
+        None
+    }
+}
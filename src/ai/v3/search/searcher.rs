@@ -1,11 +1,10 @@
-use std::time::Instant;
+use std::{time::Instant, sync::{Arc, atomic::{AtomicBool, Ordering}}};
 
-use bevy::prelude::*;
-use crate::{board::{moves::Move, board::Board, zobrist::Zobrist}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}, ai::{ai_player::{BeginSearch, SearchComplete, AIVersion}, stats::SearchStatistics}};
+use crate::{board::{moves::Move, board::Board, zobrist::Zobrist}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}};
+use crate::{ai::stats::SearchStatistics, engine::{legacy::LegacySearcher, search::SearchResult, tables::LookupTables}};
 
 use super::super::evaluation::eval::Evaluation;
 
-#[derive(Resource)]
 pub struct Searcher {
     pub current_depth: i32,
     pub best_move_so_far: Move,
@@ -24,6 +23,7 @@ pub struct Searcher {
     search_total_time: Instant,
     current_iter_depth: i32,
     move_is_from_partial_search: bool,
+    stop_flag: Arc<AtomicBool>,
 }
 
 impl Searcher {
@@ -118,7 +118,8 @@ impl Searcher {
         zobrist: &Zobrist,
     ) -> f32 {
         // Cancel search if over max think time
-        if Instant::now().duration_since(self.search_total_time).as_millis() as u32 > self.max_think_time_ms {
+        if Instant::now().duration_since(self.search_total_time).as_millis() as u32 > self.max_think_time_ms
+            || self.stop_flag.load(Ordering::Relaxed) {
             self.search_cancelled = true;
             return 0.0;
         }
@@ -214,50 +215,32 @@ impl Default for Searcher {
             search_total_time: Instant::now(),
             current_iter_depth: 0,
             move_is_from_partial_search: false,
+            stop_flag: Arc::new(AtomicBool::new(false)),
         }
     }
 }
 
-pub fn start_search(
-    mut searcher: ResMut<Searcher>,
-    mut begin_search_evr: EventReader<BeginSearch>,
-    mut search_complete_evw: EventWriter<SearchComplete>,
+impl LegacySearcher for Searcher {
+    fn search_board(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables, think_time_ms: u32, stop_flag: &Arc<AtomicBool>) {
+        self.max_think_time_ms = think_time_ms;
+        self.stop_flag = stop_flag.clone();
+        self.start_search(board, move_gen, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+    }
 
-    mut board: ResMut<Board>,
-    mut move_gen: ResMut<MoveGenerator>,
-    precomp: Res<PrecomputedMoveData>,
-    bbutils: Res<BitBoardUtils>,
-    magic: Res<MagicBitBoards>,
-    zobrist: Res<Zobrist>,
-) {
-    for begin_search_event in begin_search_evr.iter() {
-        if begin_search_event.version != AIVersion::V3 {
-            continue;
-        }
-        let time_start = std::time::Instant::now();
-        searcher.max_think_time_ms = begin_search_event.think_time;
-        searcher.start_search(
-            board.as_mut(),
-            move_gen.as_mut(),
-            precomp.as_ref(),
-            bbutils.as_ref(),
-            magic.as_ref(),
-            zobrist.as_ref(),
-        );
-        let think_time = std::time::Instant::now().duration_since(time_start).as_millis();
-        search_complete_evw.send(SearchComplete {
-            depth: searcher.current_depth,
-            chosen_move: searcher.best_move_so_far,
-            eval: searcher.best_eval_so_far as i32,
+    fn result(&self) -> SearchResult {
+        SearchResult {
+            best_move: self.best_move_so_far,
+            pv: vec![self.best_move_so_far],
+            eval: self.best_eval_so_far as i32,
+            depth: self.current_depth,
             stats: SearchStatistics {
-                num_position_evals: searcher.positions_evaled,
-                num_cutoffs: searcher.num_cutoffs,
-                think_time_ms: think_time as u32,
+                num_position_evals: self.positions_evaled,
+                num_cutoffs: self.num_cutoffs,
                 num_checks: 0,
-                num_mates: searcher.num_mates,
+                num_mates: self.num_mates,
                 is_book: false,
-                ..default()
-            }
-        });
+                ..Default::default()
+            },
+        }
     }
 }
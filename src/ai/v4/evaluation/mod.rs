@@ -1 +0,0 @@
-pub mod eval;
\ No newline at end of file
@@ -1,2 +0,0 @@
-pub mod searcher;
-pub mod transpositions;
\ No newline at end of file
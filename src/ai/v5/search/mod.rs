@@ -1,3 +1,16 @@
-pub mod searcher;
-pub mod transpositions;
-pub mod move_ordering;
\ No newline at end of file
+pub use crate::ai::shared::{searcher, transpositions, move_ordering};
+
+use self::searcher::Searcher;
+use super::evaluation::eval::Evaluation;
+
+/// V5 evaluates material at the leaves, without quiescence search or reductions
+pub fn create_searcher() -> Searcher {
+    Searcher::new(|board, _, _| Evaluation::evaluate(board), [Evaluation::PAWN_VALUE, Evaluation::KNIGHT_VALUE, Evaluation::BISHOP_VALUE, Evaluation::ROOK_VALUE, Evaluation::QUEEN_VALUE])
+        .with_quiescence(false)
+        .with_reductions(false)
+}
+
+/// V4 is V5 before move ordering
+pub fn create_v4_searcher() -> Searcher {
+    create_searcher().with_move_ordering(false)
+}
@@ -1,3 +1,11 @@
-pub mod searcher;
-pub mod transpositions;
-pub mod move_ordering;
\ No newline at end of file
+pub use crate::ai::shared::{searcher, transpositions, move_ordering};
+
+use self::searcher::Searcher;
+use super::evaluation::eval::Evaluation;
+
+/// V6 evaluates the leaves without quiescence search or reductions
+pub fn create_searcher() -> Searcher {
+    Searcher::new(|board, _, _| Evaluation::evaluate(board), [Evaluation::PAWN_VALUE, Evaluation::KNIGHT_VALUE, Evaluation::BISHOP_VALUE, Evaluation::ROOK_VALUE, Evaluation::QUEEN_VALUE])
+        .with_quiescence(false)
+        .with_reductions(false)
+}
@@ -1,3 +1,8 @@
-pub mod searcher;
-pub mod transpositions;
-pub mod move_ordering;
\ No newline at end of file
+pub use crate::ai::shared::{searcher, transpositions, move_ordering};
+
+use self::searcher::Searcher;
+use super::evaluation::{eval::Evaluation, material::PIECE_VALUE_BONUSES_MG};
+
+pub fn create_searcher() -> Searcher {
+    Searcher::new(|board, _, _| Evaluation::evaluate(board), PIECE_VALUE_BONUSES_MG)
+}
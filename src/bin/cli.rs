@@ -0,0 +1,153 @@
+use std::{env, fs, process, time::Instant};
+
+use chess_ai::{
+    ai::book::BookBuilder,
+    engine::{position::Position, tables::LookupTables},
+    move_gen::perft::{perft, perft_divide, PERFT_SUITE, CHESS960_PERFT_SUITE},
+    tablebase::retrograde::{Material, TableGenerator, MAX_GENERATED_PIECES},
+};
+
+const USAGE: &str = "\
+Usage: cli <command> [args]
+
+Commands:
+    perft <depth> [fen]     Count the leaf nodes of the move tree
+    divide <depth> [fen]    Count the leaf nodes below every root move
+    perft-suite [depth]     Run the built-in perft reference suites, standard and Chess960
+    book <output> <pgn...>  Build a Polyglot opening book from PGN files
+        --max-ply <n>       Only use the first n plies of every game (default 20)
+        --min-games <n>     Leave out moves played in fewer games (default 1)
+        --min-score <s>     Leave out moves scoring less than s, between 0 and 1 (default 0)
+    tablebase <dir> <material...>
+                            Generate endgame tables like KQK or KBNK, along with the tables they lead to";
+
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(|a| a.as_str()) {
+        Some("perft") => run_perft(&args[1..], false),
+        Some("divide") => run_perft(&args[1..], true),
+        Some("perft-suite") => run_perft_suite(&args[1..]),
+        Some("book") => run_book(&args[1..]),
+        Some("tablebase") => run_tablebase(&args[1..]),
+        _ => Err(String::from(USAGE)),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn parse_depth(arg: Option<&String>) -> Result<u8, String> {
+    match arg {
+        Some(depth) => depth.parse::<u8>().map_err(|_| format!("Invalid depth: {}", depth)),
+        None => Err(String::from(USAGE)),
+    }
+}
+
+fn run_perft(args: &[String], divide: bool) -> Result<(), String> {
+    let depth = parse_depth(args.first())?;
+    let mut position = match args.len() > 1 {
+        true => Position::from_fen(&args[1..].join(" ")).map_err(|err| format!("Invalid FEN: {}", err))?,
+        false => Position::start_pos(),
+    };
+    let tables = LookupTables::get();
+
+    let time_start = Instant::now();
+    let nodes = if divide {
+        let divide = perft_divide(&mut position.board, depth, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+        for (mov, nodes) in divide.iter() {
+            println!("{}: {}", mov.to_uci(), nodes);
+        }
+        println!();
+        divide.iter().map(|(_, nodes)| nodes).sum()
+    } else {
+        perft(&mut position.board, depth, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist)
+    };
+    let elapsed_ms = time_start.elapsed().as_millis().max(1);
+    println!("Nodes: {}", nodes);
+    println!("Time: {} ms ({} nps)", elapsed_ms, nodes as u128 * 1000 / elapsed_ms);
+    Ok(())
+}
+
+fn run_perft_suite(args: &[String]) -> Result<(), String> {
+    let max_depth = match args.first() {
+        Some(_) => parse_depth(args.first())?,
+        None => u8::MAX,
+    };
+    let tables = LookupTables::get();
+
+    let mut failed = 0;
+    let suites = PERFT_SUITE.iter().map(|test| (test, false)).chain(CHESS960_PERFT_SUITE.iter().map(|test| (test, true)));
+    for (test, chess960) in suites {
+        let mut position = Position::from_fen(test.fen).unwrap();
+        position.set_chess960(chess960);
+        for (depth_idx, expected) in test.expected.iter().enumerate().take(max_depth as usize) {
+            let depth = depth_idx as u8 + 1;
+            let nodes = perft(&mut position.board, depth, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+            let status = if nodes == *expected { "ok" } else { failed += 1; "FAILED" };
+            println!("{} depth {}: {} (expected {}) {}", test.name, depth, nodes, expected, status);
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{} perft checks failed", failed)),
+    }
+}
+
+fn run_book(args: &[String]) -> Result<(), String> {
+    let mut builder = BookBuilder::new(20);
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+        match arg.as_str() {
+            "--max-ply" => builder.max_ply = value(arg)?.parse().map_err(|_| format!("Invalid {}", arg))?,
+            "--min-games" => builder.min_games = value(arg)?.parse().map_err(|_| format!("Invalid {}", arg))?,
+            "--min-score" => builder.min_score = value(arg)?.parse().ok()
+                .filter(|score| (0.0..=1.0).contains(score))
+                .ok_or(format!("Invalid {}, expected a score between 0 and 1", arg))?,
+            _ => files.push(arg),
+        }
+    }
+    if files.len() < 2 {
+        return Err(String::from(USAGE));
+    }
+    let (output, pgn_files) = files.split_first().unwrap();
+
+    for file in pgn_files.iter() {
+        let pgn = fs::read_to_string(file).map_err(|err| format!("Could not read {}: {}", file, err))?;
+        let num_skipped = builder.num_skipped();
+        let num_added = builder.add_pgn(&pgn);
+        match builder.num_skipped() - num_skipped {
+            0 => println!("{}: {} games", file, num_added),
+            skipped => println!("{}: {} games, skipped {} that could not be parsed", file, num_added, skipped),
+        }
+    }
+    let book = builder.build();
+    book.write(output).map_err(|err| format!("Could not write {}: {}", output, err))?;
+    println!("Wrote {} entries from {} games to {}", book.len(), builder.num_games(), output);
+    Ok(())
+}
+
+fn run_tablebase(args: &[String]) -> Result<(), String> {
+    let Some((dir, names)) = args.split_first().filter(|(_, names)| !names.is_empty()) else {
+        return Err(String::from(USAGE));
+    };
+    let mut generator = TableGenerator::new();
+    for name in names.iter() {
+        let material = Material::parse(name).ok_or(format!(
+            "Invalid material: {} (at most {} pieces, with pawns on one side only)", name, MAX_GENERATED_PIECES
+        ))?;
+        let time_start = Instant::now();
+        let table = generator.generate(&material);
+        println!("{}: longest mate {} plies, {} ms", material, table.longest_mate(), time_start.elapsed().as_millis());
+    }
+
+    fs::create_dir_all(dir).map_err(|err| format!("Could not create {}: {}", dir, err))?;
+    for table in generator.tables().iter() {
+        table.write(dir).map_err(|err| format!("Could not write {}: {}", table.material(), err))?;
+    }
+    println!("Wrote {} tables to {}", generator.tables().len(), dir);
+    Ok(())
+}
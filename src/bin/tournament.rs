@@ -0,0 +1,151 @@
+use std::{env, fs, path::Path, process, time::Instant};
+
+use chess_ai::{
+    ai::ai_player::AIVersion,
+    game::clock::TimeControl,
+    tournament::{
+        DEFAULT_OPENINGS_PATH, Format, Participant, Tournament, load_openings, game::MatchSettings,
+        rating::{RatingList, RATING_LIST_CSV_PATH, RATING_LIST_MARKDOWN_PATH},
+    },
+};
+
+const USAGE: &str = "\
+Usage: tournament [options] <engine> <engine>...
+
+Engines are AI versions like V12, optionally followed by engine options like V13:Hash=64:Threads=2.
+Ratings are anchored at V0 when it plays, at the first engine otherwise.
+
+Options:
+    --all                   Add every AI version, for a rating list of the whole history
+    --format <format>       round-robin, or gauntlet to play the first engine against all others (default round-robin)
+    --rounds <n>            Openings played by every pairing, each one with both colours (default 10)
+    --concurrency <n>       Games played at the same time (default 1)
+    --tc <minutes+inc>      Time control like 1+0.1, in minutes and seconds of increment
+    --movetime <ms>         Time per move when there's no time control (default 100)
+    --max-plies <n>         Adjudicate games as a draw after n plies (default 300)
+    --openings <file>       Start positions, one FEN per line (default assets/logic/versus_positions.txt)
+    --chess960              Play from random Chess960 start positions instead
+    --out <dir>             Write the results, the rating list as CSV and Markdown, and one PGN per game to this directory";
+
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut settings = MatchSettings::default();
+    let mut format = Format::RoundRobin;
+    let mut rounds = 10;
+    let mut concurrency = 1;
+    let mut openings_path = String::from(DEFAULT_OPENINGS_PATH);
+    let mut out_dir = None;
+    let mut participants = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+        let invalid = || format!("Invalid {}", arg);
+        match arg.as_str() {
+            "--format" => format = match value(arg)?.as_str() {
+                "round-robin" => Format::RoundRobin,
+                "gauntlet" => Format::Gauntlet,
+                _ => return Err(invalid()),
+            },
+            "--rounds" => rounds = value(arg)?.parse().map_err(|_| invalid())?,
+            "--concurrency" => concurrency = value(arg)?.parse().map_err(|_| invalid())?,
+            "--tc" => settings.time_control = Some(TimeControl::parse(value(arg)?).ok_or_else(invalid)?),
+            "--movetime" => settings.move_time_ms = value(arg)?.parse().map_err(|_| invalid())?,
+            "--max-plies" => settings.max_plies = value(arg)?.parse().map_err(|_| invalid())?,
+            "--openings" => openings_path = value(arg)?.clone(),
+            "--chess960" => settings.chess960 = true,
+            "--all" => participants.extend(AIVersion::ALL.map(Participant::new)),
+            "--out" => out_dir = Some(value(arg)?.clone()),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            _ => participants.push(Participant::parse(arg)?),
+        }
+    }
+    if participants.len() < 2 {
+        return Err(String::from(USAGE));
+    }
+    // Catches invalid engine options before any game is played
+    for participant in participants.iter() {
+        participant.create_engine()?;
+    }
+    let openings = match settings.chess960 {
+        true => Tournament::chess960_openings(rounds),
+        false => load_openings(&openings_path).map_err(|err| format!("Could not read {}: {}", openings_path, err))?,
+    };
+    if openings.is_empty() {
+        return Err(format!("No openings in {}", openings_path));
+    }
+    if let Some(dir) = out_dir.as_ref() {
+        fs::create_dir_all(dir).map_err(|err| format!("Could not create {}: {}", dir, err))?;
+    }
+
+    let tournament = Tournament { participants, format, openings, rounds, concurrency, settings };
+    let num_games = tournament.games().len();
+    let time_control = match settings.time_control {
+        Some(time_control) => time_control.to_string(),
+        None => format!("{} ms per move", settings.move_time_ms),
+    };
+    println!("{} games between {} engines at {}, {} at a time", num_games, tournament.participants.len(), time_control, concurrency);
+
+    let time_start = Instant::now();
+    let mut num_finished = 0;
+    let mut write_error = None;
+    let standings = tournament.run(|game, record, _| {
+        num_finished += 1;
+        let white = &tournament.participants[game.white].name;
+        let black = &tournament.participants[game.black].name;
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => {
+                println!("[{}/{}] {} vs {}: {}", num_finished, num_games, white, black, err);
+                return;
+            },
+        };
+        println!(
+            "[{}/{}] {} vs {}: {} ({:?}, {} plies)",
+            num_finished, num_games, white, black, record.result.pgn_result(), record.result, record.moves.len(),
+        );
+        let Some(dir) = out_dir.as_ref() else { return };
+        let mut pgn = match record.to_pgn_game() {
+            Ok(pgn) => pgn,
+            Err(err) => {
+                write_error = Some(format!("Could not convert game {} to PGN: {}", game.idx + 1, err));
+                return;
+            },
+        };
+        pgn.set_tag("Event", "Tournament");
+        pgn.set_tag("Round", &(game.round + 1).to_string());
+        pgn.set_tag("White", white);
+        pgn.set_tag("Black", black);
+        if let Some(time_control) = settings.time_control {
+            pgn.set_tag("TimeControl", &format!("{}+{}", time_control.base.as_secs_f64(), time_control.increment.as_secs_f64()));
+        }
+        let path = Path::new(dir).join(format!("game_{}.pgn", game.idx + 1));
+        if let Err(err) = fs::write(&path, pgn.to_pgn()) {
+            write_error = Some(format!("Could not write {}: {}", path.display(), err));
+        }
+    });
+    if let Some(err) = write_error {
+        return Err(err);
+    }
+
+    let table = standings.to_table();
+    let ratings = RatingList::compute_anchored(&standings);
+    println!("\nFinished {} games in {} s\n", standings.num_games(), time_start.elapsed().as_secs());
+    print!("{}\n{}", table, ratings.to_markdown());
+    if let Some(dir) = out_dir.as_ref() {
+        for (file, contents) in [("results.txt", table), (RATING_LIST_CSV_PATH, ratings.to_csv()), (RATING_LIST_MARKDOWN_PATH, ratings.to_markdown())] {
+            let path = Path::new(dir).join(file);
+            fs::write(&path, contents).map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+        }
+        println!("\nWrote results, ratings and PGNs to {}", dir);
+    }
+    Ok(())
+}
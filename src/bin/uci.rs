@@ -0,0 +1,221 @@
+use std::{io::{self, BufRead}, sync::{Arc, atomic::{AtomicBool, Ordering}}, thread::{self, JoinHandle}};
+
+use chess_ai::{
+    ai::{v13::search::searcher::Searcher, stats::SearchInfo},
+    engine::{Engine, search::CoreEngine, position::Position},
+    tablebase::syzygy::MAX_PIECES,
+    board::moves::Move,
+    uci::command::{UciCommand, GoParams},
+};
+
+const ENGINE_NAME: &str = "Chess Engine V13";
+const ENGINE_AUTHOR: &str = "Rice-Rocket";
+const MAX_THREADS: usize = 256;
+const MAX_HASH_MB: usize = 65536;
+
+
+struct UciEngine {
+    position: Position,
+    engine: Option<CoreEngine>,
+    /// Returns the engine and its best move once the search is done
+    search_thread: Option<JoinHandle<(CoreEngine, Move)>>,
+    /// The running search is `go infinite`, its best move is only reported after `stop` or `quit`
+    infinite: bool,
+    stop_flag: Arc<AtomicBool>,
+    chess960: bool,
+    /// The last `position` had an invalid FEN, `go` is refused until a valid one is set
+    invalid_position: bool,
+}
+
+impl UciEngine {
+    fn new() -> Self {
+        let mut engine = CoreEngine::new();
+        engine.set_info_callback(Box::new(print_search_info));
+        let stop_flag = engine.stop_flag();
+
+        Self {
+            position: Position::start_pos(),
+            engine: Some(engine),
+            search_thread: None,
+            infinite: false,
+            stop_flag,
+            chess960: false,
+            invalid_position: false,
+        }
+    }
+
+    fn set_position(&mut self, fen: Option<String>, moves: Vec<String>) {
+        self.position = match fen.map(|fen| Position::from_fen(&fen)) {
+            Some(Ok(position)) => position,
+            Some(Err(err)) => {
+                println!("info string invalid fen: {}", err);
+                self.invalid_position = true;
+                return;
+            },
+            None => Position::start_pos(),
+        };
+        self.invalid_position = false;
+        self.position.set_chess960(self.chess960);
+        for name in moves.iter() {
+            match Move::from_uci(name, &self.position.legal_moves()) {
+                Some(mov) => self.position.make_move(mov),
+                None => {
+                    println!("info string illegal move {}", name);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn go(&mut self, params: GoParams) {
+        self.stop();
+        if self.invalid_position {
+            println!("info string no valid position to search");
+            println!("bestmove 0000");
+            return;
+        }
+        let mut engine = self.engine.take().unwrap();
+        let limits = params.limits(self.position.white_to_move());
+        self.stop_flag.store(false, Ordering::Relaxed);
+
+        let position = self.position.clone();
+        let infinite = params.infinite;
+        self.infinite = infinite;
+        self.search_thread = Some(thread::spawn(move || {
+            let result = engine.search(&position, limits);
+            if !infinite {
+                print_best_move(result.best_move);
+            }
+            (engine, result.best_move)
+        }));
+    }
+
+    fn set_option(&mut self, name: &str, value: Option<String>) {
+        match name.to_lowercase().as_str() {
+            "threads" => match value.and_then(|v| v.parse::<usize>().ok()) {
+                Some(threads) => self.engine.as_mut().unwrap().set_threads(threads.clamp(1, MAX_THREADS)),
+                None => println!("info string invalid value for option Threads"),
+            },
+            "hash" => match value.and_then(|v| v.parse::<usize>().ok()) {
+                Some(size_mb) => self.engine.as_mut().unwrap().set_hash_size_mb(size_mb.clamp(1, MAX_HASH_MB)),
+                None => println!("info string invalid value for option Hash"),
+            },
+            "uci_chess960" => match value.and_then(|v| v.parse::<bool>().ok()) {
+                Some(chess960) => {
+                    self.chess960 = chess960;
+                    self.position.set_chess960(chess960);
+                },
+                None => println!("info string invalid value for option UCI_Chess960"),
+            },
+            "syzygypath" => {
+                let path = value.filter(|path| path != "<empty>").unwrap_or_default();
+                match self.engine.as_mut().unwrap().set_syzygy_path(&path) {
+                    Ok(num_tables) => println!("info string found {} tablebases", num_tables),
+                    Err(err) => println!("info string could not read SyzygyPath: {}", err),
+                }
+            },
+            "tablebasepath" => {
+                let path = value.filter(|path| path != "<empty>").unwrap_or_default();
+                match self.engine.as_mut().unwrap().set_tablebase_path(&path) {
+                    Ok(num_tables) => println!("info string found {} generated tables", num_tables),
+                    Err(err) => println!("info string could not read TablebasePath: {}", err),
+                }
+            },
+            "syzygyprobelimit" => match value.and_then(|v| v.parse::<usize>().ok()) {
+                Some(num_pieces) => self.engine.as_mut().unwrap().set_syzygy_probe_limit(num_pieces.min(MAX_PIECES)),
+                None => println!("info string invalid value for option SyzygyProbeLimit"),
+            },
+            _ => println!("info string unknown option {}", name),
+        }
+    }
+
+    fn wait(&mut self) {
+        if let Some(search_thread) = self.search_thread.take() {
+            self.join(search_thread);
+        }
+    }
+
+    /// Stops a running search and waits for it to report its best move
+    fn stop(&mut self) {
+        if let Some(search_thread) = self.search_thread.take() {
+            self.stop_flag.store(true, Ordering::Relaxed);
+            self.join(search_thread);
+        }
+    }
+
+    /// Takes the engine back from a finished search, reporting the best move an infinite search held back
+    fn join(&mut self, search_thread: JoinHandle<(CoreEngine, Move)>) {
+        let (engine, best_move) = search_thread.join().unwrap();
+        self.engine = Some(engine);
+        if self.infinite {
+            print_best_move(best_move);
+        }
+    }
+}
+
+fn print_best_move(best_move: Move) {
+    if best_move.is_invalid() {
+        println!("bestmove 0000");
+    } else {
+        println!("bestmove {}", best_move.to_uci());
+    }
+}
+
+fn print_search_info(info: &SearchInfo) {
+    let score = if Searcher::is_mate_score(info.eval) {
+        let mate_in_moves = (Searcher::num_ply_in_mate(info.eval) + 1) / 2;
+        format!("mate {}", mate_in_moves * info.eval.signum())
+    } else {
+        format!("cp {}", info.eval)
+    };
+    let nps = info.num_nodes as u64 * 1000 / info.time_ms.max(1) as u64;
+    let pv: Vec<String> = info.pv.iter().map(|mov| mov.to_uci()).collect();
+    println!(
+        "info depth {} score {} nodes {} nps {} hashfull {} tbhits {} time {} pv {}",
+        info.depth, score, info.num_nodes, nps, info.hashfull, info.tb_hits, info.time_ms, pv.join(" ")
+    );
+}
+
+fn main() {
+    let mut engine = UciEngine::new();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break; };
+        match UciCommand::parse(&line) {
+            UciCommand::Uci => {
+                println!("id name {}", ENGINE_NAME);
+                println!("id author {}", ENGINE_AUTHOR);
+                println!("option name Hash type spin default {} min 1 max {}", Searcher::DEFAULT_TRANSPOSITION_TABLE_SIZE_MB, MAX_HASH_MB);
+                println!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS);
+                println!("option name UCI_Chess960 type check default false");
+                println!("option name SyzygyPath type string default <empty>");
+                println!("option name SyzygyProbeLimit type spin default {} min 0 max {}", MAX_PIECES, MAX_PIECES);
+                println!("option name TablebasePath type string default <empty>");
+                println!("uciok");
+            },
+            UciCommand::IsReady => println!("readyok"),
+            UciCommand::UciNewGame => {
+                engine.stop();
+                engine.engine.as_mut().unwrap().new_game();
+                engine.set_position(None, Vec::new());
+            },
+            UciCommand::Position { fen, moves } => {
+                engine.stop();
+                engine.set_position(fen, moves);
+            },
+            UciCommand::SetOption { name, value } => {
+                engine.stop();
+                engine.set_option(&name, value);
+            },
+            UciCommand::Go(params) => engine.go(params),
+            UciCommand::Stop => engine.stop(),
+            UciCommand::Quit => {
+                engine.stop();
+                return;
+            },
+            UciCommand::Unknown(_) => (),
+        }
+    }
+    // input was closed without a quit, let a running search finish on its own
+    engine.wait();
+}
@@ -1,13 +1,17 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 use super::{
     piece::Piece, piece_list::PieceList,
     moves::{self, Move}, zobrist::Zobrist, coord::Coord, game_state::GameState,
 };
-use crate::{fen, move_gen::{magics::MagicBitBoards, bitboard::bb::BitBoard}};
+use crate::{fen::{self, FenError}, game::representation::square_name_from_coord, move_gen::{magics::MagicBitBoards, bitboard::bb::BitBoard}};
 use crate::move_gen::bitboard::utils::*;
+#[cfg(feature = "gui")]
+use crate::{state::GameSettings, chess960};
 
 
-#[derive(Resource)]
+#[derive(Clone)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct Board {
     pub square: [Piece; 64],
     pub king_square: [Coord; 2],
@@ -36,6 +40,13 @@ pub struct Board {
     pub plycount: usize,
     pub current_state: GameState, 
     pub move_log: Vec<Move>,
+
+    /// Chess960 castling, where castling moves are encoded as the king capturing its own rook
+    pub chess960: bool,
+    /// Starting square of the rook for each castling right, in the order of the castling right bits
+    pub castling_rooks: [Coord; 4],
+    /// Castling rights lost when a piece moves from or to each square
+    castling_rights_mask: [u8; 64],
 }
 
 impl Default for Board {
@@ -96,6 +107,10 @@ impl Default for Board {
                 zobrist_key: 0,
             },
             move_log: Vec::new(),
+
+            chess960: false,
+            castling_rooks: [Coord::H1, Coord::A1, Coord::H8, Coord::A8],
+            castling_rights_mask: [0; 64],
         }
     }
 }
@@ -112,7 +127,7 @@ impl Board {
     }
 }
 
-#[derive(Event)]
+#[cfg_attr(feature = "gui", derive(Event))]
 pub struct BoardUnmakeMove {
     pub mov: moves::Move,
     pub in_search: bool,
@@ -129,14 +144,16 @@ impl Board {
         // print_board(self.square);
 
         let start_sqr = mov.start();
-        let target_sqr = mov.target();
         let move_flag = mov.move_flag();
         let is_promotion: bool = mov.is_promotion();
         let is_en_passant: bool = move_flag == Move::EN_PASSANT_CAPTURE;
+        let is_castling = move_flag == Move::CASTLING;
+        // The target of a Chess960 castling move is the rook, so the king's destination is looked up
+        let (target_sqr, castling_rook_from, castling_rook_to) = if is_castling { self.castling_squares(mov) } else { (mov.target(), Coord::NULL, Coord::NULL) };
     
         let moved_piece = self.square[start_sqr.index()];
         let moved_ptype = moved_piece.piece_type();
-        let captured_piece = if is_en_passant { Piece::new(Piece::PAWN | self.opponent_color) } else { self.square[target_sqr.index()] };
+        let captured_piece = if is_en_passant { Piece::new(Piece::PAWN | self.opponent_color) } else if is_castling { Piece::NULL } else { self.square[target_sqr.index()] };
         let captured_ptype = captured_piece.piece_type();
 
         let prev_castle_state = self.current_state.castling_rights;
@@ -145,7 +162,11 @@ impl Board {
         let mut new_castling_rights = self.current_state.castling_rights;
         let mut new_en_passant_file = 0;
 
-        self.move_piece(moved_piece, start_sqr, target_sqr);
+        if is_castling {
+            self.castle(moved_piece, start_sqr, target_sqr, castling_rook_from, castling_rook_to);
+        } else {
+            self.move_piece(moved_piece, start_sqr, target_sqr);
+        }
 
         if captured_ptype != Piece::NONE {
             let mut capture_sqr = target_sqr;
@@ -166,18 +187,8 @@ impl Board {
         if moved_ptype == Piece::KING {
             self.king_square[self.move_color_idx] = target_sqr;
             new_castling_rights &= if self.white_to_move { 0b1100 } else { 0b0011 };
-            if move_flag == Move::CASTLING {
+            if is_castling {
                 let rook_piece = Piece::new(Piece::ROOK | self.move_color);
-                let kingside = target_sqr == Coord::G1 || target_sqr == Coord::G8;
-                let castling_rook_from = if kingside { target_sqr + 1 } else { target_sqr - 2 };
-                let castling_rook_to = if kingside { target_sqr - 1 } else { target_sqr + 1 };
-
-                self.piece_bitboards[rook_piece.index()].toggle_squares(castling_rook_from.square(), castling_rook_to.square());
-                self.color_bitboards[self.move_color_idx].toggle_squares(castling_rook_from.square(), castling_rook_to.square());
-                self.get_piece_list_mut(rook_piece).move_piece(castling_rook_from, castling_rook_to);
-                self.square[castling_rook_from.index()] = Piece::NULL;
-                self.square[castling_rook_to.index()] = rook_piece;
-                
                 new_zobrist_key ^= zobrist.pieces_array[castling_rook_from.index()][rook_piece.index()];
                 new_zobrist_key ^= zobrist.pieces_array[castling_rook_to.index()][rook_piece.index()];
             }
@@ -206,15 +217,7 @@ impl Board {
         }
 
         if prev_castle_state != 0 {
-            if target_sqr == Coord::H1 || start_sqr == Coord::H1 {
-                new_castling_rights &= GameState::CLEAR_WHITE_KINGSIDE_MASK;
-            } else if target_sqr == Coord::A1 || start_sqr == Coord::A1 {
-                new_castling_rights &= GameState::CLEAR_WHITE_QUEENSIDE_MASK;
-            } else if target_sqr == Coord::H8 || start_sqr == Coord::H8 {
-                new_castling_rights &= GameState::CLEAR_BLACK_KINGSIDE_MASK;
-            } else if target_sqr == Coord::A8 || start_sqr == Coord::A8 {
-                new_castling_rights &= GameState::CLEAR_BLACK_QUEENSIDE_MASK;
-            }
+            new_castling_rights &= !(self.castling_rights_mask[start_sqr.index()] | self.castling_rights_mask[target_sqr.index()]);
         }
 
         new_zobrist_key ^= zobrist.side_to_move;
@@ -270,8 +273,9 @@ impl Board {
         let undoing_white_move = self.white_to_move;
 
         let move_from = mov.start();
-        let move_to = mov.target();
         let move_flag = mov.move_flag();
+        let undoing_castling = move_flag == Move::CASTLING;
+        let (move_to, castling_rook_from, castling_rook_to) = if undoing_castling { self.castling_squares(mov) } else { (mov.target(), Coord::NULL, Coord::NULL) };
 
         let undoing_en_passant = move_flag == Move::EN_PASSANT_CAPTURE;
         let undoing_promotion = mov.is_promotion();
@@ -292,7 +296,11 @@ impl Board {
             self.piece_bitboards[pawn_piece.index()].toggle_square(move_to.square());
         }
 
-        self.move_piece(moved_piece, move_to, move_from);
+        if undoing_castling {
+            self.castle(moved_piece, move_to, move_from, castling_rook_to, castling_rook_from);
+        } else {
+            self.move_piece(moved_piece, move_to, move_from);
+        }
 
         if undoing_capture {
             let mut capture_square = move_to;
@@ -313,26 +321,11 @@ impl Board {
 
         if moved_ptype == Piece::KING {
             self.king_square[self.move_color_idx] = move_from;
-            if move_flag == Move::CASTLING {
-                let rook_piece = Piece::new(Piece::ROOK | self.move_color);
-                let kingside = move_to == Coord::G1 || move_to == Coord::G8;
-                let rook_square_before_castling = if kingside { move_to + 1 } else { move_to - 2 };
-                let rook_square_after_castling = if kingside { move_to - 1 } else { move_to + 1 };
-
-                self.piece_bitboards[rook_piece.index()].toggle_squares(rook_square_after_castling.square(), rook_square_before_castling.square());
-                self.color_bitboards[self.move_color_idx].toggle_squares(rook_square_after_castling.square(), rook_square_before_castling.square());
-                self.square[rook_square_after_castling.index()] = Piece::NULL;
-                self.square[rook_square_before_castling.index()] = rook_piece;
-                self.get_piece_list_mut(rook_piece).move_piece(rook_square_after_castling, rook_square_before_castling);
-            }
         }
 
         self.all_pieces_bitboard = self.color_bitboards[Board::WHITE_INDEX] | self.color_bitboards[Board::BLACK_INDEX];
         self.update_slider_bitboards();
         
-        if !in_search && self.repeat_position_history.len() > 0 {
-            self.repeat_position_history.pop();
-        }
         if !in_search {
             self.move_log.remove(self.move_log.len() - 1);
         }
@@ -341,10 +334,22 @@ impl Board {
         self.current_state = self.game_state_history[self.game_state_history.len() - 1];
         self.plycount -= 1;
         self.has_cached_in_check_val = false;
+
+        // Positions before a capture or pawn move were cleared from the history when it was made,
+        // they are the positions since the previous one of those moves
+        if !in_search {
+            let num_reversible = (self.current_state.fifty_move_counter as usize + 1).min(self.game_state_history.len());
+            let first_reversible = self.game_state_history.len() - num_reversible;
+            self.repeat_position_history = self.game_state_history[first_reversible..].iter().map(|state| state.zobrist_key).collect();
+        }
     }
 
-    pub fn make_null_move(&mut self, zobrist: &Res<Zobrist>) {
+    pub fn make_null_move(&mut self, zobrist: &Zobrist) {
         self.white_to_move = !self.white_to_move;
+        self.move_color = if self.white_to_move { Piece::WHITE } else { Piece::BLACK };
+        self.opponent_color = if self.white_to_move { Piece::BLACK } else { Piece::WHITE };
+        self.move_color_idx = 1 - self.move_color_idx;
+        self.opponent_color_idx = 1 - self.opponent_color_idx;
         self.plycount += 1;
         
         let mut new_zobrist_key = self.current_state.zobrist_key;
@@ -367,6 +372,10 @@ impl Board {
 
     pub fn unmake_null_move(&mut self) {
         self.white_to_move = !self.white_to_move;
+        self.move_color = if self.white_to_move { Piece::WHITE } else { Piece::BLACK };
+        self.opponent_color = if self.white_to_move { Piece::BLACK } else { Piece::WHITE };
+        self.move_color_idx = 1 - self.move_color_idx;
+        self.opponent_color_idx = 1 - self.opponent_color_idx;
         self.plycount -= 1;
         self.game_state_history.pop();
         self.current_state = self.game_state_history[self.game_state_history.len() - 1];
@@ -375,12 +384,17 @@ impl Board {
         self.cached_in_check_val = false;
     }
     
-    pub fn load_position(&mut self, fen_str: Option<String>, zobrist: &mut Zobrist) {
+    /// Loads a position from a FEN string, or the start position if none is given.
+    /// The board is left untouched if the FEN is invalid.
+    pub fn load_position(&mut self, fen_str: Option<String>, zobrist: &Zobrist) -> Result<(), FenError> {
+        let loaded_pos = fen::position_from_fen(fen_str.as_deref().unwrap_or(fen::START_FEN))?;
+        self.load_position_info(&loaded_pos, zobrist);
+        Ok(())
+    }
+
+    /// Loads a position that was already parsed or built square by square, without validating it
+    pub fn load_position_info(&mut self, loaded_pos: &fen::LoadedPositionInfo, zobrist: &Zobrist) {
         *self = Self::default();
-        let loaded_pos = match fen_str {
-            Some(str) => fen::position_from_fen(str),
-            None => fen::position_from_fen(String::from(fen::START_FEN))
-        };
 
         for sqr_idx in 0i8..64i8 {
             let sqr = Coord::from_idx(sqr_idx);
@@ -412,6 +426,11 @@ impl Board {
         let white_castle = (if loaded_pos.white_castle_kingside { 1 << 0 } else { 0 }) | (if loaded_pos.white_castle_queenside { 1 << 1 } else { 0 });
         let black_castle = (if loaded_pos.black_castle_kingside { 1 << 2 } else { 0 }) | (if loaded_pos.black_castle_queenside { 1 << 3 } else { 0 });
         let castling_rights = white_castle | black_castle;
+        self.chess960 = loaded_pos.chess960;
+        for (right_idx, rook_file) in loaded_pos.castling_rook_files.iter().enumerate() {
+            self.castling_rooks[right_idx] = Coord::new(*rook_file, if right_idx < 2 { 0 } else { 7 });
+        }
+        self.update_castling_rights_mask();
 
         self.plycount = (loaded_pos.move_count as usize - 1) * 2 + (if self.white_to_move { 0 } else { 1 });
         self.current_state = GameState {
@@ -427,6 +446,50 @@ impl Board {
         self.game_state_history.push(self.current_state);
     }
 
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty_sqrs = 0;
+            for file in 0..8 {
+                let piece = self.square[rank * 8 + file];
+                if piece == Piece::NULL {
+                    empty_sqrs += 1;
+                    continue;
+                }
+                if empty_sqrs > 0 {
+                    fen += &empty_sqrs.to_string();
+                    empty_sqrs = 0;
+                }
+                fen.push(fen::symbol_from_piece(piece));
+            }
+            if empty_sqrs > 0 {
+                fen += &empty_sqrs.to_string();
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen += if self.white_to_move { " w " } else { " b " };
+
+        let castling_rights: String = (0..4)
+            .filter(|i| self.current_state.castling_rights & (1 << i) != 0)
+            .map(|i| self.castling_right_symbol(i))
+            .collect();
+        fen += if castling_rights.is_empty() { "-" } else { &castling_rights };
+
+        if self.current_state.en_passant_file > 0 {
+            let ep_rank = if self.white_to_move { 5 } else { 2 };
+            fen.push(' ');
+            fen += &square_name_from_coord(self.current_state.en_passant_file - 1, ep_rank);
+        } else {
+            fen += " -";
+        }
+
+        fen += &format!(" {} {}", self.current_state.fifty_move_counter, self.plycount / 2 + 1);
+        fen
+    }
+
     pub fn in_check(&mut self, magic: &MagicBitBoards, bbutils: &BitBoardUtils) -> bool {
         if self.has_cached_in_check_val {
             return self.cached_in_check_val;
@@ -466,6 +529,76 @@ impl Board {
         return false;
     }
 
+    /// Switches castling moves to the Chess960 encoding. The standard encoding of the king moving two squares
+    /// can't describe castling with the king outside of the e-file, so such positions keep the Chess960 encoding.
+    pub fn set_chess960(&mut self, chess960: bool) {
+        let standard_rooks = [Coord::H1, Coord::A1, Coord::H8, Coord::A8];
+        let needs_chess960 = (0..4).any(|right_idx| {
+            let king_sqr = self.king_square[right_idx / 2];
+            self.current_state.castling_rights & (1 << right_idx) != 0
+                && (king_sqr.file() != 4 || self.castling_rooks[right_idx] != standard_rooks[right_idx])
+        });
+        self.chess960 = chess960 || needs_chess960;
+    }
+
+    /// King target, rook start and rook target squares of a castling move, in either castling encoding
+    pub fn castling_squares(&self, mov: Move) -> (Coord, Coord, Coord) {
+        let king_from = mov.start();
+        let rank = king_from.rank();
+        let kingside = mov.target().file() > king_from.file();
+        let color_idx = if rank == 0 { Board::WHITE_INDEX } else { Board::BLACK_INDEX };
+        let rook_from = self.castling_rooks[color_idx * 2 + if kingside { 0 } else { 1 }];
+        if kingside {
+            (Coord::new(6, rank), rook_from, Coord::new(5, rank))
+        } else {
+            (Coord::new(2, rank), rook_from, Coord::new(3, rank))
+        }
+    }
+
+    /// X-FEN symbol of a castling right. The rook's file is used when another rook
+    /// stands between it and the edge of the board, as `KQkq` would refer to that rook instead.
+    fn castling_right_symbol(&self, right_idx: usize) -> char {
+        let rook_sqr = self.castling_rooks[right_idx];
+        let rook_piece = self.square[rook_sqr.index()];
+        let kingside = right_idx & 1 == 0;
+        let outer_files = if kingside { rook_sqr.file() + 1..8 } else { 0..rook_sqr.file() };
+        let is_outermost = outer_files.into_iter().all(|file| self.square[Coord::new(file, rook_sqr.rank()).index()] != rook_piece);
+
+        let symbol = match (is_outermost, kingside) {
+            (true, true) => 'k',
+            (true, false) => 'q',
+            (false, _) => (b'a' + rook_sqr.file() as u8) as char,
+        };
+        if right_idx < 2 { symbol.to_ascii_uppercase() } else { symbol }
+    }
+
+    fn update_castling_rights_mask(&mut self) {
+        self.castling_rights_mask = [0; 64];
+        for (right_idx, rook_sqr) in self.castling_rooks.iter().enumerate() {
+            self.castling_rights_mask[rook_sqr.index()] |= 1 << right_idx;
+        }
+        self.castling_rights_mask[self.king_square[Board::WHITE_INDEX].index()] |= 0b0011;
+        self.castling_rights_mask[self.king_square[Board::BLACK_INDEX].index()] |= 0b1100;
+    }
+
+    /// Moves the king and rook of a castling move. The rook is lifted off the board first,
+    /// as in Chess960 the king can land on the rook's square or the rook on the king's.
+    fn castle(&mut self, king: Piece, king_from: Coord, king_to: Coord, rook_from: Coord, rook_to: Coord) {
+        let rook = Piece::new(Piece::ROOK | king.color());
+        self.piece_bitboards[rook.index()].toggle_square(rook_from.square());
+        self.color_bitboards[rook.color_index()].toggle_square(rook_from.square());
+        self.square[rook_from.index()] = Piece::NULL;
+
+        if king_from != king_to {
+            self.move_piece(king, king_from, king_to);
+        }
+
+        self.piece_bitboards[rook.index()].toggle_square(rook_to.square());
+        self.color_bitboards[rook.color_index()].toggle_square(rook_to.square());
+        self.square[rook_to.index()] = rook;
+        self.get_piece_list_mut(rook).move_piece(rook_from, rook_to);
+    }
+
     fn move_piece(&mut self, piece: Piece, start: Coord, target: Coord) {
         self.piece_bitboards[piece.index()].toggle_squares(start.square(), target.square());
         self.color_bitboards[piece.color_index()].toggle_squares(start.square(), target.square());
@@ -499,12 +632,16 @@ impl Board {
 //     }
 // }
 
+#[cfg(feature = "gui")]
 pub fn spawn_main_board(
     mut commands: Commands,
-    mut zobrist: ResMut<Zobrist>,
+    zobrist: Res<Zobrist>,
+    settings: Res<GameSettings>,
 ) {
     let mut board = Board::default();
 
-    board.load_position(None, &mut zobrist);
+    let start_fen = if settings.chess960 { Some(chess960::random_start_fen()) } else { None };
+    board.load_position(start_fen, &zobrist).unwrap();
+    board.set_chess960(settings.chess960);
     commands.insert_resource(board);
 }
@@ -3,28 +3,37 @@ pub mod coord;
 pub mod piece;
 pub mod piece_list;
 pub mod zobrist;
+pub mod polyglot;
 pub mod moves;
 pub mod game_state;
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
+#[cfg(feature = "gui")]
 use crate::state::AppState;
+#[cfg(feature = "gui")]
 use board::*;
+#[cfg(feature = "gui")]
 use zobrist::*;
 
+#[cfg(feature = "gui")]
 fn finalize_zobrist(
     mut commands: Commands
 ) {
     commands.insert_resource(NextState(Some(AppState::LoadBoard)))
 }
 
+#[cfg(feature = "gui")]
 fn finalize(
     mut commands: Commands,
 ) {
     commands.insert_resource(NextState(Some(AppState::LoadMoveGen)))
 }
 
+#[cfg(feature = "gui")]
 pub struct BoardPlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
         app
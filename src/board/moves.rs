@@ -1,7 +1,10 @@
-use crate::game::representation::square_name_from_idx;
+use crate::game::representation::{square_name_from_idx, FILE_NAMES, RANK_NAMES};
+use crate::move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards};
 
 use super::piece::*;
 use super::coord::*;
+use super::board::Board;
+use super::zobrist::Zobrist;
 
 
 const START_SQUARE_MASK: u16 = 0b0000000000111111;
@@ -20,6 +23,40 @@ impl std::fmt::Debug for Move {
     }
 }
 
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+fn ptype_symbol(ptype: u8) -> char {
+    match ptype {
+        Piece::KNIGHT => 'N',
+        Piece::BISHOP => 'B',
+        Piece::ROOK => 'R',
+        Piece::QUEEN => 'Q',
+        Piece::KING => 'K',
+        _ => ' ',
+    }
+}
+
+fn ptype_from_symbol(symbol: char) -> u8 {
+    match symbol.to_ascii_uppercase() {
+        'N' => Piece::KNIGHT,
+        'B' => Piece::BISHOP,
+        'R' => Piece::ROOK,
+        'Q' => Piece::QUEEN,
+        'K' => Piece::KING,
+        _ => Piece::NONE,
+    }
+}
+
+fn square_from_name(file: char, rank: char) -> Option<i8> {
+    let file = FILE_NAMES.find(file)? as i8;
+    let rank = RANK_NAMES.find(rank)? as i8;
+    Some(rank * 8 + file)
+}
+
 
 impl Move {
     pub const NORMAL: u8 = 0;
@@ -67,6 +104,11 @@ impl Move {
         let flag = self.move_flag();
         flag == Move::QUEEN_PROMOTION || flag == Move::ROOK_PROMOTION || flag == Move::KNIGHT_PROMOTION || flag == Move::BISHOP_PROMOTION
     }
+    /// Whether the move takes the piece on its target square. Castling in Chess960 moves the king onto its own rook,
+    /// which isn't a capture. En passant isn't counted either, the captured pawn isn't on the target square.
+    pub fn is_capture(&self, board: &Board) -> bool {
+        board.square[self.target().index()].piece_type() != Piece::NONE && self.move_flag() != Move::CASTLING
+    }
     pub fn move_flag(&self) -> u8 {
         (self.val >> 12) as u8
     }
@@ -86,6 +128,144 @@ impl Move {
         self.val == 0
     }
     pub fn name(&self) -> String {
-        "Not implemented. In: move.rs/Move/name".to_string()
+        self.to_uci()
+    }
+
+    /// Converts the move into long algebraic notation (e.g. `e2e4`, `e7e8q`)
+    pub fn to_uci(&self) -> String {
+        let mut name = square_name_from_idx(self.start_idx()) + &square_name_from_idx(self.target_idx());
+        if self.is_promotion() {
+            name.push(ptype_symbol(self.promotion_ptype()).to_ascii_lowercase());
+        }
+        name
+    }
+
+    /// Finds the legal move matching a move in long algebraic notation
+    pub fn from_uci(name: &str, legal_moves: &[Move]) -> Option<Move> {
+        let chars: Vec<char> = name.chars().collect();
+        if chars.len() < 4 || chars.len() > 5 {
+            return None;
+        }
+        let start = square_from_name(chars[0], chars[1])?;
+        let target = square_from_name(chars[2], chars[3])?;
+        let prom_ptype = match chars.get(4) {
+            Some(symbol) => ptype_from_symbol(*symbol),
+            None => Piece::NONE,
+        };
+        legal_moves.iter().find(|mov| {
+            mov.start_idx() == start && mov.target_idx() == target && mov.promotion_ptype() == prom_ptype
+        }).copied()
+    }
+
+    /// Converts the move into standard algebraic notation (e.g. `Nbd7`, `exd5`, `e8=Q+`, `O-O`).
+    /// `legal_moves` are the legal moves of `board`, which is the position before the move is played.
+    pub fn to_san(&self,
+        board: &mut Board,
+        legal_moves: &[Move],
+        precomp: &PrecomputedMoveData,
+        bbutils: &BitBoardUtils,
+        magic: &MagicBitBoards,
+        zobrist: &Zobrist,
+    ) -> String {
+        let mut san = String::new();
+        let ptype = board.square[self.start().index()].piece_type();
+        let is_capture = board.square[self.target().index()] != Piece::NULL || self.move_flag() == Move::EN_PASSANT_CAPTURE;
+
+        if self.move_flag() == Move::CASTLING {
+            san += if self.target().file() > self.start().file() { "O-O" } else { "O-O-O" };
+        } else if ptype == Piece::PAWN {
+            if is_capture {
+                san.push(FILE_NAMES.chars().nth(self.start().file() as usize).unwrap());
+                san.push('x');
+            }
+            san += &square_name_from_idx(self.target_idx());
+            if self.is_promotion() {
+                san.push('=');
+                san.push(ptype_symbol(self.promotion_ptype()));
+            }
+        } else {
+            san.push(ptype_symbol(ptype));
+            let ambiguous: Vec<&Move> = legal_moves.iter().filter(|mov| {
+                mov.target_idx() == self.target_idx() && mov.start_idx() != self.start_idx()
+                    && board.square[mov.start().index()].piece_type() == ptype
+            }).collect();
+            if !ambiguous.is_empty() {
+                let shares_file = ambiguous.iter().any(|mov| mov.start().file() == self.start().file());
+                let shares_rank = ambiguous.iter().any(|mov| mov.start().rank() == self.start().rank());
+                let start_name = square_name_from_idx(self.start_idx());
+                if !shares_file {
+                    san += &start_name[0..1];
+                } else if !shares_rank {
+                    san += &start_name[1..2];
+                } else {
+                    san += &start_name;
+                }
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san += &square_name_from_idx(self.target_idx());
+        }
+
+        board.make_move(*self, true, zobrist);
+        if board.in_check(magic, bbutils) {
+            let mut move_gen = MoveGenerator::default();
+            move_gen.generate_moves(board, precomp, bbutils, magic, false);
+            san.push(if move_gen.moves.is_empty() { '#' } else { '+' });
+        }
+        board.unmake_move(*self, true);
+        san
+    }
+
+    /// Finds the legal move matching a move in standard algebraic notation.
+    /// `legal_moves` are the legal moves of `board`, e.g. `MoveGenerator::moves`.
+    pub fn from_san(san: &str, board: &Board, legal_moves: &[Move]) -> Option<Move> {
+        let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+
+        let castle_side = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(kingside) = castle_side {
+            return legal_moves.iter().find(|mov| {
+                mov.move_flag() == Move::CASTLING && (mov.target().file() > mov.start().file()) == kingside
+            }).copied();
+        }
+
+        let mut chars: Vec<char> = san.chars().filter(|c| *c != 'x' && *c != '=').collect();
+        let ptype = match chars.first().copied() {
+            Some(symbol) if symbol.is_ascii_uppercase() => {
+                chars.remove(0);
+                ptype_from_symbol(symbol)
+            },
+            Some(_) => Piece::PAWN,
+            None => return None,
+        };
+        if ptype == Piece::NONE {
+            return None;
+        }
+        let is_promotion = chars.len() >= 3 && chars[chars.len() - 1].is_ascii_alphabetic() && chars[chars.len() - 2].is_ascii_digit();
+        let prom_ptype = if is_promotion { ptype_from_symbol(chars.pop().unwrap()) } else { Piece::NONE };
+        if chars.len() < 2 || chars.len() > 4 {
+            return None;
+        }
+        let target = square_from_name(chars[chars.len() - 2], chars[chars.len() - 1])?;
+        let start_file = chars[..chars.len() - 2].iter().find_map(|c| FILE_NAMES.find(*c)).map(|f| f as i8);
+        let start_rank = chars[..chars.len() - 2].iter().find_map(|c| RANK_NAMES.find(*c)).map(|r| r as i8);
+
+        let mut candidates = legal_moves.iter().filter(|mov| {
+            mov.target_idx() == target
+                && mov.move_flag() != Move::CASTLING
+                && board.square[mov.start().index()].piece_type() == ptype
+                && mov.promotion_ptype() == prom_ptype
+                && start_file.is_none_or(|file| mov.start().file() == file)
+                && start_rank.is_none_or(|rank| mov.start().rank() == rank)
+        });
+        let mov = candidates.next().copied();
+        if candidates.next().is_some() {
+            return None;
+        }
+        mov
     }
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 use rand::{Rng, SeedableRng, rngs::StdRng};
@@ -6,7 +7,8 @@ use super::piece::*;
 
 const SEED: u64 = 29426028;
 
-#[derive(Resource, Clone)]
+#[derive(Clone)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct Zobrist {
     pub pieces_array: [[u64; (Piece::MAX_PIECE_INDEX + 1) as usize]; 64], // index [square][piece]
     pub castling_rights: [u64; 16],
@@ -41,7 +43,7 @@ impl Zobrist {
 
         return default;
     }
-    pub fn calc_zobrist_key(&mut self, board: &Board) -> u64 {
+    pub fn calc_zobrist_key(&self, board: &Board) -> u64 {
         let mut zobrist_key: u64 = 0;
         
         for sqr_idx in 0..64 {
@@ -67,6 +69,7 @@ impl Default for Zobrist {
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn spawn_zobrist(
     mut commands: Commands,
 ) {
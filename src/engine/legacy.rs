@@ -0,0 +1,71 @@
+use std::{time::Instant, sync::{Arc, atomic::AtomicBool}};
+
+use crate::{ai::{ai_player::{AIVersion, DEFAULT_AI_THINK_TIME_MS}, time_manager::TimeManager}, board::board::Board, move_gen::move_generator::MoveGenerator};
+
+use super::{Engine, position::Position, search::{SearchLimits, SearchResult}, tables::LookupTables};
+
+
+/// Searcher of a version older than the shared core, it searches the board it's given for a fixed time
+pub trait LegacySearcher: Send + Sync {
+    /// Versions with a fixed search depth ignore `think_time_ms`. Every version returns early once `stop_flag` is set
+    fn search_board(&mut self, board: &mut Board, move_gen: &mut MoveGenerator, tables: &LookupTables, think_time_ms: u32, stop_flag: &Arc<AtomicBool>);
+
+    /// Best move found by the last search
+    fn result(&self) -> SearchResult;
+}
+
+/// Runs a legacy searcher through the `Engine` interface. Only the think time is honoured,
+/// it comes from the clock when there is one. Depth and node limits are ignored.
+pub struct LegacyEngine<S> {
+    version: AIVersion,
+    create_searcher: fn() -> S,
+    searcher: S,
+    position: Position,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl<S: LegacySearcher> LegacyEngine<S> {
+    pub fn new(version: AIVersion, create_searcher: fn() -> S) -> Self {
+        Self {
+            version,
+            create_searcher,
+            searcher: create_searcher(),
+            position: Position::start_pos(),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl<S: LegacySearcher> Engine for LegacyEngine<S> {
+    fn version(&self) -> AIVersion {
+        self.version
+    }
+
+    fn new_game(&mut self) {
+        self.searcher = (self.create_searcher)();
+    }
+
+    fn set_position(&mut self, position: &Position) {
+        self.position = position.clone();
+    }
+
+    fn go(&mut self, limits: SearchLimits) -> SearchResult {
+        let think_time_ms = limits.clock.map(|clock| TimeManager::new(clock).soft_limit_ms())
+            .or(limits.think_time_ms)
+            .unwrap_or(DEFAULT_AI_THINK_TIME_MS);
+        let tables = LookupTables::get();
+        let mut board = self.position.board.clone();
+        let mut move_gen = MoveGenerator::default();
+        move_gen.generate_moves(&board, &tables.precomp, &tables.bbutils, &tables.magic, false);
+
+        let search_start = Instant::now();
+        self.searcher.search_board(&mut board, &mut move_gen, tables, think_time_ms, &self.stop_flag);
+        let mut result = self.searcher.result();
+        result.stats.think_time_ms = search_start.elapsed().as_millis() as u32;
+        result
+    }
+
+    fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_flag.clone()
+    }
+}
@@ -0,0 +1,36 @@
+pub mod tables;
+pub mod position;
+pub mod search;
+pub mod legacy;
+pub mod registry;
+
+use std::sync::{Arc, atomic::AtomicBool};
+
+use crate::ai::{ai_player::AIVersion, stats::IterationCallback};
+use self::{position::Position, search::{SearchLimits, SearchResult}};
+
+
+/// Common interface of every AI version, modelled after the UCI commands so any version can be driven
+/// the same way by the GUI, the UCI front-end or a match runner. Engines are created with `registry::create_engine`.
+pub trait Engine: Send + Sync {
+    fn version(&self) -> AIVersion;
+
+    /// Forgets everything learned in previous games, like the transposition table
+    fn new_game(&mut self);
+
+    fn set_position(&mut self, position: &Position);
+
+    /// Searches the current position until one of the limits is reached
+    fn go(&mut self, limits: SearchLimits) -> SearchResult;
+
+    /// Flag that stops a running search when set. The caller clears it before the next search
+    fn stop_flag(&self) -> Arc<AtomicBool>;
+
+    /// Called after every completed iteration, versions that don't report progress ignore it
+    fn set_info_callback(&mut self, _callback: IterationCallback) {}
+
+    /// Sets an option by its UCI name, versions without options reject every name
+    fn set_option(&mut self, name: &str, _value: &str) -> Result<(), String> {
+        Err(format!("unknown option {}", name))
+    }
+}
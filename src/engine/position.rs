@@ -0,0 +1,112 @@
+use crate::{board::{board::Board, moves::Move}, game::result::GameResult, move_gen::move_generator::MoveGenerator, utils::{fen::FenError, chess960}};
+
+use super::tables::LookupTables;
+
+
+/// A chess position that can be queried and played through without any of the GUI resources
+#[derive(Clone)]
+pub struct Position {
+    pub board: Board,
+}
+
+impl Position {
+    pub fn start_pos() -> Self {
+        let mut board = Board::default();
+        board.load_position(None, &LookupTables::get().zobrist).unwrap();
+        Self { board }
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut board = Board::default();
+        board.load_position(Some(fen.to_string()), &LookupTables::get().zobrist)?;
+        Ok(Self { board })
+    }
+
+    /// Chess960 start position with the given number, see `chess960::back_rank`
+    pub fn chess960_start_pos(id: u16) -> Self {
+        let mut position = Self::from_fen(&chess960::start_fen(id)).unwrap();
+        position.set_chess960(true);
+        position
+    }
+
+    /// Switches castling moves to the Chess960 encoding of the king capturing its own rook.
+    /// Positions whose castling rights need a king or rook outside of the standard squares always use it.
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.board.set_chess960(chess960);
+    }
+
+    pub fn to_fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    pub fn white_to_move(&self) -> bool {
+        self.board.white_to_move
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let tables = LookupTables::get();
+        let mut move_gen = MoveGenerator::default();
+        move_gen.generate_moves(&self.board, &tables.precomp, &tables.bbutils, &tables.magic, false);
+        move_gen.moves
+    }
+
+    pub fn in_check(&mut self) -> bool {
+        let tables = LookupTables::get();
+        self.board.in_check(&tables.magic, &tables.bbutils)
+    }
+
+    /// Checkmate, stalemate or one of the draw rules, `Playing` while the game goes on
+    pub fn game_result(&mut self) -> GameResult {
+        let legal_moves = self.legal_moves();
+        let in_check = self.in_check();
+        GameResult::from_board(&self.board, &legal_moves, in_check)
+    }
+
+    /// Converts a legal move of this position into standard algebraic notation
+    pub fn move_to_san(&mut self, mov: Move) -> String {
+        let tables = LookupTables::get();
+        let legal_moves = self.legal_moves();
+        mov.to_san(&mut self.board, &legal_moves, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist)
+    }
+
+    /// Converts a line of moves played from this position into standard algebraic notation,
+    /// stopping at the first move that isn't legal
+    pub fn line_to_san(&self, line: &[Move]) -> Vec<String> {
+        let mut position = self.clone();
+        let mut sans = Vec::new();
+        for mov in line.iter() {
+            if !position.legal_moves().contains(mov) {
+                break;
+            }
+            sans.push(position.move_to_san(*mov));
+            position.make_move(*mov);
+        }
+        sans
+    }
+
+    /// Finds the legal move matching a move in standard algebraic notation
+    pub fn move_from_san(&self, san: &str) -> Option<Move> {
+        Move::from_san(san, &self.board, &self.legal_moves())
+    }
+
+    /// Finds the legal move matching a move in long algebraic notation
+    pub fn move_from_uci(&self, name: &str) -> Option<Move> {
+        Move::from_uci(name, &self.legal_moves())
+    }
+
+    /// Plays a move, which is expected to be one of the legal moves of this position
+    pub fn make_move(&mut self, mov: Move) {
+        self.board.make_move(mov, false, &LookupTables::get().zobrist);
+    }
+
+    /// Takes back the last move played with `make_move`
+    pub fn unmake_move(&mut self, mov: Move) {
+        self.board.unmake_move(mov, false);
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::start_pos()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::ai::{ai_player::AIVersion, v0, v1, v2, v3, v5, v6, v7, v8, v9, v11, v12};
+
+use super::{Engine, legacy::LegacyEngine, search::CoreEngine};
+
+
+/// Creates a new engine playing as `version`, any number of them can exist side by side
+pub fn create_engine(version: AIVersion) -> Box<dyn Engine> {
+    match version {
+        AIVersion::V0 => Box::new(LegacyEngine::new(version, v0::search::searcher::Searcher::default)),
+        AIVersion::V1 => Box::new(LegacyEngine::new(version, v1::search::searcher::Searcher::default)),
+        AIVersion::V2 => Box::new(LegacyEngine::new(version, v2::search::searcher::Searcher::default)),
+        AIVersion::V3 => Box::new(LegacyEngine::new(version, v3::search::searcher::Searcher::default)),
+        // V4 to V12 share one searcher, configured by each version's search module
+        AIVersion::V4 => Box::new(LegacyEngine::new(version, v5::search::create_v4_searcher)),
+        AIVersion::V5 => Box::new(LegacyEngine::new(version, v5::search::create_searcher)),
+        AIVersion::V6 => Box::new(LegacyEngine::new(version, v6::search::create_searcher)),
+        AIVersion::V7 => Box::new(LegacyEngine::new(version, v7::search::create_searcher)),
+        AIVersion::V8 => Box::new(LegacyEngine::new(version, v8::search::create_searcher)),
+        AIVersion::V9 => Box::new(LegacyEngine::new(version, v9::search::create_searcher)),
+        AIVersion::V10 => Box::new(LegacyEngine::new(version, v11::search::create_v10_searcher)),
+        AIVersion::V11 => Box::new(LegacyEngine::new(version, v11::search::create_searcher)),
+        AIVersion::V12 => Box::new(LegacyEngine::new(version, v12::search::create_searcher)),
+        AIVersion::V13 => Box::new(CoreEngine::new()),
+    }
+}
@@ -0,0 +1,147 @@
+use std::{io, sync::{Arc, atomic::AtomicBool}};
+
+use crate::{ai::{ai_player::AIVersion, v13::search::searcher::Searcher, stats::{SearchStatistics, IterationCallback}, time_manager::{ClockTime, TimeManager}}, board::moves::Move, move_gen::move_generator::MoveGenerator, tablebase::{syzygy::{SyzygyTablebase, MAX_PIECES}, retrograde::EndgameTables}};
+
+use super::{Engine, position::Position, tables::LookupTables};
+
+
+/// Limits for a single search, any limit left as `None` is unbounded
+#[derive(Default, Clone, Copy)]
+pub struct SearchLimits {
+    pub think_time_ms: Option<u32>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u32>,
+    /// Time left on the clock, the time manager then decides how long to think and overrides `think_time_ms`
+    pub clock: Option<ClockTime>,
+}
+
+pub struct SearchResult {
+    pub best_move: Move,
+    pub pv: Vec<Move>,
+    pub eval: i32,
+    pub depth: i32,
+    pub stats: SearchStatistics,
+}
+
+/// Engine built on the newest searcher, it plays as V13
+pub struct CoreEngine {
+    searcher: Searcher,
+    position: Position,
+}
+
+impl CoreEngine {
+    pub fn new() -> Self {
+        Self {
+            searcher: Searcher::default(),
+            position: Position::start_pos(),
+        }
+    }
+
+    pub fn search(&mut self, position: &Position, limits: SearchLimits) -> SearchResult {
+        let tables = LookupTables::get();
+        let mut board = position.board.clone();
+        let mut move_gen = MoveGenerator::default();
+        move_gen.generate_moves(&board, &tables.precomp, &tables.bbutils, &tables.magic, false);
+
+        self.searcher.max_think_time_ms = limits.think_time_ms.unwrap_or(u32::MAX);
+        self.searcher.max_depth = limits.depth.unwrap_or(u8::MAX).max(1);
+        self.searcher.max_nodes = limits.nodes.unwrap_or(u32::MAX);
+        self.searcher.time_manager = limits.clock.map(TimeManager::new);
+        self.searcher.start_search(&mut board, &mut move_gen, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+
+        SearchResult {
+            best_move: self.searcher.best_move_so_far,
+            pv: self.searcher.principal_variation.clone(),
+            eval: self.searcher.best_eval_so_far,
+            depth: self.searcher.current_depth,
+            stats: self.searcher.search_statistics(),
+        }
+    }
+
+    /// Sets the number of threads used by following searches
+    pub fn set_threads(&mut self, threads: usize) {
+        self.searcher.num_threads = threads.max(1);
+    }
+
+    /// Resizes the transposition table, clearing its contents
+    pub fn set_hash_size_mb(&mut self, size_mb: usize) {
+        self.searcher.set_hash_size_mb(size_mb.max(1));
+    }
+
+    /// Probes the Syzygy tables in the given directories, separated like the `PATH` variable.
+    /// An empty path, or one without tables, turns probing off. Returns the number of tables found.
+    pub fn set_syzygy_path(&mut self, path: &str) -> io::Result<usize> {
+        if path.trim().is_empty() {
+            self.searcher.tablebase = None;
+            return Ok(0);
+        }
+        let tablebase = SyzygyTablebase::open(path)?;
+        let num_tables = tablebase.num_tables();
+        self.searcher.tablebase = (num_tables > 0).then(|| Arc::new(tablebase));
+        Ok(num_tables)
+    }
+
+    /// Only positions with at most this many pieces are probed
+    pub fn set_syzygy_probe_limit(&mut self, num_pieces: usize) {
+        self.searcher.tb_probe_limit = num_pieces;
+    }
+
+    /// Probes the generated tables in the given directories, separated like the `PATH` variable.
+    /// An empty path, or one without tables, turns probing off. Returns the number of tables found.
+    pub fn set_tablebase_path(&mut self, path: &str) -> io::Result<usize> {
+        if path.trim().is_empty() {
+            self.searcher.endgame_tables = None;
+            return Ok(0);
+        }
+        let tables = EndgameTables::open(path)?;
+        let num_tables = tables.len();
+        self.searcher.endgame_tables = (num_tables > 0).then(|| Arc::new(tables));
+        Ok(num_tables)
+    }
+}
+
+impl Engine for CoreEngine {
+    fn version(&self) -> AIVersion {
+        AIVersion::V13
+    }
+
+    fn new_game(&mut self) {
+        self.searcher.new_game();
+    }
+
+    fn set_position(&mut self, position: &Position) {
+        self.position = position.clone();
+    }
+
+    fn go(&mut self, limits: SearchLimits) -> SearchResult {
+        let position = self.position.clone();
+        self.search(&position, limits)
+    }
+
+    fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.searcher.stop_flag.clone()
+    }
+
+    fn set_info_callback(&mut self, callback: IterationCallback) {
+        self.searcher.on_iteration_complete = Some(callback);
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let invalid_value = || format!("invalid value for option {}", name);
+        match name.to_lowercase().as_str() {
+            "threads" => self.set_threads(value.parse().map_err(|_| invalid_value())?),
+            "hash" => self.set_hash_size_mb(value.parse().map_err(|_| invalid_value())?),
+            "syzygypath" => { self.set_syzygy_path(value).map_err(|err| err.to_string())?; },
+            "syzygyprobelimit" => self.set_syzygy_probe_limit(value.parse::<usize>().map_err(|_| invalid_value())?.min(MAX_PIECES)),
+            "tablebasepath" => { self.set_tablebase_path(value).map_err(|err| err.to_string())?; },
+            _ => return Err(format!("unknown option {}", name)),
+        }
+        Ok(())
+    }
+}
+
+impl Default for CoreEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::OnceLock;
+
+use crate::{board::zobrist::Zobrist, move_gen::{precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}};
+
+
+/// Lookup tables needed for move generation and search
+pub struct LookupTables {
+    pub precomp: PrecomputedMoveData,
+    pub bbutils: BitBoardUtils,
+    pub magic: MagicBitBoards,
+    pub zobrist: Zobrist,
+}
+
+impl LookupTables {
+    pub fn new() -> Self {
+        Self {
+            precomp: PrecomputedMoveData::default(),
+            bbutils: BitBoardUtils::default(),
+            magic: MagicBitBoards::default(),
+            zobrist: Zobrist::default(),
+        }
+    }
+
+    /// Returns the process-wide tables, building them on first use
+    pub fn get() -> &'static Self {
+        static TABLES: OnceLock<LookupTables> = OnceLock::new();
+        TABLES.get_or_init(Self::new)
+    }
+}
+
+impl Default for LookupTables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,309 @@
+use std::{fmt, time::Duration};
+
+#[cfg(feature = "gui")]
+use bevy::prelude::*;
+
+use crate::{board::{board::Board, piece::Piece}, move_gen::bitboard::bb::BitBoard, ai::time_manager::ClockTime};
+#[cfg(feature = "gui")]
+use crate::state::{AppState, GameSettings};
+use super::result::GameResult;
+#[cfg(feature = "gui")]
+use super::manager::GameManager;
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DelayKind {
+    /// The clock only starts counting down once the delay has passed
+    Simple,
+    /// Time used on a move is given back, up to the delay
+    Bronstein,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+    pub delay: Duration,
+    pub delay_kind: DelayKind,
+    /// The base time is added again every time this many moves have been played
+    pub moves_per_session: Option<u32>,
+}
+
+impl TimeControl {
+    /// Time controls offered in the main menu
+    pub const PRESETS: [TimeControl; 6] = [
+        TimeControl::fischer(60, 0),
+        TimeControl::fischer(180, 2),
+        TimeControl::fischer(300, 3),
+        TimeControl::delayed(600, 5, DelayKind::Bronstein),
+        TimeControl::delayed(900, 10, DelayKind::Simple),
+        TimeControl::sessions(40, 5400),
+    ];
+
+    /// Base time with a Fischer increment added after every move
+    pub const fn fischer(base_secs: u64, increment_secs: u64) -> Self {
+        Self {
+            base: Duration::from_secs(base_secs),
+            increment: Duration::from_secs(increment_secs),
+            delay: Duration::ZERO,
+            delay_kind: DelayKind::Simple,
+            moves_per_session: None,
+        }
+    }
+
+    pub const fn delayed(base_secs: u64, delay_secs: u64, delay_kind: DelayKind) -> Self {
+        Self {
+            base: Duration::from_secs(base_secs),
+            increment: Duration::ZERO,
+            delay: Duration::from_secs(delay_secs),
+            delay_kind,
+            moves_per_session: None,
+        }
+    }
+
+    /// `moves` moves have to be played in `base_secs`, then the clock is topped up again
+    pub const fn sessions(moves: u32, base_secs: u64) -> Self {
+        Self {
+            base: Duration::from_secs(base_secs),
+            increment: Duration::ZERO,
+            delay: Duration::ZERO,
+            delay_kind: DelayKind::Simple,
+            moves_per_session: Some(moves),
+        }
+    }
+
+    /// Parses the `minutes+increment` format written by `Display`, both numbers can be fractional like `0.5+0.1`
+    pub fn parse(text: &str) -> Option<Self> {
+        let (minutes, increment_secs) = text.split_once('+').unwrap_or((text, "0"));
+        let base = Duration::try_from_secs_f64(minutes.trim().parse::<f64>().ok()? * 60.0).ok()?;
+        let increment = Duration::try_from_secs_f64(increment_secs.trim().parse().ok()?).ok()?;
+        Some(Self {
+            base,
+            increment,
+            ..Self::fischer(0, 0)
+        })
+    }
+}
+
+impl fmt::Display for TimeControl {
+    /// Written as `5+3`, `10 d5`, `15 b5` or `40/90` with minutes and seconds
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let minutes = self.base.as_secs() / 60;
+        if let Some(moves) = self.moves_per_session {
+            write!(f, "{}/{}", moves, minutes)?;
+        } else {
+            write!(f, "{}", minutes)?;
+        }
+        if !self.increment.is_zero() || (self.delay.is_zero() && self.moves_per_session.is_none()) {
+            write!(f, "+{}", self.increment.as_secs())?;
+        }
+        if !self.delay.is_zero() {
+            let prefix = match self.delay_kind {
+                DelayKind::Simple => "d",
+                DelayKind::Bronstein => "b",
+            };
+            write!(f, " {}{}", prefix, self.delay.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Clock of both players, indexed by color index
+#[cfg_attr(feature = "gui", derive(Resource))]
+#[derive(Clone, Debug)]
+pub struct ChessClock {
+    pub time_control: TimeControl,
+    remaining: [Duration; 2],
+    moves_played: [u32; 2],
+    /// Time spent so far on the current move
+    move_time: Duration,
+    active: Option<usize>,
+    flagged: Option<usize>,
+}
+
+impl ChessClock {
+    pub fn new(time_control: TimeControl) -> Self {
+        Self {
+            time_control,
+            remaining: [time_control.base; 2],
+            moves_played: [0; 2],
+            move_time: Duration::ZERO,
+            active: None,
+            flagged: None,
+        }
+    }
+
+    /// Color index of the side whose clock is running
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Color index of the side that ran out of time
+    pub fn flagged(&self) -> Option<usize> {
+        self.flagged
+    }
+
+    pub fn remaining(&self, color_idx: usize) -> Duration {
+        self.remaining[color_idx]
+    }
+
+    /// Time left before the clock of `color_idx` starts counting down, non zero only with a simple delay
+    pub fn delay_left(&self, color_idx: usize) -> Duration {
+        if self.active != Some(color_idx) || self.time_control.delay_kind != DelayKind::Simple {
+            return Duration::ZERO;
+        }
+        self.time_control.delay.saturating_sub(self.move_time)
+    }
+
+    /// Starts the clock of `color_idx` without ending a move
+    pub fn start(&mut self, color_idx: usize) {
+        if self.flagged.is_none() {
+            self.active = Some(color_idx);
+            self.move_time = Duration::ZERO;
+        }
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        let Some(color_idx) = self.active else {
+            return;
+        };
+        let previous = self.move_time;
+        self.move_time += delta;
+        let used = match self.time_control.delay_kind {
+            DelayKind::Simple => self.move_time.saturating_sub(self.time_control.delay) - previous.saturating_sub(self.time_control.delay),
+            DelayKind::Bronstein => delta,
+        };
+        self.remaining[color_idx] = self.remaining[color_idx].saturating_sub(used);
+        if self.remaining[color_idx].is_zero() {
+            self.flagged = Some(color_idx);
+            self.active = None;
+        }
+    }
+
+    /// Ends the move of the running side, adds its increment and starts the opponent's clock
+    pub fn press(&mut self) {
+        let Some(color_idx) = self.active else {
+            return;
+        };
+        let time_control = self.time_control;
+        if time_control.delay_kind == DelayKind::Bronstein {
+            self.remaining[color_idx] += self.move_time.min(time_control.delay);
+        }
+        self.remaining[color_idx] += time_control.increment;
+        self.moves_played[color_idx] += 1;
+        if let Some(moves) = time_control.moves_per_session {
+            if self.moves_played[color_idx].is_multiple_of(moves) {
+                self.remaining[color_idx] += time_control.base;
+            }
+        }
+        self.start(1 - color_idx);
+    }
+
+    /// Clock of `color_idx` as seen by the searcher's time manager, a delay counts as an increment
+    pub fn clock_time(&self, color_idx: usize) -> ClockTime {
+        ClockTime {
+            time_left_ms: self.remaining[color_idx].as_millis() as u32,
+            increment_ms: (self.time_control.increment + self.time_control.delay).as_millis() as u32,
+            moves_to_go: self.time_control.moves_per_session.map(|moves| moves - self.moves_played[color_idx] % moves),
+        }
+    }
+}
+
+/// Result of a game where a side ran out of time, which is a draw only if the opponent can't checkmate
+/// the flagged side by any series of legal moves
+pub fn timeout_result(board: &Board, white_flagged: bool) -> GameResult {
+    let (flagged_idx, opponent_idx) = if white_flagged {
+        (Board::WHITE_INDEX, Board::BLACK_INDEX)
+    } else {
+        (Board::BLACK_INDEX, Board::WHITE_INDEX)
+    };
+    if opponent_cannot_mate(board, flagged_idx, opponent_idx) {
+        GameResult::TimeoutVsInsufficientMaterial
+    } else if white_flagged {
+        GameResult::WhiteTimeout
+    } else {
+        GameResult::BlackTimeout
+    }
+}
+
+/// A lone king never mates and a king with a knight only mates a king that has material to block itself in.
+/// Bishops alone can't mate when every bishop on the board, the flagged side's included, stands on one square color
+fn opponent_cannot_mate(board: &Board, flagged_idx: usize, opponent_idx: usize) -> bool {
+    let pieces = |piece_type: u8, color_idx: usize| {
+        let color = if color_idx == Board::WHITE_INDEX { Piece::WHITE } else { Piece::BLACK };
+        board.piece_bitboards[Piece::new(piece_type | color).index()]
+    };
+    let num_opponent_pieces = board.color_bitboards[opponent_idx].count();
+    let num_flagged_pieces = board.color_bitboards[flagged_idx].count();
+    if num_opponent_pieces == 1 {
+        return true;
+    }
+    if num_opponent_pieces == 2 && pieces(Piece::KNIGHT, opponent_idx).count() == 1 {
+        return num_flagged_pieces == 1;
+    }
+
+    let opponent_bishops = pieces(Piece::BISHOP, opponent_idx);
+    let flagged_bishops = pieces(Piece::BISHOP, flagged_idx);
+    if opponent_bishops.count() != num_opponent_pieces - 1 || flagged_bishops.count() != num_flagged_pieces - 1 {
+        return false;
+    }
+    let bishops = opponent_bishops.0 | flagged_bishops.0;
+    bishops & BitBoard::LIGHT_SQUARES.0 == 0 || bishops & BitBoard::DARK_SQUARES.0 == 0
+}
+
+pub fn format_clock_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs < 20 {
+        format!("{}:{:02}.{}", secs / 60, secs % 60, time.subsec_millis() / 100)
+    } else if secs < 3600 {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    } else {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+#[cfg(feature = "gui")]
+pub fn reset_clock(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    clock: Option<Res<ChessClock>>,
+    manager: Res<GameManager>,
+) {
+    // A game resumed after taking back its last moves keeps the time used so far
+    if clock.is_some() && !manager.game_moves.is_empty() {
+        return;
+    }
+    match settings.time_control {
+        Some(time_control) => commands.insert_resource(ChessClock::new(time_control)),
+        None => commands.remove_resource::<ChessClock>(),
+    }
+}
+
+#[cfg(feature = "gui")]
+pub fn update_clock(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Option<ResMut<ChessClock>>,
+    mut manager: ResMut<GameManager>,
+    board: Res<Board>,
+) {
+    let Some(mut clock) = clock else {
+        return;
+    };
+    let color_idx = match manager.move_color {
+        Piece::WHITE => Board::WHITE_INDEX,
+        Piece::BLACK => Board::BLACK_INDEX,
+        _ => return,
+    };
+    match clock.active() {
+        Some(active) if active != color_idx => clock.press(),
+        Some(_) => (),
+        None => clock.start(color_idx),
+    }
+    clock.tick(time.delta());
+    if let Some(flagged) = clock.flagged() {
+        manager.game_result = timeout_result(&board, flagged == Board::WHITE_INDEX);
+        commands.insert_resource(NextState(Some(AppState::GameOver)));
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fmt, thread::{self, JoinHandle}};
+
+use bevy::prelude::*;
+
+use crate::{
+    state::AppState,
+    board::{board::Board, moves::Move, piece::Piece},
+    move_gen::move_generator::MoveGenerator,
+    uci::{client::{UciClient, UciClientError}, command::GoParams},
+    ui::ingame_menu::DebugPositionLoaded,
+};
+
+use super::{manager::{BoardMakeMove, CanMakeMove, GameManager}, player::Player, clock::ChessClock, result::GameResult};
+
+/// Engine executable used when the main menu doesn't name another one, looked up in `PATH`
+pub const DEFAULT_EXTERNAL_ENGINE_PATH: &str = "stockfish";
+pub const DEFAULT_EXTERNAL_THINK_TIME_MS: u32 = 1000;
+
+
+/// A UCI engine installed on the same machine, launched the first time it has to move.
+/// Talking to the engine can take a while, so launching it, starting a search and stopping one
+/// run on a worker thread that holds the client until the engine answered.
+#[derive(Component)]
+pub struct ExternalEnginePlayer {
+    pub path: String,
+    pub think_time_ms: u32,
+    pub searching: bool,
+    /// The next search tells the engine a new game started, set again by the versus manager between games
+    pub new_game: bool,
+    /// Name the engine reported when it was launched
+    name: Option<String>,
+    client: Option<UciClient>,
+    task: Option<JoinHandle<Result<Option<UciClient>, ExternalEngineError>>>,
+}
+
+#[derive(Debug)]
+pub enum ExternalEngineError {
+    /// The engine couldn't be started or didn't finish the handshake
+    Launch(UciClientError),
+    /// The engine stopped answering during the game
+    Engine(UciClientError),
+}
+
+impl fmt::Display for ExternalEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Launch(err) => write!(f, "Failed to launch the engine: {}", err),
+            Self::Engine(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl ExternalEnginePlayer {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            think_time_ms: DEFAULT_EXTERNAL_THINK_TIME_MS,
+            searching: false,
+            new_game: true,
+            name: None,
+            client: None,
+            task: None,
+        }
+    }
+
+    /// Name the engine reported, or its executable before it was launched
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.path.clone())
+    }
+
+    /// Runs `task` on a worker thread once the previous task finished, it gets the client the previous task left
+    fn run_task(&mut self, task: impl FnOnce(Result<Option<UciClient>, ExternalEngineError>) -> Result<Option<UciClient>, ExternalEngineError> + Send + 'static) {
+        let client = self.client.take();
+        let previous = self.task.take();
+        self.task = Some(thread::spawn(move || {
+            let client = match previous {
+                Some(previous) => previous.join().expect("External engine thread panicked"),
+                None => Ok(client),
+            };
+            task(client)
+        }));
+    }
+
+    /// Sends the game so far to the engine and starts searching, `start_fen` is the start position when it's `None`.
+    /// Errors are reported by `poll_best_move`.
+    pub fn begin_search(&mut self, start_fen: Option<String>, moves: Vec<Move>, params: GoParams, chess960: bool) {
+        let path = self.path.clone();
+        let new_game = std::mem::take(&mut self.new_game);
+        self.searching = true;
+        self.run_task(move |client| {
+            let mut client = match client? {
+                Some(client) => client,
+                None => {
+                    let mut client = UciClient::launch(&path).map_err(ExternalEngineError::Launch)?;
+                    if chess960 {
+                        client.set_option("UCI_Chess960", "true").map_err(ExternalEngineError::Launch)?;
+                    }
+                    client
+                },
+            };
+            if new_game {
+                client.new_game().map_err(ExternalEngineError::Engine)?;
+            }
+            client.go(start_fen.as_deref(), &moves, params).map_err(ExternalEngineError::Engine)?;
+            Ok(Some(client))
+        });
+    }
+
+    pub fn poll_best_move(&mut self) -> Result<Option<String>, ExternalEngineError> {
+        if self.task.as_ref().is_some_and(|task| task.is_finished()) {
+            self.client = self.task.take().unwrap().join().expect("External engine thread panicked")?;
+            if let Some(name) = self.client.as_ref().and_then(|client| client.name.clone()) {
+                self.name = Some(name);
+            }
+        }
+        if self.task.is_some() || !self.searching {
+            return Ok(None);
+        }
+        let Some(client) = self.client.as_mut().filter(|client| client.is_searching()) else { return Ok(None) };
+        let best_move = client.poll_best_move().map_err(ExternalEngineError::Engine)?;
+        if best_move.is_some() {
+            self.searching = false;
+        }
+        Ok(best_move)
+    }
+
+    /// Stops a running search, its best move is thrown away
+    pub fn stop(&mut self) {
+        self.searching = false;
+        if self.task.is_none() && !self.client.as_ref().is_some_and(|client| client.is_searching()) {
+            return;
+        }
+        let path = self.path.clone();
+        self.run_task(move |client| {
+            // A search that failed to start was cancelled anyway, the engine is launched again for the next one
+            let Ok(Some(mut client)) = client else { return Ok(None) };
+            if let Err(err) = client.stop() {
+                warn!("Failed to stop {}: {}", path, err);
+                return Ok(None);
+            }
+            Ok(Some(client))
+        });
+    }
+}
+
+/// The engine loses the game when it crashes or plays an illegal move
+fn forfeit(commands: &mut Commands, manager: &mut GameManager, team: u8, reason: String) {
+    warn!("External engine forfeits: {}", reason);
+    manager.game_result = if team == Piece::WHITE { GameResult::WhiteIllegalMove } else { GameResult::BlackIllegalMove };
+    commands.insert_resource(NextState(Some(AppState::GameOver)));
+}
+
+/// Ends the game without a result when the engine can't be used at all
+fn abort_game(commands: &mut Commands, manager: &mut GameManager, path: &str, err: ExternalEngineError) {
+    error!("{}: {}", path, err);
+    manager.game_result = GameResult::Aborted;
+    commands.insert_resource(NextState(Some(AppState::GameOver)));
+}
+
+pub fn external_begin_search(
+    mut player_query: Query<(&mut ExternalEnginePlayer, &Player)>,
+    manager: Res<GameManager>,
+    board: Res<Board>,
+    clock: Option<Res<ChessClock>>,
+    mut can_make_move_evr: EventReader<CanMakeMove>,
+) {
+    for _can_make_move_ev in can_make_move_evr.iter() {
+        for (mut engine, player) in player_query.iter_mut() {
+            if player.team != manager.move_color || engine.searching {
+                continue;
+            }
+            // On the clock the engine manages its own time like in any other UCI GUI
+            let params = match clock.as_ref() {
+                Some(clock) => {
+                    let (white, black) = (clock.clock_time(Board::WHITE_INDEX), clock.clock_time(Board::BLACK_INDEX));
+                    let own = if player.team == Piece::WHITE { white } else { black };
+                    GoParams {
+                        wtime: Some(white.time_left_ms),
+                        btime: Some(black.time_left_ms),
+                        winc: Some(white.increment_ms),
+                        binc: Some(black.increment_ms),
+                        moves_to_go: own.moves_to_go,
+                        ..Default::default()
+                    }
+                },
+                None => GoParams { move_time: Some(engine.think_time_ms), ..Default::default() },
+            };
+            engine.begin_search(manager.custom_position.clone(), manager.game_moves.clone(), params, board.chess960);
+        }
+    }
+}
+
+pub fn external_make_move(
+    mut commands: Commands,
+    mut make_move_evw: EventWriter<BoardMakeMove>,
+    mut player_query: Query<(&mut ExternalEnginePlayer, &Player)>,
+    mut manager: ResMut<GameManager>,
+    move_gen: Res<MoveGenerator>,
+) {
+    for (mut engine, player) in player_query.iter_mut() {
+        let best_move = match engine.poll_best_move() {
+            Ok(Some(best_move)) => best_move,
+            Ok(None) => continue,
+            Err(err @ ExternalEngineError::Launch(_)) => {
+                abort_game(&mut commands, &mut manager, &engine.path, err);
+                continue;
+            },
+            Err(err) => {
+                forfeit(&mut commands, &mut manager, player.team, format!("{}: {}", engine.path, err));
+                continue;
+            },
+        };
+        match Move::from_uci(&best_move, &move_gen.moves) {
+            Some(mov) => { make_move_evw.send(BoardMakeMove { mov }); },
+            None => forfeit(&mut commands, &mut manager, player.team, format!("{} played the illegal move {}", engine.label(), best_move)),
+        }
+    }
+}
+
+/// Cancels the running search when the position it was started from is no longer on the board
+pub fn abort_external_search(
+    mut player_query: Query<&mut ExternalEnginePlayer>,
+) {
+    for mut engine in player_query.iter_mut() {
+        if engine.searching {
+            engine.stop();
+        }
+    }
+}
+
+/// A position loaded from the debug menu is a different game for the engine
+pub fn reset_external_game(
+    mut debug_position_loaded_evr: EventReader<DebugPositionLoaded>,
+    mut player_query: Query<&mut ExternalEnginePlayer>,
+) {
+    if debug_position_loaded_evr.iter().next().is_none() {
+        return;
+    }
+    for mut engine in player_query.iter_mut() {
+        engine.stop();
+        engine.new_game = true;
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
 use crate::{
-    ui::{board::{BoardUITransform, BoardUIResetPiecePosition, BoardSetSquareColor, BoardUI, BoardResetSquareColors}, theme::SquareColorTypes}, 
+    ui::{board::{BoardUITransform, BoardUIResetPiecePosition, BoardSetSquareColor, BoardUI, BoardResetSquareColors}, theme::SquareColorTypes, move_list::MoveListView, promotion_dialog::promotion_dialog_squares}, 
     board::moves::Move,
     board::coord::Coord,
     board::piece::*,
@@ -19,6 +19,8 @@ pub enum PlayerInputState {
     None,
     PieceSelected, 
     DraggingPiece,
+    /// The promotion dialog is open for a pawn move, the piece it promotes to isn't chosen yet
+    ChoosingPromotion(Move),
 }
 
 #[derive(Component)]
@@ -49,9 +51,13 @@ pub fn handle_player_input(
     mut reset_sqr_color_evw: EventWriter<BoardResetSquareColors>,
     mut board_ui: ResMut<BoardUI>,
     move_gen: Res<MoveGenerator>,
-    keyboard: Res<Input<KeyCode>>,
     manager: Res<GameManager>,
+    move_list_view: Res<MoveListView>,
 ) {
+    // Positions looked back at in the move list can't be played on
+    if move_list_view.is_viewing() {
+        return;
+    }
     if let Some(mpos) = window_query.single().cursor_position() {
         for (mut player, player_data) in player_query.iter_mut() {
             if player_data.team != manager.move_color { continue };
@@ -81,7 +87,6 @@ pub fn handle_player_input(
                         &mut reset_sqr_color_evw,
                         &mut board_ui,
                         &move_gen,
-                        &keyboard,
                     );
                 }
             } else if player.current_state == PlayerInputState::PieceSelected {
@@ -98,7 +103,19 @@ pub fn handle_player_input(
                         &mut reset_sqr_color_evw,
                         &mut board_ui,
                         &move_gen,
-                        &keyboard,
+                    );
+                }
+            } else if let PlayerInputState::ChoosingPromotion(promotion_move) = player.current_state {
+                if buttons.just_pressed(MouseButton::Left) {
+                    handle_promotion_choice(
+                        &mut player,
+                        promotion_move,
+                        &board_transform,
+                        mpos,
+                        &mut reset_piece_position_evw,
+                        &mut make_move_evw,
+                        &mut reset_sqr_color_evw,
+                        &move_gen,
                     );
                 }
             }
@@ -180,7 +197,6 @@ pub fn handle_piece_placement(
     mut reset_sqr_color_evw: &mut EventWriter<BoardResetSquareColors>,
     mut board_ui: &mut ResMut<BoardUI>,
     move_gen: &Res<MoveGenerator>,
-    keyboard: &Res<Input<KeyCode>>,
 ) {
     if let Some(target_sqr) = board_transform.get_hovered_square(mpos) {
         if target_sqr.is_eq(player.selected_piece_sqr) {
@@ -198,7 +214,11 @@ pub fn handle_piece_placement(
             }
         } else {
             let target_idx = target_sqr.square();
-            if board.square[target_idx as usize].is_color(board.move_color) && board.square[target_idx as usize] != Piece::NULL {
+            // Chess960 castling is played by dropping the king onto its own rook
+            let is_castling_drop = move_gen.moves.iter().any(|mov| {
+                mov.move_flag() == Move::CASTLING && mov.start() == player.selected_piece_sqr && mov.target() == target_sqr
+            });
+            if board.square[target_idx as usize].is_color(board.move_color) && board.square[target_idx as usize] != Piece::NULL && !is_castling_drop {
                 cancel_piece_selection(&mut player, &mut reset_piece_position_evw, &mut reset_sqr_color_evw);
                 handle_piece_selection(
                     &buttons,
@@ -218,10 +238,17 @@ pub fn handle_piece_placement(
                     &mut reset_sqr_color_evw,
                     Move::from_start_end(selected_sqr, target_idx), 
                     &move_gen,
-                    &keyboard,
                     &mut make_move_evw,
                 );
-                cancel_piece_selection(&mut player, &mut reset_piece_position_evw, &mut reset_sqr_color_evw)
+                if let PlayerInputState::ChoosingPromotion(_) = player.current_state {
+                    // The pawn stays on its square until the promotion piece is chosen
+                    reset_piece_position_evw.send(BoardUIResetPiecePosition {
+                        origin_file: player.selected_piece_sqr.file(),
+                        origin_rank: player.selected_piece_sqr.rank(),
+                    });
+                } else {
+                    cancel_piece_selection(&mut player, &mut reset_piece_position_evw, &mut reset_sqr_color_evw)
+                }
             }
         }
     } else {
@@ -235,35 +262,46 @@ pub fn player_make_move(
     reset_sqr_color_evw: &mut EventWriter<BoardResetSquareColors>,
     mov: Move,
     move_gen: &Res<MoveGenerator>,
-    keyboard: &Res<Input<KeyCode>>,
     make_move_evw: &mut EventWriter<BoardMakeMove>,
 ) {
-    let mut move_is_legal = false;
-    let mut chosen_move = Move::NULL;
-
-    let wants_knight_prom = keyboard.pressed(KeyCode::ShiftLeft);
-    let legal_moves = &move_gen.moves;  
-    for legal_move in legal_moves.iter() {
-        if legal_move.start() == mov.start() && legal_move.target() == mov.target() {
-            if legal_move.is_promotion() {
-                if legal_move.move_flag() == Move::QUEEN_PROMOTION && wants_knight_prom {
-                    continue;
-                }
-                if legal_move.move_flag() != Move::QUEEN_PROMOTION && !wants_knight_prom {
-                    continue;
-                }
-            }
-            move_is_legal = true;
-            chosen_move = *legal_move;
-            break;
-        }
+    let legal_move = move_gen.moves.iter().find(|legal_move| {
+        legal_move.start() == mov.start() && legal_move.target() == mov.target()
+    });
+    match legal_move {
+        // The promotion piece is chosen in the promotion dialog
+        Some(legal_move) if legal_move.is_promotion() => {
+            player.current_state = PlayerInputState::ChoosingPromotion(*legal_move);
+        },
+        Some(legal_move) => make_move_evw.send(BoardMakeMove {
+            mov: *legal_move,
+        }),
+        None => cancel_piece_selection(player, reset_piece_position_evw, reset_sqr_color_evw),
     }
+}
 
-    if move_is_legal {
-        make_move_evw.send(BoardMakeMove {
-            mov: chosen_move, 
+/// Plays the promotion to the piece clicked in the promotion dialog, clicking anywhere else closes it
+pub fn handle_promotion_choice(
+    player: &mut Mut<HumanPlayer>,
+    promotion_move: Move,
+    board_transform: &Res<BoardUITransform>,
+    mpos: Vec2,
+    reset_piece_position_evw: &mut EventWriter<BoardUIResetPiecePosition>,
+    make_move_evw: &mut EventWriter<BoardMakeMove>,
+    reset_sqr_color_evw: &mut EventWriter<BoardResetSquareColors>,
+    move_gen: &Res<MoveGenerator>,
+) {
+    let chosen_ptype = board_transform.get_hovered_square(mpos).and_then(|clicked_sqr| {
+        promotion_dialog_squares(promotion_move.target()).find(|(sqr, _)| *sqr == clicked_sqr).map(|(_, ptype)| ptype)
+    });
+    if let Some(ptype) = chosen_ptype {
+        let chosen_move = move_gen.moves.iter().find(|legal_move| {
+            legal_move.start() == promotion_move.start() && legal_move.target() == promotion_move.target() && legal_move.promotion_ptype() == ptype
         });
-    } else {
-        cancel_piece_selection(player, reset_piece_position_evw, reset_sqr_color_evw)
+        if let Some(chosen_move) = chosen_move {
+            make_move_evw.send(BoardMakeMove {
+                mov: *chosen_move,
+            });
+        }
     }
-}
\ No newline at end of file
+    cancel_piece_selection(player, reset_piece_position_evw, reset_sqr_color_evw);
+}
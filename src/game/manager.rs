@@ -2,22 +2,9 @@ use std::time::SystemTime;
 
 use bevy::prelude::*;
 
-use crate::{board::{moves::Move, board::Board, zobrist::Zobrist, piece::Piece}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}, ui::ingame_menu::CalcStatistics, state::{AppState, AppMode}};
-
-#[derive(Clone, Copy)]
-pub enum GameResult {
-    None,
-    Playing,
-    WhiteIsMated,
-    BlackIsMated,
-    Stalemate,
-    Repetition,
-    FiftyMoveRule,
-    InsufficientMaterial,
-    DrawByArbiter,
-    WhiteTimeout,
-    BlackTimeout
-}
+use crate::{game::result::GameResult, board::{moves::Move, board::Board, zobrist::Zobrist, piece::Piece}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}, ui::ingame_menu::CalcStatistics, state::{AppState, AppMode}, ai::{ai_player::{AIPlayer, BeginSearch, SearchComplete}, search_thread::SearchThread}};
+
+use super::{player::Player, human_player::HumanPlayer, external_player::ExternalEnginePlayer, clock::ChessClock};
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum PlayerType {
@@ -37,59 +24,18 @@ pub struct GameManager {
 
 impl GameManager {
     // Regenerates and returns game result given board and legal moves
-    pub fn gen_game_result(&mut self, board: &Board, moves: &Vec<Move>, in_check: bool) -> GameResult {
-        if moves.len() == 0 {
-            if in_check {
-                self.game_result = if board.white_to_move { GameResult::WhiteIsMated } else { GameResult::BlackIsMated };
-                return self.game_result;
-            }
-            self.game_result = GameResult::Stalemate;
-            return self.game_result;
-        }
-        if board.current_state.fifty_move_counter >= 100 {
-            self.game_result = GameResult::FiftyMoveRule;
-            return self.game_result;
-        }
-        let mut rep_count = 0;
-        for position in board.repeat_position_history.iter() {
-            if *position == board.current_state.zobrist_key {
-                rep_count += 1;
-            };
-            if rep_count >= 3 {
-                self.game_result = GameResult::Repetition;
-                return self.game_result;
-            };
-        };
-        if self.insufficient_material(board) {
-            self.game_result = GameResult::InsufficientMaterial;
-            return self.game_result;
-        }
-        self.game_result = GameResult::Playing;
-        return self.game_result;
+    pub fn gen_game_result(&mut self, board: &Board, moves: &[Move], in_check: bool) -> GameResult {
+        self.game_result = GameResult::from_board(board, moves, in_check);
+        self.game_result
     }
-    fn insufficient_material(&self, board: &Board) -> bool {
-        if board.friendly_orthogonal_sliders.0 != 0 || board.enemy_orthogonal_sliders.0 != 0 {
-            return false;
-        };
-        if board.get_piece_list(Piece::new(Piece::WHITE_PAWN)).count() > 0 || 
-            board.get_piece_list(Piece::new(Piece::BLACK_PAWN)).count() > 0 {
-                return false;
-        };
-
-        let n_white_bishops = board.get_piece_list(Piece::new(Piece::WHITE_BISHOP)).count();
-        let n_black_bishops = board.get_piece_list(Piece::new(Piece::BLACK_BISHOP)).count();
-        let n_white_knights = board.get_piece_list(Piece::new(Piece::WHITE_KNIGHT)).count();
-        let n_black_knights = board.get_piece_list(Piece::new(Piece::BLACK_KNIGHT)).count();
-        let n_white_minors = n_white_bishops + n_white_knights;
-        let n_black_minors = n_black_bishops + n_black_knights;
-
-        if n_white_minors == 0 && n_black_minors == 0 {
-            return true;
-        };
-        if (n_white_minors == 1 && n_black_minors == 0) || (n_white_minors == 0 && n_black_minors == 1) {
-            return true;
-        };
-        return false;
+
+    /// Position after the first `ply` moves of the game, the board is expected to be at the end of `game_moves`
+    pub fn board_at_ply(&self, board: &Board, ply: usize) -> Board {
+        let mut board = board.clone();
+        for mov in self.game_moves.iter().skip(ply).rev() {
+            board.unmake_move(*mov, false);
+        }
+        board
     }
 }
 
@@ -104,6 +50,14 @@ pub struct ProcessedMove {}
 #[derive(Event)]
 pub struct CanMakeMove {}
 
+/// Takes back the last move, and against the AI its reply too so a human is to move again
+#[derive(Event)]
+pub struct TakeBackMove {}
+
+/// The board was rewound, so it doesn't follow from the pieces moved so far anymore
+#[derive(Event)]
+pub struct MovesTakenBack {}
+
 pub fn initialize_game(
     mut move_gen: ResMut<MoveGenerator>,
     board: Res<Board>,
@@ -117,6 +71,7 @@ pub fn initialize_game(
 pub fn spawn_game_manager(
     mut commands: Commands,
     app_mode: Res<State<AppMode>>,
+    board: Res<Board>,
 ) {
     let (white, black) = match app_mode.clone() {
         AppMode::GameHumanHuman => (PlayerType::Human, PlayerType::Human),
@@ -125,7 +80,8 @@ pub fn spawn_game_manager(
         AppMode::None => (PlayerType::Human, PlayerType::Human),
     };
     commands.insert_resource(GameManager {
-        custom_position: None,
+        // Chess960 games don't start from the standard position
+        custom_position: if board.chess960 { Some(board.to_fen()) } else { None },
         white_player_type: white,
         black_player_type: black,
         game_result: GameResult::Playing,
@@ -179,6 +135,10 @@ pub fn on_make_move(
             GameResult::DrawByArbiter => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
             GameResult::WhiteTimeout => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
             GameResult::BlackTimeout => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
+            GameResult::TimeoutVsInsufficientMaterial => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
+            GameResult::WhiteIllegalMove => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
+            GameResult::BlackIllegalMove => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
+            GameResult::Aborted => { commands.insert_resource(NextState(Some(AppState::GameOver))) },
         }
         manager.executed_board_move = None;
         processed_move_evw.send(ProcessedMove {});
@@ -197,3 +157,62 @@ pub fn advance_turn(
     }
 }
 
+
+pub fn take_back_moves(
+    mut commands: Commands,
+    mut take_back_evr: EventReader<TakeBackMove>,
+    mut taken_back_evw: EventWriter<MovesTakenBack>,
+    mut processed_move_evw: EventWriter<ProcessedMove>,
+    (mut make_move_events, mut begin_search_events, mut search_complete_events): (ResMut<Events<BoardMakeMove>>, ResMut<Events<BeginSearch>>, ResMut<Events<SearchComplete>>),
+    mut board: ResMut<Board>,
+    mut move_gen: ResMut<MoveGenerator>,
+    (precomp, bbutils, magic): (Res<PrecomputedMoveData>, Res<BitBoardUtils>, Res<MagicBitBoards>),
+    mut manager: ResMut<GameManager>,
+    clock: Option<ResMut<ChessClock>>,
+    search_thread: Option<ResMut<SearchThread>>,
+    human_query: Query<&Player, With<HumanPlayer>>,
+    (mut ai_query, mut external_query): (Query<&mut AIPlayer>, Query<&mut ExternalEnginePlayer>),
+    mut stats: ResMut<CalcStatistics>,
+) {
+    if take_back_evr.iter().count() == 0 || manager.game_moves.is_empty() {
+        return;
+    }
+    // Flagged clocks can't be restarted
+    if clock.as_ref().is_some_and(|clock| clock.flagged().is_some()) {
+        return;
+    }
+
+    // The running search and moves that weren't played yet belong to the position being taken back
+    if let Some(mut search_thread) = search_thread {
+        search_thread.abort();
+    }
+    for mut ai in ai_query.iter_mut() {
+        ai.searching = false;
+    }
+    for mut external in external_query.iter_mut() {
+        external.stop();
+    }
+    make_move_events.clear();
+    begin_search_events.clear();
+    search_complete_events.clear();
+    stats.ai_pv.clear();
+
+    let is_human = |color: u8| human_query.iter().any(|player| player.team == color);
+    while let Some(mov) = manager.game_moves.pop() {
+        board.unmake_move(mov, false);
+        if is_human(board.move_color) || manager.game_moves.is_empty() {
+            break;
+        }
+    }
+
+    move_gen.generate_moves(&board, &precomp, &bbutils, &magic, false);
+    manager.gen_game_result(board.as_ref(), &move_gen.moves, move_gen.in_check());
+    manager.executed_board_move = None;
+    manager.move_color = board.move_color;
+    if let Some(mut clock) = clock {
+        clock.start(board.move_color_idx);
+    }
+    commands.insert_resource(NextState(Some(AppState::InGame)));
+    taken_back_evw.send(MovesTakenBack {});
+    processed_move_evw.send(ProcessedMove {});
+}
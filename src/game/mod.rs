@@ -1,48 +1,85 @@
 pub mod clock;
+#[cfg(feature = "gui")]
+pub mod external_player;
+#[cfg(feature = "gui")]
 pub mod human_player;
+#[cfg(feature = "gui")]
 pub mod manager;
+#[cfg(feature = "gui")]
 pub mod player;
 pub mod representation;
+pub mod result;
+pub mod sprt;
+#[cfg(feature = "gui")]
 pub mod versus_manager;
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
+#[cfg(feature = "gui")]
 use crate::state::{AppState, AppMode};
+#[cfg(feature = "gui")]
 use player::*;
+#[cfg(feature = "gui")]
 use human_player::*;
+#[cfg(feature = "gui")]
 use manager::*;
+#[cfg(feature = "gui")]
 use versus_manager::*;
+#[cfg(feature = "gui")]
+use clock::*;
+#[cfg(feature = "gui")]
+use external_player::*;
+#[cfg(feature = "gui")]
+use crate::ui::ingame_menu::DebugPositionLoaded;
 
+#[cfg(feature = "gui")]
 use self::manager::BoardMakeMove;
 
+#[cfg(feature = "gui")]
 fn finalize(
     mut commands: Commands,
 ) {
     commands.insert_resource(NextState(Some(AppState::LoadUI)))
 }
 
+#[cfg(feature = "gui")]
 pub struct GamePlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<BoardMakeMove>()
             .add_event::<ProcessedMove>()
             .add_event::<CanMakeMove>()
+            .add_event::<TakeBackMove>()
+            .add_event::<MovesTakenBack>()
             .add_systems(OnEnter(AppState::LoadGame), (
                 spawn_versus_manager, //.run_if(in_state(AppMode::GameAIAI)),
                 spawn_players,
                 spawn_game_manager,
                 finalize,
             ).chain())
-            .add_systems(OnEnter(AppState::InGame), initialize_game)
+            .add_systems(OnEnter(AppState::InGame), (initialize_game, reset_clock))
             .add_systems(Update, (
                 handle_player_input,
                 execute_board_move.before(on_make_move),
                 on_make_move,
                 advance_turn,
+                update_clock.after(advance_turn),
             ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                external_begin_search,
+                external_make_move,
+            ).chain().run_if(in_state(AppState::InGame)).run_if(in_state(AppMode::GameHumanAI).or_else(in_state(AppMode::GameAIAI))))
+            .add_systems(Update, take_back_moves.after(on_make_move)
+                .run_if(in_state(AppState::InGame).or_else(in_state(AppState::GameOver)))
+                .run_if(in_state(AppMode::GameHumanHuman).or_else(in_state(AppMode::GameHumanAI))))
+            .add_systems(Update, reset_external_game.run_if(on_event::<DebugPositionLoaded>()))
+            .add_systems(OnExit(AppState::InGame), abort_external_search)
             .add_systems(Update, (
                 start_versus_games,
+                toggle_versus_opponent,
                 versus_update,
             ).run_if(in_state(AppMode::GameAIAI)).run_if(in_state(AppState::InGame)))
             .add_systems(OnEnter(AppState::GameOver), (
@@ -3,7 +3,8 @@ use bevy::prelude::*;
 use crate::{
     game::manager::PlayerType,
     game::human_player::HumanPlayer,
-    board::piece::*, ai::ai_player::AIPlayer, state::AppMode,
+    game::external_player::ExternalEnginePlayer,
+    board::piece::*, ai::ai_player::AIPlayer, state::{AppMode, GameSettings, BookSettings},
 };
 
 
@@ -12,9 +13,19 @@ pub struct Player {
     pub team: u8,
 }
 
+/// Second player of versus matches, the opponent of the version being tested
+#[derive(Component)]
+pub struct VersusOpponent {}
+
+/// Applies the opening book chosen for the player in the main menu
+pub fn ai_player(ai: AIPlayer, book: &BookSettings) -> AIPlayer {
+    ai.with_book(book.use_book, &book.path)
+}
+
 pub fn spawn_players(
     mut commands: Commands,
-    app_mode: Res<State<AppMode>>
+    app_mode: Res<State<AppMode>>,
+    settings: Res<GameSettings>,
 ) {
     let (white, black) = match app_mode.clone() {
         AppMode::GameHumanHuman => (PlayerType::Human, PlayerType::Human),
@@ -25,14 +36,21 @@ pub fn spawn_players(
     if app_mode.clone() != AppMode::GameAIAI {
         match white {
             PlayerType::Human => { commands.spawn((HumanPlayer::default(), Player { team: Piece::WHITE })); },
-            PlayerType::AI => { commands.spawn((AIPlayer::default(), Player { team: Piece::WHITE })); },
+            PlayerType::AI => { commands.spawn((ai_player(AIPlayer::default(), &settings.books[0]), Player { team: Piece::WHITE })); },
         }
         match black {
             PlayerType::Human => { commands.spawn((HumanPlayer::default(), Player { team: Piece::BLACK })); },
-            PlayerType::AI => { commands.spawn((AIPlayer::default(), Player { team: Piece::BLACK })); },
+            PlayerType::AI if settings.use_external_engine => {
+                commands.spawn((ExternalEnginePlayer::new(&settings.external_engine_path), Player { team: Piece::BLACK }));
+            },
+            PlayerType::AI => { commands.spawn((ai_player(AIPlayer::default(), &settings.books[0]), Player { team: Piece::BLACK })); },
         }
     } else {
-        commands.spawn((AIPlayer::versus_p1(), Player { team: Piece::WHITE }));
-        commands.spawn((AIPlayer::versus_p2(), Player { team: Piece::BLACK }));
+        commands.spawn((ai_player(AIPlayer::versus_p1(), &settings.books[0]), Player { team: Piece::WHITE }));
+        if settings.use_external_engine {
+            commands.spawn((ExternalEnginePlayer::new(&settings.external_engine_path), Player { team: Piece::BLACK }, VersusOpponent {}));
+        } else {
+            commands.spawn((ai_player(AIPlayer::versus_p2(), &settings.books[1]), Player { team: Piece::BLACK }, VersusOpponent {}));
+        }
     }
 }
\ No newline at end of file
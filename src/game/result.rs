@@ -0,0 +1,106 @@
+use crate::board::{board::Board, moves::Move, piece::Piece};
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameResult {
+    None,
+    Playing,
+    WhiteIsMated,
+    BlackIsMated,
+    Stalemate,
+    Repetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    DrawByArbiter,
+    WhiteTimeout,
+    BlackTimeout,
+    /// A side ran out of time but the opponent has no mating material left
+    TimeoutVsInsufficientMaterial,
+    /// An engine played a move that isn't legal in the position, or crashed, and forfeited the game
+    WhiteIllegalMove,
+    BlackIllegalMove,
+    /// The game couldn't go on without a result, for example because an external engine failed to start
+    Aborted,
+}
+
+impl GameResult {
+    /// Result of the position on the board given its legal moves, `Playing` if the game goes on
+    pub fn from_board(board: &Board, moves: &[Move], in_check: bool) -> Self {
+        if moves.is_empty() {
+            if in_check {
+                return if board.white_to_move { Self::WhiteIsMated } else { Self::BlackIsMated };
+            }
+            return Self::Stalemate;
+        }
+        if board.current_state.fifty_move_counter >= 100 {
+            return Self::FiftyMoveRule;
+        }
+        let rep_count = board.repeat_position_history.iter().filter(|key| **key == board.current_state.zobrist_key).count();
+        if rep_count >= 3 {
+            return Self::Repetition;
+        }
+        if Self::insufficient_material(board) {
+            return Self::InsufficientMaterial;
+        }
+        Self::Playing
+    }
+
+    fn insufficient_material(board: &Board) -> bool {
+        if board.friendly_orthogonal_sliders.0 != 0 || board.enemy_orthogonal_sliders.0 != 0 {
+            return false;
+        };
+        if board.get_piece_list(Piece::new(Piece::WHITE_PAWN)).count() > 0 || 
+            board.get_piece_list(Piece::new(Piece::BLACK_PAWN)).count() > 0 {
+                return false;
+        };
+
+        let n_white_bishops = board.get_piece_list(Piece::new(Piece::WHITE_BISHOP)).count();
+        let n_black_bishops = board.get_piece_list(Piece::new(Piece::BLACK_BISHOP)).count();
+        let n_white_knights = board.get_piece_list(Piece::new(Piece::WHITE_KNIGHT)).count();
+        let n_black_knights = board.get_piece_list(Piece::new(Piece::BLACK_KNIGHT)).count();
+        let n_white_minors = n_white_bishops + n_white_knights;
+        let n_black_minors = n_black_bishops + n_black_knights;
+        if n_white_minors == 0 && n_black_minors == 0 {
+            return true;
+        };
+        if (n_white_minors == 1 && n_black_minors == 0) || (n_white_minors == 0 && n_black_minors == 1) {
+            return true;
+        };
+        false
+    }
+
+    pub fn is_white_win(&self) -> bool {
+        matches!(self, Self::BlackIsMated | Self::BlackTimeout | Self::BlackIllegalMove)
+    }
+    pub fn is_black_win(&self) -> bool {
+        matches!(self, Self::WhiteIsMated | Self::WhiteTimeout | Self::WhiteIllegalMove)
+    }
+    pub fn is_draw(&self) -> bool {
+        matches!(self, Self::Stalemate | Self::Repetition | Self::FiftyMoveRule | Self::InsufficientMaterial | Self::DrawByArbiter | Self::TimeoutVsInsufficientMaterial)
+    }
+
+    /// Result as written in the PGN `Result` tag and at the end of the movetext
+    pub fn pgn_result(&self) -> &'static str {
+        if self.is_white_win() {
+            "1-0"
+        } else if self.is_black_win() {
+            "0-1"
+        } else if self.is_draw() {
+            "1/2-1/2"
+        } else {
+            "*"
+        }
+    }
+
+    /// Reason for the result as written in the PGN `Termination` tag
+    pub fn pgn_termination(&self) -> Option<&'static str> {
+        match self {
+            Self::WhiteTimeout | Self::BlackTimeout | Self::TimeoutVsInsufficientMaterial => Some("time forfeit"),
+            Self::DrawByArbiter => Some("adjudication"),
+            Self::WhiteIllegalMove | Self::BlackIllegalMove => Some("rules infraction"),
+            Self::Aborted => Some("abandoned"),
+            Self::None | Self::Playing => None,
+            _ => Some("normal"),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use super::result::GameResult;
+
+
+/// Hypotheses and error rates of a sequential probability ratio test, the Elo bounds are logistic Elo
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SprtConfig {
+    /// Elo difference of the null hypothesis, the tested version is not an improvement
+    pub elo0: f64,
+    /// Elo difference of the alternative hypothesis, the tested version is an improvement
+    pub elo1: f64,
+    /// Chance of accepting H1 when H0 is true
+    pub alpha: f64,
+    /// Chance of accepting H0 when H1 is true
+    pub beta: f64,
+}
+
+impl SprtConfig {
+    /// H0 is accepted once the log likelihood ratio drops below this
+    pub fn lower_bound(&self) -> f64 {
+        (self.beta / (1.0 - self.alpha)).ln()
+    }
+
+    /// H1 is accepted once the log likelihood ratio rises above this
+    pub fn upper_bound(&self) -> f64 {
+        ((1.0 - self.beta) / self.alpha).ln()
+    }
+}
+
+impl Default for SprtConfig {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 5.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SprtStatus {
+    Running,
+    AcceptH0,
+    AcceptH1,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameScore {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl GameScore {
+    /// Score of the player with the given color, `None` while the game is still going
+    pub fn from_result(result: GameResult, is_white: bool) -> Option<Self> {
+        if result.is_draw() {
+            Some(Self::Draw)
+        } else if result.is_white_win() {
+            Some(if is_white { Self::Win } else { Self::Loss })
+        } else if result.is_black_win() {
+            Some(if is_white { Self::Loss } else { Self::Win })
+        } else {
+            None
+        }
+    }
+
+    pub fn half_points(&self) -> usize {
+        match self {
+            Self::Loss => 0,
+            Self::Draw => 1,
+            Self::Win => 2,
+        }
+    }
+}
+
+/// Number of game pairs by their combined score in half points, from two losses to two wins.
+/// Both games of a pair start from the same opening with colors swapped, which removes most of the opening bias.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Pentanomial {
+    pub counts: [u32; 5],
+}
+
+impl Pentanomial {
+    /// Added to every count so the variance is never zero, e.g. when every pair so far was won
+    const REGULARIZATION: f64 = 1e-3;
+
+    pub fn add_pair(&mut self, first: GameScore, second: GameScore) {
+        self.counts[first.half_points() + second.half_points()] += 1;
+    }
+
+    pub fn num_pairs(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+
+    /// Mean and variance of the score per pair, scaled to the range of a single game
+    fn score_distribution(&self) -> (f64, f64) {
+        let total = self.counts.iter().map(|count| *count as f64 + Self::REGULARIZATION).sum::<f64>();
+        let probabilities = self.counts.map(|count| (count as f64 + Self::REGULARIZATION) / total);
+        let mean = probabilities.iter().enumerate().map(|(i, p)| p * i as f64 / 4.0).sum::<f64>();
+        let variance = probabilities.iter().enumerate().map(|(i, p)| p * (i as f64 / 4.0 - mean).powi(2)).sum::<f64>();
+        (mean, variance)
+    }
+}
+
+/// Elo difference with the bounds of its 95% confidence interval and the likelihood of superiority
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub lower: f64,
+    pub upper: f64,
+    /// Chance that the tested version is stronger at all, between 0 and 1
+    pub los: f64,
+}
+
+impl EloEstimate {
+    /// Half the width of the confidence interval
+    pub fn error(&self) -> f64 {
+        (self.upper - self.lower) / 2.0
+    }
+}
+
+/// Sequential probability ratio test over game pairs, from the perspective of the tested version
+#[derive(Clone, Debug)]
+pub struct Sprt {
+    pub config: SprtConfig,
+    pub pentanomial: Pentanomial,
+    /// First game of the pair that is being played, `Some(None)` when it was discarded
+    pending: Option<Option<GameScore>>,
+}
+
+impl Sprt {
+    const CONFIDENCE_Z: f64 = 1.959964;
+
+    pub fn new(config: SprtConfig) -> Self {
+        Self {
+            config,
+            pentanomial: Pentanomial::default(),
+            pending: None,
+        }
+    }
+
+    /// Adds the score of a finished game, every second game completes a pair. Returns the status after the game.
+    pub fn add_game(&mut self, score: GameScore) -> SprtStatus {
+        match self.pending.take() {
+            Some(Some(first)) => self.pentanomial.add_pair(first, score),
+            Some(None) => (),
+            None => self.pending = Some(Some(score)),
+        }
+        self.status()
+    }
+
+    /// Skips a game without a score, the whole pair it belongs to is left out so later pairs keep their openings
+    pub fn discard_game(&mut self) -> SprtStatus {
+        if self.pending.take().is_none() {
+            self.pending = Some(None);
+        }
+        self.status()
+    }
+
+    /// Log likelihood ratio of H1 against H0, using the normal approximation of the pentanomial model
+    pub fn llr(&self) -> f64 {
+        let num_pairs = self.pentanomial.num_pairs();
+        if num_pairs == 0 {
+            return 0.0;
+        }
+        let (mean, variance) = self.pentanomial.score_distribution();
+        let (score0, score1) = (elo_to_score(self.config.elo0), elo_to_score(self.config.elo1));
+        num_pairs as f64 * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+
+    pub fn status(&self) -> SprtStatus {
+        let llr = self.llr();
+        if llr >= self.config.upper_bound() {
+            SprtStatus::AcceptH1
+        } else if llr <= self.config.lower_bound() {
+            SprtStatus::AcceptH0
+        } else {
+            SprtStatus::Running
+        }
+    }
+
+    /// Estimated Elo difference, `None` until the first pair is complete
+    pub fn elo_estimate(&self) -> Option<EloEstimate> {
+        let num_pairs = self.pentanomial.num_pairs();
+        if num_pairs == 0 {
+            return None;
+        }
+        let (mean, variance) = self.pentanomial.score_distribution();
+        let std_error = (variance / num_pairs as f64).sqrt();
+        Some(EloEstimate {
+            elo: score_to_elo(mean),
+            lower: score_to_elo(mean - Self::CONFIDENCE_Z * std_error),
+            upper: score_to_elo(mean + Self::CONFIDENCE_Z * std_error),
+            los: normal_cdf((mean - 0.5) / std_error),
+        })
+    }
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self::new(SprtConfig::default())
+    }
+}
+
+/// Expected score against an opponent `elo` points weaker
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+pub fn score_to_elo(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1.0 - 1e-6);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz and Stegun 7.1.26, accurate to about 1e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 { -y } else { y }
+}
@@ -1,66 +1,134 @@
+use std::{fs::OpenOptions, io::Write};
+
 use bevy::prelude::*;
 
-use crate::{state::{AppState, AppMode}, ui::{ingame_menu::{MatchManagerText, MatchManagerStatistic, MatchManagerStartButton}, text_input::TextInput}, board::{board::Board, zobrist::Zobrist, piece::Piece}, ai::ai_player::AIPlayer};
+use crate::{state::{AppState, AppMode, GameSettings}, ui::{ingame_menu::{MatchManagerText, MatchManagerStatistic, MatchManagerStartButton, VersusOpponentToggleButton}, text_input::TextInput}, board::{board::Board, zobrist::Zobrist, piece::Piece}, ai::ai_player::AIPlayer, utils::{pgn::PgnGame, chess960}, tournament::{DEFAULT_OPENINGS_PATH, load_openings}};
+
+use super::{manager::{GameManager, CanMakeMove, ProcessedMove}, player::{Player, VersusOpponent, ai_player}, external_player::ExternalEnginePlayer, result::GameResult, sprt::{Sprt, SprtStatus, GameScore}};
 
-use super::{manager::{GameManager, GameResult, CanMakeMove, ProcessedMove}, player::Player};
+/// Default file finished versus games are appended to
+pub const VERSUS_PGN_PATH: &str = "versus_games.pgn";
 
 
 #[derive(Resource)]
 pub struct VersusManager {
     pub max_think_time_ms: usize,
     pub max_game_length: usize,
+    /// Most games to play, the match stops earlier once the SPRT accepts a hypothesis
     pub total_games: usize,
     pub game_idx: usize,
-    pub white_wins: usize,
-    pub white_losses: usize,
+    /// Results of player 1, the version being tested
+    pub wins: usize,
+    pub losses: usize,
     pub draws: usize,
+    /// Colors are swapped after every game so each pair of games plays the same opening from both sides
+    pub p1_is_white: bool,
+    pub sprt: Sprt,
     position_fens: Vec<String>,
+    /// Games start from random Chess960 positions instead of the versus positions
+    pub chess960: bool,
+    /// The opponent can't be changed anymore once the first game started
+    pub started: bool,
+    /// An engine couldn't be used, the remaining games are not played
+    pub aborted: bool,
+    /// Every finished game is appended to this PGN file
+    pub archive_path: Option<String>,
+}
+
+impl VersusManager {
+    fn new(max_think_time_ms: usize, max_game_length: usize, total_games: usize, position_fens: Vec<String>, chess960: bool) -> Self {
+        Self {
+            max_think_time_ms,
+            max_game_length,
+            total_games,
+            game_idx: 0,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            p1_is_white: true,
+            sprt: Sprt::default(),
+            position_fens,
+            chess960,
+            started: false,
+            aborted: false,
+            archive_path: None,
+        }
+    }
+
+    /// Start position of the current game, shared by both games of a pair
+    fn start_fen(&self) -> &String {
+        &self.position_fens[(self.game_idx / 2) % self.position_fens.len()]
+    }
+
+    /// Sets up the start position of the current game, external engines are sent the moves from there
+    fn load_start_position(&self, board: &mut Board, zobrist: &Zobrist, manager: &mut GameManager) {
+        board.load_position(Some(self.start_fen().clone()), zobrist).expect("Invalid FEN in versus positions");
+        board.set_chess960(self.chess960);
+        manager.custom_position = Some(self.start_fen().clone());
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.aborted || self.game_idx >= self.total_games || self.sprt.status() != SprtStatus::Running
+    }
+
+    fn sprt_text(&self) -> String {
+        let config = &self.sprt.config;
+        let status = match self.sprt.status() {
+            SprtStatus::Running => "Running",
+            SprtStatus::AcceptH0 => "H0 Accepted",
+            SprtStatus::AcceptH1 => "H1 Accepted",
+        };
+        format!("SPRT [{}, {}] | LLR: {:.2} ({:.2}, {:.2}) | {}", config.elo0, config.elo1, self.sprt.llr(), config.lower_bound(), config.upper_bound(), status)
+    }
+
+    fn elo_text(&self) -> String {
+        match self.sprt.elo_estimate() {
+            Some(estimate) => format!("Elo: {:+.1} +/- {:.1} | LOS: {:.1}%", estimate.elo, estimate.error(), estimate.los * 100.0),
+            None => String::from("Elo: N/A | LOS: N/A"),
+        }
+    }
+
+    fn pentanomial_text(&self) -> String {
+        let counts = self.sprt.pentanomial.counts;
+        format!("Pairs: {} | Pentanomial: {} {} {} {} {}", self.sprt.pentanomial.num_pairs(), counts[0], counts[1], counts[2], counts[3], counts[4])
+    }
 }
 
 pub fn spawn_versus_manager(
     mut commands: Commands,
     app_mode: Res<State<AppMode>>,
     mut board: ResMut<Board>,
-    mut zobrist: ResMut<Zobrist>,
+    zobrist: Res<Zobrist>,
+    settings: Res<GameSettings>,
 ) {
     if app_mode.clone() == AppMode::GameAIAI {
-        let versus_positions_full = std::fs::read_to_string("assets/logic/versus_positions.txt").unwrap();
-        let positions: Vec<String> = versus_positions_full.split("\n").map(|x| x.to_string()).collect();
-        board.load_position(Some(positions[0].clone()), zobrist.as_mut());
-        commands.insert_resource(VersusManager {
-            max_think_time_ms: 1000,
-            max_game_length: 100,
-            total_games: 1000,
-            game_idx: 0,
-            white_wins: 0,
-            white_losses: 0,
-            draws: 0,
-            position_fens: positions,
-        }); 
+        let total_games = 1000;
+        let positions: Vec<String> = if settings.chess960 {
+            (0..total_games / 2).map(|_| chess960::random_start_fen()).collect()
+        } else {
+            load_openings(DEFAULT_OPENINGS_PATH).expect("Could not read the versus openings")
+        };
+        let mut versus_manager = VersusManager::new(1000, 100, total_games, positions, settings.chess960);
+        versus_manager.archive_path = Some(settings.versus_pgn_path.clone()).filter(|path| !path.is_empty());
+        board.load_position(Some(versus_manager.start_fen().clone()), &zobrist).expect("Invalid FEN in versus positions");
+        board.set_chess960(versus_manager.chess960);
+        commands.insert_resource(versus_manager); 
     } else {
-        commands.insert_resource(VersusManager {
-            max_think_time_ms: 0,
-            max_game_length: 0,
-            total_games: 0,
-            game_idx: 0,
-            white_wins: 0,
-            white_losses: 0,
-            draws: 0,
-            position_fens: Vec::new(),
-        });
+        commands.insert_resource(VersusManager::new(0, 0, 0, Vec::new(), false));
     }
 }
 
 pub fn start_versus_games(
     mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<MatchManagerStartButton>)>,
-    versus_manager: ResMut<VersusManager>,
+    mut versus_manager: ResMut<VersusManager>,
     mut match_manager_text_query: Query<(&MatchManagerText, &mut Text)>,
     mut match_text_input_query: Query<&mut TextInput, (With<Text>, With<Button>)>,
-    mut zobrist: ResMut<Zobrist>,
+    zobrist: Res<Zobrist>,
     mut board: ResMut<Board>,
     mut manager: ResMut<GameManager>,
     mut can_make_move_evw: EventWriter<CanMakeMove>,
     mut ai_player_query: Query<&mut AIPlayer>,
+    mut external_player_query: Query<&mut ExternalEnginePlayer>,
 ) {
     for (interaction, mut _color) in interaction_query.iter_mut() {
         match *interaction {
@@ -80,8 +148,12 @@ pub fn start_versus_games(
                 for mut ai_player in ai_player_query.iter_mut() {
                     ai_player.think_time_ms = versus_manager.max_think_time_ms as u32;
                 }
+                for mut external_player in external_player_query.iter_mut() {
+                    external_player.think_time_ms = versus_manager.max_think_time_ms as u32;
+                }
 
-                board.load_position(Some(versus_manager.position_fens[0].clone()), zobrist.as_mut());
+                versus_manager.started = true;
+                versus_manager.load_start_position(&mut board, &zobrist, &mut manager);
                 manager.move_color = board.move_color;
                 can_make_move_evw.send(CanMakeMove {});
             },
@@ -91,6 +163,64 @@ pub fn start_versus_games(
     }
 }
 
+/// Switches the opponent between the AI version and the external engine chosen in the main menu
+pub fn toggle_versus_opponent(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<VersusOpponentToggleButton>)>,
+    versus_manager: Res<VersusManager>,
+    settings: Res<GameSettings>,
+    opponent_query: Query<(Entity, &Player, Option<&AIPlayer>), With<VersusOpponent>>,
+    player_query: Query<(&Player, Option<&AIPlayer>, Option<&ExternalEnginePlayer>), Without<VersusOpponent>>,
+    mut match_manager_text_query: Query<(&mut Text, &MatchManagerText)>,
+) {
+    if versus_manager.started || !interaction_query.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        return;
+    }
+    let Ok((entity, opponent, opponent_ai)) = opponent_query.get_single() else { return };
+    let opponent_label = if opponent_ai.is_some() {
+        let external_player = ExternalEnginePlayer::new(&settings.external_engine_path);
+        let label = external_player.label();
+        commands.entity(entity).remove::<AIPlayer>().insert(external_player);
+        label
+    } else {
+        let ai = ai_player(AIPlayer::versus_p2(), &settings.books[1]);
+        let label = ai.version.label().to_string();
+        commands.entity(entity).remove::<ExternalEnginePlayer>().insert(ai);
+        label
+    };
+    let p1_label = player_query.iter().next().map_or(String::new(), |(_, ai, external)| player_label(ai, external));
+
+    for (mut text, label) in match_manager_text_query.iter_mut() {
+        match label.stat {
+            MatchManagerStatistic::Players => {
+                text.sections[2].value = opponent_label.clone();
+            },
+            MatchManagerStatistic::Opponent => {
+                text.sections[0].value = format!("Opponent: {}", opponent_label);
+            },
+            MatchManagerStatistic::Player2Stats => {
+                text.sections[0].value = format!("{} | Wins: 0  Losses: 0  Draws: 0", opponent_label);
+            },
+            MatchManagerStatistic::BlackPlayer | MatchManagerStatistic::WhitePlayer => {
+                let team = if matches!(label.stat, MatchManagerStatistic::WhitePlayer) { Piece::WHITE } else { Piece::BLACK };
+                let color = if team == Piece::WHITE { "White" } else { "Black" };
+                let name = if opponent.team == team { &opponent_label } else { &p1_label };
+                text.sections[0].value = format!("{}: {}", color, name);
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Name of a player in the match texts and PGN tags
+pub fn player_label(ai: Option<&AIPlayer>, external: Option<&ExternalEnginePlayer>) -> String {
+    match (ai, external) {
+        (Some(ai), _) => ai.version.label().to_string(),
+        (None, Some(external)) => external.label(),
+        (None, None) => String::new(),
+    }
+}
+
 pub fn versus_update(
     mut commands: Commands,
     mut manager: ResMut<GameManager>,
@@ -112,6 +242,19 @@ pub fn versus_update(
             MatchManagerStatistic::TotalGames => {
             versus_manager.total_games = text_input.value.parse().unwrap();
             },
+            // Invalid SPRT settings keep the previous value
+            MatchManagerStatistic::SprtElo0 => {
+                if let Ok(elo0) = text_input.value.parse::<f64>() { versus_manager.sprt.config.elo0 = elo0; }
+            },
+            MatchManagerStatistic::SprtElo1 => {
+                if let Ok(elo1) = text_input.value.parse::<f64>() { versus_manager.sprt.config.elo1 = elo1; }
+            },
+            MatchManagerStatistic::SprtAlpha => {
+                if let Some(alpha) = text_input.value.parse::<f64>().ok().filter(|alpha| 0.0 < *alpha && *alpha < 1.0) { versus_manager.sprt.config.alpha = alpha; }
+            },
+            MatchManagerStatistic::SprtBeta => {
+                if let Some(beta) = text_input.value.parse::<f64>().ok().filter(|beta| 0.0 < *beta && *beta < 1.0) { versus_manager.sprt.config.beta = beta; }
+            },
             _ => (),
         }
     }
@@ -121,65 +264,67 @@ pub fn versus_game_over(
     mut commands: Commands,
     mut versus_manager: ResMut<VersusManager>,
     mut board: ResMut<Board>,
-    mut zobrist: ResMut<Zobrist>,
-    mut match_manager_text_query: Query<(&mut Text, &mut MatchManagerText, Option<&TextInput>)>,
+    zobrist: Res<Zobrist>,
+    mut match_manager_text_query: Query<(&mut Text, &MatchManagerText, Option<&TextInput>)>,
     mut manager: ResMut<GameManager>,
     mut processed_move_evw: EventWriter<ProcessedMove>,
-    mut ai_player_query: Query<(&mut AIPlayer, &mut Player)>,
+    mut player_query: Query<(&mut Player, Option<&mut AIPlayer>, Option<&mut ExternalEnginePlayer>, Option<&VersusOpponent>)>,
 ) {
-    versus_manager.game_idx += 1;
-    if versus_manager.game_idx == versus_manager.total_games {
-        return;
+    // Name and team of player 1 and 2
+    let mut labels = [(String::new(), Piece::WHITE), (String::new(), Piece::BLACK)];
+    for (player, ai, external, opponent) in player_query.iter() {
+        labels[opponent.is_some() as usize] = (player_label(ai, external), player.team);
     }
-    let mut swapped_colors = false;
-    if versus_manager.game_idx == versus_manager.total_games / 2 {
-        for (mut ai, mut player) in ai_player_query.iter_mut() {
-            ai.searching = false;
-            if player.team == Piece::WHITE {
-                player.team = Piece::BLACK;
-                if !swapped_colors {
-                    let temp = versus_manager.white_wins;
-                    versus_manager.white_wins = versus_manager.white_losses;
-                    versus_manager.white_losses = temp;
-                    swapped_colors = true;
-                }
-            } else if player.team == Piece::BLACK {
-                player.team = Piece::WHITE;
-                if !swapped_colors {
-                    let temp = versus_manager.white_wins;
-                    versus_manager.white_wins = versus_manager.white_losses;
-                    versus_manager.white_losses = temp;
-                    swapped_colors = true;
-                }
+    archive_versus_game(&versus_manager, &manager, &labels);
+
+    match GameScore::from_result(manager.game_result, versus_manager.p1_is_white) {
+        Some(score) => {
+            match score {
+                GameScore::Win => versus_manager.wins += 1,
+                GameScore::Loss => versus_manager.losses += 1,
+                GameScore::Draw => versus_manager.draws += 1,
             }
-        }
+            versus_manager.sprt.add_game(score);
+        },
+        None => { versus_manager.sprt.discard_game(); },
     }
+    versus_manager.game_idx += 1;
+    versus_manager.aborted |= manager.game_result == GameResult::Aborted;
+    let is_finished = versus_manager.is_finished();
 
-    match manager.game_result {
-        GameResult::None | GameResult::Playing | GameResult::BlackTimeout | GameResult::WhiteTimeout => (),
-        GameResult::WhiteIsMated => { versus_manager.white_losses += 1; },
-        GameResult::BlackIsMated => { versus_manager.white_wins += 1; },
-        GameResult::Stalemate | GameResult::Repetition | GameResult::FiftyMoveRule | GameResult::InsufficientMaterial | GameResult::DrawByArbiter => { versus_manager.draws += 1; },
+    if !is_finished {
+        for (mut player, ai, external, _) in player_query.iter_mut() {
+            if let Some(mut ai) = ai {
+                ai.searching = false;
+            }
+            if let Some(mut external) = external {
+                external.searching = false;
+                external.new_game = true;
+            }
+            player.team = if player.team == Piece::WHITE { Piece::BLACK } else { Piece::WHITE };
+        }
+        for (_, team) in labels.iter_mut() {
+            *team = if *team == Piece::WHITE { Piece::BLACK } else { Piece::WHITE };
+        }
+        versus_manager.p1_is_white = !versus_manager.p1_is_white;
     }
 
-    for (mut text, mut label, text_input) in match_manager_text_query.iter_mut() {
+    for (mut text, label, text_input) in match_manager_text_query.iter_mut() {
         match label.stat {
             MatchManagerStatistic::GameNumber => {
-                text.sections[0].value = format!("Game Number: {} / {}", versus_manager.game_idx + 1, versus_manager.total_games);
-            },
-            MatchManagerStatistic::Player1Stats(version, mut is_white) => {
-                is_white = if swapped_colors { !is_white } else { is_white };
-                label.stat = MatchManagerStatistic::Player1Stats(version, is_white);
-                let wins = if is_white { versus_manager.white_wins } else { versus_manager.white_losses };
-                let losses = if is_white { versus_manager.white_losses } else { versus_manager.white_wins };
-                text.sections[0].value = format!("{} | Wins: {}  Losses: {}  Draws: {}", version.label(), wins, losses, versus_manager.draws);
-            },
-            MatchManagerStatistic::Player2Stats(version, mut is_white) => {
-                is_white = if swapped_colors { !is_white } else { is_white };
-                label.stat = MatchManagerStatistic::Player2Stats(version, is_white);
-                let wins = if is_white { versus_manager.white_wins } else { versus_manager.white_losses };
-                let losses = if is_white { versus_manager.white_losses } else { versus_manager.white_wins };
-                text.sections[0].value = format!("{} | Wins: {}  Losses: {}  Draws: {}", version.label(), wins, losses, versus_manager.draws);
+                text.sections[0].value = if versus_manager.aborted {
+                    format!("Match Aborted: {} / {} Games", versus_manager.game_idx, versus_manager.total_games)
+                } else if is_finished {
+                    format!("Match Finished: {} / {} Games", versus_manager.game_idx, versus_manager.total_games)
+                } else {
+                    format!("Game Number: {} / {}", versus_manager.game_idx + 1, versus_manager.total_games)
+                };
+            },
+            MatchManagerStatistic::Player1Stats => {
+                text.sections[0].value = format!("{} | Wins: {}  Losses: {}  Draws: {}", labels[0].0, versus_manager.wins, versus_manager.losses, versus_manager.draws);
+            },
+            MatchManagerStatistic::Player2Stats => {
+                text.sections[0].value = format!("{} | Wins: {}  Losses: {}  Draws: {}", labels[1].0, versus_manager.losses, versus_manager.wins, versus_manager.draws);
             },
             MatchManagerStatistic::MaxThinkTime => {
                 versus_manager.max_think_time_ms = text_input.unwrap().value.parse().unwrap();
@@ -190,26 +335,62 @@ pub fn versus_game_over(
             MatchManagerStatistic::TotalGames => {
                 versus_manager.total_games = text_input.unwrap().value.parse().unwrap();
             },
-            MatchManagerStatistic::BlackPlayer(mut version) => {
-                if swapped_colors {
-                    label.stat = MatchManagerStatistic::BlackPlayer(version.opponent().unwrap());
-                    version = version.opponent().unwrap();
+            MatchManagerStatistic::BlackPlayer | MatchManagerStatistic::WhitePlayer => {
+                let team = if matches!(label.stat, MatchManagerStatistic::WhitePlayer) { Piece::WHITE } else { Piece::BLACK };
+                if let Some((name, _)) = labels.iter().find(|(_, player_team)| *player_team == team) {
+                    let color = if team == Piece::WHITE { "White" } else { "Black" };
+                    text.sections[0].value = format!("{}: {}", color, name);
                 }
-                text.sections[0].value = format!("Black: {}", version.label());
             },
-            MatchManagerStatistic::WhitePlayer(mut version) => {
-                if swapped_colors {
-                    label.stat = MatchManagerStatistic::WhitePlayer(version.opponent().unwrap());
-                    version = version.opponent().unwrap();
-                }
-                text.sections[0].value = format!("White: {}", version.label());
-
+            MatchManagerStatistic::SprtLlr => {
+                text.sections[0].value = versus_manager.sprt_text();
+            },
+            MatchManagerStatistic::SprtElo => {
+                text.sections[0].value = versus_manager.elo_text();
             },
+            MatchManagerStatistic::SprtPentanomial => {
+                text.sections[0].value = versus_manager.pentanomial_text();
+            },
+            MatchManagerStatistic::SprtElo0 | MatchManagerStatistic::SprtElo1 | MatchManagerStatistic::SprtAlpha | MatchManagerStatistic::SprtBeta => (),
+            MatchManagerStatistic::Players | MatchManagerStatistic::Opponent => (),
         };
     }
 
-    board.load_position(Some(versus_manager.position_fens[versus_manager.game_idx % (versus_manager.total_games / 2)].clone()), zobrist.as_mut());
+    if is_finished {
+        return;
+    }
+    versus_manager.load_start_position(&mut board, &zobrist, &mut manager);
     manager.game_moves.clear();
     commands.insert_resource(NextState(Some(AppState::InGame)));
     processed_move_evw.send(ProcessedMove {});
-}
\ No newline at end of file
+}
+
+fn archive_versus_game(
+    versus_manager: &VersusManager,
+    manager: &GameManager,
+    labels: &[(String, u8)],
+) {
+    let Some(archive_path) = &versus_manager.archive_path else { return };
+    let mut game = match PgnGame::from_moves(Some(versus_manager.start_fen()), &manager.game_moves, manager.game_result) {
+        Ok(game) => game,
+        Err(err) => {
+            error!("Failed to archive versus game: {}", err);
+            return;
+        }
+    };
+    game.set_tag("Event", "Versus Match");
+    game.set_tag("Site", "chess-ai");
+    game.set_tag("Round", &(versus_manager.game_idx + 1).to_string());
+    if versus_manager.chess960 {
+        game.set_tag("Variant", "Chess960");
+    }
+    for (name, team) in labels.iter() {
+        let tag = if *team == Piece::WHITE { "White" } else { "Black" };
+        game.set_tag(tag, name);
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(archive_path);
+    if let Err(err) = file.and_then(|mut file| file.write_all(game.to_pgn().as_bytes())) {
+        error!("Failed to write {}: {}", archive_path, err);
+    }
+}
@@ -0,0 +1,17 @@
+pub mod board;
+pub mod game;
+pub mod move_gen;
+#[cfg(feature = "gui")]
+pub mod ui;
+#[cfg(feature = "gui")]
+pub mod state;
+pub mod utils;
+pub mod ai;
+pub mod uci;
+pub mod engine;
+pub mod tablebase;
+pub mod tournament;
+
+#[cfg(feature = "gui")]
+use state::*;
+use utils::*;
@@ -1,20 +1,13 @@
 use bevy::prelude::*;
 
-pub mod board;
-pub mod game;
-pub mod move_gen;
-pub mod ui;
-pub mod state;
-pub mod utils;
-pub mod ai;
-
-use ui::*;
-use state::*;
-use board::*;
-use move_gen::*;
-use utils::*;
-use game::*;
-use ai::*;
+use chess_ai::{
+    ui::UIPlugin,
+    state::{AppState, AppMode},
+    board::BoardPlugin,
+    move_gen::MoveGenPlugin,
+    game::GamePlugin,
+    ai::AIPlugin,
+};
 
 fn main() {
     App::new()
@@ -1,3 +1,4 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 use crate::board::coord::Coord;
@@ -5,7 +6,7 @@ use crate::board::coord::Coord;
 use super::bb::BitBoard;
 
 
-#[derive(Resource)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct BitBoardUtils {
     pub knight_attacks: [BitBoard; 64],
     pub king_moves: [BitBoard; 64],
@@ -106,6 +107,7 @@ impl Default for BitBoardUtils {
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn spawn_bitboard_utils(
     mut commands: Commands,
 ) {
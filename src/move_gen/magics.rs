@@ -1,3 +1,4 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 use crate::board::coord::Coord;
@@ -5,7 +6,7 @@ use crate::board::coord::Coord;
 use super::bitboard::bb::BitBoard;
 
 
-#[derive(Resource)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct MagicBitBoards {
     pub rook_mask: [BitBoard; 64],
     pub bishop_mask: [BitBoard; 64],
@@ -168,6 +169,7 @@ impl Default for MagicBitBoards {
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn spawn_magic_bitboards(
     mut commands: Commands,
 ) {
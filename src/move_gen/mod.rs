@@ -2,29 +2,41 @@ pub mod move_generator;
 pub mod precomp_move_data;
 pub mod bitboard;
 pub mod magics;
+pub mod perft;
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
+#[cfg(feature = "gui")]
 use crate::state::AppState;
+#[cfg(feature = "gui")]
 use precomp_move_data::*;
+#[cfg(feature = "gui")]
 use bitboard::utils::*;
+#[cfg(feature = "gui")]
 use bitboard::precomp_bits::*;
+#[cfg(feature = "gui")]
 use magics::*;
+#[cfg(feature = "gui")]
 use move_generator::*;
 
+#[cfg(feature = "gui")]
 fn finalize_precomp(
     mut commands: Commands,
 ) {
     commands.insert_resource(NextState(Some(AppState::LoadZobrist)))
 }
 
+#[cfg(feature = "gui")]
 fn finalize_move_gen(
     mut commands: Commands,
 ) {
     commands.insert_resource(NextState(Some(AppState::LoadGame)))
 }
 
+#[cfg(feature = "gui")]
 pub struct MoveGenPlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for MoveGenPlugin {
     fn build(&self, app: &mut App) {
         app
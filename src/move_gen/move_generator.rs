@@ -1,3 +1,4 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 use crate::board::{coord::Coord, moves::Move, board::Board, piece::Piece};
@@ -6,7 +7,6 @@ use crate::move_gen::precomp_move_data::*;
 use super::bitboard::bb::BitBoard;
 use super::bitboard::utils::BitBoardUtils;
 use super::magics::MagicBitBoards;
-use super::bitboard::precomp_bits::PrecomputedBits;
 
 
 #[derive(Default, PartialEq)]
@@ -18,7 +18,7 @@ pub enum PromotionMode {
 }
 
 
-#[derive(Resource)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct MoveGenerator {
     pub moves: Vec<Move>,
     pub promotions_to_gen: PromotionMode,
@@ -58,7 +58,7 @@ impl MoveGenerator {
         self.gen_quiet_moves = !captures_only;
 
         self.init(board, precomp, bbutils, magic);
-        self.gen_king_moves(board, bbutils);
+        self.gen_king_moves(board, bbutils, magic);
 
         if !self.in_double_check {
             self.gen_sliding_moves(board, magic, precomp);
@@ -193,7 +193,7 @@ impl MoveGenerator {
         ((self.pin_rays >> sqr.index()) & 1).0 != 0
     }
 
-    fn gen_king_moves(&mut self, board: &Board, bbutils: &BitBoardUtils) {
+    fn gen_king_moves(&mut self, board: &Board, bbutils: &BitBoardUtils, magic: &MagicBitBoards) {
         let legal_mask = !(self.enemy_attack_map | self.friendly_pieces);
         let mut king_moves = bbutils.king_moves[self.friendly_king_sqr.index()] & legal_mask & self.move_type_mask;
         while king_moves.0 != 0 {
@@ -202,24 +202,44 @@ impl MoveGenerator {
         }
 
         if !self.in_check && self.gen_quiet_moves {
-            let castle_blockers = self.enemy_attack_map | board.all_pieces_bitboard;
             if board.current_state.has_kingside_castle_right(self.white_to_move) {
-                let castle_mask = if self.white_to_move { PrecomputedBits::WHITE_KINGSIDE_MASK } else { PrecomputedBits::BLACK_KINGSIDE_MASK };
-                if (castle_mask & castle_blockers).0 == 0 {
-                    let target = if self.white_to_move { Coord::G1 } else { Coord::G8 };
-                    self.moves.push(Move::from_start_end_flagged(self.friendly_king_sqr.square(), target.square(), Move::CASTLING));
-                }
+                self.gen_castling_move(board, magic, true);
             }
             if board.current_state.has_queenside_castle_right(self.white_to_move) {
-                let castle_mask = if self.white_to_move { PrecomputedBits::WHITE_QUEENSIDE_MASK_2 } else { PrecomputedBits::BLACK_QUEENSIDE_MASK_2 };
-                let castle_block_mask = if self.white_to_move { PrecomputedBits::WHITE_QUEENSIDE_MASK } else { PrecomputedBits::BLACK_QUEENSIDE_MASK };
-                if (castle_mask & castle_blockers).0 == 0 && (castle_block_mask & board.all_pieces_bitboard).0 == 0 {
-                    let target = if self.white_to_move { Coord::C1 } else { Coord::C8 };
-                    self.moves.push(Move::from_start_end_flagged(self.friendly_king_sqr.square(), target.square(), Move::CASTLING));
-                }
+                self.gen_castling_move(board, magic, false);
             }
         }
     }
+    /// Castling needs the squares the king and rook pass over or land on to be empty apart from the two of them,
+    /// and the king may not pass over or land on an attacked square
+    fn gen_castling_move(&mut self, board: &Board, magic: &MagicBitBoards, kingside: bool) {
+        let king_from = self.friendly_king_sqr;
+        let rook_from = board.castling_rooks[self.friendly_idx * 2 + if kingside { 0 } else { 1 }];
+        let rank = king_from.rank();
+        let king_to = Coord::new(if kingside { 6 } else { 2 }, rank);
+        let rook_to = Coord::new(if kingside { 5 } else { 3 }, rank);
+
+        let blockers = self.all_pieces & !(king_from.to_bitboard() | rook_from.to_bitboard());
+        let king_path = Self::rank_span(king_from, king_to);
+        if ((king_path | Self::rank_span(rook_from, rook_to)) & blockers).0 != 0 || (king_path & self.enemy_attack_map).0 != 0 {
+            return;
+        }
+        // In Chess960 the castling rook can be the only piece shielding the king's target square from a rook or queen on the back rank
+        if (magic.get_rook_attacks(king_to, blockers | rook_to.to_bitboard()) & board.enemy_orthogonal_sliders).0 != 0 {
+            return;
+        }
+
+        let target = if board.chess960 { rook_from } else { king_to };
+        self.moves.push(Move::from_start_end_flagged(king_from.square(), target.square(), Move::CASTLING));
+    }
+    /// Squares on the rank of `a` between the files of `a` and `b`, both included
+    fn rank_span(a: Coord, b: Coord) -> BitBoard {
+        let mut span = BitBoard(0);
+        for file in a.file().min(b.file())..=a.file().max(b.file()) {
+            span |= Coord::new(file, a.rank()).to_bitboard();
+        }
+        span
+    }
     fn gen_sliding_moves(&mut self, board: &Board, magic: &MagicBitBoards, precomp: &PrecomputedMoveData) {
         let move_mask = self.empty_or_enemy_sqrs & self.check_ray_bitmask & self.move_type_mask;
         let mut orthogonal_sliders = board.friendly_orthogonal_sliders;
@@ -396,34 +416,40 @@ impl MoveGenerator {
     }
 }
 
+impl Default for MoveGenerator {
+    fn default() -> Self {
+        MoveGenerator {
+            moves: Vec::new(),
+            promotions_to_gen: PromotionMode::All,
+            white_to_move: true,
+            friendly_color: Piece::WHITE,
+            enemy_color: Piece::BLACK,
+            friendly_king_sqr: Coord::new(0, 0),
+            friendly_idx: Board::WHITE_INDEX,
+            enemy_idx: Board::BLACK_INDEX,
+            in_check: false,
+            in_double_check: false,
+            check_ray_bitmask: BitBoard(0),
+            pin_rays: BitBoard(0),
+            not_pin_rays: BitBoard(0),
+            enemy_attack_map_no_pawns: BitBoard(0),
+            enemy_attack_map: BitBoard(0),
+            enemy_pawn_attack_map: BitBoard(0),
+            enemy_sliding_attack_map: BitBoard(0),
+            gen_quiet_moves: true,
+            enemy_pieces: BitBoard(0),
+            friendly_pieces: BitBoard(0),
+            all_pieces: BitBoard(0),
+            empty_sqrs: BitBoard(0),
+            empty_or_enemy_sqrs: BitBoard(0),
+            move_type_mask: BitBoard(0)
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
 pub fn spawn_movegen(
     mut commands: Commands,
 ) {
-    let move_gen = MoveGenerator {
-        moves: Vec::new(),
-        promotions_to_gen: PromotionMode::All,
-        white_to_move: true,
-        friendly_color: Piece::WHITE,
-        enemy_color: Piece::BLACK,
-        friendly_king_sqr: Coord::new(0, 0),
-        friendly_idx: Board::WHITE_INDEX,
-        enemy_idx: Board::BLACK_INDEX,
-        in_check: false,
-        in_double_check: false,
-        check_ray_bitmask: BitBoard(0),
-        pin_rays: BitBoard(0),
-        not_pin_rays: BitBoard(0),
-        enemy_attack_map_no_pawns: BitBoard(0),
-        enemy_attack_map: BitBoard(0),
-        enemy_pawn_attack_map: BitBoard(0),
-        enemy_sliding_attack_map: BitBoard(0),
-        gen_quiet_moves: true,
-        enemy_pieces: BitBoard(0),
-        friendly_pieces: BitBoard(0),
-        all_pieces: BitBoard(0),
-        empty_sqrs: BitBoard(0),
-        empty_or_enemy_sqrs: BitBoard(0),
-        move_type_mask: BitBoard(0)
-    };
-    commands.insert_resource(move_gen);
+    commands.insert_resource(MoveGenerator::default());
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+use crate::board::{board::Board, moves::Move, zobrist::Zobrist};
+
+use super::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards};
+
+
+pub struct PerftPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// Expected node counts, starting at depth 1
+    pub expected: &'static [u64],
+}
+
+/// Well known positions with verified node counts, see https://www.chessprogramming.org/Perft_Results
+pub const PERFT_SUITE: [PerftPosition; 8] = [
+    PerftPosition {
+        name: "Start Position",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        expected: &[20, 400, 8902, 197281, 4865609],
+    },
+    PerftPosition {
+        name: "Kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        expected: &[48, 2039, 97862, 4085603],
+    },
+    PerftPosition {
+        name: "En Passant and Pins",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        expected: &[14, 191, 2812, 43238, 674624],
+    },
+    PerftPosition {
+        name: "Promotions and Castling",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        expected: &[6, 264, 9467, 422333],
+    },
+    PerftPosition {
+        name: "Promotions and Castling (Mirrored)",
+        fen: "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        expected: &[6, 264, 9467, 422333],
+    },
+    PerftPosition {
+        name: "Discovered Checks",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        expected: &[44, 1486, 62379, 2103487],
+    },
+    PerftPosition {
+        name: "Middlegame",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        expected: &[46, 2079, 89890, 3894594],
+    },
+    PerftPosition {
+        name: "En Passant Discovered Check",
+        fen: "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        expected: &[15, 126, 1928, 13931, 206379],
+    },
+];
+
+/// Chess960 positions with verified node counts, castling rights are given in Shredder-FEN
+pub const CHESS960_PERFT_SUITE: [PerftPosition; 4] = [
+    PerftPosition {
+        name: "Chess960 Position 1",
+        fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        expected: &[21, 528, 12189, 326672],
+    },
+    PerftPosition {
+        name: "Chess960 Position 2",
+        fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        expected: &[21, 807, 18002, 667366],
+    },
+    PerftPosition {
+        name: "Chess960 Position 3",
+        fen: "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        expected: &[20, 479, 10471, 273318],
+    },
+    PerftPosition {
+        name: "Chess960 Position 4",
+        fen: "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        expected: &[28, 1120, 31058, 1171749],
+    },
+];
+
+/// Counts the leaf nodes of the move tree up to the given depth
+pub fn perft(
+    board: &mut Board,
+    depth: u8,
+    precomp: &PrecomputedMoveData,
+    bbutils: &BitBoardUtils,
+    magic: &MagicBitBoards,
+    zobrist: &Zobrist,
+) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut move_gen = MoveGenerator::default();
+    move_gen.generate_moves(board, precomp, bbutils, magic, false);
+    if depth == 1 {
+        return move_gen.moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for mov in move_gen.moves.iter() {
+        board.make_move(*mov, true, zobrist);
+        nodes += perft(board, depth - 1, precomp, bbutils, magic, zobrist);
+        board.unmake_move(*mov, true);
+    }
+    nodes
+}
+
+/// Same as `perft`, but reports the node count below each root move
+pub fn perft_divide(
+    board: &mut Board,
+    depth: u8,
+    precomp: &PrecomputedMoveData,
+    bbutils: &BitBoardUtils,
+    magic: &MagicBitBoards,
+    zobrist: &Zobrist,
+) -> Vec<(Move, u64)> {
+    let mut move_gen = MoveGenerator::default();
+    move_gen.generate_moves(board, precomp, bbutils, magic, false);
+
+    let mut divide = Vec::new();
+    for mov in move_gen.moves.iter() {
+        board.make_move(*mov, true, zobrist);
+        divide.push((*mov, perft(board, depth.max(1) - 1, precomp, bbutils, magic, zobrist)));
+        board.unmake_move(*mov, true);
+    }
+    divide
+}
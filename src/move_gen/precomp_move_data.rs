@@ -1,3 +1,4 @@
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 use crate::{
     board::board::Board,
@@ -7,7 +8,7 @@ use crate::{
 use super::bitboard::bb::BitBoard;
 
 
-#[derive(Resource)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct PrecomputedMoveData {
     pub align_mask: [[BitBoard; 64]; 64],
     pub dir_ray_mask: [[BitBoard; 8]; 64],
@@ -248,6 +249,7 @@ impl Default for PrecomputedMoveData {
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn spawn_precomp(
     mut commands: Commands,
 ) {
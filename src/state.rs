@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::{ai::book::DEFAULT_BOOK_PATH, game::{clock::TimeControl, external_player::DEFAULT_EXTERNAL_ENGINE_PATH, versus_manager::VERSUS_PGN_PATH}};
+
 
 #[derive(States, Debug, Hash, Eq, PartialEq, Clone, Copy, Default)]
 pub enum AppState {
@@ -14,6 +16,8 @@ pub enum AppState {
     LoadAI,
     InGame,
     GameOver,
+    /// Round-robin of every AI version, shown as a rating list instead of a board
+    RatingList,
 }
 
 
@@ -24,4 +28,49 @@ pub enum AppMode {
     GameHumanHuman,
     GameHumanAI,
     GameAIAI,
-}
\ No newline at end of file
+}
+
+/// Settings chosen in the main menu, they apply to every game started afterwards
+#[derive(Resource)]
+pub struct GameSettings {
+    pub chess960: bool,
+    /// Both players are on the clock when set
+    pub time_control: Option<TimeControl>,
+    /// The AI opponent, or the second player of versus matches, is the UCI engine at `external_engine_path`
+    pub use_external_engine: bool,
+    pub external_engine_path: String,
+    /// Finished versus games are appended to this PGN file, nothing is archived when it is empty
+    pub versus_pgn_path: String,
+    /// Opening books of the AI, or of player 1 in versus matches, and of the versus opponent
+    pub books: [BookSettings; 2],
+}
+
+/// Opening book of one AI player
+#[derive(Clone)]
+pub struct BookSettings {
+    pub use_book: bool,
+    pub path: String,
+}
+
+impl Default for BookSettings {
+    fn default() -> Self {
+        Self {
+            // No book ships with the engine, it is turned on in the main menu once one has been built
+            use_book: false,
+            path: DEFAULT_BOOK_PATH.to_string(),
+        }
+    }
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            chess960: false,
+            time_control: None,
+            use_external_engine: false,
+            external_engine_path: DEFAULT_EXTERNAL_ENGINE_PATH.to_string(),
+            versus_pgn_path: VERSUS_PGN_PATH.to_string(),
+            books: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::ops::Neg;
+
+use crate::board::{board::Board, piece::Piece};
+
+pub mod syzygy;
+pub mod retrograde;
+
+
+/// Result of a tablebase position for the side to move. Cursed wins and blessed losses
+/// are wins and losses that can't be forced before the fifty-move rule ends the game in a draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss = -2,
+    BlessedLoss = -1,
+    Draw = 0,
+    CursedWin = 1,
+    Win = 2,
+}
+
+impl Wdl {
+    pub fn from_value(value: i32) -> Self {
+        match value {
+            i32::MIN..=-2 => Self::Loss,
+            -1 => Self::BlessedLoss,
+            0 => Self::Draw,
+            1 => Self::CursedWin,
+            _ => Self::Win,
+        }
+    }
+
+    pub fn signum(self) -> i32 {
+        (self as i32).signum()
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::from_value(-(self as i32))
+    }
+}
+
+/// Number of pieces of every type on the board, indexed by color and piece type
+fn board_material(board: &Board) -> [[u8; 7]; 2] {
+    let mut material = [[0; 7]; 2];
+    for (color_idx, color) in [Piece::WHITE, Piece::BLACK].iter().enumerate() {
+        for ptype in Piece::PAWN..=Piece::KING {
+            material[color_idx][ptype as usize] = board.piece_bitboards[Piece::new(ptype | color).index()].count() as u8;
+        }
+    }
+    material
+}
@@ -0,0 +1,692 @@
+use std::{collections::HashMap, env, ffi::OsStr, fmt, fs, io, path::Path};
+
+use crate::{board::{board::Board, moves::Move, piece::Piece}, engine::tables::LookupTables, move_gen::move_generator::MoveGenerator, utils::fen::{self, LoadedPositionInfo}};
+
+use super::{Wdl, board_material};
+
+
+pub const DEFAULT_TABLEBASE_PATH: &str = "assets/tablebases";
+/// Most pieces a table can be generated for, including the kings
+pub const MAX_GENERATED_PIECES: usize = 4;
+pub const TABLE_EXTENSION: &str = "ctb";
+
+const TABLE_MAGIC: [u8; 4] = *b"CATB";
+/// Value of drawn positions
+const DRAW_VALUE: u8 = 0;
+/// Value of illegal positions, and of indices that aren't the canonical index of their position
+const INVALID_VALUE: u8 = u8::MAX;
+/// Longest distance to mate that fits in a value, the others store the distance plus one
+const MAX_PLIES: usize = INVALID_VALUE as usize - 2;
+
+/// Piece types in the order they appear in table names
+const PIECE_CHARS: [(char, u8); 5] = [
+    ('Q', Piece::QUEEN), ('R', Piece::ROOK), ('B', Piece::BISHOP), ('N', Piece::KNIGHT), ('P', Piece::PAWN),
+];
+/// Piece values by piece type, used to decide which side is the stronger one
+const PIECE_VALUES: [u32; 7] = [0, 1, 3, 3, 5, 9, 0];
+
+const KING_STEPS: [(i8, i8); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const KNIGHT_JUMPS: [(i8, i8); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const ORTHOGONAL_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONAL_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Squares of the pieces of a position, in the order of `TableIndex::pieces`
+type Squares = [u8; MAX_GENERATED_PIECES];
+
+
+/// Pieces of a table besides the kings. The stronger side is always stored as white.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Material {
+    /// Piece types of each side, from queen to pawn
+    pieces: [Vec<u8>; 2],
+}
+
+impl Material {
+    /// Reads a material signature like `KBNK` or `KRvKP`, either side can be written first.
+    /// Pawns on both sides aren't supported, since en passant isn't part of the index.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.replace('v', "");
+        let (first, second) = name.strip_prefix('K')?.split_once('K')?;
+        let mut pieces = [Vec::new(), Vec::new()];
+        for (side, chars) in [first, second].iter().enumerate() {
+            for c in chars.chars() {
+                let (_, ptype) = PIECE_CHARS.iter().find(|(piece_char, _)| *piece_char == c)?;
+                pieces[side].push(*ptype);
+            }
+        }
+        let material = Self::new(pieces);
+        let pawns_on_both_sides = material.pieces.iter().all(|side| side.contains(&Piece::PAWN));
+        (material.num_pieces() <= MAX_GENERATED_PIECES && !pawns_on_both_sides).then_some(material)
+    }
+
+    fn new(mut pieces: [Vec<u8>; 2]) -> Self {
+        for side in pieces.iter_mut() {
+            side.sort_unstable_by(|a, b| b.cmp(a));
+        }
+        let strength = |side: &Vec<u8>| (side.iter().map(|ptype| PIECE_VALUES[*ptype as usize]).sum::<u32>(), side.clone());
+        if strength(&pieces[1]) > strength(&pieces[0]) {
+            pieces.swap(0, 1);
+        }
+        Self { pieces }
+    }
+
+    pub fn num_pieces(&self) -> usize {
+        2 + self.pieces[0].len() + self.pieces[1].len()
+    }
+
+    pub fn has_pawns(&self) -> bool {
+        self.pieces.iter().flatten().any(|ptype| *ptype == Piece::PAWN)
+    }
+
+    /// Number of pieces of every type, indexed by color and piece type
+    fn counts(&self) -> [[u8; 7]; 2] {
+        let mut counts = [[0; 7]; 2];
+        for (side, pieces) in self.pieces.iter().enumerate() {
+            counts[side][Piece::KING as usize] = 1;
+            for ptype in pieces.iter() {
+                counts[side][*ptype as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Materials left after a capture or a promotion
+    fn successors(&self) -> Vec<Material> {
+        let mut successors = Vec::new();
+        for side in 0..2 {
+            for (i, ptype) in self.pieces[side].iter().enumerate() {
+                let mut pieces = self.pieces.clone();
+                pieces[side].remove(i);
+                successors.push(Self::new(pieces));
+                if *ptype == Piece::PAWN {
+                    for promotion in [Piece::QUEEN, Piece::ROOK, Piece::BISHOP, Piece::KNIGHT] {
+                        let mut pieces = self.pieces.clone();
+                        pieces[side][i] = promotion;
+                        successors.push(Self::new(pieces));
+                    }
+                }
+            }
+        }
+        successors.dedup();
+        successors
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for side in self.pieces.iter() {
+            write!(f, "K")?;
+            for ptype in side.iter() {
+                let (c, _) = PIECE_CHARS.iter().find(|(_, piece_type)| piece_type == ptype).unwrap();
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Result of a position in a generated table, with the number of plies until mate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dtm {
+    Win(u8),
+    Draw,
+    Loss(u8),
+}
+
+impl Dtm {
+    fn from_value(value: u8) -> Option<Self> {
+        match value {
+            INVALID_VALUE => None,
+            DRAW_VALUE => Some(Self::Draw),
+            // Wins take an odd number of plies and losses an even number, being mated is a loss in 0
+            _ if value.is_multiple_of(2) => Some(Self::Win(value - 1)),
+            _ => Some(Self::Loss(value - 1)),
+        }
+    }
+
+    pub fn wdl(self) -> Wdl {
+        match self {
+            Self::Win(_) => Wdl::Win,
+            Self::Draw => Wdl::Draw,
+            Self::Loss(_) => Wdl::Loss,
+        }
+    }
+
+    /// Result for the side that moved into this position
+    pub fn parent(self) -> Self {
+        match self {
+            Self::Win(plies) => Self::Loss(plies + 1),
+            Self::Draw => Self::Draw,
+            Self::Loss(plies) => Self::Win(plies + 1),
+        }
+    }
+
+    /// Higher for better results of the side to move, winning faster and losing slower
+    fn order(self) -> i32 {
+        match self {
+            Self::Win(plies) => 1000 - plies as i32,
+            Self::Draw => 0,
+            Self::Loss(plies) => -1000 + plies as i32,
+        }
+    }
+}
+
+
+/// Maps positions to indices. White's king is mirrored into the a1-d1-d4 triangle, or onto the
+/// files a to d when there are pawns. The other pieces can be on any square.
+#[derive(Clone)]
+struct TableIndex {
+    /// White king, black king, then the other white and black pieces
+    pieces: Vec<u8>,
+    has_pawns: bool,
+    /// Squares the white king is mapped to
+    king_squares: Vec<u8>,
+    /// Position of every square in `king_squares`
+    king_index: [usize; 64],
+    size: usize,
+}
+
+impl TableIndex {
+    fn new(material: &Material) -> Self {
+        let mut pieces = vec![Piece::WHITE_KING, Piece::BLACK_KING];
+        pieces.extend(material.pieces[0].iter().map(|ptype| ptype | Piece::WHITE));
+        pieces.extend(material.pieces[1].iter().map(|ptype| ptype | Piece::BLACK));
+
+        let has_pawns = material.has_pawns();
+        let king_squares: Vec<u8> = (0..64)
+            .filter(|square| square % 8 < 4 && (has_pawns || square / 8 <= square % 8))
+            .collect();
+        let mut king_index = [usize::MAX; 64];
+        for (i, square) in king_squares.iter().enumerate() {
+            king_index[*square as usize] = i;
+        }
+        let size = 2 * king_squares.len() * 64usize.pow(pieces.len() as u32 - 1);
+        Self { pieces, has_pawns, king_squares, king_index, size }
+    }
+
+    fn encode(&self, squares: &Squares, black_to_move: bool) -> usize {
+        let mut idx = black_to_move as usize * self.king_squares.len() + self.king_index[squares[0] as usize];
+        for square in squares[1..self.pieces.len()].iter() {
+            idx = idx * 64 + *square as usize;
+        }
+        idx
+    }
+
+    fn decode(&self, mut idx: usize) -> (Squares, bool) {
+        let mut squares = [0; MAX_GENERATED_PIECES];
+        for square in squares[1..self.pieces.len()].iter_mut().rev() {
+            *square = (idx % 64) as u8;
+            idx /= 64;
+        }
+        squares[0] = self.king_squares[idx % self.king_squares.len()];
+        (squares, idx >= self.king_squares.len())
+    }
+
+    /// Index shared by all mirrored and rotated versions of a position
+    fn canonical(&self, squares: &Squares, black_to_move: bool) -> usize {
+        let king = squares[0];
+        let flip_file = king % 8 > 3;
+        if self.has_pawns {
+            return self.encode(&self.transformed(squares, flip_file, false, false), black_to_move);
+        }
+        let flip_rank = king / 8 > 3;
+        let (file, rank) = (if flip_file { 7 - king % 8 } else { king % 8 }, if flip_rank { 7 - king / 8 } else { king / 8 });
+        let idx = self.encode(&self.transformed(squares, flip_file, flip_rank, rank > file), black_to_move);
+        // A king on the diagonal stays in the triangle when flipped along it, so the smaller index is used
+        match rank == file {
+            true => idx.min(self.encode(&self.transformed(squares, flip_file, flip_rank, true), black_to_move)),
+            false => idx,
+        }
+    }
+
+    fn transformed(&self, squares: &Squares, flip_file: bool, flip_rank: bool, flip_diagonal: bool) -> Squares {
+        let mut result = *squares;
+        for square in result[..self.pieces.len()].iter_mut() {
+            let (mut file, mut rank) = (*square % 8, *square / 8);
+            if flip_file {
+                file = 7 - file;
+            }
+            if flip_rank {
+                rank = 7 - rank;
+            }
+            if flip_diagonal {
+                (file, rank) = (rank, file);
+            }
+            *square = rank * 8 + file;
+        }
+        // Identical pieces can be swapped, so their squares are kept in order
+        for k in 3..self.pieces.len() {
+            let mut j = k;
+            while j > 2 && self.pieces[j] == self.pieces[j - 1] && result[j] < result[j - 1] {
+                result.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+        result
+    }
+
+    fn board_squares(&self, squares: &Squares) -> [u8; 64] {
+        let mut board = [Piece::NONE; 64];
+        for (square, piece) in squares.iter().zip(self.pieces.iter()) {
+            board[*square as usize] = *piece;
+        }
+        board
+    }
+
+    /// Pieces on different squares, no pawns on the first or last rank and the side not to move not in check
+    fn is_valid(&self, squares: &Squares, black_to_move: bool) -> bool {
+        let num_pieces = self.pieces.len();
+        for k in 0..num_pieces {
+            if squares[..k].contains(&squares[k]) {
+                return false;
+            }
+            let rank = squares[k] / 8;
+            if Piece::new(self.pieces[k]).piece_type() == Piece::PAWN && (rank == 0 || rank == 7) {
+                return false;
+            }
+        }
+        let (waiting_king, moving_color) = if black_to_move { (squares[0], Piece::BLACK) } else { (squares[1], Piece::WHITE) };
+        !fen::square_is_attacked(&self.board_squares(squares), waiting_king as i8, moving_color)
+    }
+
+    fn position_info(&self, squares: &Squares, black_to_move: bool) -> LoadedPositionInfo {
+        LoadedPositionInfo {
+            squares: self.board_squares(squares),
+            white_to_move: !black_to_move,
+            ..LoadedPositionInfo::default()
+        }
+    }
+
+    /// Squares of the pieces on the board, with the colors swapped and the board flipped if `flip` is set
+    fn squares_on_board(&self, board: &Board, flip: bool) -> Squares {
+        let mut squares = [0; MAX_GENERATED_PIECES];
+        for k in 0..self.pieces.len() {
+            if k > 0 && self.pieces[k] == self.pieces[k - 1] {
+                continue;
+            }
+            let piece = if flip { self.pieces[k] ^ Piece::BLACK } else { self.pieces[k] };
+            let mut bitboard = board.piece_bitboards[piece as usize];
+            let mut j = k;
+            while bitboard.0 != 0 {
+                let square = bitboard.pop_lsb() as u8;
+                squares[j] = if flip { square ^ 56 } else { square };
+                j += 1;
+            }
+        }
+        squares
+    }
+
+    /// Canonical indices of the positions with a move leading to this one, without captures and promotions
+    fn predecessors(&self, idx: usize, predecessors: &mut Vec<usize>) {
+        predecessors.clear();
+        let (squares, black_to_move) = self.decode(idx);
+        let moved_color = if black_to_move { Piece::WHITE } else { Piece::BLACK };
+        let num_pieces = self.pieces.len();
+        let occupied = squares[..num_pieces].iter().fold(0u64, |occupied, square| occupied | 1 << square);
+
+        let mut origins = Vec::new();
+        for k in 0..num_pieces {
+            if Piece::new(self.pieces[k]).color() != moved_color {
+                continue;
+            }
+            unmove_origins(self.pieces[k], squares[k], occupied, &mut origins);
+            for origin in origins.iter() {
+                let mut previous = squares;
+                previous[k] = *origin;
+                predecessors.push(self.canonical(&previous, !black_to_move));
+            }
+        }
+        predecessors.sort_unstable();
+        predecessors.dedup();
+    }
+}
+
+/// Squares a piece can have come from with a quiet move
+fn unmove_origins(piece: u8, square: u8, occupied: u64, origins: &mut Vec<u8>) {
+    origins.clear();
+    let (file, rank) = ((square % 8) as i8, (square / 8) as i8);
+    let is_empty = |f: i8, r: i8| (0..8).contains(&f) && (0..8).contains(&r) && occupied & (1 << (r * 8 + f)) == 0;
+    let mut steps = |dirs: &[(i8, i8)], slide: bool| {
+        for (df, dr) in dirs.iter() {
+            let (mut f, mut r) = (file + df, rank + dr);
+            while is_empty(f, r) {
+                origins.push((r * 8 + f) as u8);
+                if !slide {
+                    break;
+                }
+                f += df;
+                r += dr;
+            }
+        }
+    };
+
+    let piece = Piece::new(piece);
+    match piece.piece_type() {
+        Piece::KING => steps(&KING_STEPS, false),
+        Piece::KNIGHT => steps(&KNIGHT_JUMPS, false),
+        Piece::BISHOP => steps(&DIAGONAL_DIRS, true),
+        Piece::ROOK => steps(&ORTHOGONAL_DIRS, true),
+        Piece::QUEEN => {
+            steps(&ORTHOGONAL_DIRS, true);
+            steps(&DIAGONAL_DIRS, true);
+        },
+        _ => {
+            // Pawns move backwards, and can't have come from their own back rank
+            let (dir, start_rank) = if piece.is_color(Piece::WHITE) { (-1, 1) } else { (1, 6) };
+            let previous_rank = rank + dir;
+            if (1..7).contains(&previous_rank) && is_empty(file, previous_rank) {
+                origins.push((previous_rank * 8 + file) as u8);
+                if previous_rank + dir == start_rank && is_empty(file, start_rank) {
+                    origins.push((start_rank * 8 + file) as u8);
+                }
+            }
+        },
+    }
+}
+
+
+/// Distance to mate of every position of one material, found by retrograde analysis
+pub struct EndgameTable {
+    material: Material,
+    index: TableIndex,
+    /// Drawn positions are 0, others the number of plies until mate plus one
+    values: Vec<u8>,
+}
+
+impl EndgameTable {
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// Number of indices, including illegal positions and mirrored copies
+    pub fn num_positions(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Probes a position with the material of this table, either color can have the stronger pieces
+    pub fn probe(&self, board: &Board) -> Option<Dtm> {
+        let counts = self.material.counts();
+        let board_counts = board_material(board);
+        let flip = match board_counts {
+            _ if board_counts == counts => false,
+            _ if board_counts == [counts[1], counts[0]] => true,
+            _ => return None,
+        };
+        if board.current_state.castling_rights != 0 {
+            return None;
+        }
+        let squares = self.index.squares_on_board(board, flip);
+        let black_to_move = board.white_to_move == flip;
+        Dtm::from_value(self.values[self.index.canonical(&squares, black_to_move)])
+    }
+
+    /// Number of legal positions with the given result, counting mirrored positions once
+    pub fn count(&self, white_to_move: bool, wdl: Wdl) -> usize {
+        let half = self.values.len() / 2;
+        let values = if white_to_move { &self.values[..half] } else { &self.values[half..] };
+        values.iter().filter(|value| Dtm::from_value(**value).is_some_and(|dtm| dtm.wdl() == wdl)).count()
+    }
+
+    /// Most plies the stronger side needs to mate from a won position
+    pub fn longest_mate(&self) -> u8 {
+        self.values.iter()
+            .filter_map(|value| match Dtm::from_value(*value) {
+                Some(Dtm::Win(plies)) => Some(plies),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The file holds a magic number, the material name and one byte per index
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.material.to_string();
+        let mut bytes = Vec::with_capacity(TABLE_MAGIC.len() + 1 + name.len() + self.values.len());
+        bytes.extend_from_slice(&TABLE_MAGIC);
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&self.values);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let rest = bytes.strip_prefix(&TABLE_MAGIC)?;
+        let (name_len, rest) = rest.split_first()?;
+        let name = std::str::from_utf8(rest.get(..*name_len as usize)?).ok()?;
+        let material = Material::parse(name)?;
+        let index = TableIndex::new(&material);
+        let values = rest[*name_len as usize..].to_vec();
+        (values.len() == index.size).then_some(Self { material, index, values })
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a valid endgame table"))
+    }
+
+    /// Writes the table to `<material>.ctb` in the directory
+    pub fn write(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(dir.as_ref().join(format!("{}.{}", self.material, TABLE_EXTENSION)), self.to_bytes())
+    }
+}
+
+
+/// Generated tables by material
+#[derive(Default)]
+pub struct EndgameTables {
+    tables: HashMap<[[u8; 7]; 2], EndgameTable>,
+    max_pieces: usize,
+}
+
+impl EndgameTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the tables in a directory, or in a list of directories separated like the `PATH` variable.
+    /// Files that aren't valid tables are skipped.
+    pub fn open(path: impl AsRef<OsStr>) -> io::Result<Self> {
+        let mut tables = Self::new();
+        for dir in env::split_paths(&path) {
+            for dir_entry in fs::read_dir(dir)? {
+                let file_path = dir_entry?.path();
+                if file_path.extension().and_then(|ext| ext.to_str()) != Some(TABLE_EXTENSION) {
+                    continue;
+                }
+                if let Ok(table) = EndgameTable::open(file_path) {
+                    tables.insert(table);
+                }
+            }
+        }
+        Ok(tables)
+    }
+
+    pub fn insert(&mut self, table: EndgameTable) {
+        self.max_pieces = self.max_pieces.max(table.material.num_pieces());
+        self.tables.insert(table.material.counts(), table);
+    }
+
+    pub fn get(&self, material: &Material) -> Option<&EndgameTable> {
+        self.tables.get(&material.counts())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EndgameTable> {
+        self.tables.values()
+    }
+
+    pub fn probe(&self, board: &Board) -> Option<Dtm> {
+        if board.all_pieces_bitboard.count() as usize > self.max_pieces {
+            return None;
+        }
+        let counts = board_material(board);
+        let table = self.tables.get(&counts).or_else(|| self.tables.get(&[counts[1], counts[0]]))?;
+        table.probe(board)
+    }
+
+    /// The move with the best result, mating as fast as possible or delaying mate as long as possible
+    pub fn probe_root(&self, board: &mut Board) -> Option<(Move, Dtm)> {
+        self.probe(board)?;
+        let tables = LookupTables::get();
+        let mut move_gen = MoveGenerator::default();
+        move_gen.generate_moves(board, &tables.precomp, &tables.bbutils, &tables.magic, false);
+
+        let mut best: Option<(Move, Dtm)> = None;
+        for mov in move_gen.moves.iter() {
+            board.make_move(*mov, true, &tables.zobrist);
+            let result = self.probe(board).map(Dtm::parent);
+            board.unmake_move(*mov, true);
+
+            let result = result?;
+            if best.is_none_or(|(_, best)| result.order() > best.order()) {
+                best = Some((*mov, result));
+            }
+        }
+        best
+    }
+}
+
+
+/// Generates tables by retrograde analysis. The tables of the materials left after captures and
+/// promotions are generated first, and kept along with the requested ones.
+#[derive(Default)]
+pub struct TableGenerator {
+    tables: EndgameTables,
+}
+
+impl TableGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn generate(&mut self, material: &Material) -> &EndgameTable {
+        if self.tables.get(material).is_none() {
+            for successor in material.successors() {
+                self.generate(&successor);
+            }
+            let table = generate_table(material, &self.tables);
+            self.tables.insert(table);
+        }
+        self.tables.get(material).unwrap()
+    }
+
+    pub fn tables(&self) -> &EndgameTables {
+        &self.tables
+    }
+
+    pub fn into_tables(self) -> EndgameTables {
+        self.tables
+    }
+}
+
+/// Finds mates and the moves leaving the table with the move generator, then walks back from the
+/// mates one ply at a time. A position is won as soon as one move leads to a lost position, and lost
+/// once every move leads to a won one.
+fn generate_table(material: &Material, successor_tables: &EndgameTables) -> EndgameTable {
+    let index = TableIndex::new(material);
+    let tables = LookupTables::get();
+    let mut values = vec![INVALID_VALUE; index.size];
+    let mut resolved = vec![false; index.size];
+    // Moves staying in the table that don't lead to a won position yet
+    let mut num_unresolved_moves = vec![0u8; index.size];
+    // Set when a move leaving the table draws or wins
+    let mut cannot_lose = vec![false; index.size];
+    // Longest loss through moves leaving the table
+    let mut exit_loss_plies = vec![0u8; index.size];
+    // Positions found at each distance to mate
+    let mut plies: Vec<Vec<u32>> = vec![Vec::new(); MAX_PLIES + 1];
+
+    let mut board = Board::default();
+    let mut move_gen = MoveGenerator::default();
+    let mut children = Vec::new();
+    for idx in 0..index.size {
+        let (squares, black_to_move) = index.decode(idx);
+        if !index.is_valid(&squares, black_to_move) || index.canonical(&squares, black_to_move) != idx {
+            continue;
+        }
+        values[idx] = DRAW_VALUE;
+        board.load_position_info(&index.position_info(&squares, black_to_move), &tables.zobrist);
+        move_gen.generate_moves(&board, &tables.precomp, &tables.bbutils, &tables.magic, false);
+        if move_gen.moves.is_empty() {
+            match move_gen.in_check() {
+                true => plies[0].push(idx as u32),
+                false => resolved[idx] = true,
+            }
+            continue;
+        }
+
+        children.clear();
+        let mut fastest_exit_win = None;
+        for mov in move_gen.moves.iter() {
+            let start = mov.start().index() as u8;
+            if mov.is_promotion() || board.square[mov.target().index()] != Piece::NULL {
+                board.make_move(*mov, true, &tables.zobrist);
+                let result = successor_tables.probe(&board)
+                    .expect("tables after captures and promotions are generated first")
+                    .parent();
+                board.unmake_move(*mov, true);
+                match result {
+                    Dtm::Win(win_plies) => {
+                        cannot_lose[idx] = true;
+                        fastest_exit_win = Some(fastest_exit_win.map_or(win_plies, |fastest: u8| fastest.min(win_plies)));
+                    },
+                    Dtm::Draw => cannot_lose[idx] = true,
+                    Dtm::Loss(loss_plies) => exit_loss_plies[idx] = exit_loss_plies[idx].max(loss_plies),
+                }
+            } else {
+                let mut child = squares;
+                let k = child.iter().position(|square| *square == start).unwrap();
+                child[k] = mov.target().index() as u8;
+                children.push(index.canonical(&child, !black_to_move));
+            }
+        }
+        children.sort_unstable();
+        children.dedup();
+        num_unresolved_moves[idx] = children.len() as u8;
+
+        if let Some(win_plies) = fastest_exit_win {
+            plies[win_plies as usize].push(idx as u32);
+        } else if children.is_empty() && !cannot_lose[idx] {
+            plies[exit_loss_plies[idx] as usize].push(idx as u32);
+        }
+    }
+
+    let mut predecessors = Vec::new();
+    for ply in 0..=MAX_PLIES {
+        for idx in std::mem::take(&mut plies[ply]) {
+            let idx = idx as usize;
+            if resolved[idx] {
+                continue;
+            }
+            resolved[idx] = true;
+            values[idx] = ply as u8 + 1;
+
+            index.predecessors(idx, &mut predecessors);
+            for previous in predecessors.iter().copied() {
+                if resolved[previous] || values[previous] == INVALID_VALUE {
+                    continue;
+                }
+                // Odd distances are wins for the side to move, so the previous position loses once all its moves are wins
+                if ply % 2 == 0 {
+                    plies[ply + 1].push(previous as u32);
+                } else {
+                    num_unresolved_moves[previous] -= 1;
+                    if num_unresolved_moves[previous] == 0 && !cannot_lose[previous] {
+                        let loss_plies = (ply + 1).max(exit_loss_plies[previous] as usize);
+                        plies[loss_plies].push(previous as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    EndgameTable { material: material.clone(), index, values }
+}
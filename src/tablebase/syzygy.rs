@@ -0,0 +1,1051 @@
+use std::{collections::HashMap, env, ffi::OsStr, fs::{self, File}, io, path::{Path, PathBuf}, sync::OnceLock};
+
+use memmap2::Mmap;
+
+use crate::{board::{board::Board, moves::Move, piece::Piece}, engine::tables::LookupTables, move_gen::move_generator::MoveGenerator};
+
+use super::{Wdl, board_material};
+
+
+pub const DEFAULT_SYZYGY_PATH: &str = "assets/syzygy";
+/// Largest number of pieces Syzygy tables have been generated for
+pub const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+const WDL_EXTENSION: &str = "rtbw";
+const DTZ_EXTENSION: &str = "rtbz";
+
+// Flags of the table header
+const HEADER_SPLIT: u8 = 1;
+const HEADER_HAS_PAWNS: u8 = 2;
+
+// Flags of each compressed sub-table
+const FLAG_STM: u8 = 1;
+const FLAG_MAPPED: u8 = 2;
+const FLAG_WIN_PLIES: u8 = 4;
+const FLAG_LOSS_PLIES: u8 = 8;
+const FLAG_WIDE: u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+/// Piece types in the order they appear in table names
+const PIECE_CHARS: [(char, u8); 6] = [
+    ('K', Piece::KING), ('Q', Piece::QUEEN), ('R', Piece::ROOK),
+    ('B', Piece::BISHOP), ('N', Piece::KNIGHT), ('P', Piece::PAWN),
+];
+
+/// Number of pieces of every type, indexed by color and piece type
+type Material = [[u8; 7]; 2];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+/// Endgame tablebases in the Syzygy format, the files are only opened once a position needs them
+pub struct SyzygyTablebase {
+    /// Tables by the material of the side written first in the file name, and the material of the other side
+    tables: HashMap<Material, TableFiles>,
+    max_pieces: usize,
+}
+
+impl SyzygyTablebase {
+    /// Finds the tables in a directory, or in a list of directories separated like the `PATH` variable
+    pub fn open(path: impl AsRef<OsStr>) -> io::Result<Self> {
+        let mut tablebase = Self {
+            tables: HashMap::new(),
+            max_pieces: 0,
+        };
+        for dir in env::split_paths(&path) {
+            for dir_entry in fs::read_dir(dir)? {
+                let file_path = dir_entry?.path();
+                tablebase.add_file(file_path);
+            }
+        }
+        Ok(tablebase)
+    }
+
+    fn add_file(&mut self, path: PathBuf) {
+        let kind = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(WDL_EXTENSION) => TableKind::Wdl,
+            Some(DTZ_EXTENSION) => TableKind::Dtz,
+            _ => return,
+        };
+        let Some(material) = path.file_stem().and_then(|stem| stem.to_str()).and_then(parse_material) else {
+            return;
+        };
+        let files = self.tables.entry(material).or_insert_with(|| TableFiles::new(material));
+        match kind {
+            TableKind::Wdl => {
+                files.wdl_path = Some(path);
+                self.max_pieces = self.max_pieces.max(files.num_pieces);
+            },
+            TableKind::Dtz => files.dtz_path = Some(path),
+        }
+    }
+
+    /// Number of material combinations with a WDL table
+    pub fn num_tables(&self) -> usize {
+        self.tables.values().filter(|files| files.wdl_path.is_some()).count()
+    }
+
+    /// Most pieces in any of the WDL tables
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// Positions with castling rights aren't stored in the tables
+    pub fn can_probe(&self, board: &Board) -> bool {
+        board.current_state.castling_rights == 0 && board.all_pieces_bitboard.count() as usize <= self.max_pieces
+    }
+
+    /// Win, draw or loss for the side to move, ignoring the fifty-move counter of the position.
+    /// The board is searched through captures, but left as it was.
+    pub fn probe_wdl(&self, board: &mut Board) -> Option<Wdl> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        self.search_wdl(board, false).map(|(wdl, _)| wdl)
+    }
+
+    /// Distance to zeroing the fifty-move counter in plies, positive when the side to move wins
+    /// and negative when it loses. Cursed wins and blessed losses are 100 plies further away.
+    /// The result can be one ply longer than the real distance, but never changes sign.
+    pub fn probe_dtz(&self, board: &mut Board) -> Option<i32> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        self.dtz(board)
+    }
+
+    /// Finds the move that keeps the best result of the position, taking into account the fifty-move counter
+    /// and earlier repetitions. Wins are converted as fast as possible and losses are dragged out.
+    pub fn probe_root(&self, board: &mut Board) -> Option<RootProbe> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        let zobrist = &LookupTables::get().zobrist;
+        let fifty_move_counter = board.current_state.fifty_move_counter as i32;
+        let repeated = has_repeated(board);
+
+        let mut best: Option<RootProbe> = None;
+        for mov in legal_moves(board).0 {
+            board.make_move(mov, true, zobrist);
+            let dtz = if board.current_state.fifty_move_counter == 0 {
+                self.search_wdl(board, false).map(|(wdl, _)| dtz_before_zeroing(-wdl))
+            } else {
+                self.dtz(board).map(|dtz| -dtz + (-dtz).signum())
+            };
+            let gives_mate = dtz == Some(2) && is_mate(board);
+            board.unmake_move(mov, true);
+
+            let dtz = if gives_mate { 1 } else { dtz? };
+            let rank = match dtz {
+                1.. if dtz + fifty_move_counter <= 99 && !repeated => 1000,
+                1.. => 1000 - (dtz + fifty_move_counter),
+                0 => 0,
+                _ if -dtz * 2 + fifty_move_counter < 100 => -1000,
+                _ => -1000 + (-dtz + fifty_move_counter),
+            };
+            // Equally ranked wins are ordered by the fastest conversion, losses by the slowest one
+            if best.as_ref().is_none_or(|best| (rank, -dtz) > (best.rank, -best.dtz)) {
+                best = Some(RootProbe { mov, dtz, rank });
+            }
+        }
+        best
+    }
+
+    /// `search<CheckZeroingMoves>` of the reference implementation. Captures, and pawn moves with
+    /// `zeroing_moves`, are searched because the tables don't store en passant rights and don't know
+    /// whether the best move resets the fifty-move counter. Returns the result and whether
+    /// the best move is one of the searched moves.
+    fn search_wdl(&self, board: &mut Board, zeroing_moves: bool) -> Option<(Wdl, bool)> {
+        let (moves, in_check) = legal_moves(board);
+        if moves.is_empty() {
+            return Some((if in_check { Wdl::Loss } else { Wdl::Draw }, false));
+        }
+        let zobrist = &LookupTables::get().zobrist;
+
+        let mut best = Wdl::Loss;
+        let mut num_searched = 0;
+        for mov in moves.iter() {
+            let zeroing = is_capture(board, *mov) || (zeroing_moves && is_pawn_move(board, *mov));
+            if !zeroing {
+                continue;
+            }
+            num_searched += 1;
+            board.make_move(*mov, true, zobrist);
+            let result = self.search_wdl(board, false);
+            board.unmake_move(*mov, true);
+
+            let value = -result?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // The stored result may be wrong when all moves are captures, for example because of en passant
+        let searched_all_moves = num_searched == moves.len();
+        let value = if searched_all_moves { best } else { self.probe_wdl_table(board)? };
+        if best >= value {
+            return Some((best, best > Wdl::Draw || searched_all_moves));
+        }
+        Some((value, false))
+    }
+
+    fn dtz(&self, board: &mut Board) -> Option<i32> {
+        let (wdl, best_move_zeroes) = self.search_wdl(board, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if best_move_zeroes || is_mate(board) {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        if let Some(dtz) = self.probe_dtz_table(board, wdl)? {
+            let cursed = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+            return Some((dtz + if cursed { 100 } else { 0 }) * wdl.signum());
+        }
+
+        // The table only stores the other side to move, so look one move ahead
+        let zobrist = &LookupTables::get().zobrist;
+        let mut min_dtz = i32::MAX;
+        for mov in legal_moves(board).0 {
+            let zeroing = is_capture(board, mov) || is_pawn_move(board, mov);
+            board.make_move(mov, true, zobrist);
+            // Zeroing moves only need the sign of the result, the distance is counted from before the move
+            let result = if zeroing {
+                self.search_wdl(board, false).map(|(wdl, _)| -dtz_before_zeroing(wdl))
+            } else {
+                self.dtz(board).map(|dtz| -dtz)
+            };
+            let gives_mate = result == Some(1) && is_mate(board);
+            board.unmake_move(mov, true);
+
+            let mut dtz = result?;
+            if gives_mate {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+        }
+        Some(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    fn probe_wdl_table(&self, board: &Board) -> Option<Wdl> {
+        if board.all_pieces_bitboard.count() == 2 {
+            return Some(Wdl::Draw);
+        }
+        let (files, table_material) = self.table_files(board)?;
+        let table = files.table(TableKind::Wdl)?;
+        table.probe(board, table_material, Wdl::Draw)?.map(Wdl::from_value)
+    }
+
+    /// `None` when the table couldn't be used, `Some(None)` when it stores positions
+    /// with the other side to move
+    fn probe_dtz_table(&self, board: &Board, wdl: Wdl) -> Option<Option<i32>> {
+        let (files, table_material) = self.table_files(board)?;
+        let table = files.table(TableKind::Dtz)?;
+        table.probe(board, table_material, wdl)
+    }
+
+    /// Tables for the material of the position, together with the material written first in their file name
+    fn table_files(&self, board: &Board) -> Option<(&TableFiles, [u8; 7])> {
+        let material = board_material(board);
+        if let Some(files) = self.tables.get(&material) {
+            return Some((files, material[0]));
+        }
+        self.tables.get(&[material[1], material[0]]).map(|files| (files, material[1]))
+    }
+}
+
+/// Best move of a tablebase position
+#[derive(Clone, Copy, Debug)]
+pub struct RootProbe {
+    pub mov: Move,
+    /// Distance to zeroing the fifty-move counter after the move, see `SyzygyTablebase::probe_dtz`
+    pub dtz: i32,
+    /// 1000 for wins, -1000 for losses and 0 for draws, in between for results the fifty-move rule turns into a draw
+    pub rank: i32,
+}
+
+impl RootProbe {
+    /// Result of the move under the fifty-move rule
+    pub fn wdl(&self) -> Wdl {
+        match self.rank {
+            900.. => Wdl::Win,
+            1.. => Wdl::CursedWin,
+            0 => Wdl::Draw,
+            -899.. => Wdl::BlessedLoss,
+            _ => Wdl::Loss,
+        }
+    }
+}
+
+/// WDL and DTZ file of a material combination, loaded on first use
+struct TableFiles {
+    material: Material,
+    num_pieces: usize,
+    wdl_path: Option<PathBuf>,
+    dtz_path: Option<PathBuf>,
+    wdl: OnceLock<Option<Table>>,
+    dtz: OnceLock<Option<Table>>,
+}
+
+impl TableFiles {
+    fn new(material: Material) -> Self {
+        Self {
+            material,
+            num_pieces: material.iter().flatten().map(|count| *count as usize).sum(),
+            wdl_path: None,
+            dtz_path: None,
+            wdl: OnceLock::new(),
+            dtz: OnceLock::new(),
+        }
+    }
+
+    fn table(&self, kind: TableKind) -> Option<&Table> {
+        let (path, table) = match kind {
+            TableKind::Wdl => (&self.wdl_path, &self.wdl),
+            TableKind::Dtz => (&self.dtz_path, &self.dtz),
+        };
+        table.get_or_init(|| path.as_ref().and_then(|path| Table::open(path, kind, self.material).ok())).as_ref()
+    }
+}
+
+/// Lookup tables for turning piece placements into table indices
+struct IndexTables {
+    /// Squares below the a1-h8 diagonal
+    map_b1h1h7: [u64; 64],
+    /// Squares of the a1-d1-d4 triangle, the diagonal comes last
+    map_a1d1d4: [usize; 64],
+    /// The 462 placements of two kings with the first one in the a1-d1-d4 triangle
+    map_kk: [[u64; 64]; 10],
+    /// Binomial coefficients, indexed by k and then n
+    binomial: [[u64; 64]; MAX_PIECES],
+    /// Pawn squares, the leading pawn is the one with the highest value
+    map_pawns: [usize; 64],
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+}
+
+impl IndexTables {
+    fn get() -> &'static Self {
+        static TABLES: OnceLock<IndexTables> = OnceLock::new();
+        TABLES.get_or_init(Self::new)
+    }
+
+    fn new() -> Self {
+        let mut tables = Self {
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_PIECES],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                tables.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut diagonal = Vec::new();
+        let mut code = 0;
+        for square in 0..=27 {
+            if off_diagonal(square) < 0 && square % 8 <= 3 {
+                tables.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 && square % 8 <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            tables.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // Placements with both kings on the diagonal come last
+        let mut both_on_diagonal = Vec::new();
+        let mut code = 0;
+        for idx in 0..10 {
+            // b1 is the only square of the triangle mapped to 0
+            for first in (0..=27).filter(|sq| tables.map_a1d1d4[*sq] == idx && (idx != 0 || *sq == 1)) {
+                for second in 0..64 {
+                    let adjacent = (first as i32 / 8 - second as i32 / 8).abs() <= 1 && (first as i32 % 8 - second as i32 % 8).abs() <= 1;
+                    if adjacent || (off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    } else {
+                        tables.map_kk[idx][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            tables.map_kk[idx][second] = code;
+            code += 1;
+        }
+
+        tables.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                tables.binomial[k][n] = if k > 0 { tables.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { tables.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // 47 squares are left for the other pawns when the leading pawn is on a2,
+        // every rank further up takes away two more because of the mirroring
+        let mut available_squares = 48;
+        for lead_pawns in 1..MAX_PIECES {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        available_squares -= 1;
+                        tables.map_pawns[square] = available_squares;
+                        available_squares -= 1;
+                        tables.map_pawns[square ^ 7] = available_squares;
+                    }
+                    tables.lead_pawn_idx[lead_pawns][square] = idx;
+                    idx += tables.binomial[lead_pawns - 1][tables.map_pawns[square]];
+                }
+                tables.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+        tables
+    }
+}
+
+/// Parameters of one compressed sub-table. Positions are stored as values of a Huffman coded
+/// stream of symbols, each of which expands into one or more values through recursive pairing.
+#[derive(Default)]
+struct PairsData {
+    flags: u8,
+    /// The stored value for tables with a single value
+    min_sym_len: u8,
+    block_size: usize,
+    /// Number of values between two entries of the sparse index
+    span: u64,
+    num_blocks: usize,
+    /// Offsets of the tables in the file
+    lowest_sym: usize,
+    btree: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    data: usize,
+    /// Lowest symbol of every length, left aligned to 64 bits
+    base64: Vec<u64>,
+    /// Number of values a symbol expands to, minus one
+    symlen: Vec<u8>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    /// Offsets of the value maps of DTZ tables, by `dtz_map_idx`
+    map_idx: [usize; 4],
+}
+
+struct Table {
+    kind: TableKind,
+    data: Mmap,
+    /// Both sides have the same material, so only one side to move is stored
+    symmetric: bool,
+    num_pieces: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    /// Pawns of the leading color, which has the fewest pawns, and of the other color
+    pawn_count: [usize; 2],
+    /// Sub-tables by side to move and file of the leading pawn
+    items: [[PairsData; 4]; 2],
+}
+
+impl Table {
+    fn open(path: &Path, kind: TableKind, material: Material) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: tablebase files are never written to while the engine runs
+        let data = unsafe { Mmap::map(&file)? };
+        let magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        if data.get(0..4) != Some(&magic[..]) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a Syzygy table", path.display())));
+        }
+
+        let has_pawns = material[0][Piece::PAWN as usize] + material[1][Piece::PAWN as usize] > 0;
+        let has_unique_pieces = material.iter().any(|counts| counts[Piece::PAWN as usize..Piece::KING as usize].contains(&1));
+        // The side with fewer pawns leads, unless it has none
+        let first_leads = material[1][Piece::PAWN as usize] == 0
+            || (material[0][Piece::PAWN as usize] > 0 && material[1][Piece::PAWN as usize] >= material[0][Piece::PAWN as usize]);
+        let (lead, other) = if first_leads { (0, 1) } else { (1, 0) };
+
+        let mut table = Self {
+            kind,
+            data,
+            symmetric: material[0] == material[1],
+            num_pieces: material.iter().flatten().map(|count| *count as usize).sum(),
+            has_pawns,
+            has_unique_pieces,
+            pawn_count: [material[lead][Piece::PAWN as usize] as usize, material[other][Piece::PAWN as usize] as usize],
+            items: Default::default(),
+        };
+        match table.parse() {
+            Some(items) => {
+                table.items = items;
+                Ok(table)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is corrupted", path.display()))),
+        }
+    }
+
+    fn num_sides(&self) -> usize {
+        if self.kind == TableKind::Wdl && !self.symmetric { 2 } else { 1 }
+    }
+
+    fn num_files(&self) -> usize {
+        if self.has_pawns { 4 } else { 1 }
+    }
+
+    fn parse(&self) -> Option<[[PairsData; 4]; 2]> {
+        let data = &self.data[..];
+        let mut items: [[PairsData; 4]; 2] = Default::default();
+        let header = *data.get(4)?;
+        if (header & HEADER_HAS_PAWNS != 0) != self.has_pawns || (self.kind == TableKind::Wdl && (header & HEADER_SPLIT != 0) == self.symmetric) {
+            return None;
+        }
+        let mut pos = 5;
+        let both_have_pawns = self.has_pawns && self.pawn_count[1] > 0;
+
+        for file in 0..self.num_files() {
+            let order_byte = *data.get(pos)?;
+            let pawn_order_byte = if both_have_pawns { *data.get(pos + 1)? } else { 0xff };
+            let order = [
+                [order_byte & 0xf, pawn_order_byte & 0xf],
+                [order_byte >> 4, pawn_order_byte >> 4],
+            ];
+            pos += 1 + both_have_pawns as usize;
+            for k in 0..self.num_pieces {
+                let pieces = *data.get(pos)?;
+                for (side, item) in items.iter_mut().enumerate().take(self.num_sides()) {
+                    item[file].pieces[k] = if side == 0 { pieces & 0xf } else { pieces >> 4 };
+                }
+                pos += 1;
+            }
+            for (side, item) in items.iter_mut().enumerate().take(self.num_sides()) {
+                self.set_groups(&mut item[file], order[side], file);
+            }
+        }
+        pos += pos & 1;
+
+        for file in 0..self.num_files() {
+            for item in items.iter_mut().take(self.num_sides()) {
+                pos = set_sizes(&mut item[file], data, pos)?;
+            }
+        }
+        if self.kind == TableKind::Dtz {
+            for file_items in items[0].iter_mut().take(self.num_files()) {
+                if file_items.flags & FLAG_MAPPED == 0 {
+                    continue;
+                }
+                if file_items.flags & FLAG_WIDE != 0 {
+                    pos += pos & 1;
+                    for map_idx in file_items.map_idx.iter_mut() {
+                        *map_idx = pos + 2;
+                        pos += 2 * read_u16_le(data, pos)? as usize + 2;
+                    }
+                } else {
+                    for map_idx in file_items.map_idx.iter_mut() {
+                        *map_idx = pos + 1;
+                        pos += *data.get(pos)? as usize + 1;
+                    }
+                }
+            }
+            pos += pos & 1;
+        }
+
+        for file in 0..self.num_files() {
+            for item in items.iter_mut().take(self.num_sides()) {
+                item[file].sparse_index = pos;
+                pos += item[file].sparse_index_size * 6;
+            }
+        }
+        for file in 0..self.num_files() {
+            for item in items.iter_mut().take(self.num_sides()) {
+                item[file].block_lengths = pos;
+                pos += item[file].block_lengths_size * 2;
+            }
+        }
+        for file in 0..self.num_files() {
+            for item in items.iter_mut().take(self.num_sides()) {
+                pos = (pos + 0x3f) & !0x3f;
+                item[file].data = pos;
+                pos += item[file].num_blocks * item[file].block_size;
+            }
+        }
+        if pos > data.len() {
+            return None;
+        }
+        Some(items)
+    }
+
+    /// Splits the pieces into groups that are encoded together. `order` gives the position
+    /// of the leading group and of the remaining pawns in the encoding.
+    fn set_groups(&self, d: &mut PairsData, order: [u8; 2], file: usize) {
+        let tables = IndexTables::get();
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns { 0 } else if self.has_unique_pieces { 3 } else { 2 };
+        d.group_len[0] = 1;
+        for i in 1..self.num_pieces {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let both_have_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if both_have_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - d.group_len[0] - if both_have_pawns { d.group_len[1] } else { 0 };
+        let mut idx = 1u64;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                d.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    tables.lead_pawns_size[d.group_len[0]][file]
+                } else if self.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] {
+                d.group_idx[1] = idx;
+                idx *= tables.binomial[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                d.group_idx[next] = idx;
+                idx *= tables.binomial[d.group_len[next]][free_squares];
+                free_squares -= d.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        d.group_idx[n] = idx;
+    }
+
+    /// Value stored for the position, `None` if the table data can't be decoded and `Some(None)`
+    /// if the table only stores the other side to move. `table_material` is the material of the side written first in the file name,
+    /// the colors are swapped when it is black.
+    fn probe(&self, board: &Board, table_material: [u8; 7], wdl: Wdl) -> Option<Option<i32>> {
+        let tables = IndexTables::get();
+        let white_material = board_material(board)[Board::WHITE_INDEX];
+        let flip = (self.symmetric && !board.white_to_move) || white_material != table_material;
+        let flip_color = if flip { Piece::BLACK } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let stm = flip as usize ^ !board.white_to_move as usize;
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut pieces = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut file = 0;
+        if self.has_pawns {
+            let lead_pawn = self.items[0][0].pieces[0] ^ flip_color;
+            lead_pawns = board.piece_bitboards[lead_pawn as usize].0;
+            let mut bitboard = lead_pawns;
+            while bitboard != 0 {
+                squares[size] = bitboard.trailing_zeros() as usize ^ flip_squares;
+                size += 1;
+                bitboard &= bitboard - 1;
+            }
+            let leading = (0..size).max_by_key(|i| tables.map_pawns[squares[*i]]).unwrap();
+            squares.swap(0, leading);
+            file = (squares[0] % 8).min(7 - squares[0] % 8);
+        }
+        let num_lead_pawns = size;
+
+        if self.kind == TableKind::Dtz
+        && (self.items[0][file].flags & FLAG_STM) as usize != stm
+        && (!self.symmetric || self.has_pawns) {
+            return Some(None);
+        }
+
+        let mut bitboard = board.all_pieces_bitboard.0 ^ lead_pawns;
+        while bitboard != 0 {
+            let square = bitboard.trailing_zeros() as usize;
+            squares[size] = square ^ flip_squares;
+            pieces[size] = board.square[square].value() ^ flip_color;
+            size += 1;
+            bitboard &= bitboard - 1;
+        }
+
+        let d = &self.items[if self.kind == TableKind::Wdl { stm } else { 0 }][file];
+
+        // Order the pieces like the table
+        for i in num_lead_pawns..size.saturating_sub(1) {
+            if let Some(j) = (i + 1..size).find(|j| d.pieces[i] == pieces[*j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // Mirror the leading piece onto files a to d
+        if squares[0] % 8 > 3 {
+            squares[..size].iter_mut().for_each(|square| *square ^= 7);
+        }
+
+        let mut idx;
+        if self.has_pawns {
+            idx = tables.lead_pawn_idx[num_lead_pawns][squares[0]];
+            squares[1..num_lead_pawns].sort_by_key(|square| tables.map_pawns[*square]);
+            for (i, square) in squares.iter().enumerate().take(num_lead_pawns).skip(1) {
+                idx += tables.binomial[i][tables.map_pawns[*square]];
+            }
+        } else {
+            // Without pawns, the leading piece is also mirrored onto ranks 1 to 4
+            if squares[0] / 8 > 3 {
+                squares[..size].iter_mut().for_each(|square| *square ^= 56);
+            }
+            // and the first piece of the leading group off the a1-h8 diagonal below it
+            for i in 0..d.group_len[0] {
+                let off = off_diagonal(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    squares[i..size].iter_mut().for_each(|square| *square = ((*square >> 3) | (*square << 3)) & 63);
+                }
+                break;
+            }
+
+            if self.has_unique_pieces {
+                let [s0, s1, s2] = [squares[0], squares[1], squares[2]];
+                let adjust1 = (s1 > s0) as u64;
+                let adjust2 = (s2 > s0) as u64 + (s2 > s1) as u64;
+                let rank = |square: usize| (square / 8) as u64;
+                idx = if off_diagonal(s0) != 0 {
+                    (tables.map_a1d1d4[s0] as u64 * 63 + (s1 as u64 - adjust1)) * 62 + s2 as u64 - adjust2
+                } else if off_diagonal(s1) != 0 {
+                    (6 * 63 + rank(s0) * 28 + tables.map_b1h1h7[s1]) * 62 + s2 as u64 - adjust2
+                } else if off_diagonal(s2) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62 + rank(s0) * 7 * 28 + (rank(s1) - adjust1) * 28 + tables.map_b1h1h7[s2]
+                } else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(s0) * 7 * 6 + (rank(s1) - adjust1) * 6 + (rank(s2) - adjust2)
+                };
+            } else {
+                idx = tables.map_kk[tables.map_a1d1d4[squares[0]]][squares[1]];
+            }
+        }
+        idx *= d.group_idx[0];
+
+        // The other groups are encoded by their squares in ascending order,
+        // skipping the squares taken by earlier groups
+        let mut group_start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let group_end = group_start + d.group_len[next];
+            squares[group_start..group_end].sort_unstable();
+            let mut n = 0;
+            for i in 0..d.group_len[next] {
+                let square = squares[group_start + i];
+                let adjust = squares[..group_start].iter().filter(|earlier| square > **earlier).count();
+                n += tables.binomial[i + 1][square - adjust - if remaining_pawns { 8 } else { 0 }];
+            }
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            group_start = group_end;
+            next += 1;
+        }
+
+        let value = decompress_pairs(d, &self.data, idx)? as i32;
+        Some(Some(match self.kind {
+            TableKind::Wdl => value - 2,
+            TableKind::Dtz => self.map_dtz(file, value, wdl)?,
+        }))
+    }
+
+    /// Converts a stored DTZ value to plies
+    fn map_dtz(&self, file: usize, value: i32, wdl: Wdl) -> Option<i32> {
+        let d = &self.items[0][file];
+        let mut value = value;
+        if d.flags & FLAG_MAPPED != 0 {
+            let map = d.map_idx[dtz_map_idx(wdl)];
+            value = if d.flags & FLAG_WIDE != 0 {
+                read_u16_le(&self.data, map + 2 * value as usize)? as i32
+            } else {
+                *self.data.get(map + value as usize)? as i32
+            };
+        }
+        let stored_in_moves = match wdl {
+            Wdl::Win => d.flags & FLAG_WIN_PLIES == 0,
+            Wdl::Loss => d.flags & FLAG_LOSS_PLIES == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+        if stored_in_moves {
+            value *= 2;
+        }
+        Some(value + 1)
+    }
+}
+
+fn set_sizes(d: &mut PairsData, data: &[u8], mut pos: usize) -> Option<usize> {
+    d.flags = *data.get(pos)?;
+    pos += 1;
+    if d.flags & FLAG_SINGLE_VALUE != 0 {
+        d.min_sym_len = *data.get(pos)?;
+        return Some(pos + 1);
+    }
+
+    let table_size = d.group_idx[d.group_len.iter().position(|len| *len == 0)?];
+    d.block_size = 1usize.checked_shl(*data.get(pos)? as u32)?;
+    d.span = 1u64.checked_shl(*data.get(pos + 1)? as u32)?;
+    d.sparse_index_size = table_size.div_ceil(d.span) as usize;
+    let padding = *data.get(pos + 2)? as usize;
+    d.num_blocks = read_u32_le(data, pos + 3)? as usize;
+    d.block_lengths_size = d.num_blocks + padding;
+    let max_sym_len = *data.get(pos + 7)? as usize;
+    d.min_sym_len = *data.get(pos + 8)?;
+    pos += 9;
+    d.lowest_sym = pos;
+
+    // Canonical Huffman codes, longer symbols have lower values
+    let min_sym_len = d.min_sym_len as usize;
+    if max_sym_len < min_sym_len || max_sym_len > 64 || min_sym_len == 0 {
+        return None;
+    }
+    let num_lengths = max_sym_len - min_sym_len + 1;
+    d.base64 = vec![0; num_lengths];
+    for i in (0..num_lengths - 1).rev() {
+        let lowest = read_u16_le(data, d.lowest_sym + 2 * i)? as u64;
+        let next_lowest = read_u16_le(data, d.lowest_sym + 2 * (i + 1))? as u64;
+        d.base64[i] = d.base64[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+    }
+    for (i, base) in d.base64.iter_mut().enumerate() {
+        *base <<= 64 - i - min_sym_len;
+    }
+    pos += num_lengths * 2;
+
+    let num_syms = read_u16_le(data, pos)? as usize;
+    pos += 2;
+    d.btree = pos;
+    if pos + num_syms * 3 > data.len() {
+        return None;
+    }
+    d.symlen = vec![0; num_syms];
+    let mut visited = vec![false; num_syms];
+    for sym in 0..num_syms {
+        if !visited[sym] {
+            set_symlen(d, data, sym, &mut visited)?;
+        }
+    }
+    Some(pos + num_syms * 3 + (num_syms & 1))
+}
+
+fn set_symlen(d: &mut PairsData, data: &[u8], sym: usize, visited: &mut [bool]) -> Option<()> {
+    visited[sym] = true;
+    let (left, right) = sym_pair(data, d.btree, sym)?;
+    if right == 0xfff {
+        d.symlen[sym] = 0;
+        return Some(());
+    }
+    if left >= d.symlen.len() || right >= d.symlen.len() {
+        return None;
+    }
+    if !visited[left] {
+        set_symlen(d, data, left, visited)?;
+    }
+    if !visited[right] {
+        set_symlen(d, data, right, visited)?;
+    }
+    d.symlen[sym] = d.symlen[left].wrapping_add(d.symlen[right]).wrapping_add(1);
+    Some(())
+}
+
+/// Left and right child of a symbol, a leaf stores its value as the left child
+fn sym_pair(data: &[u8], btree: usize, sym: usize) -> Option<(usize, usize)> {
+    let lr = data.get(btree + 3 * sym..btree + 3 * sym + 3)?;
+    let left = ((lr[1] as usize & 0xf) << 8) | lr[0] as usize;
+    let right = ((lr[2] as usize) << 4) | (lr[1] as usize >> 4);
+    Some((left, right))
+}
+
+/// Finds the value with index `idx` of a sub-table, `None` if the table data is truncated or corrupt
+fn decompress_pairs(d: &PairsData, data: &[u8], idx: u64) -> Option<u16> {
+    if d.flags & FLAG_SINGLE_VALUE != 0 {
+        return Some(d.min_sym_len as u16);
+    }
+    let block_length = |block: usize| read_u16_le(data, d.block_lengths + 2 * block).map(|length| length as i64);
+
+    // The sparse index points close to the block holding the value
+    let entry = d.sparse_index + 6 * (idx / d.span) as usize;
+    let mut block = read_u32_le(data, entry)? as usize;
+    let mut offset = read_u16_le(data, entry + 4)? as i64;
+    offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+    while offset < 0 {
+        block = block.checked_sub(1)?;
+        offset += block_length(block)? + 1;
+    }
+    while offset > block_length(block)? {
+        offset -= block_length(block)? + 1;
+        block += 1;
+    }
+
+    // Read symbols until reaching the one that expands to the value
+    let mut ptr = d.data + block * d.block_size;
+    if ptr >= data.len() {
+        return None;
+    }
+    let mut buf64 = read_be_padded(data, ptr, 8);
+    ptr += 8;
+    let mut buf64_size = 64;
+    let min_sym_len = d.min_sym_len as usize;
+    let mut sym;
+    loop {
+        let mut len = 0;
+        while buf64 < *d.base64.get(len)? {
+            len += 1;
+        }
+        sym = ((buf64 - d.base64[len]) >> (64 - len - min_sym_len)) as u16;
+        sym = sym.wrapping_add(read_u16_le(data, d.lowest_sym + 2 * len)?);
+        let sym_values = *d.symlen.get(sym as usize)? as i64 + 1;
+        if offset < sym_values {
+            break;
+        }
+        offset -= sym_values;
+        len += min_sym_len;
+        if len >= buf64_size {
+            return None;
+        }
+        buf64 <<= len;
+        buf64_size -= len;
+        if buf64_size <= 32 {
+            buf64_size += 32;
+            buf64 |= read_be_padded(data, ptr, 4) << (64 - buf64_size);
+            ptr += 4;
+        }
+    }
+
+    // Expand the symbol down to a single value
+    let mut sym = sym as usize;
+    while d.symlen[sym] != 0 {
+        let (left, right) = sym_pair(data, d.btree, sym)?;
+        let left_values = *d.symlen.get(left)? as i64 + 1;
+        if offset < left_values {
+            sym = left;
+        } else {
+            offset -= left_values;
+            sym = right;
+        }
+        if sym >= d.symlen.len() {
+            return None;
+        }
+    }
+    sym_pair(data, d.btree, sym).map(|(value, _)| value as u16)
+}
+
+/// `len` bytes as a big-endian number. The decoder reads a few bytes ahead of the symbols it uses,
+/// which can go past the end of the last block, so missing bytes are read as zeros.
+fn read_be_padded(data: &[u8], pos: usize, len: usize) -> u64 {
+    (pos..pos + len).fold(0, |bits, i| (bits << 8) | *data.get(i).unwrap_or(&0) as u64)
+}
+
+fn read_u16_le(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().unwrap()))
+}
+
+fn read_u32_le(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().unwrap()))
+}
+
+/// Rank minus file, negative below the a1-h8 diagonal
+fn off_diagonal(square: usize) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+/// Index of the DTZ value map used for a result
+fn dtz_map_idx(wdl: Wdl) -> usize {
+    match wdl {
+        Wdl::Loss => 1,
+        Wdl::BlessedLoss => 3,
+        Wdl::CursedWin => 2,
+        Wdl::Draw | Wdl::Win => 0,
+    }
+}
+
+/// DTZ of a position whose best move resets the fifty-move counter
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
+
+/// Material from a table name like `KRPvKR`
+fn parse_material(name: &str) -> Option<Material> {
+    let (first, second) = name.split_once('v')?;
+    let mut material = [[0; 7]; 2];
+    for (side, pieces) in [first, second].iter().enumerate() {
+        if !pieces.starts_with('K') || pieces.matches('K').count() != 1 {
+            return None;
+        }
+        for c in pieces.chars() {
+            let (_, ptype) = PIECE_CHARS.iter().find(|(piece_char, _)| *piece_char == c)?;
+            material[side][*ptype as usize] += 1;
+        }
+    }
+    let num_pieces: u8 = material.iter().flatten().sum();
+    (num_pieces as usize <= MAX_PIECES).then_some(material)
+}
+
+fn legal_moves(board: &Board) -> (Vec<Move>, bool) {
+    let tables = LookupTables::get();
+    let mut move_gen = MoveGenerator::default();
+    move_gen.generate_moves(board, &tables.precomp, &tables.bbutils, &tables.magic, false);
+    let in_check = move_gen.in_check();
+    (move_gen.moves, in_check)
+}
+
+fn is_mate(board: &Board) -> bool {
+    let (moves, in_check) = legal_moves(board);
+    moves.is_empty() && in_check
+}
+
+fn is_capture(board: &Board, mov: Move) -> bool {
+    mov.move_flag() == Move::EN_PASSANT_CAPTURE || (mov.move_flag() != Move::CASTLING && board.square[mov.target().index()].piece_type() != Piece::NONE)
+}
+
+fn is_pawn_move(board: &Board, mov: Move) -> bool {
+    board.square[mov.start().index()].piece_type() == Piece::PAWN
+}
+
+/// Whether a position occurred twice since the fifty-move counter was last reset
+fn has_repeated(board: &Board) -> bool {
+    let keys = &board.repeat_position_history;
+    keys.iter().enumerate().any(|(i, key)| keys[..i].contains(key))
+}
@@ -0,0 +1,107 @@
+use std::time::Instant;
+
+use crate::{
+    board::{board::Board, moves::Move},
+    engine::{Engine, position::Position, search::SearchLimits},
+    game::{clock::{ChessClock, TimeControl, timeout_result}, result::GameResult},
+    utils::pgn::{PgnGame, PgnError},
+};
+
+
+#[derive(Clone, Copy, Debug)]
+pub struct MatchSettings {
+    /// Clock both engines play on, `None` gives every move `move_time_ms`
+    pub time_control: Option<TimeControl>,
+    pub move_time_ms: u32,
+    /// Games that are still going after this many plies are adjudicated as a draw
+    pub max_plies: usize,
+    pub chess960: bool,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            time_control: None,
+            move_time_ms: 100,
+            max_plies: 300,
+            chess960: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub start_fen: String,
+    pub moves: Vec<Move>,
+    pub result: GameResult,
+}
+
+impl GameRecord {
+    pub fn to_pgn_game(&self) -> Result<PgnGame, PgnError> {
+        PgnGame::from_moves(Some(&self.start_fen), &self.moves, self.result)
+    }
+}
+
+/// Plays a game between two engines from the given start position, fails if the start position is invalid.
+/// An engine that plays an illegal move loses, like it would against a tournament arbiter.
+pub fn play_game(white: &mut dyn Engine, black: &mut dyn Engine, start_fen: &str, settings: &MatchSettings) -> Result<GameRecord, String> {
+    let mut position = Position::from_fen(start_fen).map_err(|err| format!("Invalid opening {}: {}", start_fen, err))?;
+    position.set_chess960(settings.chess960);
+    white.new_game();
+    black.new_game();
+    let mut clock = settings.time_control.map(ChessClock::new);
+    if let Some(clock) = clock.as_mut() {
+        clock.start(color_idx(&position));
+    }
+
+    let mut moves = Vec::new();
+    let result = loop {
+        let result = position.game_result();
+        if result != GameResult::Playing {
+            break result;
+        }
+        if moves.len() >= settings.max_plies {
+            break GameResult::DrawByArbiter;
+        }
+        let limits = match clock.as_ref() {
+            Some(clock) => SearchLimits { clock: Some(clock.clock_time(color_idx(&position))), ..Default::default() },
+            None => SearchLimits { think_time_ms: Some(settings.move_time_ms), ..Default::default() },
+        };
+        let time_start = Instant::now();
+        let best_move = match position.white_to_move() {
+            true => search(white, &position, limits),
+            false => search(black, &position, limits),
+        };
+        let elapsed = time_start.elapsed();
+
+        if let Some(clock) = clock.as_mut() {
+            clock.tick(elapsed);
+            if let Some(flagged) = clock.flagged() {
+                break timeout_result(&position.board, flagged == Board::WHITE_INDEX);
+            }
+        }
+        if !position.legal_moves().contains(&best_move) {
+            break if position.white_to_move() { GameResult::WhiteIllegalMove } else { GameResult::BlackIllegalMove };
+        }
+        position.make_move(best_move);
+        moves.push(best_move);
+        if let Some(clock) = clock.as_mut() {
+            clock.press();
+        }
+    };
+    Ok(GameRecord {
+        start_fen: start_fen.to_string(),
+        moves,
+        result,
+    })
+}
+
+fn color_idx(position: &Position) -> usize {
+    if position.white_to_move() { Board::WHITE_INDEX } else { Board::BLACK_INDEX }
+}
+
+
+fn search(engine: &mut dyn Engine, position: &Position, limits: SearchLimits) -> Move {
+    engine.set_position(position);
+    engine.go(limits).best_move
+}
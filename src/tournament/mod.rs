@@ -0,0 +1,185 @@
+pub mod game;
+pub mod standings;
+pub mod rating;
+
+use std::{collections::VecDeque, fs, io, sync::{Mutex, mpsc, atomic::{AtomicBool, Ordering}}, thread};
+
+use crate::{ai::ai_player::AIVersion, engine::{Engine, registry::create_engine}, utils::chess960};
+use self::{game::{GameRecord, MatchSettings, play_game}, standings::Standings};
+
+
+/// Openings of engine matches, one FEN per line
+pub const DEFAULT_OPENINGS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/logic/versus_positions.txt");
+
+/// Reads one opening FEN per line, leaving out empty lines
+pub fn load_openings(path: &str) -> io::Result<Vec<String>> {
+    let text = fs::read_to_string(path)?;
+    Ok(text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).map(|line| line.to_string()).collect())
+}
+
+/// An AI version with the engine options it plays with, written as `V13` or `V13:Hash=64:Threads=2`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Participant {
+    /// The spec it was parsed from, so different configurations of a version can be told apart
+    pub name: String,
+    pub version: AIVersion,
+    pub options: Vec<(String, String)>,
+}
+
+impl Participant {
+    pub fn new(version: AIVersion) -> Self {
+        Self {
+            name: version.name().to_string(),
+            version,
+            options: Vec::new(),
+        }
+    }
+
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.split(':');
+        let version_name = parts.next().unwrap_or_default();
+        let version = AIVersion::from_name(version_name).ok_or(format!("Unknown AI version: {}", version_name))?;
+        let mut options = Vec::new();
+        for option in parts {
+            let (name, value) = option.split_once('=').ok_or(format!("Invalid option {} in {}, expected name=value", option, spec))?;
+            options.push((name.to_string(), value.to_string()));
+        }
+        Ok(Self {
+            name: spec.to_string(),
+            version,
+            options,
+        })
+    }
+
+    /// Creates a fresh engine of this version with the options applied
+    pub fn create_engine(&self) -> Result<Box<dyn Engine>, String> {
+        let mut engine = create_engine(self.version);
+        for (name, value) in self.options.iter() {
+            engine.set_option(name, value).map_err(|err| format!("{}: {}", self.name, err))?;
+        }
+        Ok(engine)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// The first participant plays every other one
+    Gauntlet,
+    /// Every participant plays every other one
+    RoundRobin,
+}
+
+/// A single game of the tournament, participants are indices into `Tournament::participants`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameJob {
+    /// Number of the game, in the order they are scheduled
+    pub idx: usize,
+    pub round: usize,
+    pub white: usize,
+    pub black: usize,
+    pub opening_idx: usize,
+}
+
+/// Every pairing plays `rounds` openings, each of them twice with colours swapped.
+/// Games are ordered round by round so that the results of a stopped tournament are still balanced.
+pub fn schedule(num_participants: usize, format: Format, rounds: usize) -> Vec<GameJob> {
+    let pairings: Vec<(usize, usize)> = match format {
+        Format::Gauntlet => (1..num_participants).map(|opponent| (0, opponent)).collect(),
+        Format::RoundRobin => (0..num_participants)
+            .flat_map(|first| (first + 1..num_participants).map(move |second| (first, second)))
+            .collect(),
+    };
+    let mut games = Vec::new();
+    for round in 0..rounds {
+        for (first, second) in pairings.iter() {
+            for (white, black) in [(*first, *second), (*second, *first)] {
+                games.push(GameJob { idx: games.len(), round, white, black, opening_idx: round });
+            }
+        }
+    }
+    games
+}
+
+pub struct Tournament {
+    pub participants: Vec<Participant>,
+    pub format: Format,
+    /// Start positions as FEN, round `n` is played from opening `n`, wrapping around
+    pub openings: Vec<String>,
+    pub rounds: usize,
+    /// Number of games played at the same time
+    pub concurrency: usize,
+    pub settings: MatchSettings,
+}
+
+impl Tournament {
+    /// Random Chess960 start positions, one per round
+    pub fn chess960_openings(rounds: usize) -> Vec<String> {
+        (0..rounds).map(|_| chess960::random_start_fen()).collect()
+    }
+
+    pub fn games(&self) -> Vec<GameJob> {
+        schedule(self.participants.len(), self.format, self.rounds)
+    }
+
+    pub fn opening(&self, game: &GameJob) -> &str {
+        &self.openings[game.opening_idx % self.openings.len()]
+    }
+
+    /// Plays every game on `concurrency` worker threads. Every worker creates its own engines,
+    /// and `on_game_finished` is called on the calling thread in the order the games finish.
+    pub fn run(&self, on_game_finished: impl FnMut(&GameJob, &Result<GameRecord, String>, &Standings)) -> Standings {
+        self.run_until(&AtomicBool::new(false), on_game_finished)
+    }
+
+    /// Like `run`, but no new games are started once `stop` is set. Games that are already running are finished.
+    pub fn run_until(&self, stop: &AtomicBool, mut on_game_finished: impl FnMut(&GameJob, &Result<GameRecord, String>, &Standings)) -> Standings {
+        let names = self.participants.iter().map(|participant| participant.name.clone()).collect();
+        let mut standings = Standings::new(names);
+        let games = self.games();
+        if self.openings.is_empty() {
+            return standings;
+        }
+        let queue = Mutex::new(games.iter().copied().collect::<VecDeque<GameJob>>());
+        let (result_tx, result_rx) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.clamp(1, games.len().max(1)) {
+                let result_tx = result_tx.clone();
+                let queue = &queue;
+                scope.spawn(move || {
+                    let mut engines: Vec<Option<Box<dyn Engine>>> = self.participants.iter().map(|_| None).collect();
+                    while !stop.load(Ordering::Relaxed) {
+                        let Some(game) = queue.lock().unwrap().pop_front() else { break };
+                        let record = self.play(&game, &mut engines);
+                        if result_tx.send((game, record)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(result_tx);
+            for (game, record) in result_rx.iter() {
+                if let Ok(record) = &record {
+                    standings.add_result(game.white, game.black, record.result);
+                }
+                on_game_finished(&game, &record, &standings);
+            }
+        });
+        standings
+    }
+
+    fn play(&self, game: &GameJob, engines: &mut [Option<Box<dyn Engine>>]) -> Result<GameRecord, String> {
+        let mut white = match engines[game.white].take() {
+            Some(engine) => engine,
+            None => self.participants[game.white].create_engine()?,
+        };
+        let mut black = match engines[game.black].take() {
+            Some(engine) => engine,
+            None => self.participants[game.black].create_engine()?,
+        };
+        let record = play_game(white.as_mut(), black.as_mut(), self.opening(game), &self.settings);
+        engines[game.white] = Some(white);
+        engines[game.black] = Some(black);
+        record
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use crate::ai::ai_player::AIVersion;
+use super::standings::Standings;
+
+
+/// Ratings are relative to this version, which is fixed at 0 Elo
+pub const ANCHOR_VERSION: AIVersion = AIVersion::V0;
+/// Draws added to every pairing that played, like the prior of BayesElo.
+/// Without them a version that won every game against another one would be infinitely stronger.
+pub const PRIOR_DRAWS: f64 = 2.0;
+pub const RATING_LIST_CSV_PATH: &str = "rating_list.csv";
+pub const RATING_LIST_MARKDOWN_PATH: &str = "rating_list.md";
+
+const CONFIDENCE_Z: f64 = 1.959964;
+const MAX_ITERATIONS: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rating {
+    pub name: String,
+    pub elo: f64,
+    /// Half the width of the 95% confidence interval, relative to the anchor
+    pub error: f64,
+    pub games: u32,
+    /// Points per game between 0 and 1
+    pub score: f64,
+}
+
+/// Maximum likelihood ratings of every participant, in the order of the participants
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RatingList {
+    pub ratings: Vec<Rating>,
+    pub anchor: usize,
+}
+
+impl RatingList {
+    /// Fits logistic Elo ratings to all results at once like Ordo, a draw counts as half a win for both sides.
+    /// The confidence intervals come from the curvature of the likelihood at its maximum.
+    pub fn compute(standings: &Standings, anchor: usize) -> Self {
+        let num_participants = standings.names.len();
+        if anchor >= num_participants {
+            return Self::default();
+        }
+        // Points and games of every pairing, including the prior draws
+        let mut points = vec![vec![0.0; num_participants]; num_participants];
+        let mut games = vec![vec![0.0; num_participants]; num_participants];
+        for a in 0..num_participants {
+            for b in 0..num_participants {
+                let score = standings.pairings[a][b];
+                if score.games() > 0 {
+                    points[a][b] = score.points() + PRIOR_DRAWS / 2.0;
+                    games[a][b] = score.games() as f64 + PRIOR_DRAWS;
+                }
+            }
+        }
+
+        let mut elo = vec![0.0; num_participants];
+        let mut covariance = vec![vec![0.0; num_participants]; num_participants];
+        for _ in 0..MAX_ITERATIONS {
+            let (gradient, information) = likelihood_derivatives(&elo, &points, &games);
+            let (free_gradient, free_information): (Vec<f64>, Vec<Vec<f64>>) = (0..num_participants)
+                .filter(|idx| *idx != anchor)
+                .map(|idx| (gradient[idx], remove_idx(&information[idx], anchor)))
+                .unzip();
+            let free_covariance = invert(free_information);
+            covariance = insert_anchor(&free_covariance, anchor);
+
+            // Newton step towards the maximum, limited so a bad start can't overshoot far
+            let mut max_step: f64 = 0.0;
+            for (idx, row) in covariance.iter().enumerate() {
+                let step = row.iter().enumerate()
+                    .filter(|(other, _)| *other != anchor)
+                    .map(|(other, cov)| cov * free_gradient[other - (other > anchor) as usize])
+                    .sum::<f64>()
+                    .clamp(-400.0, 400.0);
+                elo[idx] += step;
+                max_step = max_step.max(step.abs());
+            }
+            if max_step < 1e-6 {
+                break;
+            }
+        }
+
+        let ratings = (0..num_participants).map(|idx| {
+            let total = standings.total(idx);
+            Rating {
+                name: standings.names[idx].clone(),
+                elo: elo[idx] - elo[anchor],
+                error: CONFIDENCE_Z * covariance[idx][idx].sqrt(),
+                games: total.games(),
+                score: total.percentage(),
+            }
+        }).collect();
+        Self { ratings, anchor }
+    }
+
+    /// Anchors the ratings at `ANCHOR_VERSION` when it took part, at the first participant otherwise
+    pub fn compute_anchored(standings: &Standings) -> Self {
+        let anchor = standings.names.iter().position(|name| name == ANCHOR_VERSION.name()).unwrap_or(0);
+        Self::compute(standings, anchor)
+    }
+
+    /// Indices into `ratings`, best first
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.ratings.len()).collect();
+        ranking.sort_by(|a, b| self.ratings[*b].elo.total_cmp(&self.ratings[*a].elo));
+        ranking
+    }
+
+    /// Difference to the participant listed before, which is the strength a feature added for consecutive versions
+    pub fn gain(&self, idx: usize) -> Option<f64> {
+        let previous = self.ratings.get(idx.checked_sub(1)?)?;
+        Some(self.ratings[idx].elo - previous.elo)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("Rank,Name,Feature,Elo,Error,Gain,Games,Score\n");
+        for (idx, rating, rank) in self.rows() {
+            let gain = self.gain(idx).map_or(String::new(), |gain| format!("{:.1}", gain));
+            let _ = writeln!(
+                csv, "{},{},{},{:.1},{:.1},{},{},{:.3}",
+                rank, rating.name, feature(&rating.name), rating.elo, rating.error, gain, rating.games, rating.score,
+            );
+        }
+        csv
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("| Rank | Name | Feature | Elo | Gain | Games | Score |\n|---:|---|---|---:|---:|---:|---:|\n");
+        for (idx, rating, rank) in self.rows() {
+            let gain = self.gain(idx).map_or(String::new(), |gain| format!("{:+.0}", gain));
+            let _ = writeln!(
+                markdown, "| {} | {} | {} | {:.0} ± {:.0} | {} | {} | {:.1}% |",
+                rank, rating.name, feature(&rating.name), rating.elo, rating.error, gain, rating.games, rating.score * 100.0,
+            );
+        }
+        let anchor = self.ratings.get(self.anchor).map_or("", |rating| rating.name.as_str());
+        let _ = write!(markdown, "\nElo relative to {}, errors are 95% confidence intervals.\n", anchor);
+        markdown
+    }
+
+    /// Rank of every participant, starting at 1
+    pub fn ranks(&self) -> Vec<usize> {
+        let mut ranks = vec![0; self.ratings.len()];
+        for (rank, idx) in self.ranking().into_iter().enumerate() {
+            ranks[idx] = rank + 1;
+        }
+        ranks
+    }
+
+    /// Ratings with their index and rank, best first
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &Rating, usize)> {
+        self.ranking().into_iter().enumerate().map(|(rank, idx)| (idx, &self.ratings[idx], rank + 1))
+    }
+}
+
+/// What the version of a participant added, like `Quiescence Search` for V8
+pub fn feature(name: &str) -> &'static str {
+    match AIVersion::from_name(name) {
+        Some(version) => version.label().split_once(" - ").map_or("", |(_, feature)| feature),
+        None => "",
+    }
+}
+
+/// Gradient and negated second derivatives of the log likelihood of the results, by rating in Elo
+fn likelihood_derivatives(elo: &[f64], points: &[Vec<f64>], games: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let num_participants = elo.len();
+    let scale = 10f64.ln() / 400.0;
+    let mut gradient = vec![0.0; num_participants];
+    let mut information = vec![vec![0.0; num_participants]; num_participants];
+    for a in 0..num_participants {
+        // Participants without games only appear here, so the matrix can be inverted
+        information[a][a] = 1e-9;
+        for b in 0..num_participants {
+            if a == b || games[a][b] == 0.0 {
+                continue;
+            }
+            let expected = 1.0 / (1.0 + 10f64.powf((elo[b] - elo[a]) / 400.0));
+            let weight = scale * scale * games[a][b] * expected * (1.0 - expected);
+            gradient[a] += scale * (points[a][b] - games[a][b] * expected);
+            information[a][a] += weight;
+            information[a][b] -= weight;
+        }
+    }
+    (gradient, information)
+}
+
+fn remove_idx(row: &[f64], idx: usize) -> Vec<f64> {
+    row.iter().enumerate().filter(|(other, _)| *other != idx).map(|(_, value)| *value).collect()
+}
+
+/// Grows a matrix without the anchor's row and column back to full size, the anchor has no variance
+fn insert_anchor(matrix: &[Vec<f64>], anchor: usize) -> Vec<Vec<f64>> {
+    let size = matrix.len() + 1;
+    let mut full = vec![vec![0.0; size]; size];
+    for (row, values) in matrix.iter().enumerate() {
+        for (column, value) in values.iter().enumerate() {
+            full[row + (row >= anchor) as usize][column + (column >= anchor) as usize] = *value;
+        }
+    }
+    full
+}
+
+/// Gauss-Jordan elimination with partial pivoting, the matrix is symmetric positive definite here
+fn invert(mut matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let size = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..size).map(|row| (0..size).map(|column| (row == column) as u8 as f64).collect()).collect();
+    for column in 0..size {
+        let pivot = (column..size).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs())).unwrap();
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let divisor = matrix[column][column];
+        for value in matrix[column].iter_mut().chain(inverse[column].iter_mut()) {
+            *value /= divisor;
+        }
+        for row in 0..size {
+            let factor = matrix[row][column];
+            if row == column || factor == 0.0 {
+                continue;
+            }
+            for idx in 0..size {
+                matrix[row][idx] -= factor * matrix[column][idx];
+                inverse[row][idx] -= factor * inverse[column][idx];
+            }
+        }
+    }
+    inverse
+}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use crate::game::result::GameResult;
+
+
+/// Wins, draws and losses from the perspective of one participant
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Score {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Score {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    /// Points per game between 0 and 1, 0.5 before any game was played
+    pub fn percentage(&self) -> f64 {
+        match self.games() {
+            0 => 0.5,
+            games => self.points() / games as f64,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+    }
+}
+
+/// Results of every participant against every other one
+#[derive(Clone, Debug)]
+pub struct Standings {
+    pub names: Vec<String>,
+    /// `pairings[a][b]` is the score of participant `a` against participant `b`
+    pub pairings: Vec<Vec<Score>>,
+}
+
+impl Standings {
+    pub fn new(names: Vec<String>) -> Self {
+        let num_participants = names.len();
+        Self {
+            names,
+            pairings: vec![vec![Score::default(); num_participants]; num_participants],
+        }
+    }
+
+    /// Counts a finished game, unfinished ones are left out
+    pub fn add_result(&mut self, white: usize, black: usize, result: GameResult) {
+        if result.is_draw() {
+            self.pairings[white][black].draws += 1;
+            self.pairings[black][white].draws += 1;
+        } else if result.is_white_win() {
+            self.pairings[white][black].wins += 1;
+            self.pairings[black][white].losses += 1;
+        } else if result.is_black_win() {
+            self.pairings[white][black].losses += 1;
+            self.pairings[black][white].wins += 1;
+        }
+    }
+
+    pub fn total(&self, idx: usize) -> Score {
+        let mut total = Score::default();
+        for score in self.pairings[idx].iter() {
+            total.add(score);
+        }
+        total
+    }
+
+    pub fn num_games(&self) -> u32 {
+        (0..self.names.len()).map(|idx| self.total(idx).games()).sum::<u32>() / 2
+    }
+
+    /// Participants ordered by points, best first
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|a, b| self.total(*b).points().total_cmp(&self.total(*a).points()));
+        ranking
+    }
+
+    /// Plain text table of the totals followed by the result of every pairing that was played
+    pub fn to_table(&self) -> String {
+        let name_width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max(4);
+        let mut table = format!("{:>4}  {:<name_width$}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6}\n", "Rank", "Name", "Games", "Wins", "Draws", "Losses", "Points", "Score");
+        for (rank, idx) in self.ranking().into_iter().enumerate() {
+            let total = self.total(idx);
+            let _ = writeln!(
+                table, "{:>4}  {:<name_width$}  {:>6}  {:>6}  {:>6}  {:>6}  {:>6.1}  {:>5.1}%",
+                rank + 1, self.names[idx], total.games(), total.wins, total.draws, total.losses, total.points(), total.percentage() * 100.0,
+            );
+        }
+        table.push('\n');
+        for first in 0..self.names.len() {
+            for second in first + 1..self.names.len() {
+                let score = self.pairings[first][second];
+                if score.games() > 0 {
+                    let _ = writeln!(
+                        table, "{} vs {}: +{} ={} -{} ({:.1}%)",
+                        self.names[first], self.names[second], score.wins, score.draws, score.losses, score.percentage() * 100.0,
+                    );
+                }
+            }
+        }
+        table
+    }
+}
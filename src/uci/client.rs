@@ -0,0 +1,194 @@
+use std::{
+    fmt, io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{Mutex, mpsc::{self, Receiver, RecvTimeoutError, TryRecvError}},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::board::moves::Move;
+use super::command::{UciCommand, GoParams};
+
+/// Time an engine gets to answer `uci` and `isready`
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time an engine gets to exit after `quit` before it is killed
+const QUIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+
+#[derive(Debug)]
+pub enum UciClientError {
+    Io(io::Error),
+    /// The engine didn't send the expected answer in time
+    Timeout(&'static str),
+    /// The engine closed its output, usually because it crashed
+    Disconnected,
+}
+
+impl fmt::Display for UciClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Timeout(answer) => write!(f, "Engine did not answer with '{}' in time", answer),
+            Self::Disconnected => write!(f, "Engine exited unexpectedly"),
+        }
+    }
+}
+
+impl std::error::Error for UciClientError {}
+
+impl From<io::Error> for UciClientError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+
+/// Runs an external UCI engine executable as a child process and talks to it over its standard input and output
+pub struct UciClient {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines the engine printed, read on a separate thread so polling never blocks
+    lines: Mutex<Receiver<String>>,
+    /// Name the engine reported with `id name`
+    pub name: Option<String>,
+    searching: bool,
+}
+
+impl UciClient {
+    /// Starts the engine and waits until it finished the `uci` and `isready` handshake
+    pub fn launch(path: &str) -> Result<Self, UciClientError> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self { child, stdin, lines: Mutex::new(receiver), name: None, searching: false };
+        client.send(&UciCommand::Uci)?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        loop {
+            let line = client.recv_until(deadline, "uciok")?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                client.name = Some(name.trim().to_string());
+            } else if line.trim() == "uciok" {
+                break;
+            }
+        }
+        client.is_ready()?;
+        Ok(client)
+    }
+
+    pub fn send(&mut self, command: &UciCommand) -> Result<(), UciClientError> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), UciClientError> {
+        self.send(&UciCommand::SetOption { name: name.to_string(), value: Some(value.to_string()) })
+    }
+
+    pub fn new_game(&mut self) -> Result<(), UciClientError> {
+        self.send(&UciCommand::UciNewGame)?;
+        self.is_ready()
+    }
+
+    /// Waits until the engine processed every command sent before
+    pub fn is_ready(&mut self) -> Result<(), UciClientError> {
+        self.send(&UciCommand::IsReady)?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+        while self.recv_until(deadline, "readyok")?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    /// Sends the position after `moves` were played from `start_fen`, or the start position, and starts a search
+    pub fn go(&mut self, start_fen: Option<&str>, moves: &[Move], params: GoParams) -> Result<(), UciClientError> {
+        let moves = moves.iter().map(|mov| mov.to_uci()).collect();
+        self.send(&UciCommand::Position { fen: start_fen.map(String::from), moves })?;
+        self.send(&UciCommand::Go(params))?;
+        self.searching = true;
+        Ok(())
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching
+    }
+
+    /// Best move in long algebraic notation once the search finished, without blocking
+    pub fn poll_best_move(&mut self) -> Result<Option<String>, UciClientError> {
+        loop {
+            let line = match self.lines.get_mut().unwrap().try_recv() {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => return Ok(None),
+                Err(TryRecvError::Disconnected) => return Err(UciClientError::Disconnected),
+            };
+            if let Some(best_move) = Self::parse_best_move(&line) {
+                self.searching = false;
+                return Ok(Some(best_move));
+            }
+        }
+    }
+
+    pub fn wait_best_move(&mut self, timeout: Duration) -> Result<String, UciClientError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let line = self.recv_until(deadline, "bestmove")?;
+            if let Some(best_move) = Self::parse_best_move(&line) {
+                self.searching = false;
+                return Ok(best_move);
+            }
+        }
+    }
+
+    /// Stops a running search and discards its best move
+    pub fn stop(&mut self) -> Result<(), UciClientError> {
+        if !self.searching {
+            return Ok(());
+        }
+        self.send(&UciCommand::Stop)?;
+        self.wait_best_move(HANDSHAKE_TIMEOUT).map(|_| ())
+    }
+
+    fn parse_best_move(line: &str) -> Option<String> {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("bestmove") => Some(tokens.next().unwrap_or("0000").to_string()),
+            _ => None,
+        }
+    }
+
+    fn recv_until(&mut self, deadline: Instant, answer: &'static str) -> Result<String, UciClientError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match self.lines.get_mut().unwrap().recv_timeout(timeout) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(UciClientError::Timeout(answer)),
+            Err(RecvTimeoutError::Disconnected) => Err(UciClientError::Disconnected),
+        }
+    }
+}
+
+impl Drop for UciClient {
+    fn drop(&mut self) {
+        let _ = self.send(&UciCommand::Quit);
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while Instant::now() < deadline {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use crate::{engine::search::SearchLimits, ai::time_manager::ClockTime};
+
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct GoParams {
+    pub wtime: Option<u32>,
+    pub btime: Option<u32>,
+    pub winc: Option<u32>,
+    pub binc: Option<u32>,
+    pub moves_to_go: Option<u32>,
+    pub move_time: Option<u32>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u32>,
+    pub infinite: bool,
+}
+
+impl GoParams {
+    const MOVE_OVERHEAD_MS: u32 = 50;
+
+    /// Returns the fixed time in milliseconds the searcher may use for this move
+    pub fn think_time_ms(&self) -> u32 {
+        if self.infinite {
+            return u32::MAX;
+        }
+        match self.move_time {
+            Some(move_time) => move_time.saturating_sub(Self::MOVE_OVERHEAD_MS).max(1),
+            None => u32::MAX,
+        }
+    }
+
+    /// Clock of the side to move, left to the time manager unless a fixed move time is given
+    pub fn clock(&self, white_to_move: bool) -> Option<ClockTime> {
+        if self.infinite || self.move_time.is_some() {
+            return None;
+        }
+        let (time, inc) = if white_to_move { (self.wtime, self.winc) } else { (self.btime, self.binc) };
+        time.map(|time_left_ms| ClockTime {
+            time_left_ms,
+            increment_ms: inc.unwrap_or(0),
+            moves_to_go: self.moves_to_go,
+        })
+    }
+
+    pub fn limits(&self, white_to_move: bool) -> SearchLimits {
+        let think_time_ms = self.think_time_ms();
+        SearchLimits {
+            think_time_ms: if think_time_ms == u32::MAX { None } else { Some(think_time_ms) },
+            depth: self.depth,
+            nodes: self.nodes,
+            clock: self.clock(white_to_move),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UciCommand {
+    Uci,
+    IsReady,
+    UciNewGame,
+    Position { fen: Option<String>, moves: Vec<String> },
+    SetOption { name: String, value: Option<String> },
+    Go(GoParams),
+    Stop,
+    Quit,
+    Unknown(String),
+}
+
+impl UciCommand {
+    pub fn parse(line: &str) -> Self {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first() {
+            Some(&"uci") => Self::Uci,
+            Some(&"isready") => Self::IsReady,
+            Some(&"ucinewgame") => Self::UciNewGame,
+            Some(&"position") => Self::parse_position(&tokens[1..]),
+            Some(&"setoption") => Self::parse_set_option(&tokens[1..]),
+            Some(&"go") => Self::Go(Self::parse_go(&tokens[1..])),
+            Some(&"stop") => Self::Stop,
+            Some(&"quit") => Self::Quit,
+            _ => Self::Unknown(line.to_string()),
+        }
+    }
+
+    fn parse_position(tokens: &[&str]) -> Self {
+        let moves_idx = tokens.iter().position(|t| *t == "moves").unwrap_or(tokens.len());
+        let fen = match tokens.first() {
+            Some(&"fen") => Some(tokens[1..moves_idx].join(" ")),
+            _ => None,
+        };
+        let moves = tokens.iter().skip(moves_idx + 1).map(|m| m.to_string()).collect();
+        Self::Position { fen, moves }
+    }
+
+    fn parse_set_option(tokens: &[&str]) -> Self {
+        let value_idx = tokens.iter().position(|t| *t == "value").unwrap_or(tokens.len());
+        let name = tokens[..value_idx].iter().skip_while(|t| **t == "name").copied().collect::<Vec<&str>>().join(" ");
+        let value = match value_idx < tokens.len() {
+            true => Some(tokens[value_idx + 1..].join(" ")),
+            false => None,
+        };
+        Self::SetOption { name, value }
+    }
+
+    fn parse_go(tokens: &[&str]) -> GoParams {
+        let mut params = GoParams::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            let value = tokens.get(i + 1).and_then(|v| v.parse::<u32>().ok());
+            match tokens[i] {
+                "wtime" => params.wtime = value,
+                "btime" => params.btime = value,
+                "winc" => params.winc = value,
+                "binc" => params.binc = value,
+                "movestogo" => params.moves_to_go = value,
+                "movetime" => params.move_time = value,
+                "depth" => params.depth = value.map(|d| d.min(u8::MAX as u32) as u8),
+                "nodes" => params.nodes = value,
+                "infinite" => { params.infinite = true; i += 1; continue; },
+                _ => { i += 1; continue; },
+            }
+            i += 2;
+        }
+        params
+    }
+}
+
+
+/// Writes the parameters the way `go` expects them, without the command itself
+impl fmt::Display for GoParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values = [
+            ("wtime", self.wtime), ("btime", self.btime), ("winc", self.winc), ("binc", self.binc),
+            ("movestogo", self.moves_to_go), ("movetime", self.move_time),
+            ("depth", self.depth.map(|depth| depth as u32)), ("nodes", self.nodes),
+        ];
+        let mut tokens: Vec<String> = values.iter()
+            .filter_map(|(name, value)| value.map(|value| format!("{} {}", name, value)))
+            .collect();
+        if self.infinite {
+            tokens.push(String::from("infinite"));
+        }
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+/// Writes the command as a line of the protocol, so a GUI can send what `parse` reads
+impl fmt::Display for UciCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Uci => write!(f, "uci"),
+            Self::IsReady => write!(f, "isready"),
+            Self::UciNewGame => write!(f, "ucinewgame"),
+            Self::Position { fen, moves } => {
+                match fen {
+                    Some(fen) => write!(f, "position fen {}", fen)?,
+                    None => write!(f, "position startpos")?,
+                }
+                if !moves.is_empty() {
+                    write!(f, " moves {}", moves.join(" "))?;
+                }
+                Ok(())
+            },
+            Self::SetOption { name, value } => match value {
+                Some(value) => write!(f, "setoption name {} value {}", name, value),
+                None => write!(f, "setoption name {}", name),
+            },
+            Self::Go(params) if *params == GoParams::default() => write!(f, "go"),
+            Self::Go(params) => write!(f, "go {}", params),
+            Self::Stop => write!(f, "stop"),
+            Self::Quit => write!(f, "quit"),
+            Self::Unknown(line) => write!(f, "{}", line),
+        }
+    }
+}
@@ -8,8 +8,8 @@ use bevy::{
     }};
 
 use crate::{
-    board::coord::*,
-    ui::board::BoardUITransform,
+    board::{coord::*, moves::Move},
+    ui::{board::BoardUITransform, ingame_menu::CalcStatistics},
 };
 
 const ARROW_COLOR_1: Color = Color::rgba(0.96, 0.68, 0.19, 0.6);
@@ -20,6 +20,8 @@ const ARROW_DEPTH: f32 = 0.3;
 const ARROW_LINE_WIDTH: f32 = 20.0;
 const ARROW_HEAD_SIZE: f32 = 8.0;
 const ARROW_FLAT_HEAD: bool = true;
+// Colours of the principal variation moves drawn on the board, in the order they are played
+const PV_ARROW_COLORS: [Color; 4] = [ARROW_COLOR_1, ARROW_COLOR_2, ARROW_COLOR_3, ARROW_COLOR_4];
 
 #[derive(Component)]
 pub struct ArrowDrawer {
@@ -30,6 +32,9 @@ pub struct ArrowDrawer {
 #[derive(Component)]
 pub struct Arrow {}
 
+#[derive(Component)]
+pub struct PvArrow {}
+
 pub fn spawn_arrow_drawer(
     mut commands: Commands,
 ) {
@@ -88,6 +93,42 @@ pub fn update_arrows(
     }
 }
 
+/// Draws the first moves of the AI's principal variation whenever it changes
+pub fn update_pv_arrows(
+    mut commands: Commands,
+    mut drawn_pv: Local<Vec<Move>>,
+    pv_arrows_query: Query<Entity, With<PvArrow>>,
+    calc_stats: Res<CalcStatistics>,
+    buttons: Res<Input<MouseButton>>,
+    board_transform: Res<BoardUITransform>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let is_cleared = buttons.just_pressed(MouseButton::Left);
+    if !is_cleared && *drawn_pv == calc_stats.ai_pv {
+        return;
+    }
+    for arrow_entity in pv_arrows_query.iter() {
+        commands.entity(arrow_entity).despawn();
+    }
+    *drawn_pv = calc_stats.ai_pv.clone();
+    if is_cleared {
+        return;
+    }
+
+    for (i, (mov, col)) in calc_stats.ai_pv.iter().zip(PV_ARROW_COLORS).enumerate() {
+        let start_pos = board_transform.pos_from_coord(mov.start());
+        let end_pos = board_transform.pos_from_coord(mov.target());
+
+        commands.spawn((MaterialMesh2dBundle {
+            mesh: meshes.add(create_arrow_mesh(Vec2::ZERO, end_pos - start_pos, board_transform.sqr_size).into()).into(),
+            material: materials.add(ColorMaterial::from(col)),
+            transform: Transform::from_xyz(start_pos.x, start_pos.y, ARROW_DEPTH + 0.01 + i as f32 * 0.01),
+            ..default()
+        }, PvArrow {}));
+    }
+}
+
 fn create_arrow_mesh(mut start: Vec2, mut end: Vec2, sqr_size: f32) -> Mesh {
     let forward = (end - start).normalize();
     let perp = forward.perp();
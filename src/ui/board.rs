@@ -201,6 +201,16 @@ pub fn reset_board_pieces(
     for piece in pieces_query.iter() {
         commands.entity(piece).despawn();
     }
+    spawn_pieces(&mut commands, &board, &board_transform, &piece_theme);
+}
+
+/// Spawns a sprite for every piece of `board`
+pub fn spawn_pieces(
+    commands: &mut Commands,
+    board: &Board,
+    board_transform: &BoardUITransform,
+    piece_theme: &PieceTheme,
+) {
     for rank in 0..8 {
         for file in 0..8 {
             let sqr = Coord::new(file, rank);
@@ -229,14 +239,34 @@ pub fn update_pieces(
     mut commands: Commands,
     mut make_move_evr: EventReader<BoardMakeMove>,
     board_transform: Res<BoardUITransform>,
-    // board: Res<Board>,
+    board: Res<Board>,
     piece_theme: Res<PieceTheme>,
     mut pieces_query: Query<(&mut BoardUIPiece, Entity, &mut Transform)>,
+    move_list_view: Res<MoveListView>,
 ) {
+    // The move list draws the whole board when it shows another position
+    if move_list_view.draws_board() {
+        make_move_evr.clear();
+        return;
+    }
     for make_move_event in make_move_evr.iter() {
         let mov = make_move_event.mov;
         let start = mov.start();
         let target = mov.target();
+
+        // Castling never captures, in Chess960 the king and rook can even swap squares
+        if mov.move_flag() == Move::CASTLING {
+            let (king_to, castling_rook_from, castling_rook_to) = board.castling_squares(mov);
+            for (mut piece, _, mut transform) in pieces_query.iter_mut() {
+                let piece_coord = Coord::new(piece.file, piece.rank);
+                let piece_to = if piece_coord == start { king_to } else if piece_coord == castling_rook_from { castling_rook_to } else { continue };
+                piece.file = piece_to.file();
+                piece.rank = piece_to.rank();
+                transform.translation = Vec3::new(board_transform.x_pos(piece_to.file()), board_transform.y_pos(piece_to.rank()), 0.0);
+            }
+            continue;
+        }
+
         let captured_sqr = if mov.move_flag() == Move::EN_PASSANT_CAPTURE {
             Coord::new(target.file(), start.rank()) } else { target };
         let (mut found_start, mut found_end) = (false, false);
@@ -275,23 +305,6 @@ pub fn update_pieces(
             }
             if found_start && found_end { break; }
         }
-
-        if mov.move_flag() == Move::CASTLING {
-            let kingside = target == Coord::G1 || target == Coord::G8;
-            let castling_rook_from = if kingside { target + 1 } else { target - 2 };
-            let castling_rook_to = if kingside { target - 1 } else { target + 1 };
-            for (mut piece, _, mut transform) in pieces_query.iter_mut() {
-                let piece_coord = Coord::new(piece.file, piece.rank);
-                if piece_coord == castling_rook_from {
-                    let x_pos = board_transform.x_pos(castling_rook_to.file());
-                    let y_pos = board_transform.y_pos(castling_rook_to.rank());
-                    piece.file = castling_rook_to.file();
-                    piece.rank = castling_rook_to.rank();
-                    transform.translation = Vec3::new(x_pos, y_pos, 0.0);
-                    break;
-                }
-            }
-        }
     }
 }
 
@@ -299,7 +312,12 @@ pub fn update_board_ui(
     mut board_ui: ResMut<BoardUI>,
     mut set_square_evw: EventWriter<BoardSetSquareColor>,
     mut make_move_evr: EventReader<BoardMakeMove>,
+    move_list_view: Res<MoveListView>,
 ) {
+    if move_list_view.draws_board() {
+        make_move_evr.clear();
+        return;
+    }
     for make_move_event in make_move_evr.iter() {
         if let Some(last_move) = board_ui.last_made_move {
             let last_move_start_coord = last_move.start();
@@ -378,20 +396,8 @@ pub fn set_square_color(
     for event in set_square_evr.iter() {
         for (mut sprite, mut square) in square_query.iter_mut() {
             if square.file == event.file && square.rank == event.rank {
-                let color = match (event.color, (square.rank + square.file) % 2 == 0) {
-                    (SquareColorTypes::Normal, true) => board_theme.light_squares.normal,
-                    (SquareColorTypes::Normal, false) => board_theme.dark_squares.normal,
-                    (SquareColorTypes::Legal, true) => board_theme.light_squares.legal,
-                    (SquareColorTypes::Legal, false) => board_theme.dark_squares.legal,
-                    (SquareColorTypes::Selected, true) => board_theme.light_squares.selected,
-                    (SquareColorTypes::Selected, false) => board_theme.dark_squares.selected,
-                    (SquareColorTypes::MoveFromHighlight, true) => board_theme.light_squares.move_from_highlight,
-                    (SquareColorTypes::MoveFromHighlight, false) => board_theme.dark_squares.move_from_highlight,
-                    (SquareColorTypes::MoveToHighlight, true) => board_theme.light_squares.move_to_highlight,
-                    (SquareColorTypes::MoveToHighlight, false) => board_theme.dark_squares.move_to_highlight,
-                };
                 square.color = event.color;
-                sprite.color = color;
+                sprite.color = board_theme.square_color(event.color, (square.rank + square.file) % 2 == 0);
             }
         }
     }
@@ -1,7 +1,9 @@
+use std::thread::{self, JoinHandle};
+
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 
-use crate::{game::{manager::{GameManager, GameResult, PlayerType}, player::Player}, ai::ai_player::{AIPlayer, AIVersion}, board::{piece::Piece, board::Board, zobrist::Zobrist, coord::Coord}, utils::fen::START_FEN, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards}};
+use crate::{game::{manager::{GameManager, PlayerType}, player::{Player, VersusOpponent}, external_player::ExternalEnginePlayer, versus_manager::player_label, result::GameResult, clock::{ChessClock, format_clock_time}, sprt::SprtConfig}, state::GameSettings, ai::ai_player::AIPlayer, board::{piece::Piece, board::Board, zobrist::Zobrist, coord::Coord, moves::Move}, utils::{fen::START_FEN, pgn::{PgnGame, parse_pgn}}, move_gen::{move_generator::MoveGenerator, precomp_move_data::PrecomputedMoveData, bitboard::utils::BitBoardUtils, magics::MagicBitBoards, perft::perft_divide}, engine::tables::LookupTables};
 
 use super::text_input::TextInput;
 
@@ -19,12 +21,15 @@ impl GameOverSplash {
 pub enum MenuStatistic {
     MoveGenTime,
     AIDepth,
+    AIPrincipalVariation,
     AIPositionsEvaluated,
     AIMatesFound,
     AIEvaluation,
     AINumCutoffs,
     AIThinkTime,
     AITranspositions,
+    AIBookMove,
+    AITablebaseHits,
 }
 
 impl MenuStatistic {
@@ -39,13 +44,24 @@ impl MenuStatistic {
 
 pub enum MatchManagerStatistic {
     GameNumber,
-    Player1Stats(AIVersion, bool),
-    Player2Stats(AIVersion, bool),
+    /// Both player names above the match statistics
+    Players,
+    Player1Stats,
+    Player2Stats,
+    /// Label of the button switching the opponent
+    Opponent,
     MaxThinkTime,
     MaxGameLength,
     TotalGames,
-    BlackPlayer(AIVersion),
-    WhitePlayer(AIVersion),
+    BlackPlayer,
+    WhitePlayer,
+    SprtElo0,
+    SprtElo1,
+    SprtAlpha,
+    SprtBeta,
+    SprtLlr,
+    SprtElo,
+    SprtPentanomial,
 }
 
 impl MatchManagerStatistic {
@@ -67,16 +83,36 @@ pub struct MatchManagerText {
 #[derive(Component)]
 pub struct MatchManagerStartButton {}
 
+#[derive(Component)]
+pub struct VersusOpponentToggleButton {}
+
+#[derive(Component)]
+pub struct ClockText {
+    pub color_idx: usize,
+}
+
+impl ClockText {
+    pub const ACTIVE_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+    pub const INACTIVE_COLOR: Color = Color::rgb(0.53, 0.49, 0.48);
+    pub const LOW_TIME_COLOR: Color = Color::rgb(0.82, 0.36, 0.37);
+    pub const LOW_TIME_SECS: u64 = 10;
+}
+
 #[derive(Resource)]
 pub struct CalcStatistics {
     pub move_gen_time: f32,
     pub ai_depth: i32,
+    /// Moves the AI expects to be played from the position it searched
+    pub ai_pv: Vec<Move>,
+    pub ai_pv_san: String,
     pub ai_positions_evaled: u32,
     pub ai_eval: i32,
     pub ai_think_time: u32,
     pub ai_mates_found: i32,
     pub ai_num_cutoffs: i32,
     pub ai_transpositions: i32,
+    pub ai_is_book: bool,
+    pub ai_tb_hits: u32,
 }
 
 impl Default for CalcStatistics {
@@ -84,12 +120,16 @@ impl Default for CalcStatistics {
         CalcStatistics {
             move_gen_time: 0.0,
             ai_depth: 0,
+            ai_pv: Vec::new(),
+            ai_pv_san: String::new(),
             ai_positions_evaled: 0,
             ai_eval: 0,
             ai_think_time: 0,
             ai_mates_found: 0,
             ai_num_cutoffs: 0,
             ai_transpositions: 0,
+            ai_is_book: false,
+            ai_tb_hits: 0,
         }
     }
 }
@@ -135,6 +175,14 @@ pub fn spawn_calc_stats(
                         color: MenuStatistic::RED,
                     }
                 ), StatMenuText { stat: MenuStatistic::AIDepth }));
+                parent.spawn((TextBundle::from_section(
+                    "Principal Variation: N/A",
+                    TextStyle {
+                        font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                        font_size: 20.0,
+                        color: MenuStatistic::DEFAULT_COLOR,
+                    }
+                ), StatMenuText { stat: MenuStatistic::AIPrincipalVariation }));
                 parent.spawn((TextBundle::from_section(
                     "Evaluation: N/A",
                     TextStyle {
@@ -167,6 +215,22 @@ pub fn spawn_calc_stats(
                         color: MenuStatistic::PURPLE,
                     }
                 ), StatMenuText { stat: MenuStatistic::AINumCutoffs }));
+                parent.spawn((TextBundle::from_section(
+                    "Book Move: N/A",
+                    TextStyle {
+                        font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                        font_size: 20.0,
+                        color: MenuStatistic::DEFAULT_COLOR,
+                    }
+                ), StatMenuText { stat: MenuStatistic::AIBookMove }));
+                parent.spawn((TextBundle::from_section(
+                    "Tablebase Hits: N/A",
+                    TextStyle {
+                        font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                        font_size: 20.0,
+                        color: MenuStatistic::DEFAULT_COLOR,
+                    }
+                ), StatMenuText { stat: MenuStatistic::AITablebaseHits }));
             }
         });
 }
@@ -179,16 +243,73 @@ pub fn update_menu_stats(
         text.sections[0].value = match stat.stat {
             MenuStatistic::MoveGenTime => { format!("Move gen time: {} micros", calc_stats.move_gen_time) },
             MenuStatistic::AIDepth => { format!("Search Depth: {}", calc_stats.ai_depth) },
+            MenuStatistic::AIPrincipalVariation => { format!("Principal Variation: {}", calc_stats.ai_pv_san) },
             MenuStatistic::AIEvaluation => { format!("Evaluation: {}", calc_stats.ai_eval) },
             MenuStatistic::AIPositionsEvaluated => { format!("Positions Evaluated: {}", calc_stats.ai_positions_evaled) },
             MenuStatistic::AIThinkTime => { format!("Think Time: {} ms", calc_stats.ai_think_time) },
             MenuStatistic::AIMatesFound => { format!("Checkmates Found: {}", calc_stats.ai_mates_found) },
             MenuStatistic::AINumCutoffs => { format!("Pruned Branches: {}", calc_stats.ai_num_cutoffs)},
             MenuStatistic::AITranspositions => { format!("Transpositions: {}", calc_stats.ai_transpositions)},
+            MenuStatistic::AIBookMove => { format!("Book Move: {}", if calc_stats.ai_is_book { "Yes" } else { "No" }) },
+            MenuStatistic::AITablebaseHits => { format!("Tablebase Hits: {}", calc_stats.ai_tb_hits) },
         }
     }
 }
 
+pub fn spawn_clocks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+) {
+    let Some(time_control) = settings.time_control else {
+        return;
+    };
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            right: Val::Percent(2.0),
+            top: Val::Percent(2.0),
+            align_items: AlignItems::FlexEnd,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        ..default()
+    })
+        .with_children(|parent| {
+            for (color_idx, label) in [(Board::BLACK_INDEX, "Black"), (Board::WHITE_INDEX, "White")] {
+                parent.spawn((TextBundle::from_section(
+                    format!("{}: {}", label, format_clock_time(time_control.base)),
+                    TextStyle {
+                        font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                        font_size: 32.0,
+                        color: ClockText::INACTIVE_COLOR,
+                    }
+                ), ClockText { color_idx }));
+            }
+        });
+}
+
+pub fn update_clocks(
+    clock: Option<Res<ChessClock>>,
+    mut clock_text_query: Query<(&mut Text, &ClockText)>,
+) {
+    let Some(clock) = clock else {
+        return;
+    };
+    for (mut text, clock_text) in clock_text_query.iter_mut() {
+        let remaining = clock.remaining(clock_text.color_idx);
+        let label = if clock_text.color_idx == Board::WHITE_INDEX { "White" } else { "Black" };
+        text.sections[0].value = format!("{}: {}", label, format_clock_time(remaining));
+        text.sections[0].style.color = if remaining.as_secs() < ClockText::LOW_TIME_SECS {
+            ClockText::LOW_TIME_COLOR
+        } else if clock.active() == Some(clock_text.color_idx) {
+            ClockText::ACTIVE_COLOR
+        } else {
+            ClockText::INACTIVE_COLOR
+        };
+    }
+}
+
 pub fn spawn_game_over_splash(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -207,6 +328,10 @@ pub fn spawn_game_over_splash(
         GameResult::DrawByArbiter => ("", ""),
         GameResult::WhiteTimeout => ("Timeout", "Black Wins"),
         GameResult::BlackTimeout => ("Timeout", "White Wins"),
+        GameResult::TimeoutVsInsufficientMaterial => ("Timeout vs Insufficient Material", "Draw"),
+        GameResult::WhiteIllegalMove => ("Illegal Move", "Black Wins"),
+        GameResult::BlackIllegalMove => ("Illegal Move", "White Wins"),
+        GameResult::Aborted => ("Engine Error", "Game Aborted"),
     };
     commands.spawn((NodeBundle {
         style: Style {
@@ -257,7 +382,7 @@ pub fn despawn_game_over_splash(
 pub fn spawn_ai_vs_ai_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    ai_player_query: Query<(&AIPlayer, &Player)>,    
+    player_query: Query<(&Player, Option<&AIPlayer>, Option<&ExternalEnginePlayer>, Option<&VersusOpponent>)>,
 ) {
     let mut parent_node = commands.spawn((NodeBundle {
         style: Style {
@@ -291,21 +416,21 @@ pub fn spawn_ai_vs_ai_menu(
         }));
     });
     
-    let (mut p1_version, mut p1_team) = (AIVersion::V0, Piece::WHITE);
-    let mut p2_version = AIVersion::V0;
-    for (i, (ai_player, player)) in ai_player_query.iter().enumerate() {
-        if i == 0 {
-            p1_version = ai_player.version;
+    let (mut p1_label, mut p1_team) = (String::new(), Piece::WHITE);
+    let mut p2_label = String::new();
+    for (player, ai_player, external_player, opponent) in player_query.iter() {
+        if opponent.is_none() {
+            p1_label = player_label(ai_player, external_player);
             p1_team = player.team;
         } else {
-            p2_version = ai_player.version;
+            p2_label = player_label(ai_player, external_player);
         }
     }
 
     parent_node.with_children(|parent| {
-        parent.spawn(TextBundle::from_sections([
+        parent.spawn((TextBundle::from_sections([
             TextSection::new(
-                format!("{}", p1_version.label()),
+                p1_label.clone(),
                 TextStyle {
                     font: asset_server.load("ui/font/LiberationSans-Bold.ttf"),
                     font_size: 20.0,
@@ -321,7 +446,7 @@ pub fn spawn_ai_vs_ai_menu(
                 },
             ),
             TextSection::new(
-                format!("{}", p2_version.label()),
+                p2_label.clone(),
                 TextStyle {
                     font: asset_server.load("ui/font/LiberationSans-Bold.ttf"),
                     font_size: 20.0,
@@ -334,7 +459,7 @@ pub fn spawn_ai_vs_ai_menu(
                 ..default()
             },
             ..default()
-        }));
+        }), MatchManagerText { stat: MatchManagerStatistic::Players }));
 
         parent.spawn((TextBundle::from_section(
             "Game Number: 0 / 1000",
@@ -351,7 +476,7 @@ pub fn spawn_ai_vs_ai_menu(
             ..default()
         }), MatchManagerText { stat: MatchManagerStatistic::GameNumber }));
         parent.spawn((TextBundle::from_section(
-            format!("{} | Wins: 0  Losses: 0  Draws: 0", p1_version.label()),
+            format!("{} | Wins: 0  Losses: 0  Draws: 0", p1_label),
             TextStyle {
                 font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
                 font_size: 20.0,
@@ -363,9 +488,9 @@ pub fn spawn_ai_vs_ai_menu(
                 ..default()
             },
             ..default()
-        }), MatchManagerText { stat: MatchManagerStatistic::Player1Stats(p1_version, p1_team == Piece::WHITE) }));
+        }), MatchManagerText { stat: MatchManagerStatistic::Player1Stats }));
         parent.spawn((TextBundle::from_section(
-            format!("{} | Wins: 0  Losses: 0  Draws: 0", p2_version.label()),
+            format!("{} | Wins: 0  Losses: 0  Draws: 0", p2_label),
             TextStyle {
                 font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
                 font_size: 20.0,
@@ -377,7 +502,7 @@ pub fn spawn_ai_vs_ai_menu(
                 ..default()
             },
             ..default()
-        }), MatchManagerText { stat: MatchManagerStatistic::Player2Stats(p2_version, p1_team == Piece::BLACK) }));
+        }), MatchManagerText { stat: MatchManagerStatistic::Player2Stats }));
 
         parent.spawn(TextBundle::from_section(
             "Settings",
@@ -461,6 +586,28 @@ pub fn spawn_ai_vs_ai_menu(
             ));
         });
 
+        parent.spawn((ButtonBundle {
+            style: Style {
+                width: Val::Percent(25.0),
+                height: Val::Percent(5.0),
+                top: Val::Percent(4.5),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::rgb(0.95, 0.95, 0.95)),
+            ..default()
+        }, VersusOpponentToggleButton {})).with_children(|parent| {
+            parent.spawn((TextBundle::from_section(
+                format!("Opponent: {}", p2_label),
+                TextStyle {
+                    font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                    font_size: 20.0,
+                    color: Color::rgb(0.4, 0.4, 0.4),
+                }
+            ), MatchManagerText { stat: MatchManagerStatistic::Opponent }));
+        });
+
         parent.spawn((ButtonBundle {
             style: Style {
                 width: Val::Percent(30.0),
@@ -484,50 +631,138 @@ pub fn spawn_ai_vs_ai_menu(
         });
 
         parent.spawn((TextBundle::from_section(
-            format!("Black: {}", if p1_team == Piece::BLACK { p1_version.label() } else { p2_version.label() }),
+            format!("Black: {}", if p1_team == Piece::BLACK { &p1_label } else { &p2_label }),
             TextStyle {
                 font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
                 font_size: 25.0,
                 color: MatchManagerStatistic::DEFAULT_COLOR,
             }
         ).with_style(Style {
-            bottom: Val::Percent(53.0),
+            bottom: Val::Percent(58.0),
             left: Val::Percent(43.0),
             width: Val::Percent(40.0),
             ..default()
-        }), MatchManagerText { stat: MatchManagerStatistic::BlackPlayer(if p1_team == Piece::BLACK { p1_version } else { p2_version}) }));
+        }), MatchManagerText { stat: MatchManagerStatistic::BlackPlayer }));
         parent.spawn((TextBundle::from_section(
-            format!("White: {}", if p1_team == Piece::WHITE { p1_version.label() } else { p2_version.label() }),
+            format!("White: {}", if p1_team == Piece::WHITE { &p1_label } else { &p2_label }),
             TextStyle {
                 font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
                 font_size: 25.0,
                 color: MatchManagerStatistic::DEFAULT_COLOR,
             }
         ).with_style(Style {
-            top: Val::Percent(37.5),
+            top: Val::Percent(32.5),
             left: Val::Percent(43.0),
             width: Val::Percent(40.0),
             ..default()
-        }), MatchManagerText { stat: MatchManagerStatistic::WhitePlayer(if p1_team == Piece::WHITE { p1_version } else { p2_version}) }));
+        }), MatchManagerText { stat: MatchManagerStatistic::WhitePlayer }));
+
+        parent.spawn(TextBundle::from_section(
+            "SPRT",
+            TextStyle {
+                font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                font_size: 20.0,
+                color: MatchManagerStatistic::DEFAULT_COLOR,
+            }
+        ).with_style(Style {
+            padding: UiRect {
+                bottom: Val::Percent(2.0),
+                ..default()
+            },
+            ..default()
+        }));
+        let sprt_config = SprtConfig::default();
+        let sprt_inputs = [
+            (MatchManagerStatistic::SprtElo0, "H0 Elo: ", sprt_config.elo0, 0.0),
+            (MatchManagerStatistic::SprtElo1, "H1 Elo: ", sprt_config.elo1, 1.5),
+            (MatchManagerStatistic::SprtAlpha, "Alpha: ", sprt_config.alpha, 3.0),
+            (MatchManagerStatistic::SprtBeta, "Beta: ", sprt_config.beta, 4.5),
+        ];
+        for (stat, prefix, default_value, top) in sprt_inputs {
+            parent.spawn((ButtonBundle {
+                style: Style {
+                    width: Val::Percent(25.0),
+                    height: Val::Percent(5.0),
+                    top: Val::Percent(top),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.95, 0.95, 0.95)),
+                ..default()
+            }, TextInput::new(prefix, &default_value.to_string(), "", false),
+            MatchManagerText { stat })).with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    prefix,
+                    TextStyle {
+                        font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                        font_size: 20.0,
+                        color: Color::rgb(0.4, 0.4, 0.4),
+                    }
+                ));
+            });
+        }
+
+        let sprt_stats = [
+            (MatchManagerStatistic::SprtLlr, format!("SPRT [{}, {}] | LLR: 0.00 ({:.2}, {:.2}) | Running", sprt_config.elo0, sprt_config.elo1, sprt_config.lower_bound(), sprt_config.upper_bound())),
+            (MatchManagerStatistic::SprtElo, String::from("Elo: N/A | LOS: N/A")),
+            (MatchManagerStatistic::SprtPentanomial, String::from("Pairs: 0 | Pentanomial: 0 0 0 0 0")),
+        ];
+        for (i, (stat, value)) in sprt_stats.into_iter().enumerate() {
+            parent.spawn((TextBundle::from_section(
+                value,
+                TextStyle {
+                    font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                    font_size: 20.0,
+                    color: MatchManagerStatistic::DEFAULT_COLOR,
+                }
+            ).with_style(Style {
+                top: Val::Percent(if i == 0 { 8.0 } else { 6.0 }),
+                padding: UiRect {
+                    bottom: Val::Percent(1.0),
+                    ..default()
+                },
+                ..default()
+            }), MatchManagerText { stat }));
+        }
     });
 }
 
 
+/// Divide of a finished perft and how long it took in milliseconds
+type PerftThread = JoinHandle<(Vec<(Move, u64)>, u128)>;
+
 #[derive(Resource)]
 pub struct DebugInfo {
     pub fen_str: String,
+    pub fen_error: Option<String>,
     pub eval_white: String,
     pub eval_black: String,
     pub eval_total: String,
+    pub perft_depth: u8,
+    pub perft_nodes: String,
+    /// Node count below each root move of the last perft
+    pub perft_divide: Vec<(String, u64)>,
+    /// Perft runs on a copy of the board in a worker thread, deep ones take minutes
+    perft_thread: Option<PerftThread>,
+    pub pgn_path: String,
+    pub pgn_status: Option<String>,
 }
 
 impl Default for DebugInfo {
     fn default() -> Self {
         DebugInfo {
             fen_str: String::from(START_FEN),
+            fen_error: None,
             eval_white: String::from("N/A"),
             eval_black: String::from("N/A"),
             eval_total: String::from("N/A"),
+            perft_depth: 4,
+            perft_nodes: String::from("N/A"),
+            perft_divide: Vec::new(),
+            perft_thread: None,
+            pgn_path: String::from("game.pgn"),
+            pgn_status: None,
         }
     }
 }
@@ -542,18 +777,74 @@ pub fn update_egui(
     mut board: ResMut<Board>,
     mut move_gen: ResMut<MoveGenerator>,
     precomp: Res<PrecomputedMoveData>,
-    mut zobrist: ResMut<Zobrist>,
+    zobrist: Res<Zobrist>,
     bbutils: Res<BitBoardUtils>,
     magic: Res<MagicBitBoards>,
     mut debug_pos_loaded_evw: EventWriter<DebugPositionLoaded>,
+    mut manager: ResMut<GameManager>,
 ) {
     egui::Window::new("Debug").show(contexts.ctx_mut(), |ui| {
         ui.text_edit_singleline(&mut debug.fen_str);
         if ui.add(egui::Button::new("Load Fen")).clicked() {
-            board.load_position(Some(debug.fen_str.clone()), &mut zobrist);
-            move_gen.generate_moves(board.as_ref(), precomp.as_ref(), bbutils.as_ref(), magic.as_ref(), false);
-            debug_pos_loaded_evw.send(DebugPositionLoaded {});
+            match board.load_position(Some(debug.fen_str.clone()), &zobrist) {
+                Ok(()) => {
+                    debug.fen_error = None;
+                    manager.custom_position = Some(debug.fen_str.clone());
+                    manager.game_moves.clear();
+                    move_gen.generate_moves(board.as_ref(), precomp.as_ref(), bbutils.as_ref(), magic.as_ref(), false);
+                    debug_pos_loaded_evw.send(DebugPositionLoaded {});
+                },
+                Err(err) => debug.fen_error = Some(err.to_string()),
+            }
         };
+        if ui.add(egui::Button::new("Copy Fen")).clicked() {
+            debug.fen_str = board.to_fen();
+            debug.fen_error = None;
+        }
+        if let Some(fen_error) = &debug.fen_error {
+            ui.colored_label(egui::Color32::RED, fen_error.as_str());
+        }
+        ui.add_space(1.0);
+        ui.text_edit_singleline(&mut debug.pgn_path);
+        ui.horizontal(|ui| {
+            if ui.add(egui::Button::new("Save PGN")).clicked() {
+                debug.pgn_status = Some(match PgnGame::from_moves(manager.custom_position.as_deref(), &manager.game_moves, manager.game_result) {
+                    Ok(game) => match std::fs::write(&debug.pgn_path, game.to_pgn()) {
+                        Ok(()) => format!("Saved {} moves", manager.game_moves.len()),
+                        Err(err) => err.to_string(),
+                    },
+                    Err(err) => err.to_string(),
+                });
+            }
+            if ui.add(egui::Button::new("Load PGN")).clicked() {
+                let games = std::fs::read_to_string(&debug.pgn_path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|pgn| parse_pgn(&pgn).map_err(|err| err.to_string()));
+                debug.pgn_status = Some(match games {
+                    Ok(games) if !games.is_empty() => {
+                        let game = &games[0];
+                        let start_fen = game.tag("FEN").map(|fen| fen.to_string());
+                        board.load_position(start_fen.clone(), &zobrist).unwrap();
+                        board.set_chess960(game.is_chess960());
+                        let moves = game.mainline();
+                        for mov in moves.iter() {
+                            board.make_move(*mov, false, &zobrist);
+                        }
+                        manager.custom_position = start_fen;
+                        manager.game_moves = moves;
+                        manager.move_color = board.move_color;
+                        move_gen.generate_moves(board.as_ref(), precomp.as_ref(), bbutils.as_ref(), magic.as_ref(), false);
+                        debug_pos_loaded_evw.send(DebugPositionLoaded {});
+                        format!("Loaded {} moves", manager.game_moves.len())
+                    },
+                    Ok(_) => String::from("No games found"),
+                    Err(err) => err,
+                });
+            }
+        });
+        if let Some(pgn_status) = &debug.pgn_status {
+            ui.label(pgn_status.as_str());
+        }
         ui.add_space(1.0);
         if ui.add(egui::Button::new("Get Evaluation")).clicked() {
             let white = crate::ai::v12::evaluation::perspective::Perspective::White;
@@ -576,5 +867,38 @@ pub fn update_egui(
         ui.label(format!("Eval White: {}", debug.eval_white));
         ui.label(format!("Eval Black: {}", debug.eval_black));
         ui.label(format!("Eval Total: {}", debug.eval_total));
+        ui.add_space(1.0);
+        if debug.perft_thread.as_ref().is_some_and(|handle| handle.is_finished()) {
+            let (divide, think_time_ms) = debug.perft_thread.take().unwrap().join().expect("Perft thread panicked");
+            let nodes: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+            debug.perft_nodes = format!("{} in {} ms", nodes, think_time_ms);
+            debug.perft_divide = divide.into_iter().map(|(mov, nodes)| (mov.to_uci(), nodes)).collect();
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut debug.perft_depth).clamp_range(1..=7).prefix("Depth: "));
+            let perft_running = debug.perft_thread.is_some();
+            if ui.add_enabled(!perft_running, egui::Button::new("Run Perft")).clicked() {
+                let mut perft_board = board.clone();
+                let depth = debug.perft_depth;
+                debug.perft_nodes = String::from("Running...");
+                debug.perft_divide.clear();
+                debug.perft_thread = Some(thread::spawn(move || {
+                    let tables = LookupTables::get();
+                    let time_start = std::time::Instant::now();
+                    let divide = perft_divide(&mut perft_board, depth, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+                    (divide, time_start.elapsed().as_millis())
+                }));
+            }
+        });
+        ui.label(format!("Perft Nodes: {}", debug.perft_nodes));
+        if !debug.perft_divide.is_empty() {
+            ui.collapsing("Perft Divide", |ui| {
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for (mov, nodes) in debug.perft_divide.iter() {
+                        ui.label(format!("{}: {}", mov, nodes));
+                    }
+                });
+            });
+        }
     });
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
-use crate::{AppState, state::AppMode};
+use crate::{AppState, state::{AppMode, GameSettings}, game::clock::TimeControl};
+use super::text_input::TextInput;
 
 
 
@@ -15,6 +16,7 @@ pub enum MainMenuButtonLabel {
     HumanVsHuman,
     HumanVsAI,
     AIVsAI,
+    RatingList,
 }
 
 #[derive(Component)]
@@ -27,10 +29,70 @@ pub struct MainMenuButton {
 #[derive(Component)]
 pub struct MainMenuParentNode {}
 
+#[derive(Component)]
+pub struct Chess960ToggleButton {}
+
+fn chess960_toggle_label(chess960: bool) -> String {
+    format!("Chess960: {}", if chess960 { "On" } else { "Off" })
+}
+
+#[derive(Component)]
+pub struct TimeControlToggleButton {}
+
+fn time_control_toggle_label(time_control: Option<TimeControl>) -> String {
+    match time_control {
+        Some(time_control) => format!("Clock: {}", time_control),
+        None => "Clock: Off".to_string(),
+    }
+}
+
+#[derive(Component)]
+pub struct OpponentToggleButton {}
+
+fn opponent_toggle_label(use_external_engine: bool) -> String {
+    format!("Opponent: {}", if use_external_engine { "External Engine" } else { "AI" })
+}
+
+/// Executable of the external UCI engine, a path or a name found in `PATH`
+#[derive(Component)]
+pub struct ExternalEnginePathInput {}
+
+/// PGN file versus games are archived to, left empty to not archive them
+#[derive(Component)]
+pub struct VersusPgnPathInput {}
+
+/// Turns the opening book of one AI player on or off, `player` indexes `GameSettings::books`
+#[derive(Component)]
+pub struct BookToggleButton {
+    player: usize,
+}
+
+const BOOK_PLAYER_LABELS: [&str; 2] = ["AI", "Opponent"];
+
+fn book_toggle_label(player: usize, use_book: bool) -> String {
+    format!("{} Book: {}", BOOK_PLAYER_LABELS[player], if use_book { "On" } else { "Off" })
+}
+
+/// Polyglot book file of one AI player
+#[derive(Component)]
+pub struct BookPathInput {
+    player: usize,
+}
+
+/// Cycles from no clock through the presets and back
+fn next_time_control(time_control: Option<TimeControl>) -> Option<TimeControl> {
+    let next_idx = match time_control {
+        Some(time_control) => TimeControl::PRESETS.iter().position(|preset| *preset == time_control).map_or(0, |idx| idx + 1),
+        None => 0,
+    };
+    TimeControl::PRESETS.get(next_idx).copied()
+}
+
 
 pub fn spawn_main_menu(
     mut commands: Commands,
-    asset_server: Res<AssetServer>
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
 ) {
     commands.spawn((NodeBundle {
         style: Style {
@@ -118,6 +180,201 @@ pub fn spawn_main_menu(
                         }
                     ));
                 });
+            parent.spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(BUTTON_REST_LENGTH),
+                        height: Val::Px(60.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BUTTON_BORDER_COLOR),
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                }, MainMenuButton { anim_time: 0.0, width: BUTTON_REST_LENGTH, label: MainMenuButtonLabel::RatingList }))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Rating List",
+                        TextStyle {
+                            font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                            font_size: 30.0,
+                            color: BUTTON_TEXT_COLOR
+                        }
+                    ));
+                });
+            parent.spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(BUTTON_REST_LENGTH),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(30.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BUTTON_BORDER_COLOR),
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                }, Chess960ToggleButton {}))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        chess960_toggle_label(settings.chess960),
+                        TextStyle {
+                            font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                            font_size: 24.0,
+                            color: BUTTON_TEXT_COLOR
+                        }
+                    ));
+                });
+            parent.spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(BUTTON_REST_LENGTH),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BUTTON_BORDER_COLOR),
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                }, TimeControlToggleButton {}))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        time_control_toggle_label(settings.time_control),
+                        TextStyle {
+                            font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                            font_size: 24.0,
+                            color: BUTTON_TEXT_COLOR
+                        }
+                    ));
+                });
+            parent.spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(BUTTON_REST_LENGTH),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BUTTON_BORDER_COLOR),
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                }, OpponentToggleButton {}))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        opponent_toggle_label(settings.use_external_engine),
+                        TextStyle {
+                            font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                            font_size: 24.0,
+                            color: BUTTON_TEXT_COLOR
+                        }
+                    ));
+                });
+            parent.spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(BUTTON_HOVER_LENGTH),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BUTTON_BORDER_COLOR),
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                }, TextInput::new("Engine: ", &settings.external_engine_path, "", false), ExternalEnginePathInput {}))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Engine: ",
+                        TextStyle {
+                            font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                            font_size: 24.0,
+                            color: BUTTON_TEXT_COLOR
+                        }
+                    ));
+                });
+            parent.spawn((ButtonBundle {
+                    style: Style {
+                        width: Val::Px(BUTTON_HOVER_LENGTH),
+                        height: Val::Px(40.0),
+                        border: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(BUTTON_BORDER_COLOR),
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                }, TextInput::new("Archive: ", &settings.versus_pgn_path, "", false), VersusPgnPathInput {}))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Archive: ",
+                        TextStyle {
+                            font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                            font_size: 24.0,
+                            color: BUTTON_TEXT_COLOR
+                        }
+                    ));
+                });
+            for (player, label) in BOOK_PLAYER_LABELS.into_iter().enumerate() {
+                parent.spawn((ButtonBundle {
+                        style: Style {
+                            width: Val::Px(BUTTON_REST_LENGTH),
+                            height: Val::Px(40.0),
+                            border: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::top(Val::Px(10.0)),
+                            ..default()
+                        },
+                        border_color: BorderColor(BUTTON_BORDER_COLOR),
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    }, BookToggleButton { player }))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            book_toggle_label(player, settings.books[player].use_book),
+                            TextStyle {
+                                font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                                font_size: 24.0,
+                                color: BUTTON_TEXT_COLOR
+                            }
+                        ));
+                    });
+                let prompt = format!("{} Book: ", label);
+                parent.spawn((ButtonBundle {
+                        style: Style {
+                            width: Val::Px(BUTTON_HOVER_LENGTH),
+                            height: Val::Px(40.0),
+                            border: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::top(Val::Px(10.0)),
+                            ..default()
+                        },
+                        border_color: BorderColor(BUTTON_BORDER_COLOR),
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    }, TextInput::new(&prompt, &settings.books[player].path, "", false), BookPathInput { player }))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            prompt.clone(),
+                            TextStyle {
+                                font: asset_server.load("ui/font/LiberationSans-Regular.ttf"),
+                                font_size: 24.0,
+                                color: BUTTON_TEXT_COLOR
+                            }
+                        ));
+                    });
+            }
         });
 }
 
@@ -146,6 +403,11 @@ pub fn update_menu_buttons(
                     MainMenuButtonLabel::HumanVsHuman => { commands.insert_resource(NextState(Some(AppMode::GameHumanHuman))); },
                     MainMenuButtonLabel::HumanVsAI => { commands.insert_resource(NextState(Some(AppMode::GameHumanAI))); },
                     MainMenuButtonLabel::AIVsAI => { commands.insert_resource(NextState(Some(AppMode::GameAIAI))); },
+                    // The rating list plays its games headless and doesn't load a board
+                    MainMenuButtonLabel::RatingList => {
+                        commands.insert_resource(NextState(Some(AppState::RatingList)));
+                        continue;
+                    },
                 };
 
                 commands.insert_resource(NextState(Some(AppState::LoadPrecomp)));
@@ -167,4 +429,98 @@ pub fn update_menu_buttons(
         button_data.width = (BUTTON_HOVER_LENGTH - BUTTON_REST_LENGTH) * step + BUTTON_REST_LENGTH;
         style.width = Val::Px(button_data.width);
     }
-}
\ No newline at end of file
+}
+
+pub fn update_chess960_toggle(
+    interaction_query: Query<(&Interaction, &Children), (Changed<Interaction>, With<Chess960ToggleButton>)>,
+    mut text_query: Query<&mut Text>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (interaction, children) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.chess960 = !settings.chess960;
+            if let Ok(mut text) = text_query.get_mut(children[0]) {
+                text.sections[0].value = chess960_toggle_label(settings.chess960);
+            }
+        }
+    }
+}
+
+pub fn update_time_control_toggle(
+    interaction_query: Query<(&Interaction, &Children), (Changed<Interaction>, With<TimeControlToggleButton>)>,
+    mut text_query: Query<&mut Text>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (interaction, children) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.time_control = next_time_control(settings.time_control);
+            if let Ok(mut text) = text_query.get_mut(children[0]) {
+                text.sections[0].value = time_control_toggle_label(settings.time_control);
+            }
+        }
+    }
+}
+
+pub fn update_opponent_toggle(
+    interaction_query: Query<(&Interaction, &Children), (Changed<Interaction>, With<OpponentToggleButton>)>,
+    mut text_query: Query<&mut Text>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (interaction, children) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.use_external_engine = !settings.use_external_engine;
+            if let Ok(mut text) = text_query.get_mut(children[0]) {
+                text.sections[0].value = opponent_toggle_label(settings.use_external_engine);
+            }
+        }
+    }
+}
+
+pub fn update_external_engine_path(
+    text_input_query: Query<&TextInput, (Changed<TextInput>, With<ExternalEnginePathInput>)>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for text_input in text_input_query.iter() {
+        if text_input.value != settings.external_engine_path {
+            settings.external_engine_path = text_input.value.clone();
+        }
+    }
+}
+
+pub fn update_versus_pgn_path(
+    text_input_query: Query<&TextInput, (Changed<TextInput>, With<VersusPgnPathInput>)>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for text_input in text_input_query.iter() {
+        if text_input.value != settings.versus_pgn_path {
+            settings.versus_pgn_path = text_input.value.clone();
+        }
+    }
+}
+
+pub fn update_book_toggles(
+    interaction_query: Query<(&Interaction, &Children, &BookToggleButton), Changed<Interaction>>,
+    mut text_query: Query<&mut Text>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (interaction, children, button) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            let book = &mut settings.books[button.player];
+            book.use_book = !book.use_book;
+            if let Ok(mut text) = text_query.get_mut(children[0]) {
+                text.sections[0].value = book_toggle_label(button.player, book.use_book);
+            }
+        }
+    }
+}
+
+pub fn update_book_paths(
+    text_input_query: Query<(&TextInput, &BookPathInput), Changed<TextInput>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    for (text_input, input) in text_input_query.iter() {
+        if text_input.value != settings.books[input.player].path {
+            settings.books[input.player].path = text_input.value.clone();
+        }
+    }
+}
@@ -6,6 +6,9 @@ pub mod main_menu;
 pub mod arrows;
 pub mod ingame_menu;
 pub mod text_input;
+pub mod rating_list;
+pub mod move_list;
+pub mod promotion_dialog;
 
 use bevy_egui::EguiPlugin;
 use board::*;
@@ -14,8 +17,11 @@ use main_menu::*;
 use arrows::*;
 use ingame_menu::*;
 use text_input::*;
+use rating_list::*;
+use move_list::*;
+use promotion_dialog::*;
 
-use crate::{AppState, state::AppMode};
+use crate::{AppState, state::{AppMode, GameSettings}, game::manager::{on_make_move, take_back_moves}};
 
 pub fn spawn_camera(
     mut commands: Commands,
@@ -55,6 +61,9 @@ impl Plugin for UIPlugin {
             .init_resource::<BoardUI>()
             .init_resource::<CalcStatistics>()
             .init_resource::<DebugInfo>()
+            .init_resource::<GameSettings>()
+            .init_resource::<RatingListRun>()
+            .init_resource::<MoveListView>()
             .add_event::<BoardUIResetPiecePosition>()
             .add_event::<BoardSetSquareColor>()
             .add_event::<BoardResetSquareColors>()
@@ -62,9 +71,19 @@ impl Plugin for UIPlugin {
             .add_plugins(TextInputPlugin)
             .add_plugins(EguiPlugin)
             .add_systems(Startup, spawn_camera)
-            .add_systems(Startup, spawn_main_menu)
-            .add_systems(Update, update_menu_buttons.run_if(in_state(AppState::MainMenu)))
+            .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(Update, (
+                update_menu_buttons,
+                update_chess960_toggle,
+                update_time_control_toggle,
+                update_opponent_toggle,
+                update_external_engine_path,
+                update_versus_pgn_path,
+                update_book_toggles,
+                update_book_paths,
+            ).run_if(in_state(AppState::MainMenu)))
             .add_systems(OnExit(AppState::MainMenu), despawn_main_menu)
+            .add_systems(Update, update_rating_list.run_if(in_state(AppState::RatingList)))
         
             .add_systems(OnEnter(AppState::LoadUI), (
                 init_board_ui_transform,
@@ -72,6 +91,7 @@ impl Plugin for UIPlugin {
                 spawn_board_ui,
                 spawn_arrow_drawer,
                 spawn_calc_stats,
+                spawn_clocks,
                 finish_load_ui,
             ).chain())
 
@@ -82,17 +102,25 @@ impl Plugin for UIPlugin {
             .add_systems(Update, update_egui.run_if(in_state(AppState::InGame).and_then(in_state(AppMode::GameHumanHuman))))
             
             .add_systems(Update, (
-                update_pieces,
+                update_move_list.before(sync_board_view),
+                sync_board_view.after(on_make_move).after(take_back_moves),
+            ).run_if(in_state(AppState::InGame).or_else(in_state(AppState::GameOver))).run_if(shows_move_list))
+            .add_systems(Update, (
+                update_pieces.after(sync_board_view),
                 reset_piece_position,
                 set_square_color,
                 reset_square_colors,
-                update_board_ui,
+                update_board_ui.after(sync_board_view),
                 update_arrows,
+                update_pv_arrows,
                 drag_piece,
                 update_board_ui_transform,
                 update_board_ui_on_resize.after(update_board_ui_transform),
                 update_menu_stats,
             ).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, update_promotion_dialog.after(update_board_ui_transform).run_if(in_state(AppState::InGame).or_else(in_state(AppState::GameOver))))
+            .add_systems(OnExit(AppState::InGame), cancel_promotion_choice)
+            .add_systems(Update, update_clocks.run_if(in_state(AppState::InGame).or_else(in_state(AppState::GameOver))))
 
             .add_systems(Update, reset_board_pieces.run_if(in_state(AppMode::GameHumanHuman).and_then(in_state(AppState::InGame))))
             
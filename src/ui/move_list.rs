@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    board::{board::Board, moves::Move, coord::Coord},
+    engine::position::Position,
+    game::{manager::{GameManager, TakeBackMove, MovesTakenBack}, human_player::{HumanPlayer, PlayerInputState}},
+    state::AppMode,
+};
+
+use super::{board::{BoardUI, BoardUIPiece, BoardUISquare, BoardUITransform, spawn_pieces}, theme::{BoardTheme, PieceTheme, SquareColorTypes}, ingame_menu::DebugPositionLoaded};
+
+/// The game in standard algebraic notation, and which of its positions the board shows
+#[derive(Resource, Default)]
+pub struct MoveListView {
+    /// Number of moves played before the position on the board while looking back at the game, `None` follows the game
+    pub viewed_ply: Option<usize>,
+    /// The board has to be drawn again from the shown position
+    redraw: bool,
+    /// The board was drawn again this frame instead of following the moves made on it
+    redrawn: bool,
+    /// Game the notation was generated for
+    moves: Vec<Move>,
+    start_fen: Option<String>,
+    sans: Vec<String>,
+    start_ply: usize,
+}
+
+impl MoveListView {
+    pub fn is_viewing(&self) -> bool {
+        self.viewed_ply.is_some()
+    }
+
+    /// The piece sprites don't follow the moves made on the board this frame
+    pub fn draws_board(&self) -> bool {
+        self.is_viewing() || self.redrawn
+    }
+
+    fn shown_ply(&self) -> usize {
+        self.viewed_ply.unwrap_or(self.moves.len())
+    }
+
+    fn show_ply(&mut self, ply: usize) {
+        let viewed_ply = if ply >= self.moves.len() { None } else { Some(ply) };
+        if viewed_ply != self.viewed_ply {
+            self.viewed_ply = viewed_ply;
+            self.redraw = true;
+        }
+    }
+
+    fn update_notation(&mut self, manager: &GameManager, board: &Board) {
+        if self.moves == manager.game_moves && self.start_fen == manager.custom_position {
+            return;
+        }
+        let start_position = Position { board: manager.board_at_ply(board, 0) };
+        self.start_ply = start_position.board.plycount;
+        self.sans = start_position.line_to_san(&manager.game_moves);
+        self.moves = manager.game_moves.clone();
+        self.start_fen = manager.custom_position.clone();
+        // New moves always bring the board back to the game
+        if self.viewed_ply.is_some() {
+            self.viewed_ply = None;
+            self.redraw = true;
+        }
+    }
+}
+
+pub fn update_move_list(
+    mut contexts: EguiContexts,
+    mut view: ResMut<MoveListView>,
+    mut take_back_evw: EventWriter<TakeBackMove>,
+    manager: Res<GameManager>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    let ctx = contexts.ctx_mut();
+    if !ctx.wants_keyboard_input() {
+        if keyboard.just_pressed(KeyCode::Left) {
+            let ply = view.shown_ply().saturating_sub(1);
+            view.show_ply(ply);
+        } else if keyboard.just_pressed(KeyCode::Right) {
+            let ply = view.shown_ply() + 1;
+            view.show_ply(ply);
+        }
+    }
+
+    egui::Window::new("Moves").anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0]).show(ctx, |ui| {
+        let shown_ply = view.shown_ply();
+        let mut clicked_ply = None;
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("move_list").num_columns(3).min_col_width(60.0).show(ui, |ui| {
+                for (idx, san) in view.sans.iter().enumerate() {
+                    let ply = view.start_ply + idx;
+                    if ply.is_multiple_of(2) || idx == 0 {
+                        ui.label(format!("{}.", ply / 2 + 1));
+                        if !ply.is_multiple_of(2) {
+                            ui.label("...");
+                        }
+                    }
+                    if ui.selectable_label(shown_ply == idx + 1, san).clicked() {
+                        clicked_ply = Some(idx + 1);
+                    }
+                    if !ply.is_multiple_of(2) {
+                        ui.end_row();
+                    }
+                }
+            });
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("|<").clicked() {
+                clicked_ply = Some(0);
+            }
+            if ui.button("<").clicked() {
+                clicked_ply = Some(shown_ply.saturating_sub(1));
+            }
+            if ui.button(">").clicked() {
+                clicked_ply = Some(shown_ply + 1);
+            }
+            if ui.button(">|").clicked() {
+                clicked_ply = Some(view.moves.len());
+            }
+            if ui.add_enabled(!manager.game_moves.is_empty(), egui::Button::new("Take Back")).clicked() {
+                take_back_evw.send(TakeBackMove {});
+            }
+        });
+        if let Some(ply) = clicked_ply {
+            view.show_ply(ply);
+        }
+    });
+}
+
+/// Draws the position the move list shows, or the game again after moves were taken back
+pub fn sync_board_view(
+    mut commands: Commands,
+    mut view: ResMut<MoveListView>,
+    mut taken_back_evr: EventReader<MovesTakenBack>,
+    mut debug_pos_loaded_evr: EventReader<DebugPositionLoaded>,
+    mut board_ui: ResMut<BoardUI>,
+    board: Res<Board>,
+    manager: Res<GameManager>,
+    board_transform: Res<BoardUITransform>,
+    (board_theme, piece_theme): (Res<BoardTheme>, Res<PieceTheme>),
+    pieces_query: Query<Entity, With<BoardUIPiece>>,
+    mut squares_query: Query<(&mut Sprite, &mut BoardUISquare)>,
+    mut player_query: Query<&mut HumanPlayer>,
+) {
+    view.redrawn = false;
+    if taken_back_evr.iter().count() > 0 {
+        view.redraw = true;
+    }
+    view.update_notation(&manager, &board);
+    // Loaded positions are drawn by `reset_board_pieces`
+    if debug_pos_loaded_evr.iter().count() > 0 {
+        view.redraw = false;
+    }
+    if !view.redraw {
+        return;
+    }
+    view.redraw = false;
+    view.redrawn = true;
+
+    let shown_ply = view.shown_ply();
+    let shown_board = manager.board_at_ply(&board, shown_ply);
+    for piece in pieces_query.iter() {
+        commands.entity(piece).despawn();
+    }
+    spawn_pieces(&mut commands, &shown_board, &board_transform, &piece_theme);
+
+    let last_move = shown_ply.checked_sub(1).map(|ply| manager.game_moves[ply]);
+    for (mut sprite, mut square) in squares_query.iter_mut() {
+        let sqr = Coord::new(square.file, square.rank);
+        square.color = match last_move {
+            Some(mov) if mov.start() == sqr => SquareColorTypes::MoveFromHighlight,
+            Some(mov) if mov.target() == sqr => SquareColorTypes::MoveToHighlight,
+            _ => SquareColorTypes::Normal,
+        };
+        sprite.color = board_theme.square_color(square.color, (square.rank + square.file) % 2 == 0);
+    }
+    board_ui.last_made_move = last_move;
+
+    // A selected piece may not be on its square anymore
+    board_ui.dragged_piece = None;
+    for mut player in player_query.iter_mut() {
+        player.current_state = PlayerInputState::None;
+    }
+}
+
+/// Moves can only be listed and taken back in games with a human player
+pub fn shows_move_list(app_mode: Res<State<AppMode>>) -> bool {
+    matches!(app_mode.get(), AppMode::GameHumanHuman | AppMode::GameHumanAI)
+}
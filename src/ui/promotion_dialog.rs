@@ -0,0 +1,94 @@
+use bevy::{prelude::*, window::WindowResized};
+
+use crate::{
+    board::{coord::Coord, piece::Piece},
+    game::{human_player::{HumanPlayer, PlayerInputState}, player::Player},
+};
+
+use super::{board::BoardUITransform, theme::PieceTheme};
+
+/// Pieces offered in the promotion dialog, from the promotion square towards the middle of the board
+pub const PROMOTION_PIECES: [u8; 4] = [Piece::QUEEN, Piece::KNIGHT, Piece::ROOK, Piece::BISHOP];
+
+const DIALOG_DEPTH: f32 = 0.3;
+const DIALOG_PIECE_DEPTH: f32 = 0.4;
+const DIALOG_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+
+#[derive(Component)]
+pub struct PromotionDialogSquare {
+    pub coord: Coord,
+}
+
+/// Squares of the promotion dialog and the piece type offered on each of them
+pub fn promotion_dialog_squares(target: Coord) -> impl Iterator<Item = (Coord, u8)> {
+    let step = if target.rank() == 7 { -1 } else { 1 };
+    PROMOTION_PIECES.into_iter().enumerate().map(move |(i, ptype)| {
+        (Coord::new(target.file(), target.rank() + step * i as i8), ptype)
+    })
+}
+
+/// Shows the dialog over the promotion square while a human player picks the promotion piece
+pub fn update_promotion_dialog(
+    mut commands: Commands,
+    player_query: Query<(&HumanPlayer, &Player)>,
+    mut dialog_query: Query<(Entity, &PromotionDialogSquare, &mut Transform, &mut Sprite)>,
+    board_transform: Res<BoardUITransform>,
+    piece_theme: Res<PieceTheme>,
+    mut window_resize_evr: EventReader<WindowResized>,
+) {
+    let promotion = player_query.iter().find_map(|(player, player_data)| match player.current_state {
+        PlayerInputState::ChoosingPromotion(mov) => Some((mov, player_data.team)),
+        _ => None,
+    });
+    let Some((promotion_move, team)) = promotion else {
+        for (entity, _, _, _) in dialog_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    };
+
+    if !dialog_query.is_empty() {
+        if window_resize_evr.iter().count() > 0 {
+            for (_, square, mut transform, mut sprite) in dialog_query.iter_mut() {
+                sprite.custom_size = Some(Vec2::new(board_transform.sqr_size, board_transform.sqr_size));
+                let pos = board_transform.pos_from_coord(square.coord);
+                transform.translation = Vec3::new(pos.x, pos.y, DIALOG_DEPTH);
+            }
+        }
+        return;
+    }
+    for (coord, ptype) in promotion_dialog_squares(promotion_move.target()) {
+        let pos = board_transform.pos_from_coord(coord);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: DIALOG_COLOR,
+                    custom_size: Some(Vec2::new(board_transform.sqr_size, board_transform.sqr_size)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(pos.x, pos.y, DIALOG_DEPTH),
+                ..default()
+            },
+            PromotionDialogSquare { coord },
+        )).with_children(|parent| {
+            if let Some(sprite) = piece_theme.get_piece_sprite(Piece::new(ptype | team)) {
+                parent.spawn(SpriteBundle {
+                    transform: Transform::from_xyz(0.0, 0.0, DIALOG_PIECE_DEPTH - DIALOG_DEPTH),
+                    texture: sprite,
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+/// The promotion piece can't be chosen anymore once the game is over
+pub fn cancel_promotion_choice(
+    mut player_query: Query<&mut HumanPlayer>,
+) {
+    for mut player in player_query.iter_mut() {
+        if let PlayerInputState::ChoosingPromotion(_) = player.current_state {
+            player.current_state = PlayerInputState::None;
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::{sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}, thread::{self, JoinHandle}};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    AppState, state::GameSettings, ai::ai_player::AIVersion,
+    tournament::{
+        DEFAULT_OPENINGS_PATH, Format, Participant, Tournament, load_openings, game::MatchSettings, standings::Standings,
+        rating::{RatingList, feature, RATING_LIST_CSV_PATH, RATING_LIST_MARKDOWN_PATH},
+    },
+};
+
+
+/// Results of the running round-robin, shared with the tournament thread
+#[derive(Default)]
+struct RatingProgress {
+    standings: Option<Standings>,
+    num_finished: usize,
+}
+
+/// Round-robin of every AI version played in the background, the rating list is updated after every game
+#[derive(Resource)]
+pub struct RatingListRun {
+    pub rounds: usize,
+    pub move_time_ms: u32,
+    pub concurrency: usize,
+    num_games: usize,
+    progress: Arc<Mutex<RatingProgress>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    /// Number of finished games the ratings were computed from
+    num_rated: usize,
+    ratings: RatingList,
+    status: Option<String>,
+}
+
+impl Default for RatingListRun {
+    fn default() -> Self {
+        Self {
+            rounds: 10,
+            move_time_ms: 50,
+            concurrency: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            num_games: 0,
+            progress: Arc::new(Mutex::new(RatingProgress::default())),
+            stop: Arc::new(AtomicBool::new(false)),
+            handle: None,
+            num_rated: 0,
+            ratings: RatingList::default(),
+            status: None,
+        }
+    }
+}
+
+impl RatingListRun {
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|handle| !handle.is_finished())
+    }
+
+    fn start(&mut self, settings: &GameSettings) {
+        let openings = match settings.chess960 {
+            true => Tournament::chess960_openings(self.rounds),
+            false => match load_openings(DEFAULT_OPENINGS_PATH) {
+                Ok(openings) => openings,
+                Err(err) => {
+                    self.status = Some(format!("Could not read the openings: {}", err));
+                    return;
+                },
+            },
+        };
+        let tournament = Tournament {
+            participants: AIVersion::ALL.map(Participant::new).to_vec(),
+            format: Format::RoundRobin,
+            openings,
+            rounds: self.rounds,
+            concurrency: self.concurrency,
+            settings: MatchSettings {
+                time_control: settings.time_control,
+                move_time_ms: self.move_time_ms,
+                chess960: settings.chess960,
+                ..Default::default()
+            },
+        };
+        self.num_games = tournament.games().len();
+        self.num_rated = 0;
+        self.ratings = RatingList::default();
+        self.status = None;
+        self.progress = Arc::new(Mutex::new(RatingProgress::default()));
+        self.stop = Arc::new(AtomicBool::new(false));
+        let (progress, stop) = (self.progress.clone(), self.stop.clone());
+        self.handle = Some(thread::spawn(move || {
+            tournament.run_until(&stop, |_, _, standings| {
+                let mut progress = progress.lock().unwrap();
+                progress.standings = Some(standings.clone());
+                progress.num_finished += 1;
+            });
+        }));
+    }
+
+    /// Running games are finished in the background, no new ones are started
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    fn update_ratings(&mut self) {
+        let progress = self.progress.lock().unwrap();
+        if progress.num_finished == self.num_rated {
+            return;
+        }
+        if let Some(standings) = progress.standings.as_ref() {
+            self.ratings = RatingList::compute_anchored(standings);
+        }
+        self.num_rated = progress.num_finished;
+    }
+
+    fn save_report(&self) -> String {
+        let written = std::fs::write(RATING_LIST_CSV_PATH, self.ratings.to_csv())
+            .and_then(|_| std::fs::write(RATING_LIST_MARKDOWN_PATH, self.ratings.to_markdown()));
+        match written {
+            Ok(()) => format!("Saved {} and {}", RATING_LIST_CSV_PATH, RATING_LIST_MARKDOWN_PATH),
+            Err(err) => err.to_string(),
+        }
+    }
+}
+
+pub fn update_rating_list(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut run: ResMut<RatingListRun>,
+    settings: Res<GameSettings>,
+) {
+    run.update_ratings();
+    egui::Window::new("Rating List").show(contexts.ctx_mut(), |ui| {
+        let is_running = run.is_running();
+        ui.add_enabled_ui(!is_running, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut run.rounds).clamp_range(1..=1000).prefix("Rounds: "));
+                ui.add(egui::DragValue::new(&mut run.move_time_ms).clamp_range(1..=10000).prefix("Move time: ").suffix(" ms"));
+                ui.add(egui::DragValue::new(&mut run.concurrency).clamp_range(1..=64).prefix("Concurrency: "));
+            });
+        });
+        if let Some(time_control) = settings.time_control {
+            ui.label(format!("Clock: {}", time_control));
+        }
+        ui.horizontal(|ui| {
+            if is_running {
+                if ui.add(egui::Button::new("Stop")).clicked() {
+                    run.stop();
+                }
+            } else if ui.add(egui::Button::new("Start")).clicked() {
+                run.start(&settings);
+            }
+            if ui.add_enabled(!run.ratings.ratings.is_empty(), egui::Button::new("Save Report")).clicked() {
+                let status = run.save_report();
+                run.status = Some(status);
+            }
+            if ui.add(egui::Button::new("Main Menu")).clicked() {
+                run.stop();
+                commands.insert_resource(NextState(Some(AppState::MainMenu)));
+            }
+        });
+        ui.label(format!("Games: {} / {}", run.num_rated, run.num_games));
+        if let Some(status) = &run.status {
+            ui.label(status.as_str());
+        }
+        ui.add_space(4.0);
+
+        egui::Grid::new("rating_list").striped(true).num_columns(7).show(ui, |ui| {
+            for heading in ["Rank", "Version", "Feature", "Elo", "Gain", "Games", "Score"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+            for (idx, rating, rank) in run.ratings.rows() {
+                ui.label(rank.to_string());
+                ui.label(rating.name.as_str());
+                ui.label(feature(&rating.name));
+                ui.label(format!("{:.0} ± {:.0}", rating.elo, rating.error));
+                ui.label(run.ratings.gain(idx).map_or(String::new(), |gain| format!("{:+.0}", gain)));
+                ui.label(rating.games.to_string());
+                ui.label(format!("{:.1}%", rating.score * 100.0));
+                ui.end_row();
+            }
+        });
+    });
+}
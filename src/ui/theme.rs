@@ -25,6 +25,19 @@ pub struct BoardTheme {
     pub dark_squares: SquareColors,
 }
 
+impl BoardTheme {
+    pub fn square_color(&self, color: SquareColorTypes, is_light: bool) -> Color {
+        let colors = if is_light { &self.light_squares } else { &self.dark_squares };
+        match color {
+            SquareColorTypes::Normal => colors.normal,
+            SquareColorTypes::Legal => colors.legal,
+            SquareColorTypes::Selected => colors.selected,
+            SquareColorTypes::MoveFromHighlight => colors.move_from_highlight,
+            SquareColorTypes::MoveToHighlight => colors.move_to_highlight,
+        }
+    }
+}
+
 impl Default for BoardTheme {
     fn default() -> Self {
         BoardTheme {
@@ -0,0 +1,52 @@
+use rand::Rng;
+
+use super::fen::symbol_from_piece;
+use crate::board::piece::Piece;
+
+
+pub const NUM_START_POSITIONS: u16 = 960;
+/// Number of the standard chess start position in the Chess960 numbering
+pub const STANDARD_START_POSITION: u16 = 518;
+
+// Knight placements on the five squares left after placing the bishops and queen
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+
+/// Back rank piece types of a start position, using the Scharnagl numbering from 0 to 959
+pub fn back_rank(id: u16) -> [u8; 8] {
+    assert!(id < NUM_START_POSITIONS, "Chess960 start positions are numbered from 0 to 959");
+    let mut rank = [Piece::NONE; 8];
+    let mut n = id as usize;
+
+    rank[n % 4 * 2 + 1] = Piece::BISHOP;
+    n /= 4;
+    rank[n % 4 * 2] = Piece::BISHOP;
+    n /= 4;
+    let queen_idx = n % 6;
+    n /= 6;
+    let (knight_a, knight_b) = KNIGHT_PLACEMENTS[n];
+
+    let empty_files = |rank: &[u8; 8]| -> Vec<usize> { (0..8).filter(|file| rank[*file] == Piece::NONE).collect() };
+    rank[empty_files(&rank)[queen_idx]] = Piece::QUEEN;
+    let files = empty_files(&rank);
+    rank[files[knight_a]] = Piece::KNIGHT;
+    rank[files[knight_b]] = Piece::KNIGHT;
+
+    // The king always stands between the two rooks
+    let files = empty_files(&rank);
+    rank[files[0]] = Piece::ROOK;
+    rank[files[1]] = Piece::KING;
+    rank[files[2]] = Piece::ROOK;
+    rank
+}
+
+/// FEN of a start position, the castling rights are written as `KQkq` since both rooks are the outermost
+pub fn start_fen(id: u16) -> String {
+    let back_rank = back_rank(id);
+    let black_pieces: String = back_rank.iter().map(|ptype| symbol_from_piece(Piece::new(ptype | Piece::BLACK))).collect();
+    let white_pieces: String = back_rank.iter().map(|ptype| symbol_from_piece(Piece::new(ptype | Piece::WHITE))).collect();
+    format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", black_pieces, white_pieces)
+}
+
+pub fn random_start_fen() -> String {
+    start_fen(rand::thread_rng().gen_range(0..NUM_START_POSITIONS))
+}
@@ -1,20 +1,79 @@
+use std::fmt;
+
 use crate::{board::piece::*, game::representation};
 
 
 pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
-fn ptype_from_symbol(symbol: &str) -> u8 {
+fn ptype_from_symbol(symbol: char) -> u8 {
     match symbol {
-        "k" => Piece::KING,
-        "p" => Piece::PAWN,
-        "n" => Piece::KNIGHT,
-        "b" => Piece::BISHOP,
-        "r" => Piece::ROOK,
-        "q" => Piece::QUEEN,
+        'k' => Piece::KING,
+        'p' => Piece::PAWN,
+        'n' => Piece::KNIGHT,
+        'b' => Piece::BISHOP,
+        'r' => Piece::ROOK,
+        'q' => Piece::QUEEN,
         _ => Piece::NONE
     }
 }
 
+/// Gets the FEN letter of a piece, uppercase for white and lowercase for black
+pub fn symbol_from_piece(piece: Piece) -> char {
+    let symbol = match piece.piece_type() {
+        Piece::KING => 'k',
+        Piece::PAWN => 'p',
+        Piece::KNIGHT => 'n',
+        Piece::BISHOP => 'b',
+        Piece::ROOK => 'r',
+        Piece::QUEEN => 'q',
+        _ => ' ',
+    };
+    if piece.is_white() { symbol.to_ascii_uppercase() } else { symbol }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    MissingFields,
+    WrongRankCount(usize),
+    BadRankLength { rank: usize, length: u32 },
+    InvalidPiece(char),
+    MissingKing(u8),
+    TooManyKings(u8),
+    PawnOnBackRank(String),
+    InvalidSideToMove(String),
+    InvalidCastlingRights(String),
+    InconsistentCastlingRights(char),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    SideNotToMoveInCheck,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let color_name = |color: &u8| if *color == Piece::WHITE { "white" } else { "black" };
+        match self {
+            Self::MissingFields => write!(f, "FEN needs at least a board and a side to move"),
+            Self::WrongRankCount(count) => write!(f, "Board has {} ranks instead of 8", count),
+            Self::BadRankLength { rank, length } => write!(f, "Rank {} has {} squares instead of 8", rank, length),
+            Self::InvalidPiece(symbol) => write!(f, "Invalid piece symbol '{}'", symbol),
+            Self::MissingKing(color) => write!(f, "The {} king is missing", color_name(color)),
+            Self::TooManyKings(color) => write!(f, "There is more than one {} king", color_name(color)),
+            Self::PawnOnBackRank(square) => write!(f, "Pawn on back rank at {}", square),
+            Self::InvalidSideToMove(side) => write!(f, "Invalid side to move '{}'", side),
+            Self::InvalidCastlingRights(rights) => write!(f, "Invalid castling rights '{}'", rights),
+            Self::InconsistentCastlingRights(right) => write!(f, "Castling right '{}' does not match the king and rook positions", right),
+            Self::InvalidEnPassant(square) => write!(f, "Invalid en passant square '{}'", square),
+            Self::InvalidHalfmoveClock(clock) => write!(f, "Invalid halfmove clock '{}'", clock),
+            Self::InvalidFullmoveNumber(number) => write!(f, "Invalid fullmove number '{}'", number),
+            Self::SideNotToMoveInCheck => write!(f, "The side not to move is in check"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
 
 pub struct LoadedPositionInfo {
     pub squares: [u8; 64],
@@ -22,6 +81,10 @@ pub struct LoadedPositionInfo {
     pub white_castle_queenside: bool,
     pub black_castle_kingside: bool,
     pub black_castle_queenside: bool,
+    /// Files of the castling rooks, ordered white kingside, white queenside, black kingside, black queenside
+    pub castling_rook_files: [i8; 4],
+    /// Set when a castling right needs a king or rook outside of the standard squares
+    pub chess960: bool,
     pub ep_file: i8,
     pub white_to_move: bool,
     pub fifty_move_ply_count: u8,
@@ -32,62 +95,215 @@ impl LoadedPositionInfo {
     pub fn default() -> Self {
         Self {
             squares: [0; 64],
-            white_castle_kingside: true,
-            white_castle_queenside: true,
-            black_castle_kingside: true,
-            black_castle_queenside: true,
+            white_castle_kingside: false,
+            white_castle_queenside: false,
+            black_castle_kingside: false,
+            black_castle_queenside: false,
+            castling_rook_files: [7, 0, 7, 0],
+            chess960: false,
             ep_file: 0,
             white_to_move: true,
             fifty_move_ply_count: 0,
-            move_count: 0
+            move_count: 1,
         }
     }
 }
 
 
-pub fn position_from_fen(fen: String) -> LoadedPositionInfo {
+/// Parses a FEN string. Only the board and side to move are required,
+/// missing castling rights, en passant square and move counters default to `- - 0 1`.
+pub fn position_from_fen(fen: &str) -> Result<LoadedPositionInfo, FenError> {
     let mut loaded_pos_info = LoadedPositionInfo::default();
-    let sections: Vec<&str> = fen.split(' ').collect();
-
-    let mut file: u32 = 0;
-    let mut rank: u32 = 7;
-
-    for symbol in sections[0].chars() {
-        if symbol == '/' {
-            file = 0;
-            rank -= 1;
-        } else {
-            if symbol.is_digit(10) {
-                file += symbol.to_digit(10).unwrap();
+    let sections: Vec<&str> = fen.split_whitespace().collect();
+    if sections.len() < 2 {
+        return Err(FenError::MissingFields);
+    }
+
+    let ranks: Vec<&str> = sections[0].split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::WrongRankCount(ranks.len()));
+    }
+    for (rank_idx, rank_str) in ranks.iter().enumerate() {
+        let rank = 7 - rank_idx;
+        let mut file: u32 = 0;
+        for symbol in rank_str.chars() {
+            if let Some(empty_sqrs) = symbol.to_digit(10) {
+                if empty_sqrs == 0 || empty_sqrs > 8 {
+                    return Err(FenError::InvalidPiece(symbol));
+                }
+                file += empty_sqrs;
             } else {
-                let pcolor = if symbol.is_uppercase() { Piece::WHITE } else { Piece::BLACK };
-                let ptype = ptype_from_symbol(&symbol.to_lowercase().to_string());
-                loaded_pos_info.squares[rank as usize * 8 + file as usize] = ptype | pcolor;
+                let ptype = ptype_from_symbol(symbol.to_ascii_lowercase());
+                if ptype == Piece::NONE {
+                    return Err(FenError::InvalidPiece(symbol));
+                }
+                if file < 8 {
+                    let pcolor = if symbol.is_uppercase() { Piece::WHITE } else { Piece::BLACK };
+                    loaded_pos_info.squares[rank * 8 + file as usize] = ptype | pcolor;
+                }
                 file += 1;
             }
         }
+        if file != 8 {
+            return Err(FenError::BadRankLength { rank: rank + 1, length: file });
+        }
+    }
+
+    for color in [Piece::WHITE, Piece::BLACK] {
+        let king_count = loaded_pos_info.squares.iter().filter(|p| **p == Piece::KING | color).count();
+        if king_count == 0 {
+            return Err(FenError::MissingKing(color));
+        } else if king_count > 1 {
+            return Err(FenError::TooManyKings(color));
+        }
+    }
+    for sqr_idx in (0..8).chain(56..64) {
+        if Piece::new(loaded_pos_info.squares[sqr_idx]).piece_type() == Piece::PAWN {
+            return Err(FenError::PawnOnBackRank(representation::square_name_from_idx(sqr_idx as i8)));
+        }
     }
 
-    loaded_pos_info.white_to_move = sections[1] == "w";
-    let castling_rights = if sections.len() > 2 { sections[2] } else { "KQkq" };
-    loaded_pos_info.white_castle_kingside = castling_rights.contains("K");
-    loaded_pos_info.white_castle_queenside = castling_rights.contains("Q");
-    loaded_pos_info.black_castle_kingside = castling_rights.contains("k");
-    loaded_pos_info.black_castle_queenside = castling_rights.contains("q");
+    loaded_pos_info.white_to_move = match sections[1] {
+        "w" => true,
+        "b" => false,
+        side => return Err(FenError::InvalidSideToMove(side.to_string())),
+    };
 
-    if sections.len() > 3 {
-        let en_passant_filename = sections[3].chars().nth(0).unwrap();
-        if representation::FILE_NAMES.contains(en_passant_filename) {
-            loaded_pos_info.ep_file = representation::FILE_NAMES.chars().position(|f| f == en_passant_filename).unwrap() as i8 + 1;
+    let castling_rights = sections.get(2).copied().unwrap_or("-");
+    if castling_rights != "-" {
+        let mut has_right = [false; 4];
+        for right in castling_rights.chars() {
+            let right_idx = parse_castling_right(&mut loaded_pos_info, right, castling_rights)?;
+            if has_right[right_idx] {
+                return Err(FenError::InvalidCastlingRights(castling_rights.to_string()));
+            }
+            has_right[right_idx] = true;
         }
+        loaded_pos_info.white_castle_kingside = has_right[0];
+        loaded_pos_info.white_castle_queenside = has_right[1];
+        loaded_pos_info.black_castle_kingside = has_right[2];
+        loaded_pos_info.black_castle_queenside = has_right[3];
+    }
+
+    let en_passant_square = sections.get(3).copied().unwrap_or("-");
+    if en_passant_square != "-" {
+        loaded_pos_info.ep_file = en_passant_file(&loaded_pos_info, en_passant_square)
+            .ok_or(FenError::InvalidEnPassant(en_passant_square.to_string()))?;
+    }
+
+    if let Some(halfmove_clock) = sections.get(4) {
+        loaded_pos_info.fifty_move_ply_count = halfmove_clock.parse()
+            .map_err(|_| FenError::InvalidHalfmoveClock(halfmove_clock.to_string()))?;
     }
 
-    if sections.len() > 4 {
-        loaded_pos_info.fifty_move_ply_count = sections[4].parse().unwrap();
+    if let Some(fullmove_number) = sections.get(5) {
+        loaded_pos_info.move_count = match fullmove_number.parse() {
+            Ok(move_count) if move_count > 0 => move_count,
+            _ => return Err(FenError::InvalidFullmoveNumber(fullmove_number.to_string())),
+        };
     }
 
-    if sections.len() > 5 {
-        loaded_pos_info.move_count = sections[5].parse().unwrap();
+    let waiting_color = if loaded_pos_info.white_to_move { Piece::BLACK } else { Piece::WHITE };
+    let moving_color = if loaded_pos_info.white_to_move { Piece::WHITE } else { Piece::BLACK };
+    let waiting_king_sqr = loaded_pos_info.squares.iter().position(|p| *p == Piece::KING | waiting_color).unwrap();
+    if square_is_attacked(&loaded_pos_info.squares, waiting_king_sqr as i8, moving_color) {
+        return Err(FenError::SideNotToMoveInCheck);
     }
-    return loaded_pos_info;
-}
\ No newline at end of file
+
+    Ok(loaded_pos_info)
+}
+
+/// Reads a castling right in standard, X-FEN or Shredder-FEN notation and stores the file of its rook.
+/// `KQkq` pick the outermost rook on that side of the king, file letters name the rook directly.
+/// Returns the index of the right in `castling_rook_files`.
+fn parse_castling_right(pos: &mut LoadedPositionInfo, right: char, castling_rights: &str) -> Result<usize, FenError> {
+    let color = if right.is_ascii_uppercase() { Piece::WHITE } else { Piece::BLACK };
+    let back_rank = if color == Piece::WHITE { 0 } else { 7 };
+    let piece_on = |file: i8| pos.squares[(back_rank * 8 + file) as usize];
+
+    let rook_file = match right.to_ascii_lowercase() {
+        'k' => (0..8).rev().find(|file| piece_on(*file) == Piece::ROOK | color),
+        'q' => (0..8).find(|file| piece_on(*file) == Piece::ROOK | color),
+        symbol @ 'a'..='h' => Some(symbol as i8 - 'a' as i8),
+        _ => return Err(FenError::InvalidCastlingRights(castling_rights.to_string())),
+    };
+    let king_file = (0..8).find(|file| piece_on(*file) == Piece::KING | color)
+        .ok_or(FenError::InconsistentCastlingRights(right))?;
+    let rook_file = rook_file.filter(|file| piece_on(*file) == Piece::ROOK | color)
+        .ok_or(FenError::InconsistentCastlingRights(right))?;
+    let kingside = rook_file > king_file;
+    if (right.eq_ignore_ascii_case(&'k') && !kingside) || (right.eq_ignore_ascii_case(&'q') && kingside) {
+        return Err(FenError::InconsistentCastlingRights(right));
+    }
+
+    let right_idx = if color == Piece::WHITE { 0 } else { 2 } + if kingside { 0 } else { 1 };
+    pos.castling_rook_files[right_idx] = rook_file;
+    if king_file != 4 || rook_file != if kingside { 7 } else { 0 } {
+        pos.chess960 = true;
+    }
+    Ok(right_idx)
+}
+
+/// Validates an en passant target square and returns its 1-based file
+fn en_passant_file(pos: &LoadedPositionInfo, square: &str) -> Option<i8> {
+    let mut chars = square.chars();
+    let file = representation::FILE_NAMES.find(chars.next()?)? as i8;
+    let rank = representation::RANK_NAMES.find(chars.next()?)? as i8;
+    if chars.next().is_some() {
+        return None;
+    }
+
+    // the pawn that just moved two squares must be in front of the target square, with the squares it passed over empty
+    let (expected_rank, pawn_dir, pawn) = if pos.white_to_move { (5, -1, Piece::BLACK_PAWN) } else { (2, 1, Piece::WHITE_PAWN) };
+    let target_idx = (rank * 8 + file) as usize;
+    let pawn_idx = ((rank + pawn_dir) * 8 + file) as usize;
+    let origin_idx = ((rank - pawn_dir) * 8 + file) as usize;
+    if rank != expected_rank || pos.squares[pawn_idx] != pawn || pos.squares[target_idx] != Piece::NONE || pos.squares[origin_idx] != Piece::NONE {
+        return None;
+    }
+    Some(file + 1)
+}
+
+/// Walks each direction from the square and checks if the first piece hit is one of the attackers
+fn square_attacked_by_slider(squares: &[u8; 64], sqr_idx: i8, dirs: &[(i8, i8)], attackers: [u8; 2]) -> bool {
+    let (file, rank) = (sqr_idx % 8, sqr_idx / 8);
+    for (df, dr) in dirs.iter() {
+        let (mut f, mut r) = (file + df, rank + dr);
+        while (0..8).contains(&f) && (0..8).contains(&r) {
+            let piece = squares[(r * 8 + f) as usize];
+            if piece != Piece::NONE {
+                if attackers.contains(&piece) {
+                    return true;
+                }
+                break;
+            }
+            f += df;
+            r += dr;
+        }
+    }
+    false
+}
+
+/// Checks if a square is attacked by any piece of the given color
+pub fn square_is_attacked(squares: &[u8; 64], sqr_idx: i8, color: u8) -> bool {
+    let (file, rank) = (sqr_idx % 8, sqr_idx / 8);
+    let piece_at = |df: i8, dr: i8| -> u8 {
+        let (f, r) = (file + df, rank + dr);
+        if (0..8).contains(&f) && (0..8).contains(&r) { squares[(r * 8 + f) as usize] } else { Piece::NONE }
+    };
+
+    let pawn_dir = if color == Piece::WHITE { -1 } else { 1 };
+    if piece_at(-1, pawn_dir) == Piece::PAWN | color || piece_at(1, pawn_dir) == Piece::PAWN | color {
+        return true;
+    }
+    let knight_jumps = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+    if knight_jumps.iter().any(|(df, dr)| piece_at(*df, *dr) == Piece::KNIGHT | color) {
+        return true;
+    }
+    let king_steps = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+    if king_steps.iter().any(|(df, dr)| piece_at(*df, *dr) == Piece::KING | color) {
+        return true;
+    }
+    square_attacked_by_slider(squares, sqr_idx, &[(1, 0), (-1, 0), (0, 1), (0, -1)], [Piece::ROOK | color, Piece::QUEEN | color])
+        || square_attacked_by_slider(squares, sqr_idx, &[(1, 1), (1, -1), (-1, 1), (-1, -1)], [Piece::BISHOP | color, Piece::QUEEN | color])
+}
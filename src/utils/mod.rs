@@ -1,2 +1,4 @@
 pub mod color;
-pub mod fen;
\ No newline at end of file
+pub mod chess960;
+pub mod fen;
+pub mod pgn;
@@ -0,0 +1,531 @@
+use std::{fmt, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::{board::{moves::Move, piece::Piece}, engine::position::Position, game::result::GameResult};
+
+use super::fen::{FenError, START_FEN};
+
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const MAX_LINE_LENGTH: usize = 80;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PgnError {
+    InvalidFen(FenError),
+    IllegalMove { san: String, fen: String },
+    UnterminatedComment,
+    UnterminatedTag,
+    UnbalancedVariation,
+    VariationWithoutMove,
+    UnexpectedToken(String),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFen(err) => write!(f, "Invalid FEN tag: {}", err),
+            Self::IllegalMove { san, fen } => write!(f, "Illegal move '{}' in position {}", san, fen),
+            Self::UnterminatedComment => write!(f, "Comment is missing its closing brace"),
+            Self::UnterminatedTag => write!(f, "Tag pair is missing its closing bracket"),
+            Self::UnbalancedVariation => write!(f, "Unbalanced parentheses around a variation"),
+            Self::VariationWithoutMove => write!(f, "Variation does not follow a move"),
+            Self::UnexpectedToken(token) => write!(f, "Unexpected token '{}'", token),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(err: FenError) -> Self {
+        Self::InvalidFen(err)
+    }
+}
+
+
+/// A single move of a game along with its annotations
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnNode {
+    pub mov: Move,
+    pub san: String,
+    /// Numeric annotation glyphs, move suffixes like `!?` are stored as their NAG equivalent
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment: Option<String>,
+    /// Alternative lines that replace this move
+    pub variations: Vec<Vec<PgnNode>>,
+}
+
+impl PgnNode {
+    fn new(mov: Move, san: String) -> Self {
+        Self {
+            mov,
+            san,
+            nags: Vec::new(),
+            comment_before: None,
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they are written, the seven tag roster always comes first
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnNode>,
+}
+
+impl PgnGame {
+    /// Builds a game from a list of moves played from the given start position (the standard start position if `None`)
+    pub fn from_moves(start_fen: Option<&str>, moves: &[Move], result: GameResult) -> Result<Self, PgnError> {
+        let mut game = Self::default();
+        let mut position = match start_fen {
+            Some(fen) => {
+                let position = Position::from_fen(fen)?;
+                if fen != START_FEN {
+                    game.set_tag("SetUp", "1");
+                    game.set_tag("FEN", fen);
+                }
+                position
+            },
+            None => Position::start_pos(),
+        };
+        for mov in moves.iter() {
+            // Moves played in Chess960 mode castle by capturing the king's own rook
+            let is_own_rook = position.board.square[mov.target().index()] == Piece::new(Piece::ROOK | position.board.move_color);
+            if mov.move_flag() == Move::CASTLING && is_own_rook {
+                position.set_chess960(true);
+            }
+            let is_legal = position.legal_moves().contains(mov);
+            let san = position.move_to_san(*mov);
+            if !is_legal {
+                return Err(PgnError::IllegalMove { san, fen: position.to_fen() });
+            }
+            position.make_move(*mov);
+            game.moves.push(PgnNode::new(*mov, san));
+        }
+        if position.board.chess960 {
+            game.set_tag("Variant", "Chess960");
+        }
+        game.set_result(result);
+        Ok(game)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, tag_value)) => *tag_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn set_result(&mut self, result: GameResult) {
+        self.set_tag("Result", result.pgn_result());
+        if let Some(termination) = result.pgn_termination() {
+            self.set_tag("Termination", termination);
+        }
+    }
+
+    pub fn result(&self) -> &str {
+        self.tag("Result").unwrap_or("*")
+    }
+
+    pub fn start_position(&self) -> Result<Position, PgnError> {
+        let mut position = match self.tag("FEN") {
+            Some(fen) => Position::from_fen(fen)?,
+            None => Position::start_pos(),
+        };
+        position.set_chess960(self.is_chess960());
+        Ok(position)
+    }
+
+    pub fn is_chess960(&self) -> bool {
+        self.tag("Variant").is_some_and(|variant| variant.eq_ignore_ascii_case("Chess960"))
+    }
+
+    pub fn mainline(&self) -> Vec<Move> {
+        self.moves.iter().map(|node| node.mov).collect()
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (name, value) in self.tags.iter() {
+            pgn += &format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\""));
+        }
+        pgn.push('\n');
+
+        let start_position = self.start_position().unwrap_or_else(|_| Position::start_pos());
+        let mut tokens = Vec::new();
+        write_line(&self.moves, start_position.board.plycount, &mut tokens);
+        tokens.push(self.result().to_string());
+
+        let mut line_length = 0;
+        for token in tokens.iter() {
+            if line_length > 0 && line_length + token.len() + 1 > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            pgn += token;
+            line_length += token.len();
+        }
+        pgn += "\n\n";
+        pgn
+    }
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        let mut tags: Vec<(String, String)> = SEVEN_TAG_ROSTER.iter().map(|tag| (tag.to_string(), String::from("?"))).collect();
+        tags[2].1 = pgn_date(SystemTime::now());
+        tags[6].1 = String::from("*");
+        Self {
+            tags,
+            moves: Vec::new(),
+        }
+    }
+}
+
+/// Formats a date in the `YYYY.MM.DD` format used by the PGN `Date` tag
+pub fn pgn_date(time: SystemTime) -> String {
+    let days = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => (duration.as_secs() / 86400) as i64,
+        Err(_) => return String::from("????.??.??"),
+    };
+    // Converts days since the epoch into a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+fn write_line(line: &[PgnNode], start_ply: usize, tokens: &mut Vec<String>) {
+    let mut needs_move_number = true;
+    for (i, node) in line.iter().enumerate() {
+        let ply = start_ply + i;
+        if let Some(comment) = &node.comment_before {
+            tokens.push(format!("{{{}}}", comment));
+            needs_move_number = true;
+        }
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if needs_move_number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(node.san.clone());
+        needs_move_number = false;
+
+        for nag in node.nags.iter() {
+            tokens.push(format!("${}", nag));
+        }
+        if let Some(comment) = &node.comment {
+            tokens.push(format!("{{{}}}", comment));
+            needs_move_number = true;
+        }
+        for variation in node.variations.iter() {
+            let first = tokens.len();
+            write_line(variation, ply, tokens);
+            if tokens.len() > first {
+                tokens[first].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+            }
+            needs_move_number = true;
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    Result(String),
+    Move(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => { line_start = true; continue; },
+            c if c.is_whitespace() => continue,
+            // escape mechanism, the rest of the line is ignored
+            '%' if line_start => { for c in chars.by_ref() { if c == '\n' { break; } } },
+            ';' => { for c in chars.by_ref() { if c == '\n' { break; } } },
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => return Err(PgnError::UnterminatedComment),
+                    }
+                }
+                tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<&str>>().join(" ")));
+            },
+            '[' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some('"') => {
+                            tag.push('"');
+                            loop {
+                                match chars.next() {
+                                    Some('\\') => tag.push(chars.next().ok_or(PgnError::UnterminatedTag)?),
+                                    Some('"') => break,
+                                    Some(c) => tag.push(c),
+                                    None => return Err(PgnError::UnterminatedTag),
+                                }
+                            }
+                        },
+                        Some(c) => tag.push(c),
+                        None => return Err(PgnError::UnterminatedTag),
+                    }
+                }
+                let (name, value) = tag.split_once('"').unwrap_or((&tag, ""));
+                tokens.push(Token::Tag(name.trim().to_string(), value.to_string()));
+            },
+            '(' => tokens.push(Token::VariationStart),
+            ')' => tokens.push(Token::VariationEnd),
+            '$' => {
+                let mut nag = String::new();
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    nag.push(digit);
+                }
+                let nag = nag.parse().map_err(|_| PgnError::UnexpectedToken(format!("${}", nag)))?;
+                tokens.push(Token::Nag(nag));
+            },
+            _ => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}()[];$".contains(*c)) {
+                    word.push(c);
+                }
+                tokenize_word(&word, &mut tokens);
+            },
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+fn tokenize_word(word: &str, tokens: &mut Vec<Token>) {
+    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&word) {
+        tokens.push(Token::Result(word.to_string()));
+        return;
+    }
+    // move numbers such as `12.` or `12...`, possibly glued to the move that follows.
+    // Castling written with zeros starts with a digit as well.
+    let word = if is_zero_castling(word) {
+        word
+    } else {
+        word.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('.')
+    };
+    if word.is_empty() {
+        return;
+    }
+    let san = word.trim_end_matches(['!', '?']);
+    let nag = match &word[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    if !san.is_empty() {
+        tokens.push(Token::Move(san.to_string()));
+    }
+    if let Some(nag) = nag {
+        tokens.push(Token::Nag(nag));
+    }
+}
+
+fn is_zero_castling(word: &str) -> bool {
+    matches!(word.trim_end_matches(['+', '#', '!', '?']), "0-0" | "0-0-0")
+}
+
+
+struct PgnParser {
+    tokens: Vec<Token>,
+    idx: usize,
+}
+
+impl PgnParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx)
+    }
+
+    fn parse_game(&mut self) -> Result<PgnGame, PgnError> {
+        let mut game = PgnGame { tags: Vec::new(), moves: Vec::new() };
+        while let Some(Token::Tag(name, value)) = self.peek() {
+            game.tags.push((name.clone(), value.clone()));
+            self.idx += 1;
+        }
+        for (i, tag) in SEVEN_TAG_ROSTER.iter().enumerate() {
+            if game.tag(tag).is_none() {
+                let default = if *tag == "Result" { "*" } else { "?" };
+                game.tags.insert(i.min(game.tags.len()), (tag.to_string(), default.to_string()));
+            }
+        }
+
+        let start_position = game.start_position()?;
+        let (moves, result) = self.parse_line(start_position, 0)?;
+        game.moves = moves;
+        if let Some(result) = result {
+            game.set_tag("Result", &result);
+        }
+        Ok(game)
+    }
+
+    /// Parses moves until the end of the current variation, or the end of the game for the main line
+    fn parse_line(&mut self, mut position: Position, depth: usize) -> Result<(Vec<PgnNode>, Option<String>), PgnError> {
+        let mut line: Vec<PgnNode> = Vec::new();
+        let mut position_before_last: Option<Position> = None;
+        let mut pending_comment: Option<String> = None;
+
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Tag(_, _) if depth == 0 => break,
+                Token::Tag(name, _) => return Err(PgnError::UnexpectedToken(format!("[{}", name))),
+                Token::Move(san) => {
+                    let mov = position.move_from_san(&san).ok_or_else(|| PgnError::IllegalMove { san: san.clone(), fen: position.to_fen() })?;
+                    let mut node = PgnNode::new(mov, position.move_to_san(mov));
+                    node.comment_before = pending_comment.take();
+                    position_before_last = Some(position.clone());
+                    position.make_move(mov);
+                    line.push(node);
+                },
+                Token::Nag(nag) => {
+                    if let Some(node) = line.last_mut() {
+                        node.nags.push(nag);
+                    }
+                },
+                Token::Comment(comment) => {
+                    match line.last_mut() {
+                        Some(node) if pending_comment.is_none() => {
+                            node.comment = Some(match node.comment.take() {
+                                Some(existing) => existing + " " + &comment,
+                                None => comment,
+                            });
+                        },
+                        _ => pending_comment = Some(comment),
+                    }
+                },
+                Token::VariationStart => {
+                    self.idx += 1;
+                    let before = position_before_last.clone().ok_or(PgnError::VariationWithoutMove)?;
+                    let (variation, _) = self.parse_line(before, depth + 1)?;
+                    line.last_mut().unwrap().variations.push(variation);
+                    continue;
+                },
+                Token::VariationEnd if depth > 0 => {
+                    self.idx += 1;
+                    return Ok((line, None));
+                },
+                Token::VariationEnd => return Err(PgnError::UnbalancedVariation),
+                Token::Result(_) if depth > 0 => return Err(PgnError::UnbalancedVariation),
+                Token::Result(result) => {
+                    self.idx += 1;
+                    return Ok((line, Some(result)));
+                },
+            }
+            self.idx += 1;
+        }
+        if depth > 0 {
+            return Err(PgnError::UnbalancedVariation);
+        }
+        Ok((line, None))
+    }
+}
+
+/// Parses every game in a PGN file
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut parser = PgnParser {
+        tokens: tokenize(text)?,
+        idx: 0,
+    };
+    let mut games = Vec::new();
+    while parser.peek().is_some() {
+        games.push(parser.parse_game()?);
+    }
+    Ok(games)
+}
+
+/// Parses every game in a PGN file on its own, so a broken game doesn't stop the ones after it.
+/// A new game starts at the first tag after the movetext of the previous one.
+/// Lines inside a `{...}` comment are movetext, even when they start with `[`. Only a whole tag pair
+/// ends a comment that is never closed, so the games after it are still read.
+pub fn parse_pgn_games(text: &str) -> Vec<Result<PgnGame, PgnError>> {
+    let mut chunks = Vec::new();
+    let (mut chunk_start, mut offset) = (0, 0);
+    let mut in_movetext = false;
+    let mut in_comment = false;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') && (!in_comment || is_tag_pair(trimmed)) {
+            in_comment = false;
+            if in_movetext {
+                chunks.push(&text[chunk_start..offset]);
+                chunk_start = offset;
+                in_movetext = false;
+            }
+        } else if !trimmed.is_empty() {
+            in_movetext = true;
+            in_comment = ends_in_comment(trimmed, in_comment);
+        }
+        offset += line.len();
+    }
+    chunks.push(&text[chunk_start..]);
+
+    chunks.into_iter().flat_map(|chunk| match parse_pgn(chunk) {
+        Ok(games) => games.into_iter().map(Ok).collect(),
+        Err(err) => vec![Err(err)],
+    }).collect()
+}
+
+/// Whether the line is a tag pair such as `[Event "Casual game"]`
+fn is_tag_pair(line: &str) -> bool {
+    let Some(tag) = line.trim_end().strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) else {
+        return false;
+    };
+    let name_len = tag.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(tag.len());
+    let value = tag[name_len..].trim_start();
+    name_len > 0 && value.len() >= 2 && value.starts_with('"') && value.ends_with('"')
+}
+
+/// Whether a `{...}` comment is still open at the end of a movetext line
+fn ends_in_comment(line: &str, mut in_comment: bool) -> bool {
+    // escape mechanism, the rest of the line is ignored
+    if !in_comment && line.starts_with('%') {
+        return false;
+    }
+    for c in line.chars() {
+        match c {
+            '}' if in_comment => in_comment = false,
+            '{' if !in_comment => in_comment = true,
+            ';' if !in_comment => break,
+            _ => {},
+        }
+    }
+    in_comment
+}
@@ -0,0 +1,170 @@
+use chess_ai::{
+    ai::book::{BookBuilder, BookEntry, OpeningBook},
+    board::{moves::Move, polyglot::polyglot_key},
+    engine::position::Position,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+
+fn polyglot_move(start: &str, target: &str, promotion: u16) -> u16 {
+    let square = |name: &str| {
+        let chars: Vec<char> = name.chars().collect();
+        (chars[1] as u16 - '1' as u16) * 8 + (chars[0] as u16 - 'a' as u16)
+    };
+    square(target) | (square(start) << 6) | (promotion << 12)
+}
+
+fn book_with(fen: &str, moves: &[(&str, &str, u16, u16)]) -> OpeningBook {
+    let key = polyglot_key(&Position::from_fen(fen).unwrap().board);
+    let bytes: Vec<u8> = moves.iter().flat_map(|(start, target, promotion, weight)| {
+        BookEntry { key, mov: polyglot_move(start, target, *promotion), weight: *weight, learn: 0 }.to_bytes()
+    }).collect();
+    OpeningBook::from_bytes(&bytes)
+}
+
+#[test]
+fn matches_polyglot_reference_keys() {
+    // Keys from the Polyglot book format specification
+    let positions = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+        ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+        ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+        ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+        ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+        ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560),
+    ];
+    for (fen, key) in positions {
+        assert_eq!(polyglot_key(&Position::from_fen(fen).unwrap().board), key, "{}", fen);
+    }
+}
+
+#[test]
+fn keys_follow_played_moves() {
+    let mut position = Position::start_pos();
+    for name in ["e2e4", "d7d5", "e4e5", "f7f5"] {
+        let mov = position.move_from_uci(name).unwrap();
+        position.make_move(mov);
+    }
+    assert_eq!(polyglot_key(&position.board), 0x22a48b5a8e47ff78);
+}
+
+#[test]
+fn chooses_weighted_book_moves() {
+    let start_fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    let book = book_with(start_fen, &[("e2", "e4", 0, 3), ("d2", "d4", 0, 1), ("a2", "a3", 0, 0)]);
+    let position = Position::start_pos();
+    assert_eq!(book.len(), 3);
+    assert_eq!(book.book_moves(&position).len(), 3);
+
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut e4_count = 0;
+    for _ in 0..400 {
+        let mov = book.choose_move(&position, &mut rng).unwrap().to_uci();
+        assert!(mov == "e2e4" || mov == "d2d4");
+        if mov == "e2e4" { e4_count += 1; }
+    }
+    assert!((250..350).contains(&e4_count));
+
+    let mut position = Position::start_pos();
+    position.make_move(position.move_from_uci("g1f3").unwrap());
+    assert!(book.choose_move(&position, &mut rng).is_none());
+}
+
+#[test]
+fn decodes_castling_and_promotions() {
+    let fen = "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let book = book_with(fen, &[("e1", "h1", 0, 1), ("e1", "a1", 0, 1), ("b7", "b8", 1, 1), ("b7", "a8", 4, 1)]);
+    let position = Position::from_fen(fen).unwrap();
+    let moves: Vec<Move> = book.book_moves(&position).iter().map(|(mov, _)| *mov).collect();
+    let names: Vec<String> = moves.iter().map(|mov| mov.to_uci()).collect();
+    assert_eq!(names, ["e1g1", "e1c1", "b7b8n", "b7a8q"]);
+    assert!(moves[0].move_flag() == Move::CASTLING && moves[1].move_flag() == Move::CASTLING);
+}
+
+const BUILDER_PGN: &str = r#"[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 1-0
+
+[Result "1/2-1/2"]
+
+1. e4 c5 2. Nf3 1/2-1/2
+
+[Result "0-1"]
+
+1. d4 d5 2. c4 0-1
+
+[Result "*"]
+
+1. c4 *
+"#;
+
+#[test]
+fn builds_book_from_pgn() {
+    let mut builder = BookBuilder::new(3);
+    assert_eq!(builder.add_pgn(BUILDER_PGN), 3);
+    assert_eq!(builder.num_games(), 3);
+
+    let start = Position::start_pos();
+    let start_key = polyglot_key(&start.board);
+    let e4 = builder.move_stats(start_key, polyglot_move("e2", "e4", 0)).unwrap();
+    assert_eq!((e4.games, e4.wins, e4.draws), (2, 1, 1));
+    let d4 = builder.move_stats(start_key, polyglot_move("d2", "d4", 0)).unwrap();
+    assert_eq!((d4.games, d4.wins, d4.draws), (1, 0, 0));
+    // Unfinished games and moves after the ply limit are not counted
+    assert!(builder.move_stats(start_key, polyglot_move("c2", "c4", 0)).is_none());
+    let after_e4_e5 = Position::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+    assert!(builder.move_stats(polyglot_key(&after_e4_e5.board), polyglot_move("g1", "f3", 0)).is_some());
+    let after_nc6 = Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    assert!(builder.move_stats(polyglot_key(&after_nc6.board), polyglot_move("f1", "b5", 0)).is_none());
+
+    // Lost moves score no points and are left out, e4 scores 2 for the win and 1 for the draw
+    let book = builder.build();
+    assert_eq!(book.book_moves(&start), vec![(start.move_from_uci("e2e4").unwrap(), 3)]);
+
+    builder.min_games = 2;
+    let book = builder.build();
+    assert_eq!(book.len(), 1);
+
+    builder.min_games = 1;
+    builder.min_score = 0.8;
+    assert!(builder.build().book_moves(&start).is_empty());
+}
+
+#[test]
+fn skips_unparsable_games() {
+    let pgn = concat!(
+        "[Result \"1-0\"]\n\n1. e4 e5 2. Qh5 1-0\n\n",
+        "[Result \"0-1\"]\n\n1. e4 e5 2. Ke3 0-1\n\n",
+        "[FEN \"not a fen\"]\n[Result \"1-0\"]\n\n1. e4 1-0\n\n",
+        "[Result \"1/2-1/2\"]\n\n1. d4 {unterminated 1/2-1/2\n\n",
+        "[Result \"0-1\"]\n\n1. d4 d5 0-1\n",
+    );
+    let mut builder = BookBuilder::new(10);
+    assert_eq!(builder.add_pgn(pgn), 2);
+    assert_eq!(builder.num_games(), 2);
+    assert_eq!(builder.num_skipped(), 3);
+
+    let start = Position::start_pos();
+    let start_key = polyglot_key(&start.board);
+    assert_eq!(builder.move_stats(start_key, polyglot_move("e2", "e4", 0)).unwrap().games, 1);
+    assert_eq!(builder.move_stats(start_key, polyglot_move("d2", "d4", 0)).unwrap().games, 1);
+}
+
+#[test]
+fn written_book_reads_back() {
+    let mut builder = BookBuilder::new(40);
+    builder.add_pgn("[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O Nf6 5. d4 1-0\n");
+    let book = builder.build();
+    let read_back = OpeningBook::from_bytes(&book.to_bytes());
+    assert_eq!(read_back.len(), book.len());
+
+    // Castling is stored as the king capturing its rook
+    let position = Position::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+    let entries = read_back.entries(polyglot_key(&position.board));
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].mov, polyglot_move("e1", "h1", 0));
+    assert_eq!(read_back.book_moves(&position)[0].0, position.move_from_uci("e1g1").unwrap());
+}
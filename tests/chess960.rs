@@ -0,0 +1,117 @@
+use chess_ai::{
+    board::{coord::Coord, moves::Move, piece::Piece},
+    engine::position::Position,
+    game::result::GameResult,
+    move_gen::perft::CHESS960_PERFT_SUITE,
+    utils::{chess960::{self, NUM_START_POSITIONS, STANDARD_START_POSITION}, fen::START_FEN, pgn::{parse_pgn, PgnGame}},
+};
+
+
+#[test]
+fn numbers_start_positions() {
+    assert_eq!(chess960::start_fen(STANDARD_START_POSITION), START_FEN);
+    assert_eq!(chess960::start_fen(0), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    let mut fens = std::collections::HashSet::new();
+    for id in 0..NUM_START_POSITIONS {
+        let fen = chess960::start_fen(id);
+        fens.insert(fen.clone());
+        let position = Position::from_fen(&fen).unwrap();
+        assert_eq!(position.to_fen(), fen);
+
+        let back_rank = chess960::back_rank(id);
+        let files = |ptype: u8| -> Vec<usize> { (0..8).filter(|file| back_rank[*file] == ptype).collect() };
+        let (rooks, king, bishops) = (files(Piece::ROOK), files(Piece::KING), files(Piece::BISHOP));
+        assert!(rooks[0] < king[0] && king[0] < rooks[1]);
+        assert_ne!(bishops[0] % 2, bishops[1] % 2);
+        assert_eq!(files(Piece::KNIGHT).len(), 2);
+        assert_eq!(files(Piece::QUEEN).len(), 1);
+    }
+    assert_eq!(fens.len(), NUM_START_POSITIONS as usize);
+}
+
+#[test]
+fn writes_shredder_fens_as_x_fen() {
+    let position = Position::from_fen(CHESS960_PERFT_SUITE[0].fen).unwrap();
+    assert_eq!(position.to_fen(), "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9");
+    assert_eq!(position.board.castling_rooks, [Coord::H1, Coord::F1, Coord::H8, Coord::F8]);
+
+    // The inner rook needs its file to be named
+    let position = Position::from_fen("rk2r3/8/8/8/8/8/8/RK2R2R w Ea - 0 1").unwrap();
+    assert_eq!(position.to_fen(), "rk2r3/8/8/8/8/8/8/RK2R2R w Eq - 0 1");
+    assert!(position.board.chess960);
+}
+
+#[test]
+fn castles_by_capturing_own_rook() {
+    let mut position = Position::from_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1").unwrap();
+    assert!(position.board.chess960);
+    let fen = position.to_fen();
+    let key = position.board.current_state.zobrist_key;
+
+    let castle = position.move_from_uci("e1b1").unwrap();
+    assert!(castle.move_flag() == Move::CASTLING);
+    assert_eq!(position.move_to_san(castle), "O-O-O");
+    assert!(!castle.is_capture(&position.board));
+    assert!(position.move_from_uci("b1b8").unwrap().is_capture(&position.board));
+    position.make_move(castle);
+    assert_eq!(position.to_fen(), "1r2k1r1/8/8/8/8/8/8/2KR2R1 b kq - 1 1");
+    assert_ne!(position.board.current_state.zobrist_key, key);
+
+    position.unmake_move(castle);
+    assert_eq!(position.to_fen(), fen);
+    assert_eq!(position.board.current_state.zobrist_key, key);
+}
+
+#[test]
+fn castles_with_king_or_rook_staying_put() {
+    // The king already stands on g1, only the rook moves
+    let mut position = Position::from_fen("4k3/8/8/8/8/8/8/5RKR w K - 0 1").unwrap();
+    assert!(position.move_from_uci("g1h1").is_none());
+    let mut position_open = Position::from_fen("4k3/8/8/8/8/8/8/6KR w K - 0 1").unwrap();
+    let castle = position_open.move_from_uci("g1h1").unwrap();
+    position_open.make_move(castle);
+    assert_eq!(position_open.to_fen(), "4k3/8/8/8/8/8/8/5RK1 b - - 1 1");
+
+    // The rook on d1 stays put while the king walks to c1
+    position = Position::from_fen("4k3/8/8/8/8/8/8/3RK3 w Q - 0 1").unwrap();
+    let castle = position.move_from_uci("e1d1").unwrap();
+    position.make_move(castle);
+    assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+}
+
+#[test]
+fn rook_cannot_shield_castling_king() {
+    // After castling the king on c1 would be attacked by the queen on a1 once the rook leaves b1
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/qR2K3 w Q - 0 1").unwrap();
+    assert!(position.move_from_uci("e1b1").is_none());
+    // The king may not pass over an attacked square
+    let position = Position::from_fen("3rk3/8/8/8/8/8/8/1R2K3 w Q - 0 1").unwrap();
+    assert!(position.move_from_uci("e1b1").is_none());
+}
+
+#[test]
+fn uses_chess960_encoding_when_enabled() {
+    let mut position = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert!(position.move_from_uci("e1g1").is_some());
+    position.set_chess960(true);
+    assert!(position.move_from_uci("e1g1").is_none());
+    assert!(position.move_from_uci("e1h1").unwrap().move_flag() == Move::CASTLING);
+    assert!(Position::chess960_start_pos(0).board.chess960);
+}
+
+#[test]
+fn round_trips_chess960_pgn() {
+    let fen = "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w KQkq - 0 1";
+    let mut position = Position::from_fen(fen).unwrap();
+    let moves: Vec<Move> = ["e2e4", "e7e5", "e1b1", "e8g8"].iter().map(|name| {
+        let mov = position.move_from_uci(name).unwrap();
+        position.make_move(mov);
+        mov
+    }).collect();
+
+    let game = PgnGame::from_moves(Some(fen), &moves, GameResult::Playing).unwrap();
+    assert_eq!(game.tag("Variant"), Some("Chess960"));
+    let parsed = &parse_pgn(&game.to_pgn()).unwrap()[0];
+    assert!(parsed.start_position().unwrap().board.chess960);
+    assert!(parsed.mainline() == moves);
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use chess_ai::{
+    engine::position::Position,
+    game::{clock::{ChessClock, DelayKind, TimeControl, timeout_result}, result::GameResult},
+};
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn fischer_increment() {
+    let mut clock = ChessClock::new(TimeControl::fischer(60, 2));
+    clock.start(0);
+    clock.tick(secs(5));
+    assert_eq!(clock.remaining(0), secs(55));
+    assert_eq!(clock.remaining(1), secs(60));
+
+    clock.press();
+    assert_eq!(clock.active(), Some(1));
+    assert_eq!(clock.remaining(0), secs(57));
+    clock.tick(secs(1));
+    clock.press();
+    assert_eq!(clock.remaining(1), secs(61));
+}
+
+#[test]
+fn delays() {
+    // A simple delay is never charged, a Bronstein delay is given back after the move
+    let mut clock = ChessClock::new(TimeControl::delayed(60, 5, DelayKind::Simple));
+    clock.start(0);
+    clock.tick(secs(3));
+    assert_eq!(clock.remaining(0), secs(60));
+    assert_eq!(clock.delay_left(0), secs(2));
+    clock.tick(secs(4));
+    assert_eq!(clock.remaining(0), secs(58));
+    clock.press();
+    assert_eq!(clock.remaining(0), secs(58));
+
+    let mut clock = ChessClock::new(TimeControl::delayed(60, 5, DelayKind::Bronstein));
+    clock.start(0);
+    clock.tick(secs(3));
+    assert_eq!(clock.remaining(0), secs(57));
+    clock.press();
+    assert_eq!(clock.remaining(0), secs(60));
+    clock.tick(secs(7));
+    clock.press();
+    assert_eq!(clock.remaining(1), secs(58));
+}
+
+#[test]
+fn sessions_and_flags() {
+    let mut clock = ChessClock::new(TimeControl::sessions(2, 60));
+    clock.start(0);
+    for _ in 0..4 {
+        clock.tick(secs(10));
+        clock.press();
+    }
+    // Both sides played two moves, which starts a new session
+    assert_eq!(clock.remaining(0), secs(100));
+    assert_eq!(clock.remaining(1), secs(100));
+
+    clock.tick(secs(101));
+    assert_eq!(clock.flagged(), Some(0));
+    assert_eq!(clock.active(), None);
+    assert_eq!(clock.remaining(0), Duration::ZERO);
+    clock.press();
+    assert_eq!(clock.remaining(1), secs(100));
+
+    assert_eq!(TimeControl::fischer(300, 3).to_string(), "5+3");
+    assert_eq!(TimeControl::fischer(60, 0).to_string(), "1+0");
+    assert_eq!(TimeControl::delayed(900, 10, DelayKind::Simple).to_string(), "15 d10");
+    assert_eq!(TimeControl::sessions(40, 5400).to_string(), "40/90");
+}
+
+#[test]
+fn timeout_results() {
+    let timeout = |fen: &str, white_flagged: bool| timeout_result(&Position::from_fen(fen).unwrap().board, white_flagged);
+    assert_eq!(timeout("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1", false), GameResult::BlackTimeout);
+    assert_eq!(timeout("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1", true), GameResult::TimeoutVsInsufficientMaterial);
+    assert_eq!(timeout("4kn2/8/8/8/8/8/8/4K3 w - - 0 1", true), GameResult::TimeoutVsInsufficientMaterial);
+    // The queen can block its own king in, so a knight alone still mates
+    assert_eq!(timeout("4kn2/8/8/8/8/8/8/4KQ2 w - - 0 1", true), GameResult::WhiteTimeout);
+    assert_eq!(timeout("4kn2/8/8/8/8/8/8/4KB2 w - - 0 1", true), GameResult::WhiteTimeout);
+    // Bishops that all stand on dark squares can't mate, with a light squared bishop they can
+    assert_eq!(timeout("3bk3/8/8/8/8/8/8/2B1K3 w - - 0 1", true), GameResult::TimeoutVsInsufficientMaterial);
+    assert_eq!(timeout("3bk3/8/8/8/8/8/8/4KB2 w - - 0 1", true), GameResult::WhiteTimeout);
+    assert_eq!(timeout("3bkn2/8/8/8/8/8/8/4KQ2 w - - 0 1", true), GameResult::WhiteTimeout);
+    assert_eq!(timeout("4k3/4p3/8/8/8/8/8/4K3 b - - 0 1", true), GameResult::WhiteTimeout);
+    assert!(GameResult::TimeoutVsInsufficientMaterial.is_draw());
+    assert_eq!(GameResult::WhiteTimeout.pgn_result(), "0-1");
+}
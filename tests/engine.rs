@@ -0,0 +1,65 @@
+use std::{sync::atomic::Ordering, thread, time::{Duration, Instant}};
+
+use chess_ai::{
+    ai::ai_player::AIVersion,
+    board::moves::Move,
+    engine::{Engine, position::Position, registry::create_engine, search::SearchLimits},
+};
+
+
+#[test]
+fn every_version_plays_a_legal_move() {
+    let position = Position::from_fen("r3k3/1pp2p2/8/3pP3/8/2N5/1PP2P2/4K2R w Kq d6 0 1").unwrap();
+    // All versions exist side by side, like both players of a versus game
+    let mut engines: Vec<Box<dyn Engine>> = AIVersion::ALL.iter().map(|version| create_engine(*version)).collect();
+    for engine in engines.iter_mut() {
+        engine.new_game();
+        engine.set_position(&position);
+        let result = engine.go(SearchLimits { think_time_ms: Some(50), depth: Some(3), ..Default::default() });
+        assert!(position.legal_moves().contains(&result.best_move), "{:?} played an illegal move", engine.version());
+    }
+    for (engine, version) in engines.iter().zip(AIVersion::ALL) {
+        assert_eq!(engine.version(), version);
+    }
+}
+
+#[test]
+fn shared_searcher_versions_find_mate_in_one() {
+    // V4 to V12 run one searcher with different features switched on. V4 is left out: it searches
+    // unordered, so an iteration cut off by the clock keeps whichever move it tried first
+    let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    for version in &AIVersion::ALL[5..13] {
+        let mut engine = create_engine(*version);
+        engine.set_position(&position);
+        let result = engine.go(SearchLimits { think_time_ms: Some(100), ..Default::default() });
+        assert_eq!(result.best_move.to_uci(), "a1a8", "{:?} missed the mate", version);
+    }
+}
+
+#[test]
+fn core_versions_accept_options() {
+    let mut engine = create_engine(AIVersion::V13);
+    assert!(engine.set_option("Threads", "2").is_ok());
+    assert!(engine.set_option("Hash", "not a number").is_err());
+    assert!(engine.set_option("Ponder", "true").is_err());
+
+    let mut engine = create_engine(AIVersion::V9);
+    assert!(engine.set_option("Threads", "2").is_err());
+}
+
+#[test]
+fn every_version_stops_when_flagged() {
+    let position = Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    for version in AIVersion::ALL {
+        let mut engine = create_engine(version);
+        engine.set_position(&position);
+        let stop_flag = engine.stop_flag();
+        let start = Instant::now();
+        let search = thread::spawn(move || engine.go(SearchLimits { think_time_ms: Some(60_000), ..Default::default() }));
+        thread::sleep(Duration::from_millis(50));
+        stop_flag.store(true, Ordering::Relaxed);
+        let result = search.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10), "{:?} ignored the stop flag", version);
+        assert_ne!(result.best_move, Move::NULL, "{:?} stopped without a move", version);
+    }
+}
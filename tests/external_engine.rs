@@ -0,0 +1,119 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use chess_ai::{
+    board::moves::Move,
+    engine::position::Position,
+    uci::{client::{UciClient, UciClientError}, command::{UciCommand, GoParams}},
+};
+
+const SCRIPTED_ENGINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/scripted_engine.sh");
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+
+fn line(names: &[&str]) -> Vec<Move> {
+    let mut position = Position::start_pos();
+    names.iter().map(|name| {
+        let mov = position.move_from_uci(name).unwrap();
+        position.make_move(mov);
+        mov
+    }).collect()
+}
+
+#[test]
+fn commands_round_trip() {
+    let commands = [
+        UciCommand::Uci,
+        UciCommand::IsReady,
+        UciCommand::UciNewGame,
+        UciCommand::Position { fen: None, moves: Vec::new() },
+        UciCommand::Position { fen: Some(String::from("8/8/8/8/8/2k5/8/K7 w - - 0 1")), moves: vec![String::from("a1a2")] },
+        UciCommand::SetOption { name: String::from("UCI_Chess960"), value: Some(String::from("true")) },
+        UciCommand::SetOption { name: String::from("Clear Hash"), value: None },
+        UciCommand::Go(GoParams::default()),
+        UciCommand::Go(GoParams { wtime: Some(60000), btime: Some(59000), winc: Some(100), binc: Some(100), moves_to_go: Some(20), ..Default::default() }),
+        UciCommand::Go(GoParams { move_time: Some(250), depth: Some(12), nodes: Some(10000), ..Default::default() }),
+        UciCommand::Go(GoParams { infinite: true, ..Default::default() }),
+        UciCommand::Stop,
+        UciCommand::Quit,
+    ];
+    for command in commands {
+        assert_eq!(UciCommand::parse(&command.to_string()), command);
+    }
+    assert_eq!(UciCommand::Position { fen: None, moves: vec![String::from("e2e4")] }.to_string(), "position startpos moves e2e4");
+}
+
+#[test]
+fn plays_the_scripted_game() {
+    let mut client = UciClient::launch(SCRIPTED_ENGINE).unwrap();
+    assert_eq!(client.name.as_deref(), Some("Scripted Engine"));
+    client.new_game().unwrap();
+
+    let params = GoParams { move_time: Some(10), ..Default::default() };
+    client.go(None, &[], params).unwrap();
+    assert_eq!(client.wait_best_move(TIMEOUT).unwrap(), "e2e4");
+    client.go(None, &line(&["e2e4", "e7e5"]), params).unwrap();
+    assert!(client.is_searching());
+    assert_eq!(client.wait_best_move(TIMEOUT).unwrap(), "g1f3");
+    assert!(!client.is_searching());
+}
+
+#[test]
+fn polls_without_blocking() {
+    let mut client = UciClient::launch(SCRIPTED_ENGINE).unwrap();
+    client.go(None, &[], GoParams { infinite: true, ..Default::default() }).unwrap();
+    assert_eq!(client.poll_best_move().unwrap(), None);
+    client.stop().unwrap();
+    assert!(!client.is_searching());
+
+    // The stopped search's best move doesn't answer the next one
+    client.go(None, &line(&["e2e4"]), GoParams::default()).unwrap();
+    let best_move = loop {
+        if let Some(best_move) = client.poll_best_move().unwrap() {
+            break best_move;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!(best_move, "e7e5");
+}
+
+#[test]
+fn reports_crashes_and_missing_engines() {
+    let mut client = UciClient::launch(SCRIPTED_ENGINE).unwrap();
+    client.set_option("Crash", "true").unwrap();
+    assert!(matches!(client.wait_best_move(TIMEOUT), Err(UciClientError::Disconnected)));
+
+    assert!(matches!(UciClient::launch("/nonexistent/engine"), Err(UciClientError::Io(_))));
+}
+
+#[test]
+fn plays_against_the_uci_binary() {
+    let mut client = UciClient::launch(env!("CARGO_BIN_EXE_uci")).unwrap();
+    assert!(client.name.as_deref().unwrap().starts_with("Chess Engine"));
+    client.new_game().unwrap();
+
+    let moves = line(&["e2e4", "e7e5", "g1f3"]);
+    client.go(None, &moves, GoParams { move_time: Some(50), ..Default::default() }).unwrap();
+    let best_move = client.wait_best_move(TIMEOUT).unwrap();
+
+    let mut position = Position::start_pos();
+    for mov in moves {
+        position.make_move(mov);
+    }
+    assert!(position.move_from_uci(&best_move).is_some());
+}
+
+#[test]
+fn uci_binary_holds_infinite_best_move_until_stop() {
+    let mut client = UciClient::launch(env!("CARGO_BIN_EXE_uci")).unwrap();
+    // Mate in one, the search runs out of work long before it is stopped
+    let fen = "7k/5Q2/6K1/8/8/8/8/8 w - - 0 1";
+    client.go(Some(fen), &[], GoParams { infinite: true, ..Default::default() }).unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(client.poll_best_move().unwrap(), None);
+
+    client.send(&UciCommand::Stop).unwrap();
+    let best_move = client.wait_best_move(TIMEOUT).unwrap();
+    assert!(Position::from_fen(fen).unwrap().move_from_uci(&best_move).is_some());
+}
@@ -0,0 +1,84 @@
+use chess_ai::{
+    board::{piece::Piece, moves::Move},
+    engine::position::Position,
+    move_gen::perft::PERFT_SUITE,
+    utils::fen::{position_from_fen, FenError, START_FEN},
+};
+
+
+fn fen_error(fen: &str) -> FenError {
+    position_from_fen(fen).err().unwrap()
+}
+
+#[test]
+fn round_trips_reference_positions() {
+    assert_eq!(Position::from_fen(START_FEN).unwrap().to_fen(), START_FEN);
+    for test in PERFT_SUITE.iter() {
+        assert_eq!(Position::from_fen(test.fen).unwrap().to_fen(), test.fen);
+    }
+}
+
+#[test]
+fn round_trips_versus_positions() {
+    let versus_positions = std::fs::read_to_string("assets/logic/versus_positions.txt").unwrap();
+    for fen in versus_positions.lines().filter(|line| !line.is_empty()) {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+    }
+}
+
+#[test]
+fn tracks_counters_and_en_passant_after_moves() {
+    let mut position = Position::start_pos();
+    for name in ["e2e4", "c7c5", "g1f3"] {
+        let mov = Move::from_uci(name, &position.legal_moves()).unwrap();
+        position.make_move(mov);
+    }
+    assert_eq!(position.to_fen(), "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+
+    let mut position = Position::from_fen("4k3/8/8/8/4p3/8/3P4/4K3 w - - 0 40").unwrap();
+    let mov = Move::from_uci("d2d4", &position.legal_moves()).unwrap();
+    position.make_move(mov);
+    assert_eq!(position.to_fen(), "4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 40");
+}
+
+#[test]
+fn optional_fields_default() {
+    let pos = position_from_fen("4k3/8/8/8/8/8/8/4K3 b").unwrap();
+    assert!(!pos.white_to_move);
+    assert!(!pos.white_castle_kingside && !pos.black_castle_queenside);
+    assert_eq!(pos.ep_file, 0);
+    assert_eq!(pos.fifty_move_ply_count, 0);
+    assert_eq!(pos.move_count, 1);
+}
+
+#[test]
+fn rejects_malformed_fens() {
+    assert_eq!(fen_error(""), FenError::MissingFields);
+    assert_eq!(fen_error("4k3/8/8/8/8/8/4K3 w - - 0 1"), FenError::WrongRankCount(7));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K4 w - - 0 1"), FenError::BadRankLength { rank: 1, length: 9 });
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2 w - - 0 1"), FenError::BadRankLength { rank: 1, length: 7 });
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2x w - - 0 1"), FenError::InvalidPiece('x'));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/8 w - - 0 1"), FenError::MissingKing(Piece::WHITE));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"), FenError::TooManyKings(Piece::WHITE));
+    assert_eq!(fen_error("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"), FenError::PawnOnBackRank(String::from("a8")));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), FenError::InvalidSideToMove(String::from("x")));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K3 w - - x 1"), FenError::InvalidHalfmoveClock(String::from("x")));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), FenError::InvalidFullmoveNumber(String::from("0")));
+}
+
+#[test]
+fn rejects_illegal_positions() {
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2R w KX - 0 1"), FenError::InvalidCastlingRights(String::from("KX")));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2R w KK - 0 1"), FenError::InvalidCastlingRights(String::from("KK")));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2R w Q - 0 1"), FenError::InconsistentCastlingRights('Q'));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/R4K2 w K - 0 1"), FenError::InconsistentCastlingRights('K'));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2R w B - 0 1"), FenError::InconsistentCastlingRights('B'));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/4K3/7R w H - 0 1"), FenError::InconsistentCastlingRights('H'));
+    assert_eq!(fen_error("4k3/8/8/8/4p3/8/8/4K3 b - e3 0 1"), FenError::InvalidEnPassant(String::from("e3")));
+    assert_eq!(fen_error("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"), FenError::InvalidEnPassant(String::from("e3")));
+    assert_eq!(fen_error("4k3/8/8/8/4P3/8/8/4K3 b - e9 0 1"), FenError::InvalidEnPassant(String::from("e9")));
+    assert_eq!(fen_error("4k3/8/8/8/8/8/8/4K2r b - - 0 1"), FenError::SideNotToMoveInCheck);
+    assert_eq!(fen_error("4k3/8/8/8/1b6/8/8/4K3 b - - 0 1"), FenError::SideNotToMoveInCheck);
+    assert_eq!(fen_error("4k3/3P4/8/8/8/8/8/4K3 w - - 0 1"), FenError::SideNotToMoveInCheck);
+    assert!(position_from_fen("4k3/8/8/8/8/8/8/4K2r w - - 0 1").is_ok());
+}
@@ -0,0 +1,30 @@
+#!/bin/sh
+# Stand-in UCI engine for the client tests. It plays the next move of a scripted game,
+# answers infinite searches only once stopped and exits on "setoption name Crash".
+script="e2e4 e7e5 g1f3 b8c6 f1b5"
+ply=0
+pending=""
+
+# Moves past the end of the script are illegal on purpose
+best_move() {
+    set -- $script
+    if [ "$ply" -lt "$#" ]; then
+        shift "$ply"
+        echo "bestmove $1"
+    else
+        echo "bestmove a1a1"
+    fi
+}
+
+while read -r line; do
+    case "$line" in
+        uci) echo "id name Scripted Engine"; echo "id author Tests"; echo "uciok" ;;
+        isready) echo "readyok" ;;
+        "setoption name Crash"*) exit 1 ;;
+        position*) ply=$(echo "$line" | awk '{ n = 0; for (i = 1; i <= NF; i++) if ($i == "moves") n = NF - i; print n }') ;;
+        "go infinite"*) pending=1 ;;
+        go*) echo "info depth 1 score cp 0"; best_move ;;
+        stop) if [ -n "$pending" ]; then pending=""; best_move; fi ;;
+        quit) exit 0 ;;
+    esac
+done
@@ -0,0 +1,80 @@
+use chess_ai::{
+    board::moves::Move,
+    engine::position::Position,
+    move_gen::perft::PERFT_SUITE,
+};
+
+
+fn san_of(fen: &str, uci: &str) -> String {
+    let mut position = Position::from_fen(fen).unwrap();
+    let mov = position.move_from_uci(uci).unwrap();
+    position.move_to_san(mov)
+}
+
+fn uci_of(fen: &str, san: &str) -> Option<String> {
+    let position = Position::from_fen(fen).unwrap();
+    position.move_from_san(san).map(|mov| mov.to_uci())
+}
+
+#[test]
+fn formats_san() {
+    let start = Position::start_pos().to_fen();
+    assert_eq!(san_of(&start, "e2e4"), "e4");
+    assert_eq!(san_of(&start, "g1f3"), "Nf3");
+
+    let kiwipete = PERFT_SUITE[1].fen;
+    assert_eq!(san_of(kiwipete, "e1g1"), "O-O");
+    assert_eq!(san_of(kiwipete, "e1c1"), "O-O-O");
+    assert_eq!(san_of(kiwipete, "e2a6"), "Bxa6");
+    assert_eq!(san_of(kiwipete, "d5e6"), "dxe6");
+
+    assert_eq!(san_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), "exd6");
+    assert_eq!(san_of("8/P3k3/8/8/8/8/8/4K3 w - - 0 1", "a7a8n"), "a8=N");
+    assert_eq!(san_of("8/P3k3/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), "a8=Q");
+    assert_eq!(san_of("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+    assert_eq!(san_of("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "h5f7"), "Qxf7#");
+}
+
+#[test]
+fn disambiguates_san() {
+    assert_eq!(san_of("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "a1d1"), "Rad1");
+    assert_eq!(san_of("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+    assert_eq!(san_of("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "a1b2"), "Qa1b2");
+    assert_eq!(san_of("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "c1b2"), "Qcb2");
+}
+
+#[test]
+fn parses_san() {
+    let start = Position::start_pos().to_fen();
+    assert_eq!(uci_of(&start, "e4").as_deref(), Some("e2e4"));
+    assert_eq!(uci_of(&start, "Nf3").as_deref(), Some("g1f3"));
+    assert_eq!(uci_of(&start, "e5"), None);
+    assert_eq!(uci_of(&start, "Ke2"), None);
+
+    let kiwipete = PERFT_SUITE[1].fen;
+    assert_eq!(uci_of(kiwipete, "O-O").as_deref(), Some("e1g1"));
+    assert_eq!(uci_of(kiwipete, "0-0-0").as_deref(), Some("e1c1"));
+    assert_eq!(uci_of(kiwipete, "Bxa6!?").as_deref(), Some("e2a6"));
+    assert_eq!(uci_of(kiwipete, "Nxf7").as_deref(), Some("e5f7"));
+
+    let promotion = "8/P3k3/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(uci_of(promotion, "a8=Q").as_deref(), Some("a7a8q"));
+    assert_eq!(uci_of(promotion, "a8N").as_deref(), Some("a7a8n"));
+    assert_eq!(uci_of(promotion, "a8=b").as_deref(), Some("a7a8b"));
+    assert_eq!(uci_of(promotion, "a8"), None);
+
+    assert_eq!(uci_of("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "Rd1"), None);
+    assert_eq!(uci_of("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "Rhd1+").as_deref(), Some("h1d1"));
+}
+
+#[test]
+fn round_trips_all_legal_moves() {
+    for test in PERFT_SUITE.iter() {
+        let mut position = Position::from_fen(test.fen).unwrap();
+        for mov in position.legal_moves() {
+            let san = position.move_to_san(mov);
+            assert_eq!(position.move_from_san(&san), Some(mov), "{} in {}", san, test.fen);
+            assert_eq!(Move::from_uci(&mov.to_uci(), &position.legal_moves()), Some(mov));
+        }
+    }
+}
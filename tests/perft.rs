@@ -0,0 +1,98 @@
+use chess_ai::{
+    engine::{position::Position, tables::LookupTables},
+    move_gen::perft::{perft, perft_divide, PerftPosition, CHESS960_PERFT_SUITE, PERFT_SUITE},
+};
+
+
+fn check_perft_position(name: &str) {
+    let test: &PerftPosition = PERFT_SUITE.iter().find(|test| test.name == name).unwrap();
+    let tables = LookupTables::get();
+    let mut position = Position::from_fen(test.fen).unwrap();
+
+    for (depth_idx, expected) in test.expected.iter().enumerate() {
+        let depth = depth_idx as u8 + 1;
+        let nodes = perft(&mut position.board, depth, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+        assert_eq!(nodes, *expected, "{} at depth {}", test.name, depth);
+    }
+}
+
+#[test]
+fn perft_start_position() {
+    check_perft_position("Start Position");
+}
+
+#[test]
+fn perft_kiwipete() {
+    check_perft_position("Kiwipete");
+}
+
+#[test]
+fn perft_en_passant_and_pins() {
+    check_perft_position("En Passant and Pins");
+}
+
+#[test]
+fn perft_promotions_and_castling() {
+    check_perft_position("Promotions and Castling");
+    check_perft_position("Promotions and Castling (Mirrored)");
+}
+
+#[test]
+fn perft_discovered_checks() {
+    check_perft_position("Discovered Checks");
+}
+
+#[test]
+fn perft_middlegame() {
+    check_perft_position("Middlegame");
+}
+
+#[test]
+fn perft_en_passant_discovered_check() {
+    check_perft_position("En Passant Discovered Check");
+}
+
+#[test]
+fn perft_divide_sums_to_perft() {
+    let tables = LookupTables::get();
+    let mut position = Position::from_fen(PERFT_SUITE[1].fen).unwrap();
+
+    let divide = perft_divide(&mut position.board, 3, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+    assert_eq!(divide.len() as u64, PERFT_SUITE[1].expected[0]);
+    assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), PERFT_SUITE[1].expected[2]);
+}
+
+#[test]
+fn perft_leaves_board_unchanged() {
+    let tables = LookupTables::get();
+    let mut position = Position::from_fen(PERFT_SUITE[1].fen).unwrap();
+    let key_before = position.board.current_state.zobrist_key;
+
+    perft(&mut position.board, 3, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+    assert_eq!(position.board.current_state.zobrist_key, key_before);
+    assert_eq!(position.legal_moves().len() as u64, PERFT_SUITE[1].expected[0]);
+}
+
+#[test]
+fn perft_chess960() {
+    let tables = LookupTables::get();
+    for test in CHESS960_PERFT_SUITE.iter() {
+        let mut position = Position::from_fen(test.fen).unwrap();
+        position.set_chess960(true);
+        for (depth_idx, expected) in test.expected.iter().enumerate() {
+            let depth = depth_idx as u8 + 1;
+            let nodes = perft(&mut position.board, depth, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+            assert_eq!(nodes, *expected, "{} at depth {}", test.name, depth);
+        }
+    }
+}
+
+#[test]
+fn perft_standard_castling_encoding_matches_chess960() {
+    let tables = LookupTables::get();
+    let mut position = Position::from_fen(PERFT_SUITE[1].fen).unwrap();
+    let standard = perft(&mut position.board, 3, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+    position.set_chess960(true);
+    let chess960 = perft(&mut position.board, 3, &tables.precomp, &tables.bbutils, &tables.magic, &tables.zobrist);
+    assert_eq!(standard, chess960);
+}
@@ -0,0 +1,133 @@
+use chess_ai::{
+    engine::position::Position,
+    game::result::GameResult,
+    utils::pgn::{parse_pgn, parse_pgn_games, PgnError, PgnGame},
+};
+
+
+fn moves_from_uci(fen: Option<&str>, moves: &[&str]) -> Vec<chess_ai::board::moves::Move> {
+    let mut position = match fen {
+        Some(fen) => Position::from_fen(fen).unwrap(),
+        None => Position::start_pos(),
+    };
+    moves.iter().map(|uci| {
+        let mov = position.move_from_uci(uci).unwrap();
+        position.make_move(mov);
+        mov
+    }).collect()
+}
+
+#[test]
+fn exports_and_reimports_games() {
+    let moves = moves_from_uci(None, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let mut game = PgnGame::from_moves(None, &moves, GameResult::WhiteIsMated).unwrap();
+    game.set_tag("White", "V12");
+    game.set_tag("Black", "V11");
+
+    let pgn = game.to_pgn();
+    assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \""));
+    assert!(pgn.contains("[White \"V12\"]\n[Black \"V11\"]\n[Result \"0-1\"]\n[Termination \"normal\"]\n"));
+    assert!(pgn.contains("1. f3 e5 2. g4 Qh4# 0-1"));
+
+    let games = parse_pgn(&pgn).unwrap();
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0], game);
+    assert_eq!(games[0].mainline(), moves);
+}
+
+#[test]
+fn exports_custom_start_positions() {
+    let fen = "4k3/8/8/8/8/8/4K3/R6R b - - 3 40";
+    let moves = moves_from_uci(Some(fen), &["e8d7", "h1h7"]);
+    let game = PgnGame::from_moves(Some(fen), &moves, GameResult::Playing).unwrap();
+
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4K3/R6R b - - 3 40\"]\n"));
+    assert!(pgn.contains("40... Kd7 41. Rh7+ *"));
+
+    let games = parse_pgn(&pgn).unwrap();
+    assert_eq!(games[0].start_position().unwrap().to_fen(), fen);
+    assert_eq!(games[0].mainline(), moves);
+}
+
+#[test]
+fn parses_comments_nags_and_variations() {
+    let pgn = "\
+[Event \"Annotated \\\"Test\\\"\"]
+[Result \"1-0\"]
+
+% this line is ignored
+{Opening} 1. e4 e5 2. Nf3!? {Develops} (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 $1 ; rest of line
+3.Bb5 a6?! 1-0
+";
+    let games = parse_pgn(pgn).unwrap();
+    let game = &games[0];
+    assert_eq!(game.tag("Event"), Some("Annotated \"Test\""));
+    assert_eq!(game.tag("Site"), Some("?"));
+    assert_eq!(game.result(), "1-0");
+
+    let sans: Vec<&str> = game.moves.iter().map(|node| node.san.as_str()).collect();
+    assert_eq!(sans, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+    assert_eq!(game.moves[0].comment_before.as_deref(), Some("Opening"));
+    assert_eq!(game.moves[2].nags, [5]);
+    assert_eq!(game.moves[2].comment.as_deref(), Some("Develops"));
+    assert_eq!(game.moves[3].nags, [1]);
+    assert_eq!(game.moves[5].nags, [6]);
+
+    let variation = &game.moves[2].variations[0];
+    let variation_sans: Vec<&str> = variation.iter().map(|node| node.san.as_str()).collect();
+    assert_eq!(variation_sans, ["f4", "exf4", "Nf3"]);
+    assert_eq!(variation[1].variations[0][0].san, "d5");
+
+    let exported = game.to_pgn();
+    assert!(exported.lines().all(|line| line.len() <= 80));
+    let movetext = exported.split("\n\n").nth(1).unwrap().split_whitespace().collect::<Vec<&str>>().join(" ");
+    assert_eq!(movetext, "{Opening} 1. e4 e5 2. Nf3 $5 {Develops} (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 $1 3. Bb5 a6 $6 1-0");
+    assert_eq!(&parse_pgn(&exported).unwrap()[0], game);
+}
+
+#[test]
+fn parses_castling_written_with_zeros() {
+    let pgn = "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4.0-0 d6 5. d3 Be6 6. Be3 Qd7 7. Nc3 0-0-0!? *";
+    let games = parse_pgn(pgn).unwrap();
+    let moves = moves_from_uci(None, &[
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "e1g1", "d7d6", "d2d3", "c8e6",
+        "c1e3", "d8d7", "b1c3", "e8c8",
+    ]);
+    assert_eq!(games[0].mainline(), moves);
+    assert_eq!(games[0].moves[13].nags, [5]);
+
+    assert!(matches!(parse_pgn("1. 0-0 *"), Err(PgnError::IllegalMove { .. })));
+}
+
+#[test]
+fn parses_multiple_games() {
+    let pgn = "[White \"A\"]\n\n1. d4 d5 1/2-1/2\n\n[White \"B\"]\n\n1. e4 *\n\n1. c4 0-1";
+    let games = parse_pgn(pgn).unwrap();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].tag("White"), Some("A"));
+    assert_eq!(games[0].result(), "1/2-1/2");
+    assert_eq!(games[1].tag("White"), Some("B"));
+    assert_eq!(games[1].moves.len(), 1);
+    assert_eq!(games[2].moves[0].san, "c4");
+    assert_eq!(games[2].result(), "0-1");
+}
+
+#[test]
+fn splits_games_outside_comments() {
+    let pgn = "[White \"A\"]\n\n1. e4 {Book move\n[%clk 0:01:00]} e5 ; [not a tag\n2. Nf3 *\n\n[White \"B\"]\n\n1. d4 *\n";
+    let games: Vec<PgnGame> = parse_pgn_games(pgn).into_iter().map(Result::unwrap).collect();
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].moves.len(), 3);
+    assert_eq!(games[0].moves[0].comment.as_deref(), Some("Book move [%clk 0:01:00]"));
+    assert_eq!(games[1].tag("White"), Some("B"));
+}
+
+#[test]
+fn rejects_invalid_pgn() {
+    assert!(matches!(parse_pgn("1. e4 e4 *"), Err(PgnError::IllegalMove { .. })));
+    assert!(matches!(parse_pgn("1. e4 (1. d4 *"), Err(PgnError::UnbalancedVariation)));
+    assert!(matches!(parse_pgn("1. e4 {open"), Err(PgnError::UnterminatedComment)));
+    assert!(matches!(parse_pgn("[FEN \"8/8/8 w\"]\n*"), Err(PgnError::InvalidFen(_))));
+    assert!(matches!(parse_pgn("( 1. e4 ) *"), Err(PgnError::VariationWithoutMove)));
+}
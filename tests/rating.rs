@@ -0,0 +1,99 @@
+use chess_ai::{
+    game::{result::GameResult, sprt::score_to_elo},
+    tournament::{rating::{RatingList, PRIOR_DRAWS}, standings::Standings},
+};
+
+
+fn standings(names: &[&str], results: &[(usize, usize, GameResult, u32)]) -> Standings {
+    let mut standings = Standings::new(names.iter().map(|name| name.to_string()).collect());
+    for (white, black, result, count) in results.iter() {
+        for _ in 0..*count {
+            standings.add_result(*white, *black, *result);
+        }
+    }
+    standings
+}
+
+#[test]
+fn two_players_match_the_score() {
+    // 60 wins, 30 draws and 10 losses
+    let standings = standings(&["V0", "V1"], &[
+        (1, 0, GameResult::BlackIsMated, 60),
+        (1, 0, GameResult::Stalemate, 30),
+        (1, 0, GameResult::WhiteIsMated, 10),
+    ]);
+    let ratings = RatingList::compute_anchored(&standings);
+    let score = (75.0 + PRIOR_DRAWS / 2.0) / (100.0 + PRIOR_DRAWS);
+    assert_eq!(ratings.ratings[0].elo, 0.0);
+    assert_eq!(ratings.ratings[0].error, 0.0);
+    assert!((ratings.ratings[1].elo - score_to_elo(score)).abs() < 0.01);
+    assert!(ratings.ratings[1].error > 30.0 && ratings.ratings[1].error < 100.0);
+    assert_eq!(ratings.ranks(), vec![2, 1]);
+    assert_eq!(ratings.gain(1), Some(ratings.ratings[1].elo));
+}
+
+#[test]
+fn even_results_give_equal_ratings() {
+    let standings = standings(&["V0", "V1", "V2"], &[
+        (0, 1, GameResult::BlackIsMated, 5),
+        (1, 0, GameResult::BlackIsMated, 5),
+        (1, 2, GameResult::Repetition, 10),
+        (2, 0, GameResult::FiftyMoveRule, 10),
+    ]);
+    let ratings = RatingList::compute_anchored(&standings);
+    for rating in ratings.ratings.iter() {
+        assert!(rating.elo.abs() < 1e-6);
+    }
+}
+
+#[test]
+fn ratings_are_finite_and_ordered() {
+    // Every version beats the previous one every time, the prior keeps the ratings finite
+    let standings = standings(&["V0", "V1", "V2", "V3"], &[
+        (1, 0, GameResult::BlackIsMated, 10),
+        (2, 1, GameResult::BlackIsMated, 10),
+        (3, 2, GameResult::BlackIsMated, 10),
+        (0, 3, GameResult::WhiteIsMated, 10),
+    ]);
+    let ratings = RatingList::compute_anchored(&standings);
+    for pair in ratings.ratings.windows(2) {
+        assert!(pair[1].elo > pair[0].elo);
+        assert!(pair[1].elo.is_finite() && pair[1].error.is_finite());
+    }
+    // Ratings further away from the anchor are less certain
+    assert!(ratings.ratings[2].error > ratings.ratings[1].error);
+}
+
+#[test]
+fn anchors_at_v0_anywhere_in_the_list() {
+    let standings = standings(&["V2", "V0"], &[(0, 1, GameResult::BlackIsMated, 8), (1, 0, GameResult::Stalemate, 2)]);
+    let ratings = RatingList::compute_anchored(&standings);
+    assert_eq!(ratings.anchor, 1);
+    assert_eq!(ratings.ratings[1].elo, 0.0);
+    assert!(ratings.ratings[0].elo > 0.0);
+}
+
+#[test]
+fn illegal_moves_lose() {
+    let standings = standings(&["V0", "V1"], &[(0, 1, GameResult::WhiteIllegalMove, 1), (1, 0, GameResult::BlackIllegalMove, 1)]);
+    assert_eq!(standings.total(1).wins, 2);
+    assert_eq!(GameResult::WhiteIllegalMove.pgn_result(), "0-1");
+}
+
+#[test]
+fn writes_reports() {
+    let standings = standings(&["V0", "V8"], &[(1, 0, GameResult::BlackIsMated, 4)]);
+    let ratings = RatingList::compute_anchored(&standings);
+    let csv = ratings.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "Rank,Name,Feature,Elo,Error,Gain,Games,Score");
+    assert!(lines[1].starts_with("1,V8,Quiescence Search,"));
+    assert!(lines[2].starts_with("2,V0,Random Moves,0.0,0.0,,4,"));
+
+    let markdown = ratings.to_markdown();
+    assert!(markdown.starts_with("| Rank | Name | Feature | Elo | Gain | Games | Score |"));
+    let rows: Vec<&str> = markdown.lines().skip(2).take(2).collect();
+    assert!(rows[0].starts_with("| 1 | V8 | Quiescence Search |"));
+    assert!(rows[1].starts_with("| 2 | V0 | Random Moves |"));
+    assert!(markdown.contains("Elo relative to V0"));
+}
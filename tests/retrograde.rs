@@ -0,0 +1,112 @@
+use std::{env, fs};
+
+use chess_ai::{
+    engine::{position::Position, search::{CoreEngine, SearchLimits}},
+    tablebase::{Wdl, retrograde::{Dtm, EndgameTable, EndgameTables, Material, TableGenerator}},
+};
+
+fn generate(name: &str) -> TableGenerator {
+    let mut generator = TableGenerator::new();
+    generator.generate(&Material::parse(name).unwrap());
+    generator
+}
+
+fn probe(tables: &EndgameTables, fen: &str) -> Option<Dtm> {
+    tables.probe(&Position::from_fen(fen).unwrap().board)
+}
+
+#[test]
+fn parses_material() {
+    assert_eq!(Material::parse("KBNK").unwrap().to_string(), "KBNK");
+    assert_eq!(Material::parse("KNBvK").unwrap().to_string(), "KBNK");
+    assert_eq!(Material::parse("KKR").unwrap().to_string(), "KRK");
+    assert_eq!(Material::parse("KPvKR").unwrap().to_string(), "KRKP");
+    assert!(Material::parse("KPKP").is_none());
+    assert!(Material::parse("KQRBK").is_none());
+    assert!(Material::parse("KQ").is_none());
+}
+
+#[test]
+fn known_longest_mates() {
+    // Mate takes at most 10 moves with a queen and 16 with a rook
+    let generator = generate("KQK");
+    let kqk = generator.tables().get(&Material::parse("KQK").unwrap()).unwrap();
+    assert_eq!(kqk.longest_mate(), 19);
+    assert_eq!(kqk.count(true, Wdl::Loss) + kqk.count(true, Wdl::Draw), 0);
+    assert!(kqk.count(false, Wdl::Draw) > 0);
+    let kk = generator.tables().get(&Material::parse("KK").unwrap()).unwrap();
+    assert_eq!(kk.count(true, Wdl::Win) + kk.count(false, Wdl::Win), 0);
+
+    let generator = generate("KRK");
+    let krk = generator.tables().get(&Material::parse("KRK").unwrap()).unwrap();
+    assert_eq!(krk.longest_mate(), 31);
+    assert_eq!(krk.count(true, Wdl::Loss) + krk.count(true, Wdl::Draw), 0);
+}
+
+#[test]
+#[ignore = "generating KBNK takes a while, run with --ignored"]
+fn known_longest_mates_of_larger_tables() {
+    // Mate takes at most 33 moves with bishop and knight and 28 with a pawn
+    let generator = generate("KBNK");
+    let kbnk = generator.tables().get(&Material::parse("KBNK").unwrap()).unwrap();
+    assert_eq!(kbnk.longest_mate(), 65);
+
+    let generator = generate("KPK");
+    let kpk = generator.tables().get(&Material::parse("KPK").unwrap()).unwrap();
+    assert_eq!(kpk.longest_mate(), 55);
+}
+
+#[test]
+fn probes_positions() {
+    let tables = generate("KPK").into_tables();
+    // Promotions need the queen, rook, bishop and knight tables, and captures the bare kings
+    assert_eq!(tables.len(), 6);
+
+    assert_eq!(probe(&tables, "k7/8/1K6/8/8/8/8/6Q1 w - - 0 1"), Some(Dtm::Win(1)));
+    assert_eq!(probe(&tables, "7k/5K2/6Q1/8/8/8/8/8 b - - 0 1"), Some(Dtm::Draw));
+    assert_eq!(probe(&tables, "k7/1Q6/1K6/8/8/8/8/8 b - - 0 1"), Some(Dtm::Loss(0)));
+    // Colors are swapped when black has the extra piece
+    assert_eq!(probe(&tables, "K7/8/1k6/8/8/8/8/6q1 b - - 0 1"), Some(Dtm::Win(1)));
+
+    // King in front of the pawn wins, a rook pawn with the defending king in front draws
+    assert_eq!(probe(&tables, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").map(Dtm::wdl), Some(Wdl::Loss));
+    assert_eq!(probe(&tables, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").map(Dtm::wdl), Some(Wdl::Win));
+    assert_eq!(probe(&tables, "8/8/8/8/8/k7/P7/K7 w - - 0 1"), Some(Dtm::Draw));
+    assert_eq!(probe(&tables, "k7/p7/K7/8/8/8/8/8 b - - 0 1"), Some(Dtm::Draw));
+
+    // Other material and castling rights aren't in the tables
+    assert_eq!(probe(&tables, "4k3/8/8/8/8/8/8/4K2R w K - 0 1"), None);
+    assert_eq!(probe(&tables, "8/8/8/4k3/8/8/8/KBN5 w - - 0 1"), None);
+}
+
+#[test]
+fn plays_fastest_mate() {
+    let dir = env::temp_dir().join(format!("chess-ai-tablebases-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for table in generate("KQK").tables().iter() {
+        table.write(&dir).unwrap();
+    }
+    let table = EndgameTable::open(dir.join("KQK.ctb")).unwrap();
+    assert_eq!(table.material().to_string(), "KQK");
+
+    let mut engine = CoreEngine::new();
+    assert_eq!(engine.set_tablebase_path(dir.to_str().unwrap()).unwrap(), 2);
+    let mut position = Position::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    let tables = EndgameTables::open(&dir).unwrap();
+    // Every move of the engine brings mate one ply closer, whatever the defense
+    loop {
+        let Some(Dtm::Win(plies)) = tables.probe(&position.board) else {
+            panic!("{} is not won", position.to_fen());
+        };
+        let result = engine.search(&position, SearchLimits { depth: Some(1), ..Default::default() });
+        assert!(result.stats.tb_hits > 0);
+        position.make_move(result.best_move);
+        assert_eq!(tables.probe(&position.board), Some(Dtm::Loss(plies - 1)));
+        let Some(reply) = position.legal_moves().first().copied() else {
+            break;
+        };
+        position.make_move(reply);
+    }
+    assert!(position.legal_moves().is_empty() && position.in_check());
+    fs::remove_dir_all(dir).unwrap();
+}
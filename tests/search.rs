@@ -0,0 +1,99 @@
+use chess_ai::{
+    engine::{position::Position, search::{CoreEngine, SearchLimits}, tables::LookupTables},
+    uci::command::UciCommand,
+};
+
+
+fn best_move(fen: &str, threads: usize, depth: u8) -> String {
+    let position = Position::from_fen(fen).unwrap();
+    let mut engine = CoreEngine::new();
+    engine.set_threads(threads);
+    let result = engine.search(&position, SearchLimits { depth: Some(depth), ..Default::default() });
+    assert!(position.legal_moves().contains(&result.best_move));
+    result.best_move.to_uci()
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let fen = "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1";
+    assert_eq!(best_move(fen, 1, 3), "a1a8");
+    assert_eq!(best_move(fen, 4, 3), "a1a8");
+}
+
+#[test]
+fn helper_threads_return_legal_moves() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    for threads in [2, 3] {
+        best_move(kiwipete, threads, 4);
+    }
+}
+
+#[test]
+fn principal_variation_is_a_legal_line() {
+    let mut position = Position::start_pos();
+    let mut engine = CoreEngine::new();
+    let result = engine.search(&position, SearchLimits { depth: Some(6), ..Default::default() });
+    assert!(result.pv.len() >= 6);
+    assert!(result.pv[0] == result.best_move);
+    for mov in result.pv.iter() {
+        assert!(position.legal_moves().contains(mov));
+        position.make_move(*mov);
+    }
+}
+
+#[test]
+fn principal_variation_ends_in_mate() {
+    // Ladder mate in two
+    let mut position = Position::from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1").unwrap();
+    let mut engine = CoreEngine::new();
+    let result = engine.search(&position, SearchLimits { depth: Some(6), ..Default::default() });
+    assert_eq!(result.pv.len(), 3);
+    assert!(position.line_to_san(&result.pv)[2].ends_with('#'));
+    for mov in result.pv.iter() {
+        position.make_move(*mov);
+    }
+    assert!(position.legal_moves().is_empty() && position.in_check());
+}
+
+#[test]
+fn null_move_switches_side_to_move() {
+    let mut position = Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    let fen = position.to_fen();
+    let key = position.board.current_state.zobrist_key;
+
+    position.board.make_null_move(&LookupTables::get().zobrist);
+    assert!(!position.white_to_move());
+    assert_ne!(position.board.current_state.zobrist_key, key);
+    // Black's moves from the same position
+    let black_to_move = Position::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1").unwrap();
+    assert_eq!(position.legal_moves().len(), black_to_move.legal_moves().len());
+
+    position.board.unmake_null_move();
+    assert_eq!(position.to_fen(), fen);
+    assert_eq!(position.board.current_state.zobrist_key, key);
+    assert_eq!(position.legal_moves().len(), 48);
+}
+
+#[test]
+fn null_move_pruning_handles_zugzwang() {
+    // Only Rf1 wins, the position is a classic trap for null move pruning
+    assert_eq!(best_move("8/8/p1p5/1p5p/1P5p/8/PPP2K1p/4R1rk w - - 0 1", 1, 12), "e1f1");
+}
+
+#[test]
+fn parses_set_option() {
+    match UciCommand::parse("setoption name Threads value 8") {
+        UciCommand::SetOption { name, value } => {
+            assert_eq!(name, "Threads");
+            assert_eq!(value.as_deref(), Some("8"));
+        },
+        _ => panic!("expected setoption"),
+    }
+    match UciCommand::parse("setoption name Clear Hash") {
+        UciCommand::SetOption { name, value } => {
+            assert_eq!(name, "Clear Hash");
+            assert_eq!(value, None);
+        },
+        _ => panic!("expected setoption"),
+    }
+}
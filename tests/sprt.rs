@@ -0,0 +1,98 @@
+use chess_ai::game::{result::GameResult, sprt::{GameScore, Sprt, SprtConfig, SprtStatus, elo_to_score, score_to_elo}};
+
+
+fn play_pairs(sprt: &mut Sprt, pairs: &[(GameScore, GameScore)], repeat: usize) -> SprtStatus {
+    let mut status = sprt.status();
+    for _ in 0..repeat {
+        for (first, second) in pairs.iter() {
+            sprt.add_game(*first);
+            status = sprt.add_game(*second);
+            if status != SprtStatus::Running {
+                return status;
+            }
+        }
+    }
+    status
+}
+
+#[test]
+fn scores_from_both_sides() {
+    assert_eq!(GameScore::from_result(GameResult::BlackIsMated, true), Some(GameScore::Win));
+    assert_eq!(GameScore::from_result(GameResult::BlackIsMated, false), Some(GameScore::Loss));
+    assert_eq!(GameScore::from_result(GameResult::WhiteTimeout, true), Some(GameScore::Loss));
+    assert_eq!(GameScore::from_result(GameResult::Repetition, false), Some(GameScore::Draw));
+    assert_eq!(GameScore::from_result(GameResult::Playing, true), None);
+}
+
+#[test]
+fn counts_pairs_in_pentanomial() {
+    let mut sprt = Sprt::default();
+    sprt.add_game(GameScore::Win);
+    assert_eq!(sprt.pentanomial.num_pairs(), 0);
+    assert!(sprt.elo_estimate().is_none());
+    sprt.add_game(GameScore::Draw);
+    sprt.add_game(GameScore::Loss);
+    sprt.add_game(GameScore::Win);
+    sprt.add_game(GameScore::Loss);
+    sprt.add_game(GameScore::Loss);
+    assert_eq!(sprt.pentanomial.counts, [1, 0, 1, 1, 0]);
+}
+
+#[test]
+fn discards_pairs_without_a_score() {
+    let mut sprt = Sprt::default();
+    // Either game of a pair missing a score drops the pair, the next pair still starts fresh
+    sprt.discard_game();
+    sprt.add_game(GameScore::Win);
+    sprt.add_game(GameScore::Win);
+    sprt.discard_game();
+    sprt.add_game(GameScore::Win);
+    sprt.add_game(GameScore::Loss);
+    assert_eq!(sprt.pentanomial.counts, [0, 0, 1, 0, 0]);
+    assert_eq!(GameScore::from_result(GameResult::Aborted, true), None);
+}
+
+#[test]
+fn converts_elo() {
+    assert!((elo_to_score(0.0) - 0.5).abs() < 1e-9);
+    assert!((score_to_elo(elo_to_score(120.0)) - 120.0).abs() < 1e-6);
+    assert!(score_to_elo(0.25) < -190.0);
+}
+
+#[test]
+fn bounds_follow_error_rates() {
+    let config = SprtConfig { elo0: 0.0, elo1: 5.0, alpha: 0.05, beta: 0.05 };
+    assert!((config.lower_bound() + 2.944).abs() < 1e-3);
+    assert!((config.upper_bound() - 2.944).abs() < 1e-3);
+}
+
+#[test]
+fn accepts_h1_for_stronger_version() {
+    let mut sprt = Sprt::new(SprtConfig { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 });
+    // Wins one opening from both sides and draws the other two, a 100 Elo advantage
+    let pairs = [
+        (GameScore::Win, GameScore::Draw),
+        (GameScore::Draw, GameScore::Draw),
+        (GameScore::Draw, GameScore::Win),
+        (GameScore::Loss, GameScore::Win),
+    ];
+    assert_eq!(play_pairs(&mut sprt, &pairs, 1000), SprtStatus::AcceptH1);
+    let estimate = sprt.elo_estimate().unwrap();
+    assert!(estimate.lower < estimate.elo && estimate.elo < estimate.upper);
+    assert!(estimate.los > 0.95);
+}
+
+#[test]
+fn accepts_h0_for_equal_versions() {
+    let mut sprt = Sprt::new(SprtConfig { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 });
+    let pairs = [
+        (GameScore::Win, GameScore::Loss),
+        (GameScore::Draw, GameScore::Draw),
+        (GameScore::Loss, GameScore::Draw),
+        (GameScore::Draw, GameScore::Win),
+    ];
+    assert_eq!(play_pairs(&mut sprt, &pairs, 10000), SprtStatus::AcceptH0);
+    let estimate = sprt.elo_estimate().unwrap();
+    assert!(estimate.lower < 0.0 && estimate.upper > 0.0);
+    assert!(estimate.elo.abs() < 5.0);
+}
@@ -0,0 +1,302 @@
+use std::{env, fs, path::PathBuf};
+
+use chess_ai::{
+    engine::{position::Position, search::{CoreEngine, SearchLimits}},
+    tablebase::{Wdl, syzygy::SyzygyTablebase},
+};
+
+fn table_dir(name: &str, files: &[&str]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("chess-ai-syzygy-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for file in files {
+        fs::write(dir.join(file), b"not a table").unwrap();
+    }
+    dir
+}
+
+/// Number of placements of three unique pieces, the size of the KQvK and KRvK tables
+const THREE_PIECE_SIZE: usize = 31332;
+const BLOCK_BITS: usize = 256;
+const SPAN: usize = 64;
+
+/// Huffman code, code length and number of values of the fixture symbols:
+/// a leaf holding the value, a pair of leaves and a pair of pairs
+const SYMBOLS: [(u32, usize, usize); 3] = [(0b00, 2, 1), (0b01, 2, 2), (0b1, 1, 4)];
+
+/// Compressed sub-table storing `value` for every position: the size fields, the sparse index,
+/// the block lengths and the blocks
+fn encode_constant(value: u8) -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<Vec<u8>>) {
+    // Cycle through all symbols so every code length and pair depth is decoded
+    let mut symbols = Vec::new();
+    let mut remaining = THREE_PIECE_SIZE;
+    while remaining >= 8 {
+        symbols.extend([2, 1, 0, 0]);
+        remaining -= 8;
+    }
+    for sym in [2, 1, 0] {
+        while remaining >= SYMBOLS[sym].2 {
+            symbols.push(sym);
+            remaining -= SYMBOLS[sym].2;
+        }
+    }
+
+    // Symbols never cross a block
+    let mut blocks: Vec<(Vec<bool>, usize)> = vec![(Vec::new(), 0)];
+    for sym in symbols {
+        let (code, len, values) = SYMBOLS[sym];
+        if blocks.last().unwrap().0.len() + len > BLOCK_BITS {
+            blocks.push((Vec::new(), 0));
+        }
+        let block = blocks.last_mut().unwrap();
+        block.0.extend((0..len).rev().map(|bit| code >> bit & 1 == 1));
+        block.1 += values;
+    }
+
+    let mut sizes = vec![0, BLOCK_BITS.trailing_zeros() as u8 - 3, SPAN.trailing_zeros() as u8, 0];
+    sizes.extend((blocks.len() as u32).to_le_bytes());
+    // Symbol lengths 1 to 2, the lowest symbol of length 1 is 2 and of length 2 is 0
+    sizes.extend([2, 1, 2, 0, 0, 0]);
+    sizes.extend(3u16.to_le_bytes());
+    sizes.extend([value, 0xf0, 0xff, 0, 0, 0, 1, 0x10, 0, 0]);
+
+    // Every entry points at the middle value of its span
+    let block_starts: Vec<usize> = blocks.iter().scan(0, |start, block| {
+        let block_start = *start;
+        *start += block.1;
+        Some(block_start)
+    }).collect();
+    let mut sparse_index = Vec::new();
+    for entry in 0..THREE_PIECE_SIZE.div_ceil(SPAN) {
+        let middle = entry * SPAN + SPAN / 2;
+        let block = block_starts.iter().rposition(|start| *start <= middle.min(THREE_PIECE_SIZE - 1)).unwrap();
+        sparse_index.extend((block as u32).to_le_bytes());
+        sparse_index.extend(((middle - block_starts[block]) as u16).to_le_bytes());
+    }
+
+    let block_lengths = blocks.iter().flat_map(|block| (block.1 as u16 - 1).to_le_bytes()).collect();
+    let data = blocks.iter().map(|block| {
+        let mut bytes = vec![0; BLOCK_BITS / 8];
+        for (i, bit) in block.0.iter().enumerate() {
+            bytes[i / 8] |= (*bit as u8) << (7 - i % 8);
+        }
+        bytes
+    }).collect();
+    (sizes, sparse_index, block_lengths, data)
+}
+
+/// WDL table of a king and `piece` against a bare king, stored as a win with white to move and a loss with black to move.
+/// That is the right result of every position, the probe handles stalemates and captures of the piece by itself.
+fn won_wdl_table(piece: u8) -> Vec<u8> {
+    let mut table = vec![0x71, 0xe8, 0x23, 0x5d, 1, 0];
+    // Both sides store the piece, the white king and the black king, in that order
+    table.extend([piece | piece << 4, 0x66, 0xee, 0]);
+    let sides = [encode_constant(4), encode_constant(0)];
+    for side in sides.iter() {
+        table.extend(&side.0);
+    }
+    for side in sides.iter() {
+        table.extend(&side.1);
+    }
+    for side in sides.iter() {
+        table.extend(&side.2);
+    }
+    for side in sides.iter() {
+        table.resize(table.len().next_multiple_of(64), 0);
+        table.extend(side.3.concat());
+    }
+    table
+}
+
+fn won_table_dir(name: &str) -> PathBuf {
+    let dir = table_dir(name, &[]);
+    fs::write(dir.join("KQvK.rtbw"), won_wdl_table(5)).unwrap();
+    fs::write(dir.join("KRvK.rtbw"), won_wdl_table(4)).unwrap();
+    dir
+}
+
+/// FEN of a king and `piece` against a bare king
+fn fen(piece: char, squares: [usize; 3], white_to_move: bool) -> String {
+    let mut board = [' '; 64];
+    board[squares[0]] = piece;
+    board[squares[1]] = 'K';
+    board[squares[2]] = 'k';
+    let ranks: Vec<String> = (0..8).rev().map(|rank| {
+        let mut row = String::new();
+        let mut empty = 0;
+        for c in &board[rank * 8..rank * 8 + 8] {
+            if *c == ' ' {
+                empty += 1;
+                continue;
+            }
+            if empty > 0 {
+                row += &empty.to_string();
+                empty = 0;
+            }
+            row.push(*c);
+        }
+        if empty > 0 {
+            row += &empty.to_string();
+        }
+        row
+    }).collect();
+    format!("{} {} - - 0 1", ranks.join("/"), if white_to_move { 'w' } else { 'b' })
+}
+
+/// Placements of the three pieces with the black king on `black_king`, where white isn't in check
+fn placements(black_king: usize) -> impl Iterator<Item = [usize; 3]> {
+    let adjacent = |a: usize, b: usize| (a / 8).abs_diff(b / 8) <= 1 && (a % 8).abs_diff(b % 8) <= 1;
+    (0..64).flat_map(move |piece| (0..64).map(move |king| [piece, king, black_king]))
+        .filter(move |[piece, king, black_king]| piece != king && piece != black_king && !adjacent(*king, *black_king))
+}
+
+#[test]
+fn finds_tables_by_name() {
+    let dir = table_dir("names", &["KQvK.rtbw", "KQvK.rtbz", "KRvKN.rtbw", "KRvKN.txt", "KvKvK.rtbw", "README"]);
+    let tablebase = SyzygyTablebase::open(&dir).unwrap();
+    assert_eq!(tablebase.num_tables(), 2);
+    assert_eq!(tablebase.max_pieces(), 4);
+    fs::remove_dir_all(dir).unwrap();
+
+    assert!(SyzygyTablebase::open(env::temp_dir().join("chess-ai-syzygy-missing")).is_err());
+}
+
+#[test]
+fn unusable_tables_are_not_probed() {
+    let dir = table_dir("corrupt", &["KQvK.rtbw", "KQvK.rtbz"]);
+    let tablebase = SyzygyTablebase::open(&dir).unwrap();
+
+    // Files without the Syzygy header are skipped
+    let mut position = Position::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    assert_eq!(tablebase.probe_wdl(&mut position.board), None);
+    assert!(tablebase.probe_root(&mut position.board).is_none());
+
+    // Bare kings are a draw without any table, and too many pieces or castling rights can't be probed
+    let mut bare_kings = Position::from_fen("8/8/8/4k3/8/8/8/K7 w - - 0 1").unwrap();
+    assert_eq!(tablebase.probe_wdl(&mut bare_kings.board), Some(Wdl::Draw));
+    let mut castling = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    assert!(!tablebase.can_probe(&castling.board));
+    assert_eq!(tablebase.probe_wdl(&mut castling.board), None);
+    assert!(!tablebase.can_probe(&Position::start_pos().board));
+
+    // The search falls back to its own evaluation
+    let mut engine = CoreEngine::new();
+    assert_eq!(engine.set_syzygy_path(dir.to_str().unwrap()).unwrap(), 1);
+    let result = engine.search(&position, SearchLimits { depth: Some(4), ..Default::default() });
+    assert!(position.legal_moves().contains(&result.best_move));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn decodes_compressed_tables() {
+    let dir = won_table_dir("decode");
+    let tablebase = SyzygyTablebase::open(&dir).unwrap();
+    assert_eq!(tablebase.num_tables(), 2);
+
+    let known = [
+        ("8/8/8/4k3/8/8/8/KQ6 w - - 0 1", Wdl::Win),
+        ("8/8/8/4k3/8/8/8/KQ6 b - - 0 1", Wdl::Loss),
+        ("8/8/8/4k3/8/8/8/KR6 w - - 0 1", Wdl::Win),
+        ("8/8/8/4k3/8/8/8/KR6 b - - 0 1", Wdl::Loss),
+        // Checkmate, stalemate and a hanging queen
+        ("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1", Wdl::Loss),
+        ("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", Wdl::Draw),
+        ("7K/8/8/4k3/3Q4/8/8/8 b - - 0 1", Wdl::Draw),
+    ];
+    for (fen, wdl) in known {
+        let mut position = Position::from_fen(fen).unwrap();
+        assert_eq!(tablebase.probe_wdl(&mut position.board), Some(wdl), "{}", fen);
+    }
+
+    // Sweeping the pieces over the board reaches every block of the tables through the sparse index
+    for (piece, black_king) in [('Q', 36), ('R', 0), ('Q', 57), ('R', 19)] {
+        for squares in placements(black_king) {
+            let mut black_to_move = Position::from_fen(&fen(piece, squares, false)).unwrap();
+            if black_to_move.in_check() {
+                continue;
+            }
+            let mut white_to_move = Position::from_fen(&fen(piece, squares, true)).unwrap();
+            assert_eq!(tablebase.probe_wdl(&mut white_to_move.board), Some(Wdl::Win), "{}", white_to_move.to_fen());
+        }
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn corrupt_table_data_misses_the_probe() {
+    let dir = won_table_dir("corrupt-data");
+    let table = won_wdl_table(5);
+    // The sparse index starts after the header, the order and piece bytes and the two size fields
+    let sizes_len = encode_constant(4).0.len();
+    let sparse_index = 10 + 2 * sizes_len;
+    let entries = THREE_PIECE_SIZE.div_ceil(SPAN);
+
+    // Blocks past the end of the file
+    let mut past_end = table.clone();
+    for entry in 0..entries {
+        past_end[sparse_index + 6 * entry..sparse_index + 6 * entry + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    }
+    fs::write(dir.join("KQvK.rtbw"), past_end).unwrap();
+    let tablebase = SyzygyTablebase::open(&dir).unwrap();
+    let mut position = Position::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    assert_eq!(tablebase.probe_wdl(&mut position.board), None);
+
+    // Offsets before the first block
+    let mut before_start = table.clone();
+    before_start[sparse_index..sparse_index + 6 * entries].fill(0);
+    fs::write(dir.join("KQvK.rtbw"), before_start).unwrap();
+    let tablebase = SyzygyTablebase::open(&dir).unwrap();
+    let mut missed = 0;
+    for squares in placements(36) {
+        // Black can't be in check with white to move
+        let Ok(mut position) = Position::from_fen(&fen('Q', squares, true)) else {
+            continue;
+        };
+        missed += tablebase.probe_wdl(&mut position.board).is_none() as usize;
+    }
+    assert!(missed > 0);
+
+    // The search keeps going on its own evaluation
+    let mut engine = CoreEngine::new();
+    engine.set_syzygy_path(dir.to_str().unwrap()).unwrap();
+    let position = Position::from_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+    let result = engine.search(&position, SearchLimits { depth: Some(3), ..Default::default() });
+    assert!(position.legal_moves().contains(&result.best_move));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Checks known results against real tables, run with `SYZYGY_PATH` pointing at a directory holding the 3 to 5 piece tables
+#[test]
+#[ignore = "needs the Syzygy tables, run with SYZYGY_PATH set and --ignored"]
+fn probes_real_tables() {
+    let path = env::var("SYZYGY_PATH").expect("SYZYGY_PATH must point at the Syzygy tables");
+    let tablebase = SyzygyTablebase::open(&path).unwrap();
+    let wdl_positions = [
+        ("8/8/8/4k3/8/8/8/KQ6 w - - 0 1", Wdl::Win),
+        ("8/8/8/4k3/8/8/8/KQ6 b - - 0 1", Wdl::Loss),
+        ("8/8/8/8/8/2k5/8/K1n5 w - - 0 1", Wdl::Draw),
+        ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Wdl::Loss),
+        ("8/8/8/8/3k4/8/8/K1N1B3 w - - 0 1", Wdl::Win),
+        ("7r/8/4k3/8/8/8/2R5/4K3 w - - 0 1", Wdl::Draw),
+    ];
+    for (fen, wdl) in wdl_positions {
+        let mut position = Position::from_fen(fen).unwrap();
+        assert_eq!(tablebase.probe_wdl(&mut position.board), Some(wdl), "{}", fen);
+    }
+
+    // Mate in one is played right away, and wins are converted before the fifty-move rule
+    let mut position = Position::from_fen("k7/8/1K6/8/8/8/8/6Q1 w - - 0 1").unwrap();
+    let root = tablebase.probe_root(&mut position.board).unwrap();
+    assert_eq!(root.dtz, 1);
+    assert_eq!(root.wdl(), Wdl::Win);
+    position.make_move(root.mov);
+    assert!(position.legal_moves().is_empty() && position.in_check());
+
+    let mut engine = CoreEngine::new();
+    assert!(engine.set_syzygy_path(&path).unwrap() > 0);
+    let mut position = Position::from_fen("8/8/8/4k3/8/8/8/KR6 w - - 0 1").unwrap();
+    let result = engine.search(&position, SearchLimits { depth: Some(4), ..Default::default() });
+    assert!(result.stats.tb_hits > 0);
+    assert!(tablebase.probe_dtz(&mut position.board).unwrap() > 0);
+    position.make_move(result.best_move);
+    assert!(tablebase.probe_dtz(&mut position.board).unwrap() < 0);
+}
@@ -0,0 +1,59 @@
+use chess_ai::{
+    board::moves::Move,
+    engine::position::Position,
+    game::result::GameResult,
+};
+
+
+fn play(position: &mut Position, line: &[&str]) -> Vec<Move> {
+    line.iter().map(|name| {
+        let mov = position.move_from_uci(name).unwrap();
+        position.make_move(mov);
+        mov
+    }).collect()
+}
+
+fn take_back(position: &mut Position, moves: &[Move]) {
+    for mov in moves.iter().rev() {
+        position.unmake_move(*mov);
+    }
+}
+
+#[test]
+fn restores_the_position() {
+    let mut position = Position::from_fen("r3k2r/pPppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPP1/R3K2R w KQkq - 0 1").unwrap();
+    let fen = position.to_fen();
+    let history = position.board.repeat_position_history.clone();
+    // A capture, castling on both sides, an en passant capture and a promotion with capture
+    let moves = play(&mut position, &["e5f7", "e8g8", "e1c1", "c7c5", "d5c6", "a6b5", "b7a8q"]);
+    take_back(&mut position, &moves);
+    assert_eq!(position.to_fen(), fen);
+    assert_eq!(position.board.repeat_position_history, history);
+    assert_eq!(position.board.move_log.len(), 0);
+}
+
+#[test]
+fn keeps_repetitions_across_irreversible_moves() {
+    let mut position = Position::start_pos();
+    play(&mut position, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    // The pawn move clears the history, taking it back has to restore it
+    let moves = play(&mut position, &["e2e4"]);
+    take_back(&mut position, &moves);
+    assert_eq!(position.board.repeat_position_history.len(), 5);
+    assert_eq!(position.game_result(), GameResult::Playing);
+
+    play(&mut position, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+    assert_eq!(position.game_result(), GameResult::Repetition);
+}
+
+#[test]
+fn takes_back_checkmate() {
+    let mut position = Position::start_pos();
+    let moves = play(&mut position, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert_eq!(position.game_result(), GameResult::WhiteIsMated);
+
+    take_back(&mut position, &moves[3..]);
+    assert_eq!(position.game_result(), GameResult::Playing);
+    assert!(position.legal_moves().contains(&moves[3]));
+    assert!(!position.white_to_move());
+}
@@ -0,0 +1,81 @@
+use std::time::Instant;
+
+use chess_ai::{
+    ai::time_manager::{ClockTime, TimeManager},
+    board::moves::Move,
+    engine::{position::Position, search::CoreEngine},
+    uci::command::UciCommand,
+};
+
+fn clock(time_left_ms: u32, increment_ms: u32, moves_to_go: Option<u32>) -> ClockTime {
+    ClockTime { time_left_ms, increment_ms, moves_to_go }
+}
+
+#[test]
+fn allocates_limits() {
+    let manager = TimeManager::new(clock(60050, 0, None));
+    assert_eq!(manager.soft_limit_ms(), 2000);
+    assert_eq!(manager.hard_limit_ms(), 8000);
+
+    // The increment is mostly spent, the last move of a session may use a large part of the clock
+    let manager = TimeManager::new(clock(60050, 4000, None));
+    assert_eq!(manager.soft_limit_ms(), 5000);
+    let manager = TimeManager::new(clock(10050, 0, Some(1)));
+    assert_eq!(manager.soft_limit_ms(), 5000);
+    assert_eq!(manager.hard_limit_ms(), 8000);
+
+    // Almost flagging still leaves time for a move
+    let manager = TimeManager::new(clock(20, 0, None));
+    assert!(manager.soft_limit_ms() >= 1 && manager.hard_limit_ms() >= manager.soft_limit_ms());
+}
+
+#[test]
+fn adapts_to_the_search() {
+    let (e2e4, d2d4) = (Move::from_start_end(12, 28), Move::from_start_end(11, 27));
+
+    // A stable best move stops before the soft limit
+    let mut manager = TimeManager::new(clock(60050, 0, None));
+    assert!(manager.iteration_complete_at(e2e4, 20, 100));
+    assert!(manager.iteration_complete_at(e2e4, 20, 200));
+    assert!(manager.iteration_complete_at(e2e4, 20, 400));
+    assert!(manager.iteration_complete_at(e2e4, 20, 700));
+    assert!(!manager.iteration_complete_at(e2e4, 20, 1500));
+
+    // A changing best move or a dropping score extends past it
+    let mut manager = TimeManager::new(clock(60050, 0, None));
+    assert!(manager.iteration_complete_at(e2e4, 20, 1000));
+    assert!(manager.iteration_complete_at(d2d4, 20, 2500));
+    let mut manager = TimeManager::new(clock(60050, 0, None));
+    assert!(manager.iteration_complete_at(e2e4, 20, 500));
+    assert!(manager.iteration_complete_at(e2e4, 20, 1000));
+    assert!(manager.iteration_complete_at(e2e4, 20, 1200));
+    assert!(manager.iteration_complete_at(e2e4, -80, 2200));
+
+    // The next iteration would run past the hard limit
+    let mut manager = TimeManager::new(clock(60050, 0, None));
+    assert!(!manager.iteration_complete_at(e2e4, 20, 2900));
+}
+
+#[test]
+fn searches_on_the_clock() {
+    let go = match UciCommand::parse("go wtime 2000 btime 90000 winc 100 binc 0") {
+        UciCommand::Go(go) => go,
+        _ => panic!("expected go"),
+    };
+    assert_eq!(go.clock(true), Some(clock(2000, 100, None)));
+    assert_eq!(go.clock(false), Some(clock(90000, 0, None)));
+    let limits = go.limits(true);
+    assert_eq!(limits.think_time_ms, None);
+    match UciCommand::parse("go wtime 2000 btime 2000 movetime 500") {
+        UciCommand::Go(go) => assert_eq!(go.clock(true), None),
+        _ => panic!("expected go"),
+    }
+
+    let position = Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+    let hard_limit_ms = TimeManager::new(limits.clock.unwrap()).hard_limit_ms();
+    let mut engine = CoreEngine::new();
+    let start = Instant::now();
+    let result = engine.search(&position, limits);
+    assert!(position.legal_moves().contains(&result.best_move));
+    assert!(start.elapsed().as_millis() < hard_limit_ms as u128 + 100);
+}
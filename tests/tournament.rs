@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use chess_ai::{
+    ai::ai_player::AIVersion,
+    engine::{position::Position, registry::create_engine},
+    game::{clock::TimeControl, result::GameResult},
+    tournament::{DEFAULT_OPENINGS_PATH, Format, Participant, Tournament, load_openings, schedule, game::{MatchSettings, play_game}},
+};
+
+
+#[test]
+fn parses_participants() {
+    let participant = Participant::parse("v13:Hash=64:Threads=2").unwrap();
+    assert_eq!(participant.version, AIVersion::V13);
+    assert_eq!(participant.options, vec![(String::from("Hash"), String::from("64")), (String::from("Threads"), String::from("2"))]);
+    assert!(participant.create_engine().is_ok());
+
+    assert!(Participant::parse("V14").is_err());
+    assert!(Participant::parse("V13:Hash").is_err());
+    assert!(Participant::parse("V9:Threads=2").unwrap().create_engine().is_err());
+    assert_eq!(Participant::new(AIVersion::V10).name, "V10");
+}
+
+#[test]
+fn parses_time_controls() {
+    let time_control = TimeControl::parse("0.5+0.1").unwrap();
+    assert_eq!(time_control.base, Duration::from_secs(30));
+    assert_eq!(time_control.increment, Duration::from_millis(100));
+    assert_eq!(TimeControl::parse("5"), Some(TimeControl::fischer(300, 0)));
+    assert_eq!(TimeControl::parse(&TimeControl::fischer(180, 2).to_string()), Some(TimeControl::fischer(180, 2)));
+    assert_eq!(TimeControl::parse("five"), None);
+    assert_eq!(TimeControl::parse("-1+0"), None);
+}
+
+#[test]
+fn schedules_both_colours_per_opening() {
+    let games = schedule(4, Format::RoundRobin, 3);
+    assert_eq!(games.len(), 6 * 2 * 3);
+    for pair in games.chunks(2) {
+        assert_eq!((pair[0].white, pair[0].black), (pair[1].black, pair[1].white));
+        assert_eq!(pair[0].opening_idx, pair[1].opening_idx);
+    }
+
+    let games = schedule(4, Format::Gauntlet, 2);
+    assert_eq!(games.len(), 3 * 2 * 2);
+    assert!(games.iter().all(|game| game.white == 0 || game.black == 0));
+    assert!(games.iter().enumerate().all(|(idx, game)| game.idx == idx));
+}
+
+#[test]
+fn loads_default_openings() {
+    let openings = load_openings(DEFAULT_OPENINGS_PATH).unwrap();
+    assert!(!openings.is_empty());
+    assert!(openings.iter().all(|fen| Position::from_fen(fen).is_ok()));
+}
+
+#[test]
+fn plays_a_game_to_the_end() {
+    let settings = MatchSettings { move_time_ms: 5, max_plies: 40, ..Default::default() };
+    let mut white = create_engine(AIVersion::V3);
+    let mut black = create_engine(AIVersion::V0);
+    let start_fen = "r3k3/1pp2p2/8/3pP3/8/2N5/1PP2P2/4K2R w Kq - 0 1";
+    let record = play_game(white.as_mut(), black.as_mut(), start_fen, &settings).unwrap();
+    assert_ne!(record.result, GameResult::Playing);
+    assert!(record.moves.len() <= settings.max_plies);
+    // The PGN replays the moves, so every one of them was legal
+    assert_eq!(record.to_pgn_game().unwrap().mainline(), record.moves);
+}
+
+#[test]
+fn flags_on_an_empty_clock() {
+    let settings = MatchSettings { time_control: TimeControl::parse("0+0"), ..Default::default() };
+    let mut white = create_engine(AIVersion::V13);
+    let mut black = create_engine(AIVersion::V13);
+    let record = play_game(white.as_mut(), black.as_mut(), "r3k3/8/8/8/8/8/4P3/R3K3 w Qq - 0 1", &settings).unwrap();
+    assert_eq!(record.result, GameResult::WhiteTimeout);
+    assert!(record.moves.is_empty());
+}
+
+#[test]
+fn runs_games_concurrently() {
+    let tournament = Tournament {
+        participants: vec![Participant::new(AIVersion::V0), Participant::new(AIVersion::V1), Participant::new(AIVersion::V3)],
+        format: Format::RoundRobin,
+        openings: vec![String::from("4k3/8/8/8/8/8/3PPP2/4K3 w - - 0 1")],
+        rounds: 1,
+        concurrency: 3,
+        settings: MatchSettings { move_time_ms: 2, max_plies: 16, ..Default::default() },
+    };
+    let mut num_finished = 0;
+    let standings = tournament.run(|_, record, _| {
+        assert!(record.is_ok());
+        num_finished += 1;
+    });
+    assert_eq!(num_finished, 6);
+    assert_eq!(standings.num_games(), 6);
+    for idx in 0..3 {
+        assert_eq!(standings.total(idx).games(), 4);
+    }
+    let points = (0..3).map(|idx| standings.total(idx).points()).sum::<f64>();
+    assert_eq!(points, 6.0);
+    assert!(standings.to_table().contains("V0 vs V1"));
+}
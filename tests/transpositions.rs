@@ -0,0 +1,116 @@
+use chess_ai::{
+    ai::{v12, v13::search::{searcher::Searcher, transpositions::{EvaluationType, TranspositionTable}}},
+    board::moves::Move,
+    engine::position::Position,
+};
+
+
+fn position_after(moves: &[&str]) -> Position {
+    let mut position = Position::start_pos();
+    for name in moves.iter() {
+        let mov = position.move_from_uci(name).unwrap();
+        position.make_move(mov);
+    }
+    position
+}
+
+#[test]
+fn stores_and_retrieves_packed_entries() {
+    let table = TranspositionTable::new(1);
+    let position = Position::start_pos();
+    let mov = position.move_from_uci("e2e4").unwrap();
+
+    table.store_evaluation(5, 0, -123, EvaluationType::Exact, mov, &position.board);
+    table.store_static_eval(37, &position.board);
+    let entry = table.get_entry(position.board.current_state.zobrist_key).unwrap();
+    assert_eq!(entry.value, -123);
+    assert_eq!(entry.static_eval, Some(37));
+    assert!(entry.mov == mov);
+    assert_eq!(entry.depth, 5);
+    assert!(entry.node_type == Some(EvaluationType::Exact));
+
+    assert_eq!(table.get_evaluation(5, 0, -1000, 1000, &position.board).map(|(eval, _)| eval), Some(-123));
+    assert_eq!(table.get_evaluation(6, 0, -1000, 1000, &position.board), None);
+    assert_eq!(table.get_static_eval(&position.board), Some(37));
+}
+
+#[test]
+fn keeps_mate_distance_in_packed_scores() {
+    let table = TranspositionTable::new(1);
+    let position = Position::start_pos();
+    for mate_score in [Searcher::MATE_SCORE - 7, -(Searcher::MATE_SCORE - 12)] {
+        // Stored at ply 3, so the score is relative to the position it was found in
+        table.store_evaluation(4, 3, mate_score, EvaluationType::Exact, Move::NULL, &position.board);
+        let (eval, _) = table.get_evaluation(4, 3, -Searcher::MATE_SCORE, Searcher::MATE_SCORE, &position.board).unwrap();
+        assert_eq!(eval, mate_score);
+        let (eval_at_root, _) = table.get_evaluation(4, 0, -Searcher::MATE_SCORE, Searcher::MATE_SCORE, &position.board).unwrap();
+        assert_eq!(eval_at_root, mate_score + 3 * mate_score.signum());
+    }
+}
+
+#[test]
+fn round_trips_mate_and_tablebase_scores() {
+    let table = TranspositionTable::new(1);
+    let position = Position::start_pos();
+    for plies in [0, 1, 17, Searcher::MAX_MATE_DEPTH - 1] {
+        for score in [Searcher::MATE_SCORE - plies, Searcher::TB_WIN_SCORE - plies] {
+            for score in [score, -score] {
+                table.store_evaluation(1, 0, score, EvaluationType::Exact, Move::NULL, &position.board);
+                let (eval, _) = table.get_evaluation(1, 0, -Searcher::MATE_SCORE, Searcher::MATE_SCORE, &position.board).unwrap();
+                assert_eq!(eval, score);
+            }
+        }
+    }
+    // Ordinary evaluations stay below both bands
+    table.store_evaluation(1, 0, 2500, EvaluationType::Exact, Move::NULL, &position.board);
+    assert_eq!(table.get_evaluation(1, 0, -Searcher::MATE_SCORE, Searcher::MATE_SCORE, &position.board).map(|(eval, _)| eval), Some(2500));
+}
+
+#[test]
+fn keeps_deeper_entries_of_the_current_search() {
+    let table = TranspositionTable::new(1);
+    let position = Position::start_pos();
+    table.store_evaluation(8, 0, 50, EvaluationType::LowerBound, Move::NULL, &position.board);
+    table.store_evaluation(2, 0, -50, EvaluationType::LowerBound, Move::NULL, &position.board);
+    assert_eq!(table.get_entry(position.board.current_state.zobrist_key).unwrap().depth, 8);
+
+    // Entries of an earlier search are always replaced
+    table.new_search();
+    table.store_evaluation(2, 0, -50, EvaluationType::LowerBound, Move::NULL, &position.board);
+    assert_eq!(table.get_entry(position.board.current_state.zobrist_key).unwrap().depth, 2);
+}
+
+#[test]
+fn clears_and_reports_hashfull() {
+    let table = TranspositionTable::new(1);
+    assert_eq!(table.hashfull(), 0);
+
+    let positions = [position_after(&[]), position_after(&["e2e4"]), position_after(&["d2d4"]), position_after(&["e2e4", "e7e5"])];
+    for position in positions.iter() {
+        table.store_evaluation(1, 0, 0, EvaluationType::Exact, Move::NULL, &position.board);
+        assert!(table.get_entry(position.board.current_state.zobrist_key).is_some());
+    }
+
+    table.clear();
+    for position in positions.iter() {
+        assert!(table.get_entry(position.board.current_state.zobrist_key).is_none());
+    }
+    // Storing after a clear must still work
+    table.store_evaluation(1, 0, 0, EvaluationType::Exact, Move::NULL, &positions[0].board);
+    assert!(table.get_entry(positions[0].board.current_state.zobrist_key).is_some());
+}
+
+#[test]
+fn clears_legacy_tables_in_place() {
+    let mut table = v12::search::transpositions::TranspositionTable::new(1);
+    let position = position_after(&["e2e4"]);
+    let mov = position.move_from_uci("e7e5").unwrap();
+    table.store_evaluation(3, 0, 42, v12::search::transpositions::EvaluationType::Exact, mov, &position.board);
+    assert!(table.get_stored_move(&position.board) == Some(mov));
+
+    table.clear();
+    assert!(table.get_stored_move(&position.board).is_none());
+    assert_eq!(table.get_evaluation(3, 0, -1000, 1000, &position.board), None);
+    table.store_evaluation(3, 0, 42, v12::search::transpositions::EvaluationType::Exact, mov, &position.board);
+    assert_eq!(table.get_evaluation(3, 0, -1000, 1000, &position.board), Some(42));
+}
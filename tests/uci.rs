@@ -0,0 +1,102 @@
+use chess_ai::uci::command::{UciCommand, GoParams};
+
+const FEN: &str = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+
+fn moves(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn go(line: &str) -> GoParams {
+    match UciCommand::parse(line) {
+        UciCommand::Go(params) => params,
+        command => panic!("expected go, got {:?}", command),
+    }
+}
+
+
+#[test]
+fn parses_start_position() {
+    assert_eq!(UciCommand::parse("position startpos"), UciCommand::Position { fen: None, moves: Vec::new() });
+    assert_eq!(
+        UciCommand::parse("position startpos moves e2e4 e7e5 g1f3"),
+        UciCommand::Position { fen: None, moves: moves(&["e2e4", "e7e5", "g1f3"]) },
+    );
+    // Extra whitespace between tokens is allowed
+    assert_eq!(
+        UciCommand::parse("  position   startpos  moves  e2e4\te7e5 "),
+        UciCommand::Position { fen: None, moves: moves(&["e2e4", "e7e5"]) },
+    );
+}
+
+#[test]
+fn parses_fen_position() {
+    assert_eq!(
+        UciCommand::parse(&format!("position fen {}", FEN)),
+        UciCommand::Position { fen: Some(FEN.to_string()), moves: Vec::new() },
+    );
+    assert_eq!(
+        UciCommand::parse(&format!("position fen {} moves f1b5 a7a6 b5a4", FEN)),
+        UciCommand::Position { fen: Some(FEN.to_string()), moves: moves(&["f1b5", "a7a6", "b5a4"]) },
+    );
+    assert_eq!(
+        UciCommand::parse(&format!("position fen {} moves", FEN)),
+        UciCommand::Position { fen: Some(FEN.to_string()), moves: Vec::new() },
+    );
+}
+
+#[test]
+fn parses_go_on_the_clock() {
+    assert_eq!(go("go wtime 60000 btime 59000 winc 1000 binc 500 movestogo 20"), GoParams {
+        wtime: Some(60000),
+        btime: Some(59000),
+        winc: Some(1000),
+        binc: Some(500),
+        moves_to_go: Some(20),
+        ..Default::default()
+    });
+    // The order of the parameters doesn't matter
+    assert_eq!(go("go binc 500 movestogo 20 btime 59000 winc 1000 wtime 60000"), go("go wtime 60000 btime 59000 winc 1000 binc 500 movestogo 20"));
+}
+
+#[test]
+fn parses_go_limits() {
+    assert_eq!(go("go depth 12"), GoParams { depth: Some(12), ..Default::default() });
+    assert_eq!(go("go depth 1000").depth, Some(u8::MAX));
+    assert_eq!(go("go movetime 2500"), GoParams { move_time: Some(2500), ..Default::default() });
+    assert_eq!(go("go nodes 100000"), GoParams { nodes: Some(100000), ..Default::default() });
+    assert_eq!(go("go infinite"), GoParams { infinite: true, ..Default::default() });
+    assert_eq!(go("go infinite depth 8"), GoParams { infinite: true, depth: Some(8), ..Default::default() });
+    assert_eq!(go("go"), GoParams::default());
+}
+
+#[test]
+fn go_params_set_search_limits() {
+    let params = go("go movetime 1000");
+    assert_eq!(params.think_time_ms(), 950);
+    assert_eq!(params.limits(true).think_time_ms, Some(950));
+    assert_eq!(params.limits(true).clock, None);
+
+    let params = go("go infinite wtime 1000");
+    assert_eq!(params.limits(true).think_time_ms, None);
+    assert_eq!(params.limits(true).clock, None);
+
+    let limits = go("go depth 6 nodes 5000").limits(false);
+    assert_eq!((limits.depth, limits.nodes, limits.think_time_ms), (Some(6), Some(5000), None));
+}
+
+#[test]
+fn ignores_malformed_go_values() {
+    assert_eq!(go("go depth"), GoParams::default());
+    assert_eq!(go("go wtime abc btime 1000"), GoParams { btime: Some(1000), ..Default::default() });
+    assert_eq!(go("go movetime -5"), GoParams::default());
+    assert_eq!(go("go ponder depth 3"), GoParams { depth: Some(3), ..Default::default() });
+}
+
+#[test]
+fn parses_malformed_commands() {
+    assert_eq!(UciCommand::parse(""), UciCommand::Unknown(String::new()));
+    assert_eq!(UciCommand::parse("goo depth 3"), UciCommand::Unknown(String::from("goo depth 3")));
+    assert_eq!(UciCommand::parse("position"), UciCommand::Position { fen: None, moves: Vec::new() });
+    assert_eq!(UciCommand::parse("position fen"), UciCommand::Position { fen: Some(String::new()), moves: Vec::new() });
+    assert_eq!(UciCommand::parse("position moves e2e4"), UciCommand::Position { fen: None, moves: moves(&["e2e4"]) });
+}